@@ -11,6 +11,7 @@ use events_api::{
     mbus_nats::{message_bus_init, BusSubscription},
     Bus,
 };
+use http_server::{authenticate, health_routes, on_connect, ServerArgs};
 use k8s_openapi::api::core::v1::ConfigMap;
 use obs::common::{
     constants::{DEFAULT_MBUS_URL, DEFAULT_NAMESPACE, DEFAULT_RELEASE_NAME},
@@ -18,7 +19,10 @@ use obs::common::{
 };
 use prometheus::{Encoder, Registry};
 use snafu::ResultExt;
-use std::net::SocketAddr;
+use std::{
+    net::SocketAddr,
+    sync::atomic::{AtomicBool, Ordering},
+};
 use tracing::{error, info, trace};
 use utils::{
     raw_version_str,
@@ -62,6 +66,18 @@ struct Cli {
     /// Use ANSI colors for the logs.
     #[clap(long, default_value_t = true, action = clap::ArgAction::Set)]
     ansi_colors: bool,
+
+    /// TLS and authentication of the stats endpoint.
+    #[clap(flatten)]
+    server: ServerArgs,
+}
+
+/// This is set while the events are being stored to the cache, i.e. while the stats are current.
+static STORING_EVENTS: AtomicBool = AtomicBool::new(false);
+
+/// Returns true if the events are being stored to the cache.
+fn storing_events() -> bool {
+    STORING_EVENTS.load(Ordering::Relaxed)
 }
 
 impl Cli {
@@ -119,15 +135,24 @@ async fn main() -> errors::Result<()> {
     initialize_exporter(&args);
     info!("exporter initialized successfully!");
 
+    let tls_config = args.server.tls_config().context(errors::HttpServerConfig)?;
+    let authentication = args
+        .server
+        .authentication()
+        .context(errors::HttpServerConfig)?;
+
     // spawn a new task to store the data in cache.
+    STORING_EVENTS.store(true, Ordering::Relaxed);
     tokio::spawn(async move {
-        cache::events_cache::store_events(bus_sub)
+        let result = cache::events_cache::store_events(bus_sub)
             .await
             .map_err(|error| {
                 error!(%error, "Error while storing the events to cahce");
                 flush_traces();
                 error
-            })
+            });
+        STORING_EVENTS.store(false, Ordering::Relaxed);
+        result
     });
 
     // spawn a new task to update the config map from cache.
@@ -147,12 +172,21 @@ async fn main() -> errors::Result<()> {
 
     let app = move || {
         actix_web::App::new()
+            .app_data(web::Data::new(authentication.clone()))
+            .wrap(middleware::from_fn(authenticate))
             .wrap(middleware::Logger::default())
+            .configure(health_routes(storing_events))
             .configure(stats_route)
     };
 
-    HttpServer::new(app)
-        .bind(exporter::exporter_config::ExporterConfig::get_config().metrics_endpoint())
+    let metrics_endpoint =
+        exporter::exporter_config::ExporterConfig::get_config().metrics_endpoint();
+    let server = HttpServer::new(app).on_connect(on_connect);
+    let server = match tls_config {
+        Some(tls_config) => server.bind_rustls_021(metrics_endpoint, tls_config),
+        None => server.bind(metrics_endpoint),
+    };
+    server
         .context(errors::SocketBindingFailure)?
         .run()
         .await
@@ -170,6 +170,10 @@ pub enum Error {
     #[snafu(display("Error while binding socket {} ", source))]
     SocketBindingFailure { source: std::io::Error },
 
+    /// Could not set up the TLS or the authentication of the stats endpoint.
+    #[snafu(display("Error while configuring the stats server: {}", source))]
+    HttpServerConfig { source: http_server::Error },
+
     /// Could not parse prometheus output.
     #[snafu(display("Error while parsing prometheus output {} ", source))]
     PrometheusOutPutParseFailure { source: std::io::Error },
@@ -51,3 +51,57 @@ pub const CALL_HOME_ENDPOINT: &str = "https://openebs.phonehome.datacore.com/ope
 
 /// Label key containing controller revision hash for a controller resource for DaemonSets.
 pub const DS_CONTROLLER_REVISION_HASH_LABEL_KEY: &str = "controller-revision-hash";
+
+/// Name of the ConfigMap which holds the durable upgrade history record of a helm release.
+pub fn upgrade_history_configmap_name(release_name: &str) -> String {
+    format!("{release_name}-upgrade-history")
+}
+
+/// Key in the upgrade history ConfigMap's data, which maps to the list of history records.
+pub const UPGRADE_HISTORY_DATA_KEY: &str = "history";
+
+/// Name of the ConfigMap which holds the upgrade plan of a helm release, computed by a dry-run
+/// of the upgrade.
+pub fn upgrade_plan_configmap_name(release_name: &str) -> String {
+    format!("{release_name}-upgrade-plan")
+}
+
+/// Key in the upgrade plan ConfigMap's data, which maps to the upgrade plan.
+pub const UPGRADE_PLAN_DATA_KEY: &str = "plan";
+
+/// Key in the upgrade plan ConfigMap's data, which maps to the error which failed the upgrade
+/// plan, if any.
+pub const UPGRADE_PLAN_ERROR_KEY: &str = "error";
+
+/// Name of the Secret which holds the pre-upgrade backup of a helm release, taken before
+/// upgrading from the source version. Characters which are not allowed in Kubernetes object
+/// names, e.g. the '+' of semver build metadata, are replaced with '-'.
+pub fn upgrade_backup_secret_name(release_name: &str, source_version: &str) -> String {
+    let version: String = source_version
+        .to_lowercase()
+        .chars()
+        .map(
+            |c| match c.is_ascii_alphanumeric() || c == '-' || c == '.' {
+                true => c,
+                false => '-',
+            },
+        )
+        .collect();
+    format!("{release_name}-upgrade-backup-{version}")
+}
+
+/// Label key of the pre-upgrade backup Secrets, which maps to the helm release name.
+pub fn upgrade_backup_label_key() -> String {
+    format!("{PRODUCT_DOMAIN_NAME}/upgrade-backup")
+}
+
+/// Key in the pre-upgrade backup Secret's data, which maps to the release metadata, as JSON.
+pub const UPGRADE_BACKUP_RELEASE_KEY: &str = "release.json";
+
+/// Key in the pre-upgrade backup Secret's data, which maps to the gzip compressed helm values of
+/// the source version, as YAML.
+pub const UPGRADE_BACKUP_VALUES_KEY: &str = "values.yaml.gz";
+
+/// Key in the pre-upgrade backup Secret's data, which maps to the gzip compressed dump of the
+/// control-plane's keys in etcd, as JSON.
+pub const UPGRADE_BACKUP_ETCD_KEY: &str = "etcd.json.gz";
@@ -0,0 +1,59 @@
+use pstor::{Error, StoreKv};
+use serde_json::Value;
+
+/// This is the number of keys which are read from etcd in a single request.
+const ETCD_PAGED_LIMIT: i64 = 1000;
+
+/// Reads the keys which start with a prefix, and their values, from etcd a page at a time, so
+/// that large stores are not read in a single request.
+pub struct PagedReader {
+    next_key: String,
+    first: bool,
+    done: bool,
+}
+
+impl PagedReader {
+    /// Creates a PagedReader for the keys which start with the key prefix. Pages have up to
+    /// ETCD_PAGED_LIMIT keys.
+    pub fn new(key_prefix: &str) -> Self {
+        Self {
+            next_key: key_prefix.to_string(),
+            first: true,
+            done: false,
+        }
+    }
+
+    /// Returns the next page of keys and their values, or None when all of the keys have been
+    /// read.
+    pub async fn next_page<S>(
+        &mut self,
+        store: &mut S,
+    ) -> Result<Option<Vec<(String, Value)>>, Error>
+    where
+        S: StoreKv,
+    {
+        if self.done {
+            return Ok(None);
+        }
+
+        let mut page = store
+            .get_values_paged_all(self.next_key.as_str(), ETCD_PAGED_LIMIT)
+            .await?;
+        // Every page after the first one starts with the last key of the previous page.
+        if !self.first && !page.is_empty() {
+            page.remove(0);
+        }
+        self.first = false;
+
+        match page.last() {
+            Some((last_key, _)) => {
+                self.next_key = last_key.clone();
+                Ok(Some(page))
+            }
+            None => {
+                self.done = true;
+                Ok(None)
+            }
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use crate::{
+    error::{EmptyAuthToken, ReadFile, Result},
+    watch_files,
+};
+use actix_tls::accept::rustls_0_21::TlsStream;
+use actix_web::{
+    body::MessageBody,
+    dev::{Extensions, ServiceRequest, ServiceResponse},
+    error::ErrorUnauthorized,
+    http::header::AUTHORIZATION,
+    middleware::Next,
+    rt::net::TcpStream,
+    web,
+};
+use snafu::ResultExt;
+use std::{
+    any::Any,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+};
+use tracing::info;
+
+/// The paths which are served without authentication, so that these can be probed.
+const UNAUTHENTICATED_PATHS: [&str; 2] = ["/healthz", "/readyz"];
+
+/// Marks the connections on which the client presented a certificate. The certificate is
+/// verified during the TLS handshake, so it is valid if it is present.
+#[derive(Clone, Copy, Debug)]
+struct ClientCertificate;
+
+/// The authentication which the requests, other than those to the health endpoints, must pass.
+/// This must be added to the App's data, for the authenticate middleware to enforce it.
+#[derive(Clone, Debug, Default)]
+pub struct Authentication {
+    token: Option<Arc<RwLock<String>>>,
+    client_certificate: bool,
+}
+
+impl Authentication {
+    /// Returns the authentication with the bearer token from the token file, if any, which is
+    /// reloaded when the file changes. A client certificate is required as well, if
+    /// client_certificate is set.
+    pub(crate) fn new(token_file: Option<&Path>, client_certificate: bool) -> Result<Self> {
+        let token = match token_file {
+            Some(token_file) => {
+                let token = Arc::new(RwLock::new(read_token(token_file)?));
+
+                let (current, token_file) = (token.clone(), token_file.to_path_buf());
+                watch_files(vec![token_file.clone()], move || {
+                    let token = read_token(&token_file)?;
+                    if let Ok(mut current) = current.write() {
+                        *current = token;
+                    }
+                    info!(token_file = %token_file.display(), "Reloaded the bearer token");
+                    Ok(())
+                });
+                Some(token)
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            token,
+            client_certificate,
+        })
+    }
+
+    /// Checks that the request presents the credentials which are required, if any.
+    fn verify(&self, request: &ServiceRequest) -> Result<(), &'static str> {
+        if self.client_certificate && request.conn_data::<ClientCertificate>().is_none() {
+            return Err("A client certificate is required");
+        }
+
+        if let Some(token) = &self.token {
+            let presented = request
+                .headers()
+                .get(AUTHORIZATION)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.strip_prefix("Bearer "))
+                .ok_or("A bearer token is required")?;
+            let token = token
+                .read()
+                .map_err(|_| "The bearer token is unavailable")?;
+            if !constant_time_eq(presented.as_bytes(), token.as_bytes()) {
+                return Err("The bearer token is not valid");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Middleware which rejects the requests, other than those to the health endpoints, which do not
+/// pass the Authentication in the App's data. All requests pass if there is none.
+pub async fn authenticate(
+    request: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    if !UNAUTHENTICATED_PATHS.contains(&request.path()) {
+        if let Some(authentication) = request.app_data::<web::Data<Authentication>>() {
+            authentication.verify(&request).map_err(ErrorUnauthorized)?;
+        }
+    }
+    next.call(request).await
+}
+
+/// Records whether the client presented a certificate on the connection, for the authentication
+/// to check. This must be set as the HttpServer's on_connect callback, if client certificates
+/// are required.
+pub fn on_connect(connection: &dyn Any, extensions: &mut Extensions) {
+    if let Some(tls_stream) = connection.downcast_ref::<TlsStream<TcpStream>>() {
+        if tls_stream.get_ref().1.peer_certificates().is_some() {
+            extensions.insert(ClientCertificate);
+        }
+    }
+}
+
+/// Reads the bearer token from the file, without the surrounding whitespace.
+fn read_token(token_file: &Path) -> Result<String> {
+    let token = std::fs::read_to_string(token_file)
+        .context(ReadFile {
+            path: PathBuf::from(token_file),
+        })?
+        .trim()
+        .to_string();
+    if token.is_empty() {
+        return EmptyAuthToken { path: token_file }.fail();
+    }
+    Ok(token)
+}
+
+/// Compares the bytes in a time which does not depend on where they differ, so that the token
+/// cannot be guessed a byte at a time.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::constant_time_eq;
+
+    #[test]
+    fn token_comparison() {
+        assert!(constant_time_eq(b"secret-token", b"secret-token"));
+        assert!(!constant_time_eq(b"secret-token", b"secret-tokem"));
+        assert!(!constant_time_eq(b"secret-token", b"secret"));
+        assert!(!constant_time_eq(b"", b"secret"));
+    }
+}
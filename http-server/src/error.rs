@@ -0,0 +1,43 @@
+use snafu::Snafu;
+use std::path::PathBuf;
+
+/// Errors while setting up the TLS and the authentication of an HTTP server.
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)), context(suffix(false)))]
+pub enum Error {
+    /// Error reading a file.
+    #[snafu(display("Failed to read '{}': {}", path.display(), source))]
+    ReadFile {
+        source: std::io::Error,
+        path: PathBuf,
+    },
+
+    /// The certificate file has no certificates.
+    #[snafu(display("No PEM encoded certificates found in '{}'", path.display()))]
+    NoCertificates { path: PathBuf },
+
+    /// The key file has no private key.
+    #[snafu(display("No PEM encoded private key found in '{}'", path.display()))]
+    NoPrivateKey { path: PathBuf },
+
+    /// The private key is of a type which is not supported.
+    #[snafu(display("Unsupported private key in '{}': {}", path.display(), source))]
+    UnsupportedPrivateKey {
+        source: rustls::sign::SignError,
+        path: PathBuf,
+    },
+
+    /// A client CA certificate is not valid.
+    #[snafu(display("Invalid client CA certificate in '{}': {}", path.display(), source))]
+    InvalidClientCa {
+        source: rustls::Error,
+        path: PathBuf,
+    },
+
+    /// The token file is empty.
+    #[snafu(display("The bearer token file '{}' is empty", path.display()))]
+    EmptyAuthToken { path: PathBuf },
+}
+
+/// Result with the Error of this crate.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
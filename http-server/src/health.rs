@@ -0,0 +1,22 @@
+use actix_web::{web, HttpResponse};
+
+/// Returns the configuration of the /healthz and /readyz endpoints. The server is healthy as
+/// long as it responds, and is ready when ready returns true. These are served without
+/// authentication, so that these can be probed.
+pub fn health_routes(ready: fn() -> bool) -> impl FnOnce(&mut web::ServiceConfig) {
+    move |cfg| {
+        cfg.route(
+            "/healthz",
+            web::get().to(|| async { HttpResponse::Ok().body("ok") }),
+        );
+        cfg.route(
+            "/readyz",
+            web::get().to(move || async move {
+                match ready() {
+                    true => HttpResponse::Ok().body("ok"),
+                    false => HttpResponse::ServiceUnavailable().body("not ready"),
+                }
+            }),
+        );
+    }
+}
@@ -0,0 +1,106 @@
+//! Optional TLS, with certificate reload, bearer token or mTLS client authentication, and health
+//! endpoints for the HTTP servers which expose metrics.
+
+/// Bearer token and client certificate authentication.
+mod auth;
+/// Error module.
+mod error;
+/// Health endpoints.
+mod health;
+/// TLS configuration, and the reload of the certificate.
+mod tls;
+
+pub use auth::{authenticate, on_connect, Authentication};
+pub use error::{Error, Result};
+pub use health::health_routes;
+
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+use tracing::warn;
+
+/// Interval at which the certificate and the token files are checked for changes.
+const RELOAD_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Command line arguments of the TLS and the authentication of an HTTP server.
+#[derive(clap::Args, Debug, Clone, Default)]
+pub struct ServerArgs {
+    /// Path to the PEM encoded certificate chain to serve HTTPS with. The certificate and its key
+    /// are reloaded when these change. Plain HTTP is served if this is not set.
+    #[clap(long, requires = "tls_key_file")]
+    pub tls_cert_file: Option<PathBuf>,
+
+    /// Path to the PEM encoded private key of the certificate.
+    #[clap(long, requires = "tls_cert_file")]
+    pub tls_key_file: Option<PathBuf>,
+
+    /// Path to the PEM encoded CA certificates which client certificates are verified with. The
+    /// requests, other than those to the health endpoints, must present a client certificate
+    /// signed by these, if this is set.
+    #[clap(long, requires = "tls_cert_file")]
+    pub tls_client_ca_file: Option<PathBuf>,
+
+    /// Path to a file with the bearer token which the requests, other than those to the health
+    /// endpoints, must present, if this is set. The token is reloaded when the file changes.
+    #[clap(long)]
+    pub auth_token_file: Option<PathBuf>,
+}
+
+impl ServerArgs {
+    /// Returns the rustls configuration to serve HTTPS with, if TLS is enabled. This starts the
+    /// reload of the certificate, so it must be called from within a tokio runtime.
+    pub fn tls_config(&self) -> Result<Option<rustls::ServerConfig>> {
+        match (&self.tls_cert_file, &self.tls_key_file) {
+            (Some(cert_file), Some(key_file)) => {
+                tls::server_config(cert_file, key_file, self.tls_client_ca_file.as_deref())
+                    .map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Returns the authentication which the requests must pass. This starts the reload of the
+    /// token, so it must be called from within a tokio runtime.
+    pub fn authentication(&self) -> Result<Authentication> {
+        Authentication::new(
+            self.auth_token_file.as_deref(),
+            self.tls_client_ca_file.is_some(),
+        )
+    }
+}
+
+/// Calls reload every time any of the files is modified, until reload fails. A failed reload is
+/// retried at the next interval, e.g. for when only some of the files were updated.
+fn watch_files<F>(paths: Vec<PathBuf>, reload: F)
+where
+    F: Fn() -> Result<()> + Send + 'static,
+{
+    let modified = |paths: &[PathBuf]| -> Vec<Option<SystemTime>> {
+        paths.iter().map(|path| modified_time(path)).collect()
+    };
+    let mut last_loaded = modified(&paths);
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(RELOAD_INTERVAL);
+        loop {
+            ticker.tick().await;
+            let current = modified(&paths);
+            if current == last_loaded {
+                continue;
+            }
+            match reload() {
+                Ok(()) => last_loaded = current,
+                Err(error) => warn!(%error, "Failed to reload, retrying at the next interval"),
+            }
+        }
+    });
+}
+
+/// Returns the modification time of the file, following symlinks, as the files of Kubernetes
+/// Secret volumes are symlinks which are swapped on update.
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
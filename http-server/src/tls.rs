@@ -0,0 +1,111 @@
+use crate::{
+    error::{
+        InvalidClientCa, NoCertificates, NoPrivateKey, ReadFile, Result, UnsupportedPrivateKey,
+    },
+    watch_files,
+};
+use rustls::{
+    server::{AllowAnyAnonymousOrAuthenticatedClient, ClientHello, ResolvesServerCert},
+    sign::CertifiedKey,
+    Certificate, PrivateKey, RootCertStore, ServerConfig,
+};
+use rustls_pemfile::Item;
+use snafu::ResultExt;
+use std::{
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+};
+use tracing::info;
+
+/// Resolves the server certificate to the one which was last loaded from its files.
+struct ReloadingCertResolver {
+    certified_key: RwLock<Arc<CertifiedKey>>,
+}
+
+impl ResolvesServerCert for ReloadingCertResolver {
+    fn resolve(&self, _client_hello: ClientHello) -> Option<Arc<CertifiedKey>> {
+        self.certified_key.read().ok().map(|key| key.clone())
+    }
+}
+
+/// Returns the rustls configuration with the certificate and the key, which are reloaded when
+/// either file changes. Client certificates are verified with the client CA certificates, if
+/// any. The clients may still connect without a certificate, so that the health endpoints can be
+/// probed, and the requests to the other endpoints are rejected by the authentication.
+pub(crate) fn server_config(
+    cert_file: &Path,
+    key_file: &Path,
+    client_ca_file: Option<&Path>,
+) -> Result<ServerConfig> {
+    let resolver = Arc::new(ReloadingCertResolver {
+        certified_key: RwLock::new(Arc::new(load_certified_key(cert_file, key_file)?)),
+    });
+
+    let builder = ServerConfig::builder().with_safe_defaults();
+    let builder = match client_ca_file {
+        Some(client_ca_file) => builder.with_client_cert_verifier(
+            AllowAnyAnonymousOrAuthenticatedClient::new(load_client_ca(client_ca_file)?).boxed(),
+        ),
+        None => builder.with_no_client_auth(),
+    };
+    let config = builder.with_cert_resolver(resolver.clone());
+
+    let (cert_file, key_file) = (cert_file.to_path_buf(), key_file.to_path_buf());
+    watch_files(vec![cert_file.clone(), key_file.clone()], move || {
+        let certified_key = load_certified_key(&cert_file, &key_file)?;
+        if let Ok(mut current) = resolver.certified_key.write() {
+            *current = Arc::new(certified_key);
+        }
+        info!(cert_file = %cert_file.display(), "Reloaded the TLS certificate");
+        Ok(())
+    });
+
+    Ok(config)
+}
+
+/// Loads the certificate chain and its private key.
+fn load_certified_key(cert_file: &Path, key_file: &Path) -> Result<CertifiedKey> {
+    let certs = load_certs(cert_file)?;
+
+    let key = rustls_pemfile::read_all(&mut open(key_file)?)
+        .context(ReadFile { path: key_file })?
+        .into_iter()
+        .find_map(|item| match item {
+            Item::PKCS8Key(key) | Item::RSAKey(key) | Item::ECKey(key) => Some(PrivateKey(key)),
+            _ => None,
+        })
+        .ok_or_else(|| NoPrivateKey { path: key_file }.build())?;
+    let signing_key =
+        rustls::sign::any_supported_type(&key).context(UnsupportedPrivateKey { path: key_file })?;
+
+    Ok(CertifiedKey::new(certs, signing_key))
+}
+
+/// Loads the CA certificates which the client certificates are verified with.
+fn load_client_ca(client_ca_file: &Path) -> Result<RootCertStore> {
+    let mut roots = RootCertStore::empty();
+    for cert in load_certs(client_ca_file)? {
+        roots.add(&cert).context(InvalidClientCa {
+            path: client_ca_file,
+        })?;
+    }
+    Ok(roots)
+}
+
+/// Loads the PEM encoded certificates from the file. There must be at least one.
+fn load_certs(path: &Path) -> Result<Vec<Certificate>> {
+    let certs = rustls_pemfile::certs(&mut open(path)?).context(ReadFile { path })?;
+    if certs.is_empty() {
+        return NoCertificates { path }.fail();
+    }
+    Ok(certs.into_iter().map(Certificate).collect())
+}
+
+/// Opens the file for buffered reads.
+fn open(path: &Path) -> Result<BufReader<File>> {
+    File::open(path).map(BufReader::new).context(ReadFile {
+        path: PathBuf::from(path),
+    })
+}
@@ -94,7 +94,9 @@ impl ExecuteOperation for Operations {
                 GetResourcesK8s::Rest(resource) => resource.execute(cli_args).await?,
                 GetResourcesK8s::UpgradeStatus(resources) => {
                     // todo: use generic execute trait
-                    resources.get_upgrade(&cli_args.namespace).await?
+                    resources
+                        .get_upgrade(&cli_args.namespace, &cli_args.output)
+                        .await?
                 }
             },
             Operations::Drain(resource) => resource.execute(cli_args).await?,
@@ -113,14 +115,20 @@ impl ExecuteOperation for Operations {
             }
             Operations::Upgrade(resources) => {
                 // todo: use generic execute trait
-                preflight_validations::preflight_check(
-                    &cli_args.namespace,
-                    cli_args.kube_config_path.clone(),
-                    cli_args.timeout,
-                    resources,
-                )
-                .await?;
-                resources.execute(&cli_args.namespace).await?
+                // The preflight checks are only for upgrades, not for inspecting them.
+                if resources.command.is_none() {
+                    preflight_validations::preflight_check(
+                        &cli_args.namespace,
+                        cli_args.kube_config_path.clone(),
+                        cli_args.timeout,
+                        resources,
+                        &cli_args.output,
+                    )
+                    .await?;
+                }
+                resources
+                    .execute(&cli_args.namespace, &cli_args.output)
+                    .await?
             }
             Operations::Delete(resource) => match resource {
                 // todo: use generic execute trait
@@ -28,9 +28,6 @@ pub(crate) const CSI_NODE_SERVICE: &str = "csi-node";
 /// Defines the name of the etcd service
 pub(crate) const ETCD_SERVICE: &str = "etcd";
 
-/// Defines the name of the etcd service
-pub(crate) const ETCD_PAGED_LIMIT: i64 = 1000;
-
 /// Defines the name of mayastor service
 pub(crate) const MAYASTOR_SERVICE: &str = "io-engine";
 
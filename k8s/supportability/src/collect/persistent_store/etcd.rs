@@ -1,7 +1,6 @@
-use crate::collect::{
-    constants::ETCD_PAGED_LIMIT, k8s_resources::client::ClientSet, persistent_store::EtcdError,
-};
-use pstor::{etcd, StoreKv, API_VERSION};
+use crate::collect::{k8s_resources::client::ClientSet, persistent_store::EtcdError};
+use etcd_dump::PagedReader;
+use pstor::{etcd, API_VERSION};
 use std::{io::Write, path::PathBuf};
 
 /// EtcdStore is used to abstract connection to etcd database for dumping the contents
@@ -53,8 +52,6 @@ impl EtcdStore {
         working_dir: PathBuf,
         stdout: bool,
     ) -> Result<(), EtcdError> {
-        let mut prefix = &self.key_prefix;
-
         let mut etcd_dump_file = match stdout {
             false => {
                 let file_name = "etcd_dump".to_string();
@@ -64,19 +61,9 @@ impl EtcdStore {
             true => None,
         };
 
-        let mut first = true;
-        let mut dump;
-        loop {
-            dump = self
-                .etcd
-                .get_values_paged_all(prefix, ETCD_PAGED_LIMIT)
-                .await?;
-            if !first && !dump.is_empty() {
-                dump.remove(0);
-            }
-            first = false;
-
-            for val in dump.iter_mut() {
+        let mut reader = PagedReader::new(self.key_prefix.as_str());
+        while let Some(dump) = reader.next_page(&mut self.etcd).await? {
+            for val in dump.iter() {
                 // unwrap or default because we dont want the code to panic in case of errors. need
                 // to write all data to file even if parsing of one value fails.
                 let pretty_json = serde_json::to_string_pretty(&val.1).unwrap_or_default();
@@ -92,12 +79,6 @@ impl EtcdStore {
             if let Some(etcd_dump_file) = &mut etcd_dump_file {
                 etcd_dump_file.flush()?;
             }
-
-            if let Some((key, _)) = dump.last() {
-                prefix = key;
-            } else {
-                break;
-            }
         }
 
         Ok(())
@@ -16,6 +16,9 @@ pub(crate) const IO_ENGINE_LABEL: &str = "app=io-engine";
 /// This is the shared Pod label of the <helm-release>-agent-core Deployment.
 pub(crate) const AGENT_CORE_LABEL: &str = "app=agent-core";
 
+/// This is the shared Pod label of the <helm-release>-csi-node DaemonSet.
+pub(crate) const CSI_NODE_LABEL: &str = "app=csi-node";
+
 /// This is the label set on a storage API Node resource when a 'Node Drain' is issued.
 pub fn drain_for_upgrade() -> String {
     format!("{CORE_CHART_NAME}-upgrade")
@@ -37,20 +40,15 @@ pub(crate) const KUBE_API_PAGE_SIZE: u32 = 500;
 pub(crate) const PARTIAL_REBUILD_DISABLE_EXTENTS: (Version, Version) =
     (Version::new(2, 2, 0), Version::new(2, 5, 0));
 
-/// Version value for the earliest possible 2.0 release.
-pub(crate) const TWO_DOT_O_RC_ONE: &str = "2.0.0-rc.1";
-
-/// Version value for the earliest possible 2.1 release (there were no pre-releases).
-pub(crate) const TWO_DOT_ONE: &str = "2.1.0";
-
-/// Version value for the earliest possible 2.3 release (there were no pre-releases).
-pub(crate) const TWO_DOT_THREE: &str = "2.3.0";
+/// This is the port of the etcd client Service, if it isn't set in the helm values.
+pub(crate) const DEFAULT_ETCD_PORT: u64 = 2379;
 
-/// Version value for the earliest possible 2.4 release (there were no pre-releases).
-pub(crate) const TWO_DOT_FOUR: &str = "2.4.0";
+/// This is the maximum size of the data of a Kubernetes Secret.
+pub(crate) const SECRET_DATA_SIZE_LIMIT: usize = 1024 * 1024;
 
-/// Version value for the earliest possible 2.5 release.
-pub(crate) const TWO_DOT_FIVE: &str = "2.5.0";
+/// This is the number of times the post-upgrade verification checks are run, while the upgraded
+/// components settle, before the regressions are reported.
+pub(crate) const POST_UPGRADE_VERIFICATION_ATTEMPTS: u32 = 30;
 
-/// Version value for the earliest possible 2.6 release.
-pub(crate) const TWO_DOT_SIX: &str = "2.6.0";
+/// This is the number of seconds between the post-upgrade verification attempts.
+pub(crate) const POST_UPGRADE_VERIFICATION_INTERVAL_SECS: u64 = 10;
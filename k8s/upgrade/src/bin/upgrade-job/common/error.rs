@@ -3,11 +3,9 @@ use crate::{
         product_train, CORE_CHART_NAME, UMBRELLA_CHART_NAME, UMBRELLA_CHART_UPGRADE_DOCS_URL,
     },
     events::event_recorder::EventNote,
-    helm::chart::PromtailConfigClient,
 };
-use k8s_openapi::api::core::v1::Container;
 use snafu::Snafu;
-use std::path::PathBuf;
+use std::{path::PathBuf, time::Duration};
 use url::Url;
 
 /// For use with multiple fallible operations which may fail for different reasons, but are
@@ -79,26 +77,6 @@ pub(crate) enum Error {
     #[snafu(display("No helm storage driver specified"))]
     NoHelmStorageDriver,
 
-    /// Error for when there's too few or too many helm secrets for a release in a namespace.
-    #[snafu(display(
-        "'{count}' is an invalid no. of helm Secrets for release '{release_name}' in namespace '{namespace}'"
-    ))]
-    InvalidNoOfHelmSecrets {
-        release_name: String,
-        namespace: String,
-        count: usize,
-    },
-
-    /// Error for when there's too few or too many helm configmaps for a release in a namespace.
-    #[snafu(display(
-        "'{count}' is an invalid no. of helm ConfigMaps for release '{release_name}' in namespace '{namespace}'"
-    ))]
-    InvalidNoOfHelmConfigMaps {
-        release_name: String,
-        namespace: String,
-        count: usize,
-    },
-
     /// Error for when there's no data in helm storage driver.
     #[snafu(display("No data in helm {driver}"))]
     HelmStorageNoData { driver: &'static str },
@@ -321,6 +299,12 @@ pub(crate) enum Error {
         source: openapi::tower::client::Error<openapi::models::RestJsonError>,
     },
 
+    /// Error for when a GET request for a list of storage pools fails.
+    #[snafu(display("Failed to list {} Pools: {source}", product_train()))]
+    ListStoragePools {
+        source: openapi::tower::client::Error<openapi::models::RestJsonError>,
+    },
+
     /// Error for when a storage node drain request fails.
     #[snafu(display("Failed to drain {} Node {node_id}: {source}", product_train()))]
     DrainStorageNode {
@@ -354,16 +338,6 @@ pub(crate) enum Error {
     ))]
     HelmChartNameSplit { chart_name: String, delimiter: char },
 
-    /// Error for when a Helm list command execution succeeds, but with an error.
-    #[snafu(display(
-        "`helm list` command return an error,\ncommand: {command},\nargs: {args:?},\nstd_err: {std_err}",
-    ))]
-    HelmListCommand {
-        command: String,
-        args: Vec<String>,
-        std_err: String,
-    },
-
     /// Error for when a Helm version command execution succeeds, but with an error.
     #[snafu(display(
         "`helm version` command return an error,\ncommand: {command},\nargs: {args:?},\nstd_err: {std_err}",
@@ -384,16 +358,6 @@ pub(crate) enum Error {
         std_err: String,
     },
 
-    /// Error for when a Helm get values command execution succeeds, but with an error.
-    #[snafu(display(
-        "`helm get values` command return an error,\ncommand: {command},\nargs: {args:?},\nstd_err: {std_err}",
-    ))]
-    HelmGetValuesCommand {
-        command: String,
-        args: Vec<String>,
-        std_err: String,
-    },
-
     /// Error for when detected helm chart name is not known helm chart.
     #[snafu(display(
         "'{chart_name}' is not a known {} helm chart, only helm charts '{CORE_CHART_NAME}-<version-tag>' and '{UMBRELLA_CHART_NAME}-<version-tag>' are supported",
@@ -441,42 +405,6 @@ pub(crate) enum Error {
         note: EventNote,
     },
 
-    /// Error in serializing a helm::chart::PromtailConfigClient to a JSON string.
-    #[snafu(display(
-        "Failed to serialize .loki-stack.promtail.config.client {object:?}: {source}",
-    ))]
-    SerializePromtailConfigClientToJson {
-        source: serde_json::Error,
-        object: PromtailConfigClient,
-    },
-
-    /// Error in serializing a k8s_openapi::api::core::v1::Container to a JSON string.
-    #[snafu(display(
-        "Failed to serialize .loki-stack.promtail.initContainer {object:?}: {source}",
-    ))]
-    SerializePromtailInitContainerToJson {
-        source: serde_json::Error,
-        object: Container,
-    },
-
-    /// Error in deserializing a promtail helm chart's deprecated extraClientConfig to a
-    /// serde_json::Value.
-    #[snafu(display(
-        "Failed to deserialize .loki-stack.promtail.config.snippets.extraClientConfig to a serde_json::Value {config}: {source}",
-    ))]
-    DeserializePromtailExtraConfig {
-        source: serde_yaml::Error,
-        config: String,
-    },
-
-    /// Error in serializing a promtail helm chart's deprecated extraClientConfig, in a
-    /// serde_json::Value, to JSON.
-    #[snafu(display("Failed to serialize to JSON {config:?}: {source}"))]
-    SerializePromtailExtraConfigToJson {
-        source: serde_json::Error,
-        config: serde_json::Value,
-    },
-
     /// Error in serializing the deprecated config.snippets.extraClientConfig from the promtail
     /// helm chart v3.11.0.
     #[snafu(display("Failed to serialize object to a serde_json::Value {object}: {source}",))]
@@ -500,7 +428,7 @@ pub(crate) enum Error {
 
     /// Error for the Umbrella chart is not upgraded.
     #[snafu(display(
-        "The '{UMBRELLA_CHART_NAME}' helm chart is not upgraded to a version with '{CORE_CHART_NAME}' dependency helm chart version '{target_version}': Upgrade for helm chart {UMBRELLA_CHART_NAME} is not supported, refer to the instructions at {UMBRELLA_CHART_UPGRADE_DOCS_URL} to upgrade your release of the '{UMBRELLA_CHART_NAME}' helm chart.",
+        "The '{UMBRELLA_CHART_NAME}' helm chart is not upgraded to a version with '{CORE_CHART_NAME}' dependency helm chart version '{target_version}': the '{UMBRELLA_CHART_NAME}' helm chart directory is not available to upgrade it, set the umbrella chart version to pull it from the helm chart repository, or refer to the instructions at {UMBRELLA_CHART_UPGRADE_DOCS_URL} to upgrade your release of the '{UMBRELLA_CHART_NAME}' helm chart.",
     ))]
     UmbrellaChartNotUpgraded { target_version: String },
 
@@ -523,40 +451,6 @@ pub(crate) enum Error {
         target_version: String,
     },
 
-    /// Error for when yq command execution fails.
-    #[snafu(display(
-        "Failed to run yq command,\ncommand: {command},\nargs: {args:?},\ncommand_error: {source}",
-    ))]
-    YqCommandExec {
-        source: std::io::Error,
-        command: String,
-        args: Vec<String>,
-    },
-
-    /// Error for when the `yq -V` command returns an error.
-    #[snafu(display(
-        "`yq -V` command return an error,\ncommand: {command},\narg: {arg},\nstd_err: {std_err}",
-    ))]
-    YqVersionCommand {
-        command: String,
-        arg: String,
-        std_err: String,
-    },
-
-    /// Error for when the `yq eq` command returns an error.
-    #[snafu(display(
-        "`yq ea` command return an error,\ncommand: {command},\nargs: {args:?},\nstd_err: {std_err}",
-    ))]
-    YqMergeCommand {
-        command: String,
-        args: Vec<String>,
-        std_err: String,
-    },
-
-    /// Error for when the yq version present is not v4.x.y.
-    #[snafu(display("yq version is not v4"))]
-    NotYqV4,
-
     /// Error for when temporary file creation fails.
     #[snafu(display("Failed to create temporary file: {source}"))]
     TempFileCreation { source: std::io::Error },
@@ -572,46 +466,6 @@ pub(crate) enum Error {
     #[snafu(display("{key} is not a valid yaml key for a string value"))]
     NotAValidYamlKeyForStringValue { key: String },
 
-    /// Error for when the yq command to update the value of a yaml field returns an error.
-    #[snafu(display(
-        "`yq` set-value-command returned an error,\ncommand: {command},\nargs: {args:?},\nstd_err: {std_err}",
-    ))]
-    YqSetCommand {
-        command: String,
-        args: Vec<String>,
-        std_err: String,
-    },
-
-    /// Error for when the yq command to delete an object path returns an error.
-    #[snafu(display(
-        "`yq` delete-object-command returned an error,\ncommand: {command},\nargs: {args:?},\nstd_err: {std_err}",
-    ))]
-    YqDeleteObjectCommand {
-        command: String,
-        args: Vec<String>,
-        std_err: String,
-    },
-
-    /// Error for when the yq command to append to an array returns an error.
-    #[snafu(display(
-        "`yq` append-to-array-command returned an error,\ncommand: {command},\nargs: {args:?},\nstd_err: {std_err}",
-    ))]
-    YqAppendToArrayCommand {
-        command: String,
-        args: Vec<String>,
-        std_err: String,
-    },
-
-    /// Error for when the yq command to append to an object returns an error.
-    #[snafu(display(
-        "`yq` append-to-object-command returned an error,\ncommand: {command},\nargs: {args:?},\nstd_err: {std_err}",
-    ))]
-    YqAppendToObjectCommand {
-        command: String,
-        args: Vec<String>,
-        std_err: String,
-    },
-
     #[snafu(display("failed to list CustomResourceDefinitions: {source}"))]
     ListCrds { source: kube::Error },
 
@@ -663,6 +517,368 @@ pub(crate) enum Error {
     /// entry for the CORE_CHART version.
     #[snafu(display("Helm release data doesn't have chart version or contains an invalid version for dependency chart '{CORE_CHART_NAME}'"))]
     InvalidDependencyVersionInHelmReleaseData,
+
+    /// Error for when a Kubernetes API request for GET-ing the upgrade history ConfigMap fails.
+    #[snafu(display(
+        "Failed to GET upgrade history ConfigMap '{name}' in namespace '{namespace}': {source}"
+    ))]
+    GetUpgradeHistory {
+        source: kube::Error,
+        name: String,
+        namespace: String,
+    },
+
+    /// Error for when a Kubernetes API request for creating the upgrade history ConfigMap fails.
+    #[snafu(display(
+        "Failed to create upgrade history ConfigMap '{name}' in namespace '{namespace}': {source}"
+    ))]
+    CreateUpgradeHistory {
+        source: kube::Error,
+        name: String,
+        namespace: String,
+    },
+
+    /// Error for when a Kubernetes API request for replacing the upgrade history ConfigMap fails.
+    #[snafu(display(
+        "Failed to replace upgrade history ConfigMap '{name}' in namespace '{namespace}': {source}"
+    ))]
+    ReplaceUpgradeHistory {
+        source: kube::Error,
+        name: String,
+        namespace: String,
+    },
+
+    /// Error for when the upgrade history records could not be serialized to JSON.
+    #[snafu(display("Failed to serialize upgrade history records to JSON: {source}"))]
+    SerializeUpgradeHistory { source: serde_json::Error },
+
+    /// Error for when the upgrade history records could not be deserialized from JSON.
+    #[snafu(display("Failed to deserialize upgrade history records from JSON: {source}"))]
+    DeserializeUpgradeHistory { source: serde_json::Error },
+
+    /// Error for when a value could not be serialized to YAML.
+    #[snafu(display("Failed to serialize to YAML: {source}"))]
+    SerializeYamlValue { source: serde_yaml::Error },
+
+    /// Error for when a YAML key's path runs through a value which is neither an object nor an
+    /// array, or uses an array index on an object or an object key on an array.
+    #[snafu(display("Failed to traverse YAML path {key}: path does not match the document"))]
+    YamlPathNotTraversable { key: String },
+
+    /// Error for when a YAML key's array index is out of bounds.
+    #[snafu(display(
+        "Failed to traverse YAML path {key}: index {index} is out of bounds for array of length {length}"
+    ))]
+    YamlArrayIndexOutOfBounds {
+        key: String,
+        index: usize,
+        length: usize,
+    },
+
+    /// Error for when appending to a YAML value which is not an array.
+    #[snafu(display("Failed to append to YAML path {key}: value is not an array"))]
+    YamlAppendToNonArray { key: String },
+
+    /// Error for when appending to a YAML value which is neither an object nor an array.
+    #[snafu(display("Failed to append to YAML path {key}: value is not an object"))]
+    YamlAppendToNonObject { key: String },
+
+    /// Error for when the embedded helm values migrations yaml could not be parsed.
+    #[snafu(display("Failed to parse helm values migrations yaml: {source}"))]
+    YamlParseBufferForValuesMigrations { source: serde_yaml::Error },
+
+    /// Error for when a yaml string value could not be parsed as yaml.
+    #[snafu(display("Failed to parse the string value for YAML path {key} as yaml: {source}"))]
+    DeserializeYamlStringValue {
+        source: serde_yaml::Error,
+        key: String,
+    },
+
+    /// Error for when the Chart.yaml file of the Umbrella helm chart does not have a valid
+    /// version for the Core helm chart dependency.
+    #[snafu(display("Helm chart file {} doesn't have a valid version for dependency chart '{CORE_CHART_NAME}'", filepath.display()))]
+    InvalidDependencyVersionInChartYaml { filepath: PathBuf },
+
+    /// Error for when the Umbrella helm chart's Core helm chart dependency is not the Core helm
+    /// chart which is available to this binary.
+    #[snafu(display(
+        "The '{UMBRELLA_CHART_NAME}' helm chart depends on '{CORE_CHART_NAME}' helm chart version '{dependency_version}', but the '{CORE_CHART_NAME}' helm chart version '{core_chart_version}' is available for upgrade"
+    ))]
+    UmbrellaChartCoreDependencyMismatch {
+        dependency_version: String,
+        core_chart_version: String,
+    },
+
+    /// Error for when temporary directory creation fails.
+    #[snafu(display("Failed to create temporary directory: {source}"))]
+    TempDirCreation { source: std::io::Error },
+
+    /// Error for when the helm pull command execution succeeds, but with an error.
+    #[snafu(display(
+        "`helm pull` command return an error,\ncommand: {command},\nargs: {args:?},\nstd_err: {std_err}",
+    ))]
+    HelmPullCommand {
+        command: String,
+        args: Vec<String>,
+        std_err: String,
+    },
+
+    /// Error for when the helm command which logs in to a helm chart repository fails.
+    #[snafu(display(
+        "Helm chart repository login command return an error,\ncommand: {command},\nargs: {args:?},\nstd_err: {std_err}",
+    ))]
+    HelmRepoLoginCommand {
+        command: String,
+        args: Vec<String>,
+        std_err: String,
+    },
+
+    /// Error for when the helm chart tarball is absent after a helm pull.
+    #[snafu(display("Failed to find helm chart tarball {} after helm pull", filepath.display()))]
+    ChartTarballNotFound { filepath: PathBuf },
+
+    /// Error for when the digest of the helm chart tarball is not the expected one.
+    #[snafu(display(
+        "The sha256 digest of helm chart tarball {} is '{actual}', expected '{expected}'",
+        filepath.display()
+    ))]
+    ChartDigestMismatch {
+        filepath: PathBuf,
+        expected: String,
+        actual: String,
+    },
+
+    /// Error for when neither a digest nor a keyring is available to verify the helm chart, and
+    /// unverified helm charts are not allowed.
+    #[snafu(display(
+        "Neither a digest nor a keyring is available to verify the '{chart_name}' helm chart version '{version}': set the expected digest or a keyring in the helm chart repository's credentials, or allow unverified helm charts explicitly"
+    ))]
+    UnverifiableChart { chart_name: String, version: String },
+
+    /// Error for when the helm chart tarball could not be extracted.
+    #[snafu(display("Failed to extract helm chart tarball {}: {source}", filepath.display()))]
+    ExtractChartTarball {
+        source: std::io::Error,
+        filepath: PathBuf,
+    },
+
+    /// Error for when the version of the pulled helm chart is not the requested version.
+    #[snafu(display(
+        "The pulled '{CORE_CHART_NAME}' helm chart has version '{actual}', expected '{expected}'"
+    ))]
+    PulledChartVersionMismatch { expected: String, actual: String },
+
+    /// Error for when the chart repository is not set for the HelmChartPuller.
+    #[snafu(display("Helm chart repository is not set for helm pull"))]
+    HelmChartPullerOptionRepoAbsent,
+
+    /// Error for when the chart version is not set for the HelmChartPuller.
+    #[snafu(display("Helm chart version is not set for helm pull"))]
+    HelmChartPullerOptionVersionAbsent,
+    /// Error for when the output of 'helm upgrade --dry-run -o json' could not be deserialized.
+    #[snafu(display("Failed to deserialize helm upgrade dry-run output from JSON: {source}"))]
+    DeserializeHelmUpgradeDryRun { source: serde_json::Error },
+
+    /// Error for when a helm release's manifest is not valid YAML.
+    #[snafu(display("Failed to parse helm release manifest YAML: {source}"))]
+    ParseHelmManifest { source: serde_yaml::Error },
+
+    /// Error for when a Kubernetes API request for GET-ing the upgrade plan ConfigMap fails.
+    #[snafu(display(
+        "Failed to GET upgrade plan ConfigMap '{name}' in namespace '{namespace}': {source}"
+    ))]
+    GetUpgradePlan {
+        source: kube::Error,
+        name: String,
+        namespace: String,
+    },
+
+    /// Error for when a Kubernetes API request for creating the upgrade plan ConfigMap fails.
+    #[snafu(display(
+        "Failed to create upgrade plan ConfigMap '{name}' in namespace '{namespace}': {source}"
+    ))]
+    CreateUpgradePlan {
+        source: kube::Error,
+        name: String,
+        namespace: String,
+    },
+
+    /// Error for when a Kubernetes API request for replacing the upgrade plan ConfigMap fails.
+    #[snafu(display(
+        "Failed to replace upgrade plan ConfigMap '{name}' in namespace '{namespace}': {source}"
+    ))]
+    ReplaceUpgradePlan {
+        source: kube::Error,
+        name: String,
+        namespace: String,
+    },
+
+    /// Error for when the upgrade plan could not be serialized to JSON.
+    #[snafu(display("Failed to serialize upgrade plan to JSON: {source}"))]
+    SerializeUpgradePlan { source: serde_json::Error },
+
+    /// Error for when the PodDisruptionBudgets of the application Pods do not allow a
+    /// disruption before the timeout.
+    #[snafu(display(
+        "Timed out after {} waiting for PodDisruptionBudgets {pdbs} to allow a disruption",
+        humantime::format_duration(*timeout)
+    ))]
+    DisruptionBudgetsTimeout { pdbs: String, timeout: Duration },
+
+    /// Error for when an application Pod is not Ready before the timeout.
+    #[snafu(display(
+        "Timed out after {} waiting for application Pod '{name}' in namespace '{namespace}' to be Ready",
+        humantime::format_duration(*timeout)
+    ))]
+    WorkloadReadyTimeout {
+        name: String,
+        namespace: String,
+        timeout: Duration,
+    },
+
+    /// Error for when listing Kubernetes PersistentVolumes fails.
+    #[snafu(display("Failed to list PersistentVolumes: {source}"))]
+    ListPersistentVolumes { source: kube::Error },
+
+    /// Error for when listing Kubernetes VolumeAttachments fails.
+    #[snafu(display("Failed to list VolumeAttachments: {source}"))]
+    ListVolumeAttachments { source: kube::Error },
+
+    /// Error for when listing Kubernetes PodDisruptionBudgets fails.
+    #[snafu(display("Failed to list PodDisruptionBudgets in namespace '{namespace}': {source}"))]
+    ListPodDisruptionBudgets {
+        source: kube::Error,
+        namespace: String,
+    },
+
+    /// Error for when the notification configuration directory does not have the webhook's URL.
+    #[snafu(display(
+        "Webhook URL is absent in notification configuration directory {}",
+        dir.display()
+    ))]
+    WebhookUrlAbsent { dir: PathBuf },
+
+    /// Error for when the webhook format is not one of the supported ones.
+    #[snafu(display(
+        "Invalid webhook format '{format}', expected one of 'generic', 'slack' or 'teams'"
+    ))]
+    InvalidWebhookFormat { format: String },
+
+    /// Error for when the webhook HMAC key cannot be used to sign requests.
+    #[snafu(display("Invalid webhook HMAC key"))]
+    InvalidWebhookHmacKey,
+
+    /// Error for when the HTTP client for webhooks could not be built.
+    #[snafu(display("Failed to build webhook HTTP client: {source}"))]
+    BuildWebhookClient { source: reqwest::Error },
+
+    /// Error for when an upgrade notification could not be serialized to JSON.
+    #[snafu(display("Failed to serialize upgrade notification to JSON: {source}"))]
+    SerializeNotification { source: serde_json::Error },
+
+    /// Error for when a webhook request fails.
+    #[snafu(display("Failed to send upgrade notification to webhook '{url}': {source}"))]
+    SendWebhookNotification { source: reqwest::Error, url: String },
+
+    /// Error for when a webhook responds with an unsuccessful HTTP status.
+    #[snafu(display("Webhook '{url}' rejected upgrade notification with HTTP status {status}"))]
+    WebhookNotificationRejected { url: String, status: u16 },
+
+    /// Error for when the Kubernetes platform info, which the etcd key prefix is built from,
+    /// could not be fetched.
+    #[snafu(display("Failed to get Kubernetes platform info: {reason}"))]
+    GetPlatformInfo { reason: String },
+
+    /// Error for when the external etcd URL is not set in the helm values, while the etcd
+    /// sub-chart is disabled.
+    #[snafu(display(
+        "etcd.externalUrl must be set in the helm values when etcd.enabled is false"
+    ))]
+    EtcdExternalUrlAbsent,
+
+    /// Error for when connecting to etcd fails.
+    #[snafu(display("Failed to connect to etcd at '{endpoint}': {source}"))]
+    EtcdConnect {
+        source: pstor::Error,
+        endpoint: String,
+    },
+
+    /// Error for when reading the control-plane keys from etcd fails.
+    #[snafu(display("Failed to read keys with prefix '{prefix}' from etcd: {source}"))]
+    EtcdDump {
+        source: pstor::Error,
+        prefix: String,
+    },
+
+    /// Error for when gzip compression of the backup data fails.
+    #[snafu(display("Failed to gzip compress upgrade backup data: {source}"))]
+    GzipEncode { source: std::io::Error },
+
+    /// Error for when the upgrade backup could not be serialized to JSON.
+    #[snafu(display("Failed to serialize upgrade backup to JSON: {source}"))]
+    SerializeUpgradeBackup { source: serde_json::Error },
+
+    /// Error for when the upgrade backup is larger than a Secret can hold.
+    #[snafu(display(
+        "Upgrade backup Secret '{name}' would be {size} bytes, which exceeds the limit of {limit} bytes"
+    ))]
+    UpgradeBackupTooLarge {
+        name: String,
+        size: usize,
+        limit: usize,
+    },
+
+    /// Error for when a Kubernetes API request for GET-ing the upgrade backup Secret fails.
+    #[snafu(display(
+        "Failed to GET upgrade backup Secret '{name}' in namespace '{namespace}': {source}"
+    ))]
+    GetUpgradeBackup {
+        source: kube::Error,
+        name: String,
+        namespace: String,
+    },
+
+    /// Error for when a Kubernetes API request for creating the upgrade backup Secret fails.
+    #[snafu(display(
+        "Failed to create upgrade backup Secret '{name}' in namespace '{namespace}': {source}"
+    ))]
+    CreateUpgradeBackup {
+        source: kube::Error,
+        name: String,
+        namespace: String,
+    },
+
+    /// Error for when a Kubernetes API request for replacing the upgrade backup Secret fails.
+    #[snafu(display(
+        "Failed to replace upgrade backup Secret '{name}' in namespace '{namespace}': {source}"
+    ))]
+    ReplaceUpgradeBackup {
+        source: kube::Error,
+        name: String,
+        namespace: String,
+    },
+
+    /// Error for when the upgrade path must go through an intermediate version, as per the
+    /// compatibility matrix.
+    #[snafu(display(
+        "Cannot upgrade from {source_version} to {target_version} directly, upgrade to {via} first: {reason}"
+    ))]
+    IntermediateUpgradeRequired {
+        source_version: String,
+        target_version: String,
+        via: String,
+        reason: String,
+    },
+
+    /// Error for when the upgrade path is not supported, as per the compatibility matrix.
+    #[snafu(display(
+        "Upgrade from {source_version} to {target_version} is not supported: {reason}"
+    ))]
+    UnsupportedUpgradePath {
+        source_version: String,
+        target_version: String,
+        reason: String,
+    },
 }
 
 /// A wrapper type to remove repeated Result<T, Error> returns.
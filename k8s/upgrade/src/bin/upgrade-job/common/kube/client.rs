@@ -1,16 +1,18 @@
 use crate::common::{
     constants::KUBE_API_PAGE_SIZE,
     error::{
-        ControllerRevisionDoesntHaveHashLabel, ControllerRevisionListEmpty,
-        InvalidNoOfHelmConfigMaps, InvalidNoOfHelmSecrets, K8sClientGeneration,
+        ControllerRevisionDoesntHaveHashLabel, ControllerRevisionListEmpty, K8sClientGeneration,
         ListConfigMapsWithLabelAndField, ListCtrlRevsWithLabelAndField, ListNodesWithLabelAndField,
-        ListPodsWithLabelAndField, ListSecretsWithLabelAndField, Result,
+        ListPersistentVolumes, ListPodDisruptionBudgets, ListPodsWithLabelAndField,
+        ListSecretsWithLabelAndField, ListVolumeAttachments, Result,
     },
 };
 use k8s_openapi::{
     api::{
         apps::v1::ControllerRevision,
-        core::v1::{ConfigMap, Namespace, Node, Pod, Secret},
+        core::v1::{ConfigMap, Namespace, Node, PersistentVolume, Pod, Secret},
+        policy::v1::PodDisruptionBudget,
+        storage::v1::VolumeAttachment,
     },
     apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition,
 };
@@ -62,6 +64,23 @@ pub(crate) async fn configmaps_api(namespace: &str) -> Result<Api<ConfigMap>> {
     Ok(Api::namespaced(client().await?, namespace))
 }
 
+/// Generate the PersistentVolume api client.
+pub(crate) async fn persistent_volumes_api() -> Result<Api<PersistentVolume>> {
+    Ok(Api::all(client().await?))
+}
+
+/// Generate the VolumeAttachment api client.
+pub(crate) async fn volume_attachments_api() -> Result<Api<VolumeAttachment>> {
+    Ok(Api::all(client().await?))
+}
+
+/// Generate the PodDisruptionBudget api client.
+pub(crate) async fn pod_disruption_budgets_api(
+    namespace: &str,
+) -> Result<Api<PodDisruptionBudget>> {
+    Ok(Api::namespaced(client().await?, namespace))
+}
+
 pub(crate) async fn list_pods(
     namespace: String,
     label_selector: Option<String>,
@@ -269,49 +288,57 @@ pub(crate) async fn list_configmaps(
     Ok(configmaps)
 }
 
-/// GET the helm release secret for a helm release in a namespace.
-pub(crate) async fn get_helm_release_secret(
-    release_name: String,
-    namespace: String,
-) -> Result<Secret> {
-    let secrets = list_secrets(
-        namespace.clone(),
-        Some(format!("name={release_name},status=deployed")),
-        Some("type=helm.sh/release.v1".to_string()),
+/// This returns all of the PersistentVolumes in the cluster.
+pub(crate) async fn list_persistent_volumes() -> Result<Vec<PersistentVolume>> {
+    let mut persistent_volumes: Vec<PersistentVolume> =
+        Vec::with_capacity(KUBE_API_PAGE_SIZE as usize);
+
+    paginated_list(
+        persistent_volumes_api().await?,
+        &mut persistent_volumes,
+        None,
+        ListPersistentVolumes,
     )
     .await?;
-    let wrong_no_of_secrets = InvalidNoOfHelmSecrets {
-        release_name,
-        namespace,
-        count: secrets.len(),
-    };
-    ensure!(secrets.len() == 1, wrong_no_of_secrets.clone());
 
-    secrets
-        .into_iter()
-        .next()
-        .ok_or(wrong_no_of_secrets.build())
+    Ok(persistent_volumes)
 }
 
-/// GET the helm release configmap for a helm release in a namespace.
-pub(crate) async fn get_helm_release_configmap(
-    release_name: String,
-    namespace: String,
-) -> Result<ConfigMap> {
-    let cms = list_configmaps(
-        namespace.clone(),
-        Some(format!("name={release_name},owner=helm,status=deployed")),
+/// This returns all of the VolumeAttachments in the cluster.
+pub(crate) async fn list_volume_attachments() -> Result<Vec<VolumeAttachment>> {
+    let mut volume_attachments: Vec<VolumeAttachment> =
+        Vec::with_capacity(KUBE_API_PAGE_SIZE as usize);
+
+    paginated_list(
+        volume_attachments_api().await?,
+        &mut volume_attachments,
         None,
+        ListVolumeAttachments,
     )
     .await?;
-    let wrong_no_of_cms = InvalidNoOfHelmConfigMaps {
-        release_name,
-        namespace,
-        count: cms.len(),
+
+    Ok(volume_attachments)
+}
+
+/// This returns all of the PodDisruptionBudgets in a namespace.
+pub(crate) async fn list_pod_disruption_budgets(
+    namespace: String,
+) -> Result<Vec<PodDisruptionBudget>> {
+    let mut pdbs: Vec<PodDisruptionBudget> = Vec::with_capacity(KUBE_API_PAGE_SIZE as usize);
+
+    let list_pdbs_error = ListPodDisruptionBudgets {
+        namespace: namespace.clone(),
     };
-    ensure!(cms.len() == 1, wrong_no_of_cms.clone());
 
-    cms.into_iter().next().ok_or(wrong_no_of_cms.build())
+    paginated_list(
+        pod_disruption_budgets_api(namespace.as_str()).await?,
+        &mut pdbs,
+        None,
+        list_pdbs_error,
+    )
+    .await?;
+
+    Ok(pdbs)
 }
 
 async fn paginated_list<K, C, E2>(
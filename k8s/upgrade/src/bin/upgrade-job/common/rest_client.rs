@@ -35,6 +35,10 @@ impl RestClientSet {
         self.client.nodes_api()
     }
 
+    pub(crate) fn pools_api(&self) -> &dyn openapi::apis::pools_api::tower::client::Pools {
+        self.client.pools_api()
+    }
+
     pub(crate) fn volumes_api(&self) -> &dyn openapi::apis::volumes_api::tower::client::Volumes {
         self.client.volumes_api()
     }
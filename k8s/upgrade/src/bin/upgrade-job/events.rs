@@ -1,2 +1,8 @@
 /// This contains the builder and the Events helper functions.
 pub(crate) mod event_recorder;
+
+/// This contains the durable upgrade history record, which outlives the Kubernetes Events.
+pub(crate) mod history;
+
+/// This contains the notifiers, which upgrade progress is sent to, e.g. webhooks.
+pub(crate) mod notifier;
@@ -1,10 +1,17 @@
-use crate::common::{
-    constants::product_train,
-    error::{
-        EventChannelSend, EventPublish, EventRecorderOptionsAbsent, GetPod, JobPodHasTooManyOwners,
-        JobPodOwnerIsNotJob, JobPodOwnerNotFound, Result, SerializeEventNote,
+use crate::{
+    common::{
+        constants::product_train,
+        error::{
+            EventChannelSend, EventPublish, EventRecorderOptionsAbsent, GetPod,
+            JobPodHasTooManyOwners, JobPodOwnerIsNotJob, JobPodOwnerNotFound, Result,
+            SerializeEventNote,
+        },
+        kube::client as KubeClient,
+    },
+    events::{
+        history::{UpgradeHistory, UpgradeHistoryRecord},
+        notifier::{Notification, Notifier},
     },
-    kube::client as KubeClient,
 };
 use k8s_openapi::{api::core::v1::ObjectReference, serde_json};
 use kube::runtime::events::{Event, EventType, Recorder};
@@ -15,7 +22,7 @@ use std::{
     time::Duration,
 };
 use tokio::{select, sync::mpsc, time::sleep};
-use tracing::error;
+use tracing::{error, warn};
 
 #[derive(Serialize, Debug)]
 #[serde(rename_all(serialize = "camelCase"))]
@@ -23,6 +30,10 @@ pub(crate) struct EventNote {
     from_version: String,
     to_version: String,
     message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    progress: Option<DataPlaneProgress>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<String>,
 }
 
 impl From<&EventRecorder> for EventNote {
@@ -31,6 +42,8 @@ impl From<&EventRecorder> for EventNote {
             from_version: er.source_version.clone(),
             to_version: er.target_version.clone(),
             message: Default::default(),
+            progress: None,
+            warnings: Vec::new(),
         }
     }
 }
@@ -40,6 +53,54 @@ impl EventNote {
         self.message = msg;
         self
     }
+
+    fn with_progress(mut self, progress: DataPlaneProgress) -> EventNote {
+        self.progress = Some(progress);
+        self
+    }
+
+    fn with_warnings(mut self, warnings: Vec<String>) -> EventNote {
+        self.warnings = warnings;
+        self
+    }
+}
+
+/// This is the progress of the data-plane upgrade, which is added to the per-node upgrade events.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DataPlaneProgress {
+    node: String,
+    completed_nodes: usize,
+    total_nodes: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rebuilding_volumes: Option<usize>,
+    started_at: String,
+}
+
+impl DataPlaneProgress {
+    /// Creates the progress of the data-plane upgrade of a node. 'started_at' is the RFC 3339
+    /// timestamp of the start of the data-plane upgrade.
+    pub(crate) fn new(
+        node: &str,
+        completed_nodes: usize,
+        total_nodes: usize,
+        started_at: &str,
+    ) -> Self {
+        Self {
+            node: node.to_string(),
+            completed_nodes,
+            total_nodes,
+            rebuilding_volumes: None,
+            started_at: started_at.to_string(),
+        }
+    }
+
+    /// This adds the number of volumes which are being rebuilt.
+    #[must_use]
+    pub(crate) fn with_rebuilding_volumes(mut self, rebuilding_volumes: usize) -> Self {
+        self.rebuilding_volumes = Some(rebuilding_volumes);
+        self
+    }
 }
 
 /// A builder for the Kubernetes event publisher.
@@ -47,8 +108,10 @@ impl EventNote {
 pub(crate) struct EventRecorderBuilder {
     pod_name: Option<String>,
     namespace: Option<String>,
+    release_name: Option<String>,
     source_version: Option<String>,
     target_version: Option<String>,
+    notifiers: Vec<Box<dyn Notifier>>,
 }
 
 impl EventRecorderBuilder {
@@ -74,16 +137,37 @@ impl EventRecorderBuilder {
         self
     }
 
+    /// This is a builder option to add the name of the helm release which is being upgraded.
+    /// The upgrade history record is kept against this release name.
+    #[must_use]
+    pub(crate) fn with_release_name<T>(mut self, release_name: T) -> Self
+    where
+        T: ToString,
+    {
+        self.release_name = Some(release_name.to_string());
+        self
+    }
+
+    /// This is a builder option to add the notifiers, which each of the upgrade events are also
+    /// sent to.
+    #[must_use]
+    pub(crate) fn with_notifiers(mut self, notifiers: Vec<Box<dyn Notifier>>) -> Self {
+        self.notifiers = notifiers;
+        self
+    }
+
     // TODO: Make the builder option validations error out at compile-time, using std::compile_error
     // or something similar.
     /// This builds the EventRecorder. This fails if Kubernetes API requests fail.
-    pub(crate) async fn build(&self) -> Result<EventRecorder> {
+    pub(crate) async fn build(self) -> Result<EventRecorder> {
         ensure!(
-            self.pod_name.is_some() && self.namespace.is_some(),
+            self.pod_name.is_some() && self.namespace.is_some() && self.release_name.is_some(),
             EventRecorderOptionsAbsent
         );
         let pod_name = self.pod_name.clone().unwrap();
         let namespace = self.namespace.clone().unwrap();
+        let release_name = self.release_name.clone().unwrap();
+        let notifier_namespace = namespace.clone();
 
         // Initialize version to '--'. These can be updated later with set_source_version()
         // and set_target_version() EventRecorder methods.
@@ -94,6 +178,8 @@ impl EventRecorderBuilder {
             .unwrap_or(vers_placeholder.clone());
         let target_version = self.target_version.clone().unwrap_or(vers_placeholder);
 
+        let history = UpgradeHistory::new(release_name.as_str(), namespace.as_str()).await?;
+
         let pods_api = KubeClient::pods_api(namespace.as_str()).await?;
 
         let pod = pods_api.get(pod_name.as_str()).await.context(GetPod {
@@ -131,7 +217,7 @@ impl EventRecorderBuilder {
             api_version: Some(pod_owner.api_version),
             kind: Some(pod_owner.kind),
             name: Some(pod_owner.name.clone()),
-            namespace: Some(namespace),
+            namespace: Some(namespace.clone()),
             uid: Some(pod_owner.uid),
             field_path: None,
             resource_version: None,
@@ -165,9 +251,33 @@ impl EventRecorderBuilder {
             }
         });
 
+        // Notifications are sent from a worker of their own, so that slow or unreachable
+        // notifiers do not hold up the Kubernetes Events.
+        let (notification_sender, notification_loop_handle) = if self.notifiers.is_empty() {
+            (None, None)
+        } else {
+            let notifiers = self.notifiers;
+            let (tx, mut rx) = mpsc::unbounded_channel::<Notification>();
+            let handle = tokio::spawn(async move {
+                while let Some(notification) = rx.recv().await {
+                    for notifier in notifiers.iter() {
+                        if let Err(error) = notifier.notify(&notification).await {
+                            warn!(%error, "Failed to send upgrade notification");
+                        }
+                    }
+                }
+            });
+            (Some(tx), Some(handle))
+        };
+
         Ok(EventRecorder {
             event_sender: Some(tx),
             event_loop_handle,
+            notification_sender,
+            notification_loop_handle,
+            release_name,
+            namespace: notifier_namespace,
+            history,
             source_version,
             target_version,
         })
@@ -178,6 +288,11 @@ impl EventRecorderBuilder {
 pub(crate) struct EventRecorder {
     event_sender: Option<mpsc::UnboundedSender<Event>>,
     event_loop_handle: tokio::task::JoinHandle<()>,
+    notification_sender: Option<mpsc::UnboundedSender<Notification>>,
+    notification_loop_handle: Option<tokio::task::JoinHandle<()>>,
+    release_name: String,
+    namespace: String,
+    history: UpgradeHistory,
     source_version: String,
     target_version: String,
 }
@@ -199,42 +314,121 @@ impl EventRecorder {
 
     /// This is a helper method with calls the publish method above and fills out the boilerplate
     /// Event fields. type is set to publish a Normal event.
-    pub(crate) async fn publish_normal<J, K>(&self, note: J, action: K) -> Result<()>
+    pub(crate) async fn publish_normal<J>(&self, note: J, action: EventAction) -> Result<()>
     where
         J: ToString,
-        K: ToString,
     {
         let note = EventNote::from(self).with_message(note.to_string());
-        let note_s = serde_json::to_string(&note).context(SerializeEventNote { note })?;
-        self.publish(Event {
-            type_: EventType::Normal,
-            reason: constants::upgrade_event_reason(),
-            note: Some(note_s),
-            action: action.to_string(),
-            secondary: None,
-        })
-        .await
+        self.publish_note(EventType::Normal, action, note).await
     }
 
     /// This is a helper method with calls the publish method above and fills out the boilerplate
     /// Event fields. type is set to publish a Warning event.
-    pub(crate) async fn publish_warning<J, K>(&self, note: J, action: K) -> Result<()>
+    pub(crate) async fn publish_warning<J>(&self, note: J, action: EventAction) -> Result<()>
     where
         J: ToString,
-        K: ToString,
     {
         let note = EventNote::from(self).with_message(note.to_string());
+        self.publish_note(EventType::Warning, action, note).await
+    }
+
+    /// This publishes a Normal event with the progress of the data-plane upgrade. Failing to
+    /// publish the progress does not fail the upgrade.
+    pub(crate) async fn publish_progress<J>(
+        &self,
+        note: J,
+        action: EventAction,
+        progress: DataPlaneProgress,
+    ) where
+        J: ToString,
+    {
+        let note = EventNote::from(self)
+            .with_message(note.to_string())
+            .with_progress(progress);
+        if let Err(error) = self.publish_note(EventType::Normal, action, note).await {
+            warn!(%error, "Failed to publish data-plane upgrade progress");
+        }
+    }
+
+    /// This publishes a Warning event with a list of warnings, e.g. the regressions found by the
+    /// post-upgrade verification.
+    pub(crate) async fn publish_with_warnings<J>(
+        &self,
+        note: J,
+        action: EventAction,
+        warnings: Vec<String>,
+    ) -> Result<()>
+    where
+        J: ToString,
+    {
+        let note = EventNote::from(self)
+            .with_message(note.to_string())
+            .with_warnings(warnings);
+        self.publish_note(EventType::Warning, action, note).await
+    }
+
+    /// This records the event in the upgrade history, unless it is the progress of a single node,
+    /// sends it to the notifiers and publishes it.
+    async fn publish_note(
+        &self,
+        event_type: EventType,
+        action: EventAction,
+        note: EventNote,
+    ) -> Result<()> {
+        let action_s = action.to_string();
+        if action.is_recorded_in_history() {
+            self.record_history(event_type, action_s.clone(), &note)
+                .await;
+        }
+        self.notify(event_type, action_s.clone(), &note);
         let note_s = serde_json::to_string(&note).context(SerializeEventNote { note })?;
         self.publish(Event {
-            type_: EventType::Warning,
+            type_: event_type,
             reason: constants::upgrade_event_reason(),
             note: Some(note_s),
-            action: action.to_string(),
+            action: action_s,
             secondary: None,
         })
         .await
     }
 
+    /// This sends the upgrade event to the notifiers, if any. Failing to notify does not fail the
+    /// upgrade.
+    fn notify(&self, event_type: EventType, action: String, note: &EventNote) {
+        let Some(sender) = self.notification_sender.as_ref() else {
+            return;
+        };
+
+        let notification = Notification::new(
+            self.release_name.clone(),
+            self.namespace.clone(),
+            event_type_str(event_type).to_string(),
+            action,
+            note.from_version.clone(),
+            note.to_version.clone(),
+            note.message.clone(),
+        )
+        .with_progress(note.progress.clone());
+        if sender.send(notification).is_err() {
+            warn!("Failed to queue upgrade notification");
+        }
+    }
+
+    /// This adds an entry to the upgrade history. Failing to record history does not fail the
+    /// upgrade, the Kubernetes Event is still published.
+    async fn record_history(&self, event_type: EventType, action: String, note: &EventNote) {
+        let record = UpgradeHistoryRecord::new(
+            event_type_str(event_type).to_string(),
+            action,
+            note.from_version.clone(),
+            note.to_version.clone(),
+            note.message.clone(),
+        );
+        if let Err(error) = self.history.append(record).await {
+            warn!(%error, "Failed to record upgrade history");
+        }
+    }
+
     /// This method is intended for use when upgrade fails.
     pub(crate) async fn publish_unrecoverable<Error>(&self, err: &Error, validation_error: bool)
     where
@@ -253,11 +447,17 @@ impl EventRecorder {
 
     /// Shuts down the event channel which makes the event loop worker exit its loop and return.
     pub(crate) async fn shutdown_worker(mut self) {
-        // Dropping the sender, to signify no more channel messages.
+        // Dropping the senders, to signify no more channel messages.
         let _ = self.event_sender.take();
+        let _ = self.notification_sender.take();
 
         // Wait for event loop to publish its last events and exit.
         let _ = self.event_loop_handle.await;
+
+        // Wait for the notification loop to send its last notifications and exit.
+        if let Some(handle) = self.notification_loop_handle.take() {
+            let _ = handle.await;
+        }
     }
 
     /// Updates the EventRecorder's source_version memeber with a new value.
@@ -271,6 +471,14 @@ impl EventRecorder {
     }
 }
 
+/// This is the name of the Kubernetes Event type.
+fn event_type_str(event_type: EventType) -> &'static str {
+    match event_type {
+        EventType::Normal => "Normal",
+        EventType::Warning => "Warning",
+    }
+}
+
 /// current volume status
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize)]
 pub enum EventAction {
@@ -288,6 +496,53 @@ pub enum EventAction {
     UpgradedDP,
     #[serde(rename = "Successful")]
     Successful,
+    #[serde(rename = "Completed with warnings")]
+    CompletedWithWarnings,
+    #[serde(rename = "Waiting for start time")]
+    WaitingForStartTime,
+    #[serde(rename = "Waiting for window")]
+    WaitingForWindow,
+    #[serde(rename = "Waiting for rebuilds")]
+    WaitingForRebuilds,
+    #[serde(rename = "Draining node")]
+    DrainingNode,
+    #[serde(rename = "Restarting io-engine")]
+    RestartingIoEngine,
+    #[serde(rename = "Upgraded node")]
+    UpgradedNode,
+    #[serde(rename = "Backing up")]
+    BackingUp,
+    #[serde(rename = "Backed up")]
+    BackedUp,
+    #[serde(rename = "Verifying")]
+    Verifying,
+}
+
+impl EventAction {
+    /// This is a predicate to decide if the event is recorded in the upgrade history. The progress
+    /// of the data-plane upgrade on each of the nodes is not, as it would evict the records of
+    /// the earlier upgrades from the upgrade history on large clusters.
+    pub(crate) fn is_recorded_in_history(&self) -> bool {
+        match self {
+            Self::WaitingForWindow
+            | Self::WaitingForRebuilds
+            | Self::DrainingNode
+            | Self::RestartingIoEngine
+            | Self::UpgradedNode => false,
+            Self::Failed
+            | Self::ValidationFailed
+            | Self::UpgradingCP
+            | Self::UpgradedCP
+            | Self::UpgradingDP
+            | Self::UpgradedDP
+            | Self::Successful
+            | Self::CompletedWithWarnings
+            | Self::WaitingForStartTime
+            | Self::BackingUp
+            | Self::BackedUp
+            | Self::Verifying => true,
+        }
+    }
 }
 
 impl Display for EventAction {
@@ -300,7 +555,50 @@ impl Display for EventAction {
             Self::UpgradingDP => "Upgrading data-plane",
             Self::UpgradedDP => "Upgraded data-plane",
             Self::Successful => "Successful",
+            Self::CompletedWithWarnings => "Completed with warnings",
+            Self::WaitingForStartTime => "Waiting for start time",
+            Self::WaitingForWindow => "Waiting for window",
+            Self::WaitingForRebuilds => "Waiting for rebuilds",
+            Self::DrainingNode => "Draining node",
+            Self::RestartingIoEngine => "Restarting io-engine",
+            Self::UpgradedNode => "Upgraded node",
+            Self::BackingUp => "Backing up",
+            Self::BackedUp => "Backed up",
+            Self::Verifying => "Verifying",
         };
         write!(f, "{action}")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::EventAction;
+
+    #[test]
+    fn node_progress_is_not_recorded_in_history() {
+        // The per-node progress of the data-plane upgrade.
+        for action in [
+            EventAction::WaitingForWindow,
+            EventAction::WaitingForRebuilds,
+            EventAction::DrainingNode,
+            EventAction::RestartingIoEngine,
+            EventAction::UpgradedNode,
+        ] {
+            assert!(!action.is_recorded_in_history(), "{action}");
+        }
+
+        // The upgrade's milestones, once per upgrade.
+        for action in [
+            EventAction::UpgradingCP,
+            EventAction::UpgradedCP,
+            EventAction::UpgradingDP,
+            EventAction::UpgradedDP,
+            EventAction::Successful,
+            EventAction::CompletedWithWarnings,
+            EventAction::Failed,
+            EventAction::ValidationFailed,
+        ] {
+            assert!(action.is_recorded_in_history(), "{action}");
+        }
+    }
+}
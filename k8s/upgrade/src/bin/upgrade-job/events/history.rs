@@ -0,0 +1,344 @@
+use crate::common::{
+    error::{
+        CreateUpgradeHistory, DeserializeUpgradeHistory, Error, GetUpgradeHistory,
+        ReplaceUpgradeHistory, Result, SerializeUpgradeHistory,
+    },
+    kube::client as KubeClient,
+};
+use constants::{helm_release_name_key, upgrade_history_configmap_name, UPGRADE_HISTORY_DATA_KEY};
+use k8s_openapi::{api::core::v1::ConfigMap, chrono::Utc};
+use kube::{
+    api::{Api, PostParams},
+    core::ObjectMeta,
+};
+use serde::{Deserialize, Serialize};
+use snafu::ResultExt;
+use std::collections::BTreeMap;
+
+/// This is the maximum number of records retained in the upgrade history. The oldest records are
+/// dropped first. This keeps the ConfigMap well within the size limit for Kubernetes objects.
+const MAX_HISTORY_RECORDS: usize = 256;
+
+/// This is the maximum number of attempts to add a record to the upgrade history, when the
+/// ConfigMap is modified by another writer in between reading and writing it.
+const MAX_APPEND_ATTEMPTS: usize = 5;
+
+/// This is a single entry in the upgrade history. Unlike Kubernetes Events, these are not
+/// garbage-collected by the Kubernetes API server.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct UpgradeHistoryRecord {
+    timestamp: String,
+    event_type: String,
+    action: String,
+    from_version: String,
+    to_version: String,
+    message: String,
+}
+
+impl UpgradeHistoryRecord {
+    /// Creates a new record, timestamped with the current time.
+    pub(crate) fn new(
+        event_type: String,
+        action: String,
+        from_version: String,
+        to_version: String,
+        message: String,
+    ) -> Self {
+        Self {
+            timestamp: Utc::now().to_rfc3339(),
+            event_type,
+            action,
+            from_version,
+            to_version,
+            message,
+        }
+    }
+}
+
+/// This is used to add records to the upgrade history ConfigMap of a helm release.
+#[derive(Clone)]
+pub(crate) struct UpgradeHistory {
+    configmaps_api: Api<ConfigMap>,
+    name: String,
+    namespace: String,
+    release_name: String,
+}
+
+impl UpgradeHistory {
+    /// Creates an UpgradeHistory for the helm release in the namespace.
+    pub(crate) async fn new(release_name: &str, namespace: &str) -> Result<Self> {
+        Ok(Self {
+            configmaps_api: KubeClient::configmaps_api(namespace).await?,
+            name: upgrade_history_configmap_name(release_name),
+            namespace: namespace.to_string(),
+            release_name: release_name.to_string(),
+        })
+    }
+
+    /// Appends a record to the upgrade history. The ConfigMap is created if it does not exist.
+    /// The ConfigMap is read again, and the record appended again, if the ConfigMap was created
+    /// or modified concurrently.
+    pub(crate) async fn append(&self, record: UpgradeHistoryRecord) -> Result<()> {
+        let mut attempt = 1;
+        loop {
+            match self.try_append(record.clone()).await {
+                Err(error) if is_conflict(&error) && attempt < MAX_APPEND_ATTEMPTS => {
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Appends a record to the upgrade history, failing with a conflict if the ConfigMap is
+    /// created or modified in between reading and writing it.
+    async fn try_append(&self, record: UpgradeHistoryRecord) -> Result<()> {
+        let maybe_cm = self
+            .configmaps_api
+            .get_opt(self.name.as_str())
+            .await
+            .context(GetUpgradeHistory {
+                name: self.name.clone(),
+                namespace: self.namespace.clone(),
+            })?;
+        let exists = maybe_cm.is_some();
+        let cm = history_with_record(
+            maybe_cm,
+            record,
+            self.release_name.as_str(),
+            self.namespace.as_str(),
+        )?;
+
+        if exists {
+            // The resourceVersion of the ConfigMap which was read makes this fail with a
+            // conflict, if the ConfigMap has been modified since.
+            self.configmaps_api
+                .replace(self.name.as_str(), &PostParams::default(), &cm)
+                .await
+                .context(ReplaceUpgradeHistory {
+                    name: self.name.clone(),
+                    namespace: self.namespace.clone(),
+                })?;
+        } else {
+            self.configmaps_api
+                .create(&PostParams::default(), &cm)
+                .await
+                .context(CreateUpgradeHistory {
+                    name: self.name.clone(),
+                    namespace: self.namespace.clone(),
+                })?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the upgrade history ConfigMap with the record appended to its records, or a new
+/// ConfigMap with only the record, if there is none. The ConfigMap is labelled with the helm
+/// release, so that it can be selected, e.g. to clean it up with the release.
+fn history_with_record(
+    maybe_cm: Option<ConfigMap>,
+    record: UpgradeHistoryRecord,
+    release_name: &str,
+    namespace: &str,
+) -> Result<ConfigMap> {
+    let mut cm = maybe_cm.unwrap_or_else(|| ConfigMap {
+        metadata: ObjectMeta {
+            name: Some(upgrade_history_configmap_name(release_name)),
+            namespace: Some(namespace.to_string()),
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+
+    let mut records = records_from_data(cm.data.as_ref())?;
+    records.push(record);
+    cm.data
+        .get_or_insert_with(BTreeMap::new)
+        .extend(data_from_records(records)?);
+    cm.metadata
+        .labels
+        .get_or_insert_with(BTreeMap::new)
+        .insert(helm_release_name_key(), release_name.to_string());
+
+    Ok(cm)
+}
+
+/// Returns true if the error is a conflict of a write to the upgrade history ConfigMap with a
+/// concurrent write.
+fn is_conflict(error: &Error) -> bool {
+    matches!(
+        error,
+        Error::CreateUpgradeHistory {
+            source: kube::Error::Api(response),
+            ..
+        } | Error::ReplaceUpgradeHistory {
+            source: kube::Error::Api(response),
+            ..
+        } if response.code == 409
+    )
+}
+
+/// Deserializes the list of records from the data of the upgrade history ConfigMap.
+fn records_from_data(data: Option<&BTreeMap<String, String>>) -> Result<Vec<UpgradeHistoryRecord>> {
+    match data.and_then(|data| data.get(UPGRADE_HISTORY_DATA_KEY)) {
+        Some(history) => serde_json::from_str(history.as_str()).context(DeserializeUpgradeHistory),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Serializes the list of records to the data of the upgrade history ConfigMap, after dropping
+/// the oldest records in excess of MAX_HISTORY_RECORDS.
+fn data_from_records(mut records: Vec<UpgradeHistoryRecord>) -> Result<BTreeMap<String, String>> {
+    if records.len() > MAX_HISTORY_RECORDS {
+        records.drain(.. records.len() - MAX_HISTORY_RECORDS);
+    }
+
+    let history = serde_json::to_string(&records).context(SerializeUpgradeHistory)?;
+    Ok(BTreeMap::from([(
+        UPGRADE_HISTORY_DATA_KEY.to_string(),
+        history,
+    )]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        history_with_record, is_conflict, records_from_data, UpgradeHistoryRecord,
+        MAX_HISTORY_RECORDS,
+    };
+    use crate::common::error::Error;
+    use constants::{helm_release_name_key, UPGRADE_HISTORY_DATA_KEY};
+    use k8s_openapi::api::core::v1::ConfigMap;
+    use kube::core::ErrorResponse;
+    use std::collections::BTreeMap;
+
+    fn record(message: &str) -> UpgradeHistoryRecord {
+        UpgradeHistoryRecord::new(
+            "Normal".to_string(),
+            "Upgrading".to_string(),
+            "2.5.0".to_string(),
+            "2.6.0".to_string(),
+            message.to_string(),
+        )
+    }
+
+    fn messages(cm: &ConfigMap) -> Vec<String> {
+        records_from_data(cm.data.as_ref())
+            .unwrap()
+            .into_iter()
+            .map(|record| record.message)
+            .collect()
+    }
+
+    #[test]
+    fn record_into_new_history() {
+        let cm = history_with_record(None, record("first"), "mayastor", "openebs").unwrap();
+
+        assert_eq!(
+            cm.metadata.name.as_deref(),
+            Some("mayastor-upgrade-history")
+        );
+        assert_eq!(cm.metadata.namespace.as_deref(), Some("openebs"));
+        assert_eq!(
+            cm.metadata.labels.as_ref().unwrap().get(&helm_release_name_key()),
+            Some(&"mayastor".to_string())
+        );
+        assert_eq!(messages(&cm), vec!["first"]);
+    }
+
+    #[test]
+    fn record_into_existing_history() {
+        let existing = history_with_record(None, record("first"), "mayastor", "openebs").unwrap();
+        let mut existing = ConfigMap {
+            metadata: kube::core::ObjectMeta {
+                labels: None,
+                resource_version: Some("42".to_string()),
+                ..existing.metadata
+            },
+            ..existing
+        };
+        existing
+            .data
+            .as_mut()
+            .unwrap()
+            .insert("other".to_string(), "kept".to_string());
+
+        let cm = history_with_record(Some(existing), record("second"), "mayastor", "openebs")
+            .unwrap();
+
+        // The resourceVersion is kept, so that concurrent writes conflict.
+        assert_eq!(cm.metadata.resource_version.as_deref(), Some("42"));
+        assert_eq!(
+            cm.metadata.labels.as_ref().unwrap().get(&helm_release_name_key()),
+            Some(&"mayastor".to_string())
+        );
+        assert_eq!(cm.data.as_ref().unwrap().get("other").unwrap(), "kept");
+        assert_eq!(messages(&cm), vec!["first", "second"]);
+    }
+
+    #[test]
+    fn history_drops_oldest_records() {
+        let mut cm = None;
+        for i in 0 ..= MAX_HISTORY_RECORDS {
+            cm = Some(
+                history_with_record(cm, record(&i.to_string()), "mayastor", "openebs").unwrap(),
+            );
+        }
+
+        let messages = messages(&cm.unwrap());
+        assert_eq!(messages.len(), MAX_HISTORY_RECORDS);
+        assert_eq!(messages.first().unwrap(), "1");
+        assert_eq!(messages.last().unwrap(), &MAX_HISTORY_RECORDS.to_string());
+    }
+
+    #[test]
+    fn read_history() {
+        let data = BTreeMap::from([(
+            UPGRADE_HISTORY_DATA_KEY.to_string(),
+            r#"[{"timestamp":"2024-10-01T10:00:00+00:00","eventType":"Normal","action":"Upgrading","fromVersion":"2.5.0","toVersion":"2.6.0","message":"Upgrading"}]"#
+                .to_string(),
+        )]);
+
+        let records = records_from_data(Some(&data)).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].from_version, "2.5.0");
+        assert_eq!(records[0].to_version, "2.6.0");
+
+        assert!(records_from_data(None).unwrap().is_empty());
+        let corrupt = BTreeMap::from([(UPGRADE_HISTORY_DATA_KEY.to_string(), "{".to_string())]);
+        assert!(records_from_data(Some(&corrupt)).is_err());
+    }
+
+    #[test]
+    fn conflicts_are_retried() {
+        let api_error = |code: u16| {
+            kube::Error::Api(ErrorResponse {
+                status: "Failure".to_string(),
+                message: String::new(),
+                reason: String::new(),
+                code,
+            })
+        };
+        let replace_error = |code| Error::ReplaceUpgradeHistory {
+            source: api_error(code),
+            name: String::new(),
+            namespace: String::new(),
+        };
+        let create_error = |code| Error::CreateUpgradeHistory {
+            source: api_error(code),
+            name: String::new(),
+            namespace: String::new(),
+        };
+
+        assert!(is_conflict(&replace_error(409)));
+        assert!(is_conflict(&create_error(409)));
+        assert!(!is_conflict(&replace_error(403)));
+        assert!(!is_conflict(&Error::GetUpgradeHistory {
+            source: api_error(409),
+            name: String::new(),
+            namespace: String::new(),
+        }));
+    }
+}
@@ -0,0 +1,400 @@
+use crate::{
+    common::error::{
+        BuildWebhookClient, InvalidWebhookFormat, InvalidWebhookHmacKey, ReadingFile, Result,
+        SendWebhookNotification, SerializeNotification, WebhookNotificationRejected,
+        WebhookUrlAbsent,
+    },
+    events::event_recorder::DataPlaneProgress,
+};
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use k8s_openapi::chrono::Utc;
+use serde::Serialize;
+use serde_json::json;
+use sha2::Sha256;
+use snafu::ResultExt;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+use tokio::time::sleep;
+use tracing::warn;
+
+/// This is the file in the notification configuration directory with the webhook's URL.
+const WEBHOOK_URL_FILE: &str = "url";
+
+/// This is the optional file in the notification configuration directory with the key which the
+/// webhook requests are signed with.
+const WEBHOOK_HMAC_KEY_FILE: &str = "hmac-key";
+
+/// This is the optional file in the notification configuration directory with the format of the
+/// webhook request body, i.e. 'generic', 'slack' or 'teams'.
+const WEBHOOK_FORMAT_FILE: &str = "format";
+
+/// This is the header with the hex-encoded HMAC-SHA256 signature of the webhook request body.
+pub(crate) const WEBHOOK_SIGNATURE_HEADER: &str = "X-Upgrade-Signature";
+
+/// This is the number of times a webhook request is attempted, before the notification is
+/// dropped.
+const WEBHOOK_MAX_ATTEMPTS: u32 = 5;
+
+/// This is the timeout for a webhook request.
+const WEBHOOK_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// This is the upgrade progress which is sent to the notifiers, one for each upgrade event.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Notification {
+    release_name: String,
+    namespace: String,
+    event_type: String,
+    action: String,
+    from_version: String,
+    to_version: String,
+    message: String,
+    timestamp: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    progress: Option<DataPlaneProgress>,
+}
+
+impl Notification {
+    /// Creates a Notification for an upgrade event, timestamped now.
+    pub(crate) fn new(
+        release_name: String,
+        namespace: String,
+        event_type: String,
+        action: String,
+        from_version: String,
+        to_version: String,
+        message: String,
+    ) -> Self {
+        Self {
+            release_name,
+            namespace,
+            event_type,
+            action,
+            from_version,
+            to_version,
+            message,
+            timestamp: Utc::now().to_rfc3339(),
+            progress: None,
+        }
+    }
+
+    /// This adds the progress of the data-plane upgrade, if any.
+    #[must_use]
+    pub(crate) fn with_progress(mut self, progress: Option<DataPlaneProgress>) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// This is a human-readable line for chat applications.
+    fn text(&self) -> String {
+        format!(
+            "[{}/{}] {}: {} ({} -> {})",
+            self.namespace,
+            self.release_name,
+            self.action,
+            self.message,
+            self.from_version,
+            self.to_version
+        )
+    }
+}
+
+/// This is a sink for upgrade notifications.
+#[async_trait]
+pub(crate) trait Notifier: Send + Sync {
+    /// Sends the notification.
+    async fn notify(&self, notification: &Notification) -> Result<()>;
+}
+
+/// This is the format of the webhook request body.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) enum WebhookFormat {
+    /// The Notification as a JSON object.
+    #[default]
+    Generic,
+    /// A Slack incoming webhook message.
+    Slack,
+    /// A Microsoft Teams incoming webhook message.
+    Teams,
+}
+
+impl FromStr for WebhookFormat {
+    type Err = crate::common::error::Error;
+
+    fn from_str(format: &str) -> Result<Self> {
+        match format.trim().to_lowercase().as_str() {
+            "" | "generic" => Ok(Self::Generic),
+            "slack" => Ok(Self::Slack),
+            "teams" => Ok(Self::Teams),
+            _ => InvalidWebhookFormat {
+                format: format.to_string(),
+            }
+            .fail(),
+        }
+    }
+}
+
+/// This sends notifications to a webhook as JSON POST requests. Failed requests are retried with
+/// exponential backoff.
+pub(crate) struct WebhookNotifier {
+    client: reqwest::Client,
+    url: String,
+    hmac_key: Option<Vec<u8>>,
+    format: WebhookFormat,
+    retry_interval: Duration,
+}
+
+impl WebhookNotifier {
+    /// Creates a WebhookNotifier. The request bodies are signed if there is an HMAC key.
+    pub(crate) fn new(
+        url: String,
+        hmac_key: Option<Vec<u8>>,
+        format: WebhookFormat,
+    ) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(WEBHOOK_REQUEST_TIMEOUT)
+            .build()
+            .context(BuildWebhookClient)?;
+
+        Ok(Self {
+            client,
+            url,
+            hmac_key,
+            format,
+            retry_interval: Duration::from_secs(1),
+        })
+    }
+
+    /// Creates a WebhookNotifier from the files in the notification configuration directory,
+    /// usually a Secret volume.
+    pub(crate) fn from_config_dir(dir: &Path) -> Result<Self> {
+        let read_file = |name: &str| -> Result<Option<String>> {
+            let path = dir.join(name);
+            if !path.is_file() {
+                return Ok(None);
+            }
+            fs::read_to_string(path.as_path())
+                .map(|contents| Some(contents.trim().to_string()))
+                .context(ReadingFile { filepath: path })
+        };
+
+        let url = read_file(WEBHOOK_URL_FILE)?
+            .filter(|url| !url.is_empty())
+            .ok_or(
+                WebhookUrlAbsent {
+                    dir: dir.to_path_buf(),
+                }
+                .build(),
+            )?;
+        let hmac_key = read_file(WEBHOOK_HMAC_KEY_FILE)?.map(String::into_bytes);
+        let format = read_file(WEBHOOK_FORMAT_FILE)?
+            .map(|format| WebhookFormat::from_str(format.as_str()))
+            .transpose()?
+            .unwrap_or_default();
+
+        Self::new(url, hmac_key, format)
+    }
+
+    /// This is the request body for the notification.
+    fn body(&self, notification: &Notification) -> Result<Vec<u8>> {
+        let body = match self.format {
+            WebhookFormat::Generic => serde_json::to_vec(notification),
+            WebhookFormat::Slack | WebhookFormat::Teams => {
+                serde_json::to_vec(&json!({ "text": notification.text() }))
+            }
+        };
+        body.context(SerializeNotification)
+    }
+
+    /// This is the hex-encoded HMAC-SHA256 signature of the request body, if there is a key.
+    fn signature(&self, body: &[u8]) -> Result<Option<String>> {
+        let Some(key) = self.hmac_key.as_ref() else {
+            return Ok(None);
+        };
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(key.as_slice())
+            .map_err(|_| InvalidWebhookHmacKey.build())?;
+        mac.update(body);
+        let signature = mac
+            .finalize()
+            .into_bytes()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+
+        Ok(Some(signature))
+    }
+
+    /// Makes one webhook request.
+    async fn send(&self, body: Vec<u8>, signature: Option<&str>) -> Result<()> {
+        let mut request = self
+            .client
+            .post(self.url.as_str())
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body);
+        if let Some(signature) = signature {
+            request = request.header(WEBHOOK_SIGNATURE_HEADER, format!("sha256={signature}"));
+        }
+
+        let response = request.send().await.context(SendWebhookNotification {
+            url: self.url.clone(),
+        })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return WebhookNotificationRejected {
+                url: self.url.clone(),
+                status: status.as_u16(),
+            }
+            .fail();
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Notifier for WebhookNotifier {
+    async fn notify(&self, notification: &Notification) -> Result<()> {
+        let body = self.body(notification)?;
+        let signature = self.signature(body.as_slice())?;
+
+        let mut attempt = 1;
+        loop {
+            match self.send(body.clone(), signature.as_deref()).await {
+                Ok(()) => return Ok(()),
+                Err(error) if attempt < WEBHOOK_MAX_ATTEMPTS => {
+                    warn!(%error, attempt, "Failed to send upgrade notification, retrying");
+                    sleep(self.retry_interval * 2_u32.pow(attempt - 1)).await;
+                    attempt += 1;
+                }
+                Err(error) => return Err(error),
+            }
+        }
+    }
+}
+
+/// Returns the notifiers configured in the notification configuration directory, if any.
+pub(crate) fn notifiers(config_dir: Option<PathBuf>) -> Result<Vec<Box<dyn Notifier>>> {
+    let Some(dir) = config_dir else {
+        return Ok(Vec::new());
+    };
+
+    let webhook = WebhookNotifier::from_config_dir(dir.as_path())?;
+    Ok(vec![Box::new(webhook)])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Notification, Notifier, WebhookFormat, WebhookNotifier, WEBHOOK_SIGNATURE_HEADER};
+    use std::time::Duration;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, TcpStream},
+    };
+
+    /// Reads one HTTP request, and returns its headers and body.
+    async fn read_request(stream: &mut TcpStream) -> (String, Vec<u8>) {
+        let mut buf = Vec::new();
+        let mut chunk = [0_u8; 1024];
+        let header_end = loop {
+            let n = stream.read(&mut chunk).await.unwrap();
+            buf.extend_from_slice(&chunk[.. n]);
+            if let Some(end) = buf.windows(4).position(|window| window == b"\r\n\r\n") {
+                break end + 4;
+            }
+        };
+
+        let headers = String::from_utf8(buf[.. header_end].to_vec()).unwrap();
+        let content_length: usize = headers
+            .lines()
+            .find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                name.eq_ignore_ascii_case("content-length")
+                    .then(|| value.trim().parse().unwrap())
+            })
+            .unwrap_or_default();
+        while buf.len() < header_end + content_length {
+            let n = stream.read(&mut chunk).await.unwrap();
+            buf.extend_from_slice(&chunk[.. n]);
+        }
+
+        (headers, buf[header_end ..].to_vec())
+    }
+
+    #[tokio::test]
+    async fn webhook_notification_is_signed_and_retried() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+
+        // The webhook stub rejects the first request, and accepts the next one.
+        let stub = tokio::spawn(async move {
+            let mut requests = Vec::new();
+            for status in ["503 Service Unavailable", "200 OK"] {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                requests.push(read_request(&mut stream).await);
+                let response =
+                    format!("HTTP/1.1 {status}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+            requests
+        });
+
+        let mut notifier =
+            WebhookNotifier::new(url, Some(b"key".to_vec()), WebhookFormat::Generic).unwrap();
+        notifier.retry_interval = Duration::from_millis(10);
+
+        let notification = Notification::new(
+            "mayastor".to_string(),
+            "mayastor".to_string(),
+            "Normal".to_string(),
+            "Upgrading data-plane".to_string(),
+            "2.6.0".to_string(),
+            "2.7.0".to_string(),
+            "Upgrading data-plane Pod on node 'node-1'".to_string(),
+        );
+        notifier.notify(&notification).await.unwrap();
+
+        let requests = stub.await.unwrap();
+        assert_eq!(requests.len(), 2);
+
+        let (headers, body) = &requests[1];
+        let expected_signature = notifier.signature(body.as_slice()).unwrap().unwrap();
+        assert!(headers.lines().any(|line| line.eq_ignore_ascii_case(
+            format!("{WEBHOOK_SIGNATURE_HEADER}: sha256={expected_signature}").as_str()
+        )));
+
+        let body: serde_json::Value = serde_json::from_slice(body.as_slice()).unwrap();
+        assert_eq!(body["action"], "Upgrading data-plane");
+        assert_eq!(body["toVersion"], "2.7.0");
+    }
+
+    #[test]
+    fn webhook_format_and_signature() {
+        assert_eq!(
+            "Slack".parse::<WebhookFormat>().unwrap(),
+            WebhookFormat::Slack
+        );
+        assert!("email".parse::<WebhookFormat>().is_err());
+
+        // RFC 4231, test case 2.
+        let notifier = WebhookNotifier::new(
+            "http://localhost".to_string(),
+            Some(b"Jefe".to_vec()),
+            WebhookFormat::Generic,
+        )
+        .unwrap();
+        assert_eq!(
+            notifier
+                .signature(b"what do ya want for nothing?")
+                .unwrap()
+                .unwrap(),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+}
@@ -2,6 +2,14 @@
 pub(crate) mod chart;
 /// Contains the HelmReleaseClient. Used for interacting with installed helm chart releases.
 pub(crate) mod client;
+/// Contains the comparison of helm release revisions, used for the upgrade plan.
+pub(crate) mod diff;
+/// Contains the declarative helm values migrations, and the engine which applies them.
+pub(crate) mod migrations;
+/// Contains the HelmChartPuller. Used for pulling helm charts from helm chart repositories.
+pub(crate) mod repository;
+/// Contains the types which helm release revisions in the helm storage driver are decoded into.
+pub(crate) mod storage;
 /// Contains helm chart upgrade logic.
 pub(crate) mod upgrade;
 /// Contains validation and logic to generate helm values options for the `helm upgrade` command.
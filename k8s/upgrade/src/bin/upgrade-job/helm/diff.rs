@@ -0,0 +1,405 @@
+use crate::{
+    common::error::{ParseHelmManifest, Result, YamlParseFromSlice},
+    helm::storage::HelmChartRelease,
+};
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+use snafu::ResultExt;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// These are the kinds of workloads whose container images are compared.
+const WORKLOAD_KINDS: [&str; 3] = ["Deployment", "DaemonSet", "StatefulSet"];
+
+/// This is the kind of the CustomResourceDefinition objects.
+const CRD_KIND: &str = "CustomResourceDefinition";
+
+/// This is the difference between the deployed revision of a helm release and the revision which
+/// a helm upgrade would create.
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ReleaseDiff {
+    values: Vec<ValueChange>,
+    manifest: ManifestDiff,
+}
+
+impl ReleaseDiff {
+    /// Compares the computed values and the manifests of two revisions of a helm release.
+    pub(crate) fn new(source: &HelmChartRelease, target: &HelmChartRelease) -> Result<Self> {
+        Ok(Self {
+            values: values_diff(
+                source.values_as_yaml()?.as_slice(),
+                target.values_as_yaml()?.as_slice(),
+            )?,
+            manifest: manifest_diff(source.manifest(), target.manifest())?,
+        })
+    }
+}
+
+/// This is a change to a helm value, keyed by its dot-separated YAML path. The value is None in
+/// the revision which it is absent from.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ValueChange {
+    key: String,
+    from: Option<Value>,
+    to: Option<Value>,
+}
+
+/// This is the difference between the Kubernetes objects of two revisions of a helm release.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ManifestDiff {
+    image_changes: Vec<ImageChange>,
+    added_objects: Vec<ManifestObject>,
+    removed_objects: Vec<ManifestObject>,
+    crd_changes: Vec<CrdChange>,
+}
+
+/// This identifies a Kubernetes object in a helm release's manifest.
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+pub(crate) struct ManifestObject {
+    kind: String,
+    name: String,
+}
+
+/// This is a change to the image of a container of a workload. The image is None in the
+/// revision which the container is absent from.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ImageChange {
+    kind: String,
+    name: String,
+    container: String,
+    from: Option<String>,
+    to: Option<String>,
+}
+
+/// This is a change to a CustomResourceDefinition.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub(crate) struct CrdChange {
+    name: String,
+    change: CrdChangeType,
+}
+
+/// These are the ways in which a CustomResourceDefinition may change.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum CrdChangeType {
+    Added,
+    Removed,
+    Modified,
+}
+
+/// Compares two sets of helm values, and returns the changes sorted by their YAML paths. Arrays
+/// are compared as a whole.
+pub(crate) fn values_diff(source: &[u8], target: &[u8]) -> Result<Vec<ValueChange>> {
+    let mut source_values = BTreeMap::new();
+    flatten_values(None, parse_values(source)?, &mut source_values);
+    let mut target_values = BTreeMap::new();
+    flatten_values(None, parse_values(target)?, &mut target_values);
+
+    let keys: BTreeSet<&String> = source_values.keys().chain(target_values.keys()).collect();
+    Ok(keys
+        .into_iter()
+        .filter_map(|key| {
+            let from = source_values.get(key);
+            let to = target_values.get(key);
+            (from != to).then(|| ValueChange {
+                key: key.clone(),
+                from: from.cloned(),
+                to: to.cloned(),
+            })
+        })
+        .collect())
+}
+
+/// Parses a YAML buffer of helm values.
+fn parse_values(buf: &[u8]) -> Result<Value> {
+    serde_yaml::from_slice(buf).context(YamlParseFromSlice {
+        input_yaml: String::from_utf8_lossy(buf).to_string(),
+    })
+}
+
+/// Collects the leaves of a YAML object, keyed by their dot-separated paths. Arrays and empty
+/// objects are leaves.
+fn flatten_values(prefix: Option<&str>, value: Value, flat: &mut BTreeMap<String, Value>) {
+    match value {
+        Value::Mapping(mapping) if !mapping.is_empty() => {
+            for (key, value) in mapping {
+                let key = match key {
+                    Value::String(key) => key,
+                    Value::Bool(key) => key.to_string(),
+                    Value::Number(key) => key.to_string(),
+                    key => format!("{key:?}"),
+                };
+                let path = match prefix {
+                    Some(prefix) => format!("{prefix}.{key}"),
+                    None => key,
+                };
+                flatten_values(Some(path.as_str()), value, flat);
+            }
+        }
+        value => {
+            if let Some(prefix) = prefix {
+                flat.insert(prefix.to_string(), value);
+            }
+        }
+    }
+}
+
+/// Compares two helm release manifests.
+pub(crate) fn manifest_diff(source: &str, target: &str) -> Result<ManifestDiff> {
+    let source_objects = manifest_objects(source)?;
+    let target_objects = manifest_objects(target)?;
+
+    let mut diff = ManifestDiff::default();
+    for (object, source_object) in source_objects.iter() {
+        match target_objects.get(object) {
+            None if object.kind.eq(CRD_KIND) => diff.crd_changes.push(CrdChange {
+                name: object.name.clone(),
+                change: CrdChangeType::Removed,
+            }),
+            None => diff.removed_objects.push(object.clone()),
+            Some(target_object) if object.kind.eq(CRD_KIND) => {
+                if source_object.get("spec") != target_object.get("spec") {
+                    diff.crd_changes.push(CrdChange {
+                        name: object.name.clone(),
+                        change: CrdChangeType::Modified,
+                    });
+                }
+            }
+            Some(target_object) if WORKLOAD_KINDS.contains(&object.kind.as_str()) => {
+                diff.image_changes
+                    .extend(image_changes(object, source_object, target_object));
+            }
+            Some(_) => {}
+        }
+    }
+    for object in target_objects.keys() {
+        if source_objects.contains_key(object) {
+            continue;
+        }
+        if object.kind.eq(CRD_KIND) {
+            diff.crd_changes.push(CrdChange {
+                name: object.name.clone(),
+                change: CrdChangeType::Added,
+            });
+        } else {
+            diff.added_objects.push(object.clone());
+        }
+    }
+
+    Ok(diff)
+}
+
+/// Parses the documents of a helm release manifest. Documents without a kind and a name, e.g.
+/// the ones which only have comments, are skipped.
+fn manifest_objects(manifest: &str) -> Result<BTreeMap<ManifestObject, Value>> {
+    let mut objects = BTreeMap::new();
+    for document in serde_yaml::Deserializer::from_str(manifest) {
+        let object = Value::deserialize(document).context(ParseHelmManifest)?;
+        let kind = object.get("kind").and_then(Value::as_str);
+        let name = object
+            .get("metadata")
+            .and_then(|metadata| metadata.get("name"))
+            .and_then(Value::as_str);
+        if let (Some(kind), Some(name)) = (kind, name) {
+            objects.insert(
+                ManifestObject {
+                    kind: kind.to_string(),
+                    name: name.to_string(),
+                },
+                object,
+            );
+        }
+    }
+    Ok(objects)
+}
+
+/// Compares the container images of two revisions of a workload.
+fn image_changes(object: &ManifestObject, source: &Value, target: &Value) -> Vec<ImageChange> {
+    let source_images = container_images(source);
+    let target_images = container_images(target);
+
+    let containers: BTreeSet<&String> = source_images.keys().chain(target_images.keys()).collect();
+    containers
+        .into_iter()
+        .filter_map(|container| {
+            let from = source_images.get(container);
+            let to = target_images.get(container);
+            (from != to).then(|| ImageChange {
+                kind: object.kind.clone(),
+                name: object.name.clone(),
+                container: container.clone(),
+                from: from.cloned(),
+                to: to.cloned(),
+            })
+        })
+        .collect()
+}
+
+/// Returns the images of the init containers and the containers of a workload, keyed by the
+/// container names.
+fn container_images(workload: &Value) -> BTreeMap<String, String> {
+    let Some(pod_spec) = workload
+        .get("spec")
+        .and_then(|spec| spec.get("template"))
+        .and_then(|template| template.get("spec"))
+    else {
+        return BTreeMap::new();
+    };
+
+    ["initContainers", "containers"]
+        .into_iter()
+        .filter_map(|key| pod_spec.get(key).and_then(Value::as_sequence))
+        .flatten()
+        .filter_map(|container| {
+            let name = container.get("name")?.as_str()?;
+            let image = container.get("image")?.as_str()?;
+            Some((name.to_string(), image.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::helm::diff::{
+        manifest_diff, values_diff, CrdChangeType, ImageChange, ManifestObject,
+    };
+    use serde_yaml::Value;
+
+    #[test]
+    fn values_diff_by_yaml_path() {
+        let source = br#"
+agents:
+  core:
+    capacity:
+      thin:
+        poolCommitment: "250%"
+    logLevel: info
+io_engine:
+  coreList: []
+"#;
+        let target = br#"
+agents:
+  core:
+    capacity:
+      thin:
+        poolCommitment: "250%"
+    logLevel: debug
+io_engine:
+  coreList: [1, 2]
+csi:
+  node:
+    topology: {}
+"#;
+
+        let diff = values_diff(source, target).unwrap();
+        let keys: Vec<&str> = diff.iter().map(|change| change.key.as_str()).collect();
+        assert_eq!(
+            keys,
+            vec![
+                "agents.core.logLevel",
+                "csi.node.topology",
+                "io_engine.coreList"
+            ]
+        );
+        assert_eq!(diff[0].from, Some(Value::from("info")));
+        assert_eq!(diff[0].to, Some(Value::from("debug")));
+        assert_eq!(diff[1].from, None);
+        assert!(values_diff(source, source).unwrap().is_empty());
+    }
+
+    #[test]
+    fn manifest_diff_of_objects() {
+        let source = r#"
+---
+# Source: mayastor/templates/io-engine.yaml
+apiVersion: apps/v1
+kind: DaemonSet
+metadata:
+  name: mayastor-io-engine
+spec:
+  template:
+    spec:
+      initContainers:
+        - name: agent-core-grpc-probe
+          image: busybox:latest
+      containers:
+        - name: io-engine
+          image: docker.io/openebs/mayastor-io-engine:v2.6.0
+---
+# Source: mayastor/templates/removed.yaml
+apiVersion: v1
+kind: ConfigMap
+metadata:
+  name: mayastor-removed
+---
+apiVersion: apiextensions.k8s.io/v1
+kind: CustomResourceDefinition
+metadata:
+  name: diskpools.openebs.io
+spec:
+  versions: [v1beta1]
+"#;
+        let target = r#"
+---
+# Source: mayastor/templates/io-engine.yaml
+apiVersion: apps/v1
+kind: DaemonSet
+metadata:
+  name: mayastor-io-engine
+spec:
+  template:
+    spec:
+      initContainers:
+        - name: agent-core-grpc-probe
+          image: busybox:latest
+      containers:
+        - name: io-engine
+          image: docker.io/openebs/mayastor-io-engine:v2.7.0
+---
+# Source: mayastor/templates/added.yaml
+apiVersion: v1
+kind: Service
+metadata:
+  name: mayastor-added
+---
+apiVersion: apiextensions.k8s.io/v1
+kind: CustomResourceDefinition
+metadata:
+  name: diskpools.openebs.io
+spec:
+  versions: [v1beta1, v1beta2]
+"#;
+
+        let diff = manifest_diff(source, target).unwrap();
+        assert_eq!(
+            diff.image_changes,
+            vec![ImageChange {
+                kind: "DaemonSet".to_string(),
+                name: "mayastor-io-engine".to_string(),
+                container: "io-engine".to_string(),
+                from: Some("docker.io/openebs/mayastor-io-engine:v2.6.0".to_string()),
+                to: Some("docker.io/openebs/mayastor-io-engine:v2.7.0".to_string()),
+            }]
+        );
+        assert_eq!(
+            diff.added_objects,
+            vec![ManifestObject {
+                kind: "Service".to_string(),
+                name: "mayastor-added".to_string(),
+            }]
+        );
+        assert_eq!(
+            diff.removed_objects,
+            vec![ManifestObject {
+                kind: "ConfigMap".to_string(),
+                name: "mayastor-removed".to_string(),
+            }]
+        );
+        assert_eq!(diff.crd_changes.len(), 1);
+        assert_eq!(diff.crd_changes[0].change, CrdChangeType::Modified);
+        assert_eq!(manifest_diff(source, source).unwrap(), Default::default());
+    }
+}
@@ -0,0 +1,375 @@
+use crate::{
+    common::error::{DeserializeYamlStringValue, Result, YamlParseBufferForValuesMigrations},
+    helm::yaml::editor::{YamlDocument, YamlKey},
+};
+use semver::Version;
+use serde::Deserialize;
+use serde_yaml::Value;
+use snafu::ResultExt;
+use tracing::debug;
+
+/// This is the set of helm values migrations for the Core helm chart, in the order in which they
+/// are applied.
+#[derive(Deserialize)]
+pub(crate) struct ValuesMigrations {
+    migrations: Vec<ValuesMigration>,
+}
+
+impl TryFrom<&[u8]> for ValuesMigrations {
+    type Error = serde_yaml::Error;
+
+    /// Returns a ValuesMigrations object.
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        serde_yaml::from_reader(bytes)
+    }
+}
+
+impl ValuesMigrations {
+    /// Returns the values migrations which are embedded into this binary.
+    pub(crate) fn embedded() -> Result<Self> {
+        let values_migrations_buf =
+            &include_bytes!("../../../../../upgrade/config/values_migrations.yaml")[..];
+        ValuesMigrations::try_from(values_migrations_buf)
+            .context(YamlParseBufferForValuesMigrations)
+    }
+
+    /// Applies the migrations which apply to an upgrade from source_version to target_version,
+    /// in order, on the upgrade_values.
+    pub(crate) fn apply(
+        &self,
+        source_version: &Version,
+        target_version: &Version,
+        source_values: &YamlDocument,
+        target_values: &YamlDocument,
+        upgrade_values: &mut YamlDocument,
+    ) -> Result<()> {
+        for migration in self
+            .migrations
+            .iter()
+            .filter(|migration| migration.applies_to(source_version, target_version, source_values))
+        {
+            debug!(
+                name = migration.name,
+                description = migration.description,
+                "Applying helm values migration"
+            );
+            migration.apply(source_values, target_values, upgrade_values)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// This is a single helm values migration. It is a set of changes to the upgrade values, which
+/// are required for upgrades from a range of source versions to a range of target versions.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub(crate) struct ValuesMigration {
+    name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    source_version: VersionRange,
+    #[serde(default)]
+    target_version: VersionRange,
+    #[serde(default)]
+    when: Vec<Condition>,
+    operations: Vec<Operation>,
+}
+
+impl ValuesMigration {
+    /// This is a predicate which is true if the source and target versions are within the
+    /// migration's version ranges, and if all of the migration's conditions hold for the source
+    /// values.
+    fn applies_to(
+        &self,
+        source_version: &Version,
+        target_version: &Version,
+        source_values: &YamlDocument,
+    ) -> bool {
+        self.source_version.contains(source_version)
+            && self.target_version.contains(target_version)
+            && self
+                .when
+                .iter()
+                .all(|condition| condition.holds(source_values))
+    }
+
+    /// Applies the migration's operations, in order, on the upgrade_values.
+    fn apply(
+        &self,
+        source_values: &YamlDocument,
+        target_values: &YamlDocument,
+        upgrade_values: &mut YamlDocument,
+    ) -> Result<()> {
+        for operation in &self.operations {
+            operation.apply(source_values, target_values, upgrade_values)?;
+        }
+        Ok(())
+    }
+}
+
+/// This is a range of versions. The lower bound is inclusive and the upper bound is exclusive.
+/// Absent bounds are unbounded.
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct VersionRange {
+    from: Option<Version>,
+    to: Option<Version>,
+}
+
+impl VersionRange {
+    /// Not using semver::VersionReq because expressions like '>=2.1.0' don't include
+    /// 2.3.0-rc.0. So the bounds are compared directly.
+    fn contains(&self, version: &Version) -> bool {
+        self.from.as_ref().is_none_or(|from| version.ge(from))
+            && self.to.as_ref().is_none_or(|to| version.lt(to))
+    }
+}
+
+/// This is a condition on the source values. It holds if the value at the key is equal to the
+/// expected value.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Condition {
+    key: YamlKey,
+    equals: Value,
+}
+
+impl Condition {
+    fn holds(&self, source_values: &YamlDocument) -> bool {
+        source_values
+            .get(&self.key)
+            .is_some_and(|value| value.eq(&self.equals))
+    }
+}
+
+/// This is a change to a key in the upgrade values.
+#[derive(Deserialize)]
+#[serde(tag = "op", rename_all = "camelCase")]
+enum Operation {
+    /// Sets the key to a value. If 'literal' is set, a string value of 'true', 'false', 'null'
+    /// or an integer is set as a yaml boolean, null or number respectively.
+    Set {
+        key: YamlKey,
+        #[serde(flatten)]
+        value: OperationValue,
+        #[serde(default)]
+        literal: bool,
+    },
+    /// Deletes the key.
+    Delete { key: YamlKey },
+    /// Moves the value at the key to another key.
+    Rename { key: YamlKey, to: YamlKey },
+    /// Appends a value to the array at the key, or merges an object value's fields into the
+    /// object at the key. The members of an array value are appended one by one, so an empty
+    /// array value appends nothing.
+    #[serde(rename_all = "camelCase")]
+    Append {
+        key: YamlKey,
+        #[serde(flatten)]
+        value: OperationValue,
+        #[serde(default)]
+        parse_yaml_string: bool,
+    },
+}
+
+impl Operation {
+    fn apply(
+        &self,
+        source_values: &YamlDocument,
+        target_values: &YamlDocument,
+        upgrade_values: &mut YamlDocument,
+    ) -> Result<()> {
+        match self {
+            Self::Set {
+                key,
+                value,
+                literal,
+            } => match value.resolve(source_values, target_values) {
+                Some(Value::String(value)) if *literal => {
+                    upgrade_values.set_literal_value(key.clone(), value)?
+                }
+                Some(value) => upgrade_values.set_value(key.clone(), value)?,
+                None => {}
+            },
+            Self::Delete { key } => upgrade_values.delete_object(key.clone())?,
+            Self::Rename { key, to } => {
+                if let Some(value) = upgrade_values.get(key).cloned() {
+                    upgrade_values.delete_object(key.clone())?;
+                    upgrade_values.set_value(to.clone(), value)?;
+                }
+            }
+            Self::Append {
+                key,
+                value,
+                parse_yaml_string,
+            } => {
+                let Some(mut value) = value.resolve(source_values, target_values) else {
+                    return Ok(());
+                };
+                if *parse_yaml_string {
+                    if let Value::String(yaml) = &value {
+                        value = serde_yaml::from_str(yaml.as_str()).context(
+                            DeserializeYamlStringValue {
+                                key: key.to_string(),
+                            },
+                        )?;
+                    }
+                }
+                match value {
+                    // An empty value, e.g. from an empty yaml string, has nothing to append.
+                    Value::Null => {}
+                    Value::Sequence(members) => {
+                        for member in members {
+                            upgrade_values.append_to_array(key.clone(), member)?;
+                        }
+                    }
+                    value => upgrade_values.append_to_object(key.clone(), value)?,
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// This is the value for a 'set' or an 'append' operation.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum OperationValue {
+    /// A literal yaml value.
+    Value(Value),
+    /// The value at a key in the source values.
+    FromSource(YamlKey),
+    /// The value at a key in the target values.
+    FromTarget(YamlKey),
+}
+
+impl OperationValue {
+    /// Returns the value, if it exists.
+    fn resolve(&self, source_values: &YamlDocument, target_values: &YamlDocument) -> Option<Value> {
+        match self {
+            Self::Value(value) => Some(value.clone()),
+            Self::FromSource(key) => source_values.get(key).cloned(),
+            Self::FromTarget(key) => target_values.get(key).cloned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ValuesMigrations;
+    use crate::helm::yaml::editor::YamlDocument;
+    use semver::Version;
+
+    fn document(yaml: &str) -> YamlDocument {
+        YamlDocument::try_from(yaml.as_bytes()).unwrap()
+    }
+
+    /// Applies the migrations in the yaml on the merged source and target values.
+    fn migrate(migrations: &str, source_version: &str, source: &str, target: &str) -> YamlDocument {
+        let migrations = ValuesMigrations::try_from(migrations.as_bytes()).unwrap();
+        let (source, target) = (document(source), document(target));
+        let mut upgrade_values = YamlDocument::merge(source.clone(), target.clone());
+        migrations
+            .apply(
+                &Version::parse(source_version).unwrap(),
+                &Version::new(2, 7, 0),
+                &source,
+                &target,
+                &mut upgrade_values,
+            )
+            .unwrap();
+        upgrade_values
+    }
+
+    #[test]
+    fn embedded_migrations_are_valid() {
+        let migrations = ValuesMigrations::embedded().unwrap();
+        let mut names: Vec<&str> = migrations
+            .migrations
+            .iter()
+            .map(|migration| migration.name.as_str())
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), migrations.migrations.len());
+    }
+
+    #[test]
+    fn version_range_and_conditions() {
+        let migrations = r"
+migrations:
+  - name: log-level
+    sourceVersion:
+      from: 2.0.0-rc.1
+      to: 2.1.0
+    when:
+      - key: .logLevel
+        equals: debug
+    operations:
+      - op: set
+        key: .logLevel
+        fromTarget: .logLevel
+";
+        let source = "logLevel: debug\n";
+        let target = "logLevel: info\n";
+        assert_eq!(
+            migrate(migrations, "2.0.1", source, target),
+            document("logLevel: info\n")
+        );
+        assert_eq!(
+            migrate(migrations, "2.1.0", source, target),
+            document(source)
+        );
+        assert_eq!(
+            migrate(migrations, "2.0.1", "logLevel: warn\n", target),
+            document("logLevel: warn\n")
+        );
+    }
+
+    #[test]
+    fn operations() {
+        let migrations = r"
+migrations:
+  - name: operations
+    operations:
+      - op: set
+        key: .a.b
+        value: 30
+      - op: set
+        key: .a.c
+        fromSource: .absent
+      - op: delete
+        key: .d
+      - op: rename
+        key: .e.old
+        to: .e.new
+      - op: append
+        key: .f[0]
+        fromSource: .extra
+        parseYamlString: true
+      - op: append
+        key: .g
+        value: z
+      - op: set
+        key: .timeout
+        fromTarget: .timeout
+        literal: true
+      - op: append
+        key: .g
+        fromTarget: .g
+      - op: append
+        key: .k
+        fromTarget: .k
+";
+        let source = "a:\n  c: y\nd: 1\ne:\n  old: x\nextra: |\n  h: i\nf:\n- url: u\ng:\n- w\n";
+        let target = "e:\n  new: default\ntimeout: \"110\"\ng:\n- v\nk: []\n";
+        let migrated = migrate(migrations, "2.6.0", source, target);
+        assert_eq!(
+            migrated,
+            document("e:\n  new: x\ntimeout: 110\ng:\n- w\n- z\n- v\nk: []\na:\n  c: y\n  b: 30\nextra: |\n  h: i\nf:\n- url: u\n  h: i\n")
+        );
+    }
+}
@@ -0,0 +1,784 @@
+use crate::{
+    common::{
+        constants::CORE_CHART_NAME,
+        error::{
+            ChartDigestMismatch, ChartTarballNotFound, ExtractChartTarball,
+            HelmChartPullerOptionRepoAbsent, HelmChartPullerOptionVersionAbsent, HelmCommand,
+            HelmPullCommand, HelmRepoLoginCommand, PulledChartVersionMismatch, ReadingFile, Result,
+            TempDirCreation, U8VectorToString, UnverifiableChart,
+        },
+    },
+    upgrade::path::version_from_chart_yaml_file,
+    vec_to_strings,
+};
+use flate2::read::GzDecoder;
+use semver::Version;
+use sha2::{Digest, Sha256};
+use snafu::{ensure, ResultExt};
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    str,
+};
+use tar::Archive;
+use tempfile::TempDir;
+use tracing::{debug, info, warn};
+
+/// This is the URL scheme of helm chart repositories which are OCI registries.
+const OCI_URL_SCHEME: &str = "oci://";
+
+/// This is the name of the HTTP(S) helm chart repository in the helm repository config, when the
+/// repository is logged in to with credentials.
+const REPO_NAME: &str = "upgrade-chart-repo";
+
+/// These are the helm config files which hold the credentials for the helm chart repository.
+/// They are written by the helm login commands, so that the password is never passed as an
+/// argument to a helm command.
+/// The registry config, for OCI registries.
+const REGISTRY_CONFIG_FILE: &str = "registry.json";
+/// The repository config, for HTTP(S) helm chart repositories.
+const REPOSITORY_CONFIG_FILE: &str = "repositories.yaml";
+/// The repository cache, for HTTP(S) helm chart repositories.
+const REPOSITORY_CACHE_DIR: &str = "repository-cache";
+
+/// This is the prefix of the expected helm chart tarball digest, e.g. 'sha256:<hex>'.
+const SHA256_DIGEST_PREFIX: &str = "sha256:";
+
+/// These are the files which may be present in the chart repository credentials directory. This
+/// directory is usually a Kubernetes Secret volume. All of them are optional.
+/// The username for the chart repository.
+const CREDENTIALS_USERNAME_FILE: &str = "username";
+/// The password for the chart repository.
+const CREDENTIALS_PASSWORD_FILE: &str = "password";
+/// The CA certificate bundle for the chart repository's TLS certificate, e.g. that of a mirror
+/// in an air-gapped environment.
+const CREDENTIALS_CA_FILE: &str = "ca.crt";
+/// The public keyring which is used to verify the helm chart's provenance file.
+const CREDENTIALS_KEYRING_FILE: &str = "keyring.gpg";
+
+/// This is a builder for HelmChartPuller.
+#[derive(Default)]
+pub(crate) struct HelmChartPullerBuilder {
+    chart_name: Option<String>,
+    repo: Option<String>,
+    version: Option<Version>,
+    digest: Option<String>,
+    credentials_dir: Option<PathBuf>,
+    plain_http: bool,
+    allow_unverified: bool,
+    helm_command: Option<String>,
+}
+
+impl HelmChartPullerBuilder {
+    /// This is a builder option to set the name of the helm chart. This defaults to the name of
+    /// the Core helm chart.
+    #[must_use]
+    pub(crate) fn with_chart_name<J>(mut self, chart_name: J) -> Self
+    where
+        J: ToString,
+    {
+        self.chart_name = Some(chart_name.to_string());
+        self
+    }
+
+    /// This is a builder option to set the helm chart repository. This may be the URL of an
+    /// HTTP(S) helm chart repository, or that of an OCI registry, i.e. 'oci://<registry>/<path>'.
+    /// This is mandatory.
+    #[must_use]
+    pub(crate) fn with_repo<J>(mut self, repo: J) -> Self
+    where
+        J: ToString,
+    {
+        self.repo = Some(repo.to_string());
+        self
+    }
+
+    /// This is a builder option to set the version of the helm chart. This is mandatory.
+    #[must_use]
+    pub(crate) fn with_version(mut self, version: Version) -> Self {
+        self.version = Some(version);
+        self
+    }
+
+    /// This is a builder option to set the expected sha256 digest of the helm chart tarball.
+    #[must_use]
+    pub(crate) fn with_digest(mut self, digest: Option<String>) -> Self {
+        self.digest = digest;
+        self
+    }
+
+    /// This is a builder option to set the directory with the chart repository's credentials.
+    #[must_use]
+    pub(crate) fn with_credentials_dir(mut self, dir: Option<PathBuf>) -> Self {
+        self.credentials_dir = dir;
+        self
+    }
+
+    /// This is a builder option to use plain HTTP with OCI registries.
+    #[must_use]
+    pub(crate) fn with_plain_http(mut self, plain_http: bool) -> Self {
+        self.plain_http = plain_http;
+        self
+    }
+
+    /// This is a builder option to allow a helm chart which can be verified neither with a digest
+    /// nor with a keyring.
+    #[must_use]
+    pub(crate) fn with_allow_unverified(mut self, allow_unverified: bool) -> Self {
+        self.allow_unverified = allow_unverified;
+        self
+    }
+
+    /// This is a builder option to run a helm binary other than the one in the PATH.
+    #[cfg(test)]
+    #[must_use]
+    pub(crate) fn with_helm_command(mut self, command: String) -> Self {
+        self.helm_command = Some(command);
+        self
+    }
+
+    /// Build the HelmChartPuller.
+    pub(crate) fn build(self) -> Result<HelmChartPuller> {
+        Ok(HelmChartPuller {
+            chart_name: self.chart_name.unwrap_or(CORE_CHART_NAME.to_string()),
+            repo: self.repo.ok_or(HelmChartPullerOptionRepoAbsent.build())?,
+            version: self
+                .version
+                .ok_or(HelmChartPullerOptionVersionAbsent.build())?,
+            digest: self.digest.map(|digest| {
+                digest
+                    .trim_start_matches(SHA256_DIGEST_PREFIX)
+                    .to_ascii_lowercase()
+            }),
+            credentials_dir: self.credentials_dir,
+            plain_http: self.plain_http,
+            allow_unverified: self.allow_unverified,
+            helm_command: self.helm_command.unwrap_or("helm".to_string()),
+        })
+    }
+}
+
+/// This pulls a helm chart, i.e. the Core or the Umbrella helm chart, from a helm chart repository,
+/// verifies it and extracts it.
+pub(crate) struct HelmChartPuller {
+    chart_name: String,
+    repo: String,
+    version: Version,
+    digest: Option<String>,
+    credentials_dir: Option<PathBuf>,
+    plain_http: bool,
+    allow_unverified: bool,
+    helm_command: String,
+}
+
+impl HelmChartPuller {
+    /// This returns a builder for HelmChartPuller.
+    pub(crate) fn builder() -> HelmChartPullerBuilder {
+        HelmChartPullerBuilder::default()
+    }
+
+    /// Logs in to the helm chart repository if there are credentials, runs command `helm pull`
+    /// for the helm chart, verifies the helm chart tarball's digest if one is expected, and
+    /// extracts the helm chart tarball. The helm chart's provenance file is verified by helm, if
+    /// a keyring is present in the credentials directory. This fails if the helm chart can be
+    /// verified neither way, unless unverified helm charts are allowed.
+    pub(crate) async fn pull(&self) -> Result<PulledChart> {
+        if self.digest.is_none() && self.keyring().is_none() {
+            ensure!(
+                self.allow_unverified,
+                UnverifiableChart {
+                    chart_name: self.chart_name.clone(),
+                    version: self.version.to_string(),
+                }
+            );
+            warn!(
+                chart=%self.chart_name,
+                version=%self.version,
+                "Neither a digest nor a keyring is available to verify the helm chart"
+            );
+        }
+
+        let download_dir = TempDir::new().context(TempDirCreation)?;
+        // The helm config files with the credentials are removed along with this directory.
+        let config_dir = TempDir::new().context(TempDirCreation)?;
+
+        self.login(config_dir.path())?;
+
+        let command = self.helm_command.as_str();
+        let args = self.pull_args(download_dir.path(), config_dir.path())?;
+
+        debug!(%command, ?args, "Helm pull command");
+        let output = Command::new(command)
+            .args(args.as_slice())
+            .output()
+            .context(HelmCommand {
+                command: command.to_string(),
+                args: args.clone(),
+            })?;
+
+        let stdout_str = str::from_utf8(output.stdout.as_slice()).context(U8VectorToString)?;
+        debug!(stdout=%stdout_str, "Helm pull command standard output");
+        ensure!(
+            output.status.success(),
+            HelmPullCommand {
+                command: command.to_string(),
+                args,
+                std_err: str::from_utf8(output.stderr.as_slice())
+                    .context(U8VectorToString)?
+                    .to_string()
+            }
+        );
+
+        let tarball = download_dir
+            .path()
+            .join(format!("{}-{}.tgz", self.chart_name, self.version));
+        ensure!(
+            tarball.is_file(),
+            ChartTarballNotFound { filepath: tarball }
+        );
+
+        if let Some(digest) = self.digest.as_deref() {
+            verify_digest(tarball.as_path(), digest)?;
+        }
+
+        let chart_dir = extract_chart(
+            tarball.as_path(),
+            download_dir.path(),
+            self.chart_name.as_str(),
+        )?;
+        let version = version_from_chart_yaml_file(chart_dir.join("Chart.yaml"))?;
+        ensure!(
+            version.eq(&self.version),
+            PulledChartVersionMismatch {
+                expected: self.version.to_string(),
+                actual: version.to_string()
+            }
+        );
+        info!(%version, repo=%self.repo, "Pulled helm chart {}", self.chart_name);
+
+        Ok(PulledChart {
+            chart_dir,
+            _download_dir: download_dir,
+        })
+    }
+
+    /// Logs in to the helm chart repository, if there is a username in the credentials
+    /// directory. The password is written to the standard input of the helm login command, and
+    /// helm saves the credentials in its config files in the config directory.
+    fn login(&self, config_dir: &Path) -> Result<()> {
+        let Some(args) = self.login_args(config_dir)? else {
+            return Ok(());
+        };
+        let password = self
+            .credential(CREDENTIALS_PASSWORD_FILE)?
+            .unwrap_or_default();
+
+        let command = self.helm_command.as_str();
+        debug!(%command, ?args, "Helm chart repository login command");
+        let output = Command::new(command)
+            .args(args.as_slice())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .and_then(|mut child| {
+                if let Some(mut stdin) = child.stdin.take() {
+                    stdin.write_all(password.as_bytes())?;
+                }
+                child.wait_with_output()
+            })
+            .context(HelmCommand {
+                command: command.to_string(),
+                args: args.clone(),
+            })?;
+
+        ensure!(
+            output.status.success(),
+            HelmRepoLoginCommand {
+                command: command.to_string(),
+                args,
+                std_err: str::from_utf8(output.stderr.as_slice())
+                    .context(U8VectorToString)?
+                    .to_string()
+            }
+        );
+        Ok(())
+    }
+
+    /// This returns the arguments for the helm command which logs in to the helm chart
+    /// repository, i.e. `helm registry login` for OCI registries, and `helm repo add` for HTTP(S)
+    /// helm chart repositories. This is None if there is no username in the credentials
+    /// directory.
+    fn login_args(&self, config_dir: &Path) -> Result<Option<Vec<String>>> {
+        let Some(username) = self.credential(CREDENTIALS_USERNAME_FILE)? else {
+            return Ok(None);
+        };
+
+        let mut args = match self.oci_registry() {
+            Some(registry) => vec_to_strings![
+                "registry",
+                "login",
+                registry,
+                "--registry-config",
+                config_dir.join(REGISTRY_CONFIG_FILE).to_string_lossy()
+            ],
+            None => vec_to_strings![
+                "repo",
+                "add",
+                REPO_NAME,
+                self.repo,
+                "--repository-config",
+                config_dir.join(REPOSITORY_CONFIG_FILE).to_string_lossy(),
+                "--repository-cache",
+                config_dir.join(REPOSITORY_CACHE_DIR).to_string_lossy()
+            ],
+        };
+        args.extend(vec_to_strings!["--username", username, "--password-stdin"]);
+
+        if let Some(ca_file) = self.credential_file(CREDENTIALS_CA_FILE) {
+            args.extend(vec_to_strings!["--ca-file", ca_file.to_string_lossy()]);
+        }
+        if self.plain_http && self.oci_registry().is_some() {
+            args.push("--insecure".to_string());
+        }
+
+        Ok(Some(args))
+    }
+
+    /// This returns the arguments for the `helm pull` command. The helm config files which the
+    /// login command writes to are used, if there is a username in the credentials directory.
+    fn pull_args(&self, destination: &Path, config_dir: &Path) -> Result<Vec<String>> {
+        let logged_in = self.credential(CREDENTIALS_USERNAME_FILE)?.is_some();
+
+        let mut args: Vec<String> = vec_to_strings!["pull"];
+        if self.oci_registry().is_some() {
+            args.push(format!(
+                "{}/{}",
+                self.repo.trim_end_matches('/'),
+                self.chart_name
+            ));
+        } else if logged_in {
+            args.push(format!("{REPO_NAME}/{}", self.chart_name));
+        } else {
+            args.extend(vec_to_strings![self.chart_name, "--repo", self.repo]);
+        }
+        args.extend(vec_to_strings![
+            "--version",
+            self.version,
+            "--destination",
+            destination.to_string_lossy()
+        ]);
+
+        if self.plain_http {
+            args.push("--plain-http".to_string());
+        }
+
+        if let Some(ca_file) = self.credential_file(CREDENTIALS_CA_FILE) {
+            args.extend(vec_to_strings!["--ca-file", ca_file.to_string_lossy()]);
+        }
+
+        match (logged_in, self.oci_registry().is_some()) {
+            (true, true) => args.extend(vec_to_strings![
+                "--registry-config",
+                config_dir.join(REGISTRY_CONFIG_FILE).to_string_lossy()
+            ]),
+            (true, false) => args.extend(vec_to_strings![
+                "--repository-config",
+                config_dir.join(REPOSITORY_CONFIG_FILE).to_string_lossy(),
+                "--repository-cache",
+                config_dir.join(REPOSITORY_CACHE_DIR).to_string_lossy()
+            ]),
+            (false, _) => {}
+        }
+
+        if let Some(keyring) = self.keyring() {
+            args.extend(vec_to_strings![
+                "--verify",
+                "--keyring",
+                keyring.to_string_lossy()
+            ]);
+        }
+
+        Ok(args)
+    }
+
+    /// This returns the host of the OCI registry, if the helm chart repository is one.
+    fn oci_registry(&self) -> Option<&str> {
+        self.repo
+            .strip_prefix(OCI_URL_SCHEME)
+            .and_then(|reference| reference.split('/').next())
+    }
+
+    /// This returns the path to the keyring for helm chart provenance verification, if present.
+    fn keyring(&self) -> Option<PathBuf> {
+        self.credential_file(CREDENTIALS_KEYRING_FILE)
+    }
+
+    /// This returns the path to a file in the credentials directory, if present.
+    fn credential_file(&self, name: &str) -> Option<PathBuf> {
+        self.credentials_dir
+            .as_ref()
+            .map(|dir| dir.join(name))
+            .filter(|path| path.is_file())
+    }
+
+    /// This returns the trimmed contents of a file in the credentials directory, if present.
+    fn credential(&self, name: &str) -> Result<Option<String>> {
+        self.credential_file(name)
+            .map(|path| {
+                fs::read_to_string(path.as_path())
+                    .map(|contents| contents.trim().to_string())
+                    .context(ReadingFile { filepath: path })
+            })
+            .transpose()
+    }
+}
+
+/// This is a helm chart which has been pulled from a helm chart repository. The helm chart is
+/// removed from the filesystem when this is dropped.
+pub(crate) struct PulledChart {
+    chart_dir: PathBuf,
+    _download_dir: TempDir,
+}
+
+impl PulledChart {
+    /// This is the directory of the extracted helm chart.
+    pub(crate) fn chart_dir(&self) -> PathBuf {
+        self.chart_dir.clone()
+    }
+}
+
+/// Verifies that the sha256 digest of the helm chart tarball is the expected one. The expected
+/// digest is a lowercase hex string.
+fn verify_digest(tarball: &Path, expected: &str) -> Result<()> {
+    let buf = fs::read(tarball).context(ReadingFile {
+        filepath: tarball.to_path_buf(),
+    })?;
+    let actual = format!("{:x}", Sha256::digest(buf.as_slice()));
+    ensure!(
+        actual.eq(expected),
+        ChartDigestMismatch {
+            filepath: tarball.to_path_buf(),
+            expected: format!("{SHA256_DIGEST_PREFIX}{expected}"),
+            actual: format!("{SHA256_DIGEST_PREFIX}{actual}")
+        }
+    );
+    debug!(digest=%actual, "Verified helm chart tarball digest");
+    Ok(())
+}
+
+/// Extracts the helm chart tarball into the directory, and returns the path to the helm chart's
+/// directory. Helm chart tarballs contain a single directory named after the chart.
+fn extract_chart(tarball: &Path, dir: &Path, chart_name: &str) -> Result<PathBuf> {
+    let file = fs::File::open(tarball).context(ReadingFile {
+        filepath: tarball.to_path_buf(),
+    })?;
+    Archive::new(GzDecoder::new(file))
+        .unpack(dir)
+        .context(ExtractChartTarball {
+            filepath: tarball.to_path_buf(),
+        })?;
+    Ok(dir.join(chart_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{extract_chart, verify_digest, HelmChartPuller};
+    use crate::common::{
+        constants::{CORE_CHART_NAME, UMBRELLA_CHART_NAME},
+        error::Error,
+    };
+    use flate2::{write::GzEncoder, Compression};
+    use semver::Version;
+    use sha2::{Digest, Sha256};
+    use std::{
+        fs,
+        os::unix::fs::PermissionsExt,
+        path::{Path, PathBuf},
+    };
+    use tempfile::TempDir;
+
+    /// This is a fake helm binary. It records its arguments and its standard input, saves the
+    /// standard input of the login commands as the credentials in the helm config file, and
+    /// copies the helm chart tarball next to it into the destination of `helm pull`. A pull
+    /// fails if the helm config file which it is given has not been written by a login.
+    const FAKE_HELM: &str = r#"#!/bin/sh
+dir=$(dirname "$0")
+echo "$@" >> "$dir/args"
+command="$1"
+while [ $# -gt 0 ]; do
+  case "$1" in
+    --registry-config|--repository-config) config="$2" ;;
+    --destination) destination="$2" ;;
+  esac
+  shift
+done
+case "$command" in
+  registry|repo) tee "$dir/stdin" > "$config" ;;
+  pull)
+    if [ -n "$config" ] && [ ! -f "$config" ]; then
+      echo "not logged in" >&2
+      exit 1
+    fi
+    cp "$dir"/*.tgz "$destination"/
+    ;;
+esac
+"#;
+
+    /// Writes a helm chart tarball with only a Chart.yaml file, and returns its path.
+    fn chart_tarball(dir: &Path) -> PathBuf {
+        let chart_yaml = format!("apiVersion: v2\nname: {CORE_CHART_NAME}\nversion: 2.7.0\n");
+        let mut header = tar::Header::new_gnu();
+        header.set_size(chart_yaml.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+
+        let tarball = dir.join(format!("{CORE_CHART_NAME}-2.7.0.tgz"));
+        let mut builder = tar::Builder::new(GzEncoder::new(
+            fs::File::create(tarball.as_path()).unwrap(),
+            Compression::default(),
+        ));
+        builder
+            .append_data(
+                &mut header,
+                format!("{CORE_CHART_NAME}/Chart.yaml"),
+                chart_yaml.as_bytes(),
+            )
+            .unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+        tarball
+    }
+
+    /// Writes the fake helm binary and the helm chart tarball it serves, and returns the path to
+    /// the fake helm binary.
+    fn fake_helm(dir: &Path) -> String {
+        chart_tarball(dir);
+        let helm = dir.join("helm");
+        fs::write(helm.as_path(), FAKE_HELM).unwrap();
+        fs::set_permissions(helm.as_path(), fs::Permissions::from_mode(0o755)).unwrap();
+        helm.to_string_lossy().to_string()
+    }
+
+    /// Writes a credentials directory with a username and a password.
+    fn credentials() -> TempDir {
+        let credentials = TempDir::new().unwrap();
+        fs::write(credentials.path().join("username"), "user\n").unwrap();
+        fs::write(credentials.path().join("password"), "secret\n").unwrap();
+        credentials
+    }
+
+    #[test]
+    fn pull_args() {
+        let puller = HelmChartPuller::builder()
+            .with_repo("https://charts.example.com/mirror")
+            .with_version(Version::new(2, 7, 0))
+            .build()
+            .unwrap();
+        let config = Path::new("/tmp/config");
+        assert!(puller.login_args(config).unwrap().is_none());
+        assert_eq!(
+            puller.pull_args(Path::new("/tmp/chart"), config).unwrap(),
+            vec![
+                "pull",
+                CORE_CHART_NAME,
+                "--repo",
+                "https://charts.example.com/mirror",
+                "--version",
+                "2.7.0",
+                "--destination",
+                "/tmp/chart"
+            ]
+        );
+
+        // The Umbrella helm chart, from the same helm chart repository.
+        let puller = HelmChartPuller::builder()
+            .with_chart_name(UMBRELLA_CHART_NAME)
+            .with_repo("https://charts.example.com/mirror")
+            .with_version(Version::new(4, 1, 0))
+            .build()
+            .unwrap();
+        assert_eq!(
+            puller.pull_args(Path::new("/tmp/chart"), config).unwrap(),
+            vec![
+                "pull",
+                UMBRELLA_CHART_NAME,
+                "--repo",
+                "https://charts.example.com/mirror",
+                "--version",
+                "4.1.0",
+                "--destination",
+                "/tmp/chart"
+            ]
+        );
+
+        // A local OCI registry, with credentials.
+        let credentials = credentials();
+        for file in ["ca.crt", "keyring.gpg"] {
+            fs::write(credentials.path().join(file), "").unwrap();
+        }
+        let puller = HelmChartPuller::builder()
+            .with_repo("oci://localhost:5000/charts/")
+            .with_version(Version::new(2, 7, 0))
+            .with_credentials_dir(Some(credentials.path().to_path_buf()))
+            .with_plain_http(true)
+            .build()
+            .unwrap();
+        let dir = credentials.path().display();
+        assert_eq!(
+            puller.login_args(config).unwrap().unwrap(),
+            vec![
+                "registry".to_string(),
+                "login".to_string(),
+                "localhost:5000".to_string(),
+                "--registry-config".to_string(),
+                "/tmp/config/registry.json".to_string(),
+                "--username".to_string(),
+                "user".to_string(),
+                "--password-stdin".to_string(),
+                "--ca-file".to_string(),
+                format!("{dir}/ca.crt"),
+                "--insecure".to_string(),
+            ]
+        );
+        assert_eq!(
+            puller.pull_args(Path::new("/tmp/chart"), config).unwrap(),
+            vec![
+                "pull".to_string(),
+                format!("oci://localhost:5000/charts/{CORE_CHART_NAME}"),
+                "--version".to_string(),
+                "2.7.0".to_string(),
+                "--destination".to_string(),
+                "/tmp/chart".to_string(),
+                "--plain-http".to_string(),
+                "--ca-file".to_string(),
+                format!("{dir}/ca.crt"),
+                "--registry-config".to_string(),
+                "/tmp/config/registry.json".to_string(),
+                "--verify".to_string(),
+                "--keyring".to_string(),
+                format!("{dir}/keyring.gpg"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn pull_from_oci_registry_with_credentials() {
+        let helm_dir = TempDir::new().unwrap();
+        let credentials = credentials();
+        let helm_command = fake_helm(helm_dir.path());
+        let tarball = helm_dir.path().join(format!("{CORE_CHART_NAME}-2.7.0.tgz"));
+        let digest = format!("{:x}", Sha256::digest(fs::read(tarball).unwrap()));
+        let chart = HelmChartPuller::builder()
+            .with_repo("oci://localhost:5000/charts")
+            .with_version(Version::new(2, 7, 0))
+            .with_digest(Some(format!("sha256:{digest}")))
+            .with_credentials_dir(Some(credentials.path().to_path_buf()))
+            .with_helm_command(helm_command)
+            .build()
+            .unwrap()
+            .pull()
+            .await
+            .unwrap();
+        assert!(chart.chart_dir().join("Chart.yaml").is_file());
+
+        let args = fs::read_to_string(helm_dir.path().join("args")).unwrap();
+        let args: Vec<&str> = args.lines().collect();
+        assert_eq!(args.len(), 2);
+        assert!(args[0].starts_with("registry login localhost:5000 --registry-config "));
+        assert!(args[0].ends_with(" --username user --password-stdin"));
+        assert!(args[1].starts_with(&format!(
+            "pull oci://localhost:5000/charts/{CORE_CHART_NAME} "
+        )));
+        assert!(args[1].contains(" --registry-config "));
+        assert!(args.iter().all(|args| !args.contains("secret")));
+        assert_eq!(
+            fs::read_to_string(helm_dir.path().join("stdin")).unwrap(),
+            "secret"
+        );
+    }
+
+    #[tokio::test]
+    async fn pull_from_http_repository_with_credentials() {
+        let helm_dir = TempDir::new().unwrap();
+        let credentials = credentials();
+        let chart = HelmChartPuller::builder()
+            .with_repo("https://charts.example.com/mirror")
+            .with_version(Version::new(2, 7, 0))
+            .with_credentials_dir(Some(credentials.path().to_path_buf()))
+            .with_allow_unverified(true)
+            .with_helm_command(fake_helm(helm_dir.path()))
+            .build()
+            .unwrap()
+            .pull()
+            .await
+            .unwrap();
+        assert!(chart.chart_dir().join("Chart.yaml").is_file());
+
+        let args = fs::read_to_string(helm_dir.path().join("args")).unwrap();
+        let args: Vec<&str> = args.lines().collect();
+        assert_eq!(args.len(), 2);
+        assert!(args[0].starts_with(
+            "repo add upgrade-chart-repo https://charts.example.com/mirror --repository-config "
+        ));
+        assert!(args[0].ends_with(" --username user --password-stdin"));
+        assert!(args[1].starts_with(&format!("pull upgrade-chart-repo/{CORE_CHART_NAME} ")));
+        assert!(args[1].contains(" --repository-config "));
+        assert!(args.iter().all(|args| !args.contains("secret")));
+        assert_eq!(
+            fs::read_to_string(helm_dir.path().join("stdin")).unwrap(),
+            "secret"
+        );
+    }
+
+    #[tokio::test]
+    async fn refuse_unverifiable_chart() {
+        let helm_dir = TempDir::new().unwrap();
+        let credentials = credentials();
+        let puller = |allow_unverified: bool| {
+            HelmChartPuller::builder()
+                .with_repo("oci://localhost:5000/charts")
+                .with_version(Version::new(2, 7, 0))
+                .with_credentials_dir(Some(credentials.path().to_path_buf()))
+                .with_allow_unverified(allow_unverified)
+                .with_helm_command(fake_helm(helm_dir.path()))
+                .build()
+                .unwrap()
+        };
+
+        // The helm chart is not pulled without a digest or a keyring.
+        assert!(matches!(
+            puller(false).pull().await,
+            Err(Error::UnverifiableChart { .. })
+        ));
+        assert!(!helm_dir.path().join("args").exists());
+
+        // A keyring verifies the helm chart's provenance file.
+        fs::write(credentials.path().join("keyring.gpg"), "").unwrap();
+        puller(false).pull().await.unwrap();
+        let args = fs::read_to_string(helm_dir.path().join("args")).unwrap();
+        assert!(args
+            .lines()
+            .last()
+            .unwrap()
+            .contains(" --verify --keyring "));
+        fs::remove_file(credentials.path().join("keyring.gpg")).unwrap();
+
+        // Unverified helm charts may be allowed explicitly.
+        puller(true).pull().await.unwrap();
+    }
+
+    #[test]
+    fn verify_and_extract_chart() {
+        let dir = TempDir::new().unwrap();
+        let tarball = chart_tarball(dir.path());
+        let digest = format!("{:x}", Sha256::digest(fs::read(tarball.as_path()).unwrap()));
+
+        verify_digest(tarball.as_path(), digest.as_str()).unwrap();
+        assert!(verify_digest(tarball.as_path(), "0123").is_err());
+
+        let chart_dir = extract_chart(tarball.as_path(), dir.path(), CORE_CHART_NAME).unwrap();
+        assert!(chart_dir.join("Chart.yaml").is_file());
+    }
+}
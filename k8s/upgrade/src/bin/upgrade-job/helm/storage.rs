@@ -0,0 +1,311 @@
+use crate::common::error::{
+    Base64DecodeHelmStorage, DeserializaHelmStorageData, GzipDecoderReadToEnd, HelmStorageNoData,
+    HelmStorageNoReleaseValue, MissingMemberInHelmStorageData, Result, SerializeYamlValue,
+};
+use base64::engine::{general_purpose::STANDARD, Engine as base64_engine};
+use flate2::read::GzDecoder;
+use k8s_openapi::{
+    api::core::v1::{ConfigMap, Secret},
+    kind,
+};
+use serde::Deserialize;
+use serde_json::Value;
+use snafu::ResultExt;
+use std::io::Read;
+
+/// This is the 'type' of the Secrets which helm uses to store releases.
+pub(crate) const HELM_RELEASE_SECRET_TYPE: &str = "helm.sh/release.v1";
+
+/// This is the status of a helm release revision which is currently deployed.
+const HELM_RELEASE_STATUS_DEPLOYED: &str = "deployed";
+
+/// These are the first bytes of gzip compressed data. Helm compresses release data before
+/// storing it, but it also accepts uncompressed release data.
+/// Ref: https://github.com/helm/helm/blob/v3.15.0/pkg/storage/driver/util.go#L32
+const GZIP_MAGIC_BYTES: [u8; 3] = [0x1f, 0x8b, 0x08];
+
+/// This is used to pick out the .data field from a kubernetes secret or a configmap.
+macro_rules! extract_data {
+    ($source:ident) => {{
+        let driver = kind(&$source);
+        $source
+            .data
+            .ok_or(HelmStorageNoData { driver }.build())?
+            .into_iter()
+            .find_map(|(k, v)| k.eq("release").then_some(v))
+            .ok_or(HelmStorageNoReleaseValue { driver }.build())
+    }};
+}
+
+/// This is used to deserialize the JSON data in a helm storage resource (secret or configmap).
+/// Each of these is a revision of a helm release.
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct HelmChartRelease {
+    name: String,
+    version: u64,
+    info: Option<HelmChartReleaseInfo>,
+    chart: Option<HelmChartReleaseChart>,
+    config: Option<Value>,
+    manifest: Option<String>,
+}
+
+/// This is used to deserialize release.info.
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct HelmChartReleaseInfo {
+    status: String,
+}
+
+/// This is used to deserialize release.chart.
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct HelmChartReleaseChart {
+    metadata: HelmChartReleaseChartMetadata,
+    values: Option<Value>,
+}
+
+/// This is used to deserialize release.chart.metadata.
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct HelmChartReleaseChartMetadata {
+    name: String,
+    version: String,
+    dependencies: Option<Vec<HelmChartReleaseChartMetadataDependency>>,
+}
+
+/// This is used to deserialize release.chart.metadata.dependency[].
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct HelmChartReleaseChartMetadataDependency {
+    name: String,
+    version: Option<String>,
+}
+
+impl HelmChartReleaseChartMetadataDependency {
+    /// Returns the name of the dependency chart.
+    pub(crate) fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    /// Returns the version of the dependency chart.
+    pub(crate) fn version(self) -> Option<String> {
+        self.version
+    }
+}
+
+impl HelmChartRelease {
+    /// This is a getter function for the name of the release.
+    pub(crate) fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    /// This is the revision number of the release. It is incremented with every install, upgrade
+    /// and rollback.
+    pub(crate) fn revision(&self) -> u64 {
+        self.version
+    }
+
+    /// This is a predicate which is true if the release revision is the one which is deployed.
+    pub(crate) fn is_deployed(&self) -> bool {
+        self.info
+            .as_ref()
+            .is_some_and(|info| info.status.eq(HELM_RELEASE_STATUS_DEPLOYED))
+    }
+
+    /// This returns the chart name and version as '<chart-name>-<chart-version>', same as the
+    /// CHART column of `helm list`.
+    pub(crate) fn chart(&self) -> Result<String> {
+        let metadata = &self.chart_member()?.metadata;
+        Ok(format!("{}-{}", metadata.name, metadata.version))
+    }
+
+    /// Extract list of dependencies from the release's chart metadata or fail.
+    pub(crate) fn dependencies(self) -> Result<Vec<HelmChartReleaseChartMetadataDependency>> {
+        self.chart
+            .ok_or(MissingMemberInHelmStorageData { member: ".chart" }.build())?
+            .metadata
+            .dependencies
+            .ok_or(
+                MissingMemberInHelmStorageData {
+                    member: ".chart.metadata.dependencies",
+                }
+                .build(),
+            )
+    }
+
+    /// This returns the rendered Kubernetes manifest of the release, i.e. a multi-document YAML
+    /// string. This is empty if the release has no manifest.
+    pub(crate) fn manifest(&self) -> &str {
+        self.manifest.as_deref().unwrap_or_default()
+    }
+
+    /// This returns the computed values of the release, i.e. the user-supplied values merged over
+    /// the chart's default values. This is the same as `helm get values --all -o yaml`.
+    pub(crate) fn values_as_yaml(&self) -> Result<Vec<u8>> {
+        let defaults = self
+            .chart
+            .as_ref()
+            .and_then(|chart| chart.values.clone())
+            .unwrap_or_default();
+        let values = coalesce_values(defaults, self.config.clone().unwrap_or_default());
+
+        // An empty set of values is printed as an empty object by helm.
+        let values = match values {
+            Value::Null => Value::Object(Default::default()),
+            values => values,
+        };
+
+        serde_yaml::to_string(&values)
+            .map(String::into_bytes)
+            .context(SerializeYamlValue)
+    }
+
+    /// Returns the release's chart or fails.
+    fn chart_member(&self) -> Result<&HelmChartReleaseChart> {
+        self.chart
+            .as_ref()
+            .ok_or(MissingMemberInHelmStorageData { member: ".chart" }.build())
+    }
+}
+
+/// This decodes a helm release revision from a helm storage Secret.
+pub(crate) fn release_from_secret(secret: Secret) -> Result<HelmChartRelease> {
+    let release_data = extract_data!(secret)?;
+    release_from_data(release_data.0)
+}
+
+/// This decodes a helm release revision from a helm storage ConfigMap.
+pub(crate) fn release_from_configmap(cm: ConfigMap) -> Result<HelmChartRelease> {
+    let release_data = extract_data!(cm)?;
+    release_from_data(release_data)
+}
+
+/// This picks the deployed revision from a helm release's revisions. This is the latest of the
+/// deployed revisions, if there are more than one.
+pub(crate) fn deployed_release(releases: Vec<HelmChartRelease>) -> Option<HelmChartRelease> {
+    releases
+        .into_iter()
+        .filter(HelmChartRelease::is_deployed)
+        .max_by_key(HelmChartRelease::revision)
+}
+
+/// Deserialize a helm release from the data of a helm storage resource.
+fn release_from_data(data: impl AsRef<[u8]>) -> Result<HelmChartRelease> {
+    let data = decode_decompress_data(data)?;
+    serde_json::from_slice(data.as_slice()).context(DeserializaHelmStorageData)
+}
+
+/// This performs a base64 decode and Gzip Decode for the data extracted from the helm storage.
+fn decode_decompress_data(data: impl AsRef<[u8]>) -> Result<Vec<u8>> {
+    let data_compressed =
+        base64_engine::decode(&STANDARD, data).context(Base64DecodeHelmStorage)?;
+
+    if !data_compressed.starts_with(&GZIP_MAGIC_BYTES) {
+        return Ok(data_compressed);
+    }
+
+    let mut gzip_decoder = GzDecoder::new(&data_compressed[..]);
+    let mut data: Vec<u8> = Vec::new();
+    gzip_decoder
+        .read_to_end(&mut data)
+        .context(GzipDecoderReadToEnd)?;
+
+    Ok(data)
+}
+
+/// This merges the user-supplied values over the chart's default values, the way helm does. Objects
+/// are merged recursively, and a null user-supplied value removes the default value for the key.
+/// Ref: https://github.com/helm/helm/blob/v3.15.0/pkg/chartutil/coalesce.go
+fn coalesce_values(defaults: Value, overrides: Value) -> Value {
+    match (defaults, overrides) {
+        (Value::Object(mut defaults), Value::Object(overrides)) => {
+            for (key, value) in overrides {
+                match (defaults.remove(&key), value) {
+                    (Some(_), Value::Null) => {}
+                    (Some(default @ Value::Object(_)), value @ Value::Object(_)) => {
+                        defaults.insert(key, coalesce_values(default, value));
+                    }
+                    (_, value) => {
+                        defaults.insert(key, value);
+                    }
+                }
+            }
+            Value::Object(defaults)
+        }
+        (defaults, Value::Null) => defaults,
+        (_, overrides) => overrides,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{deployed_release, release_from_configmap, release_from_secret, HelmChartRelease};
+    use k8s_openapi::api::core::v1::{ConfigMap, Secret};
+    use std::{fs, path::PathBuf};
+
+    fn testdata_file(name: &str) -> Vec<u8> {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("src/bin/upgrade-job/helm/testdata/storage")
+            .join(name);
+        fs::read(path.as_path()).unwrap_or_else(|error| panic!("{}: {error}", path.display()))
+    }
+
+    fn secret_release(name: &str) -> HelmChartRelease {
+        let secret: Secret = serde_yaml::from_slice(testdata_file(name).as_slice()).unwrap();
+        release_from_secret(secret).unwrap()
+    }
+
+    #[test]
+    fn release_metadata_from_secret() {
+        let release = secret_release("secret_mayastor_v2.yaml");
+
+        assert_eq!(release.name(), "mayastor");
+        assert_eq!(release.revision(), 2);
+        assert!(release.is_deployed());
+        assert_eq!(release.chart().unwrap(), "mayastor-2.6.1");
+
+        let dependencies = release.dependencies().unwrap();
+        let crds = dependencies
+            .iter()
+            .find(|dep| dep.name().eq("crds"))
+            .unwrap();
+        assert_eq!(crds.clone().version().as_deref(), Some("2.6.1"));
+    }
+
+    #[test]
+    fn release_values_from_secret() {
+        let release = secret_release("secret_mayastor_v2.yaml");
+
+        let values: serde_yaml::Value =
+            serde_yaml::from_slice(release.values_as_yaml().unwrap().as_slice()).unwrap();
+        let expected: serde_yaml::Value =
+            serde_yaml::from_slice(testdata_file("values_mayastor_v2.yaml").as_slice()).unwrap();
+        assert_eq!(values, expected);
+    }
+
+    #[test]
+    fn release_from_configmap_matches_secret() {
+        let cm: ConfigMap =
+            serde_yaml::from_slice(testdata_file("configmap_mayastor_v2.yaml").as_slice()).unwrap();
+        let from_cm = release_from_configmap(cm).unwrap();
+        let from_secret = secret_release("secret_mayastor_v2.yaml");
+
+        assert_eq!(from_cm.revision(), from_secret.revision());
+        assert_eq!(from_cm.chart().unwrap(), from_secret.chart().unwrap());
+        assert_eq!(
+            from_cm.values_as_yaml().unwrap(),
+            from_secret.values_as_yaml().unwrap()
+        );
+    }
+
+    #[test]
+    fn deployed_release_from_history() {
+        let history = vec![
+            secret_release("secret_mayastor_v2.yaml"),
+            secret_release("secret_mayastor_v1.yaml"),
+        ];
+
+        let deployed = deployed_release(history.clone()).unwrap();
+        assert_eq!(deployed.revision(), 2);
+        assert_eq!(deployed.chart().unwrap(), "mayastor-2.6.1");
+
+        let superseded = history.into_iter().filter(|r| !r.is_deployed()).collect();
+        assert!(deployed_release(superseded).is_none());
+    }
+}
@@ -2,27 +2,36 @@ use crate::{
     common::{
         constants::{CORE_CHART_NAME, UMBRELLA_CHART_NAME},
         error::{
-            HelmUpgradeOptionNamespaceAbsent, HelmUpgradeOptionReleaseNameAbsent,
-            InvalidUpgradePath, NoHelmStorageDriver, NoInputHelmChartDir, NotAKnownHelmChart,
-            Result, RollbackForbidden, UmbrellaChartNotUpgraded,
+            DeserializeHelmUpgradeDryRun, HelmUpgradeOptionNamespaceAbsent,
+            HelmUpgradeOptionReleaseNameAbsent, NoHelmStorageDriver, NoInputHelmChartDir,
+            NotAKnownHelmChart, Result, RollbackForbidden, UmbrellaChartCoreDependencyMismatch,
+            UmbrellaChartNotUpgraded,
         },
         regex::Regex,
     },
     helm::{
         chart::{CoreValues, HelmValuesCollection, UmbrellaValues},
         client::HelmReleaseClient,
-        values::generate_values_yaml_file,
+        diff::ReleaseDiff,
+        storage::HelmChartRelease,
+        values::{generate_umbrella_values_yaml_file, generate_values_yaml_file},
     },
     upgrade::path::{
-        core_version_from_umbrella_release, is_valid_for_core_chart, version_from_chart_yaml_file,
+        core_version_from_umbrella_chart_yaml_file, core_version_from_umbrella_release,
+        validate_core_chart_upgrade_path, version_from_chart_yaml_file,
         version_from_core_chart_release,
     },
     vec_to_strings,
 };
 use async_trait::async_trait;
 use semver::Version;
-use snafu::ensure;
-use std::{future::Future, path::PathBuf, pin::Pin, str};
+use snafu::{ensure, ResultExt};
+use std::{
+    future::Future,
+    path::{Path, PathBuf},
+    pin::Pin,
+    str,
+};
 use tempfile::NamedTempFile as TempFile;
 use tracing::info;
 
@@ -33,12 +42,14 @@ pub(crate) type HelmUpgradeRunner =
 
 /// A trait object of type HelmUpgrader is either CoreHelmUpgrader or an UmbrellaHelmUpgrader.
 /// They either deal with upgrading the Core helm chart or the Umbrella helm chart respectively.
-/// The Umbrella helm chart is not upgraded using this binary, as it is out of scope.
 #[async_trait]
 pub(crate) trait HelmUpgrader {
     /// Returns a closure which runs the real upgrade, post-dry-run.
     async fn dry_run(self: Box<Self>) -> Result<HelmUpgradeRunner>;
 
+    /// Returns the changes which the helm upgrade would make to the release, without upgrading.
+    async fn diff(&self) -> Result<ReleaseDiff>;
+
     /// Return the source helm chart version as a String.
     fn source_version(&self) -> Version;
 
@@ -52,6 +63,7 @@ pub(crate) struct HelmUpgraderBuilder {
     release_name: Option<String>,
     namespace: Option<String>,
     core_chart_dir: Option<PathBuf>,
+    umbrella_chart_dir: Option<PathBuf>,
     skip_upgrade_path_validation: bool,
     helm_args_set: Option<String>,
     helm_args_set_file: Option<String>,
@@ -81,13 +93,21 @@ impl HelmUpgraderBuilder {
         self
     }
 
-    /// This is a builder option to set the directory path of the Umbrella helm chart CLI option.
+    /// This is a builder option to set the directory path of the Core helm chart CLI option.
     #[must_use]
     pub(crate) fn with_core_chart_dir(mut self, dir: PathBuf) -> Self {
         self.core_chart_dir = Some(dir);
         self
     }
 
+    /// This is a builder option to set the directory path of the Umbrella helm chart CLI option.
+    /// Releases of the Umbrella helm chart may only be upgraded if this is set.
+    #[must_use]
+    pub(crate) fn with_umbrella_chart_dir(mut self, dir: Option<PathBuf>) -> Self {
+        self.umbrella_chart_dir = dir;
+        self
+    }
+
     /// This sets the flag to skip upgrade path validation.
     #[must_use]
     pub(crate) fn with_skip_upgrade_path_validation(
@@ -156,14 +176,12 @@ impl HelmUpgraderBuilder {
             .with_storage_driver(storage_driver)
             .build()?;
 
-        // Get the chart_name from the HelmListReleaseElement object for the release specified in
-        // CLI options.
-        let helm_release = client.release_info(release_name.as_str())?;
-        let chart = helm_release.chart();
+        // Get the chart_name from the deployed revision of the release specified in CLI options.
+        let helm_release = client.release_info(release_name.as_str()).await?;
+        let chart = helm_release.chart()?;
 
         // source_values from installed helm chart release.
-        let source_values_buf =
-            client.get_values_as_yaml::<&str, String>(release_name.as_str(), None)?;
+        let source_values_buf = helm_release.values_as_yaml()?;
 
         let chart_dot_yaml_path = chart_dir.join("Chart.yaml");
         // The version of the Core chart which we are (maybe) upgrading to.
@@ -193,22 +211,52 @@ impl HelmUpgraderBuilder {
             format!(r"^({CORE_CHART_NAME}-[0-9]+\.[0-9]+\.[0-9]+(-[a-zA-Z0-9]+(\.[0-9]+)?)*)$");
 
         // Determine chart variant.
-        if Regex::new(umbrella_regex.as_str())?.is_match(chart.as_ref()) {
+        if Regex::new(umbrella_regex.as_str())?.is_match(chart.as_str()) {
             // The version of the Core helm chart (installed as a dependent chart) which is
             // installed in the cluster.
-            let source_version =
-                core_version_from_umbrella_release(&client, helm_release.name()).await?;
+            let source_version = core_version_from_umbrella_release(helm_release)?;
             info!(version=%source_version, "Found version of dependency chart {CORE_CHART_NAME}");
 
-            // Check if already upgraded.
-            let already_upgraded = target_version.eq(&source_version);
+            let Some(umbrella_chart_dir) = umbrella_chart_dir_to_upgrade_to(
+                &source_version,
+                &target_version,
+                self.umbrella_chart_dir,
+            )?
+            else {
+                return Ok(Box::new(UmbrellaHelmUpgrader {
+                    release_name,
+                    client,
+                    source_version,
+                    target_version,
+                    chart_upgrade: None,
+                }));
+            };
+            let umbrella_target_version =
+                version_from_chart_yaml_file(umbrella_chart_dir.join("Chart.yaml"))?;
+            info!(version=%umbrella_target_version, "Found version of chart {UMBRELLA_CHART_NAME} to upgrade to");
 
-            // Fail if the Umbrella chart isn't already upgraded.
-            ensure!(
-                already_upgraded,
-                UmbrellaChartNotUpgraded {
-                    target_version: target_version.to_string()
-                }
+            if !self.skip_upgrade_path_validation {
+                validate_upgrade_path(&source_version, &target_version)?;
+            }
+
+            // Generate values yaml file for upgrade by merging the target_values and source_values
+            // of the Umbrella chart, and those of the Core chart nested within them.
+            let upgrade_values_file = generate_umbrella_values_yaml_file(
+                &source_version,
+                &target_version,
+                source_values_buf,
+                umbrella_chart_dir.join("values.yaml"),
+                chart_dir.join("values.yaml"),
+                umbrella_chart_dir.as_path(),
+            )
+            .await?;
+
+            let chart_upgrade = HelmChartUpgrade::new(
+                umbrella_chart_dir,
+                upgrade_values_file,
+                self.helm_reset_then_reuse_values,
+                helm_args_set,
+                helm_args_set_file,
             );
 
             Ok(Box::new(UmbrellaHelmUpgrader {
@@ -216,75 +264,48 @@ impl HelmUpgraderBuilder {
                 client,
                 source_version,
                 target_version,
+                chart_upgrade: Some(chart_upgrade),
             }))
-        } else if Regex::new(core_regex.as_str())?.is_match(chart) {
+        } else if Regex::new(core_regex.as_str())?.is_match(chart.as_str()) {
             // The version of the Core helm chart (installed as the parent chart)
             // which is installed in the cluster.
-            let source_version = version_from_core_chart_release(chart)?;
+            let source_version = version_from_core_chart_release(chart.as_str())?;
             info!(version=%source_version, "Found version of chart {CORE_CHART_NAME}");
 
             // Skip upgrade-path validation and allow all upgrades for the Core helm chart, if
             // the flag is set.
             if !self.skip_upgrade_path_validation {
-                // Rollbacks not supported.
-                ensure!(
-                    target_version.ge(&source_version),
-                    RollbackForbidden {
-                        source_version: source_version.to_string(),
-                        target_version: target_version.to_string()
-                    }
-                );
-
-                // Check if upgrade path is explicitly disallowed via config file.
-                let upgrade_path_is_valid = is_valid_for_core_chart(&source_version)?;
-                ensure!(upgrade_path_is_valid, InvalidUpgradePath);
+                validate_upgrade_path(&source_version, &target_version)?;
             }
 
             // target_values from values.yaml file.
             let target_values_filepath = chart_dir.join("values.yaml");
-            let target_values = CoreValues::try_from(target_values_filepath.as_path())?;
-
-            // source_values from installed helm chart release.
-            let source_values = CoreValues::try_from(source_values_buf.as_slice())?;
 
             // Generate values yaml file for upgrade by merging target_values and source_values
             // yaml files.
             let upgrade_values_file = generate_values_yaml_file(
                 &source_version,
                 &target_version,
-                &source_values,
-                &target_values,
                 source_values_buf,
                 target_values_filepath.as_path(),
                 chart_dir.as_path(),
             )
             .await?;
 
-            let mut helm_upgrade_extra_args: Vec<String>;
-            if self.helm_reset_then_reuse_values {
-                // helm upgrade .. --reset-then-reuse-values --set <a> --set-file <args> --atomic
-                helm_upgrade_extra_args = vec_to_strings!["--reset-then-reuse-values"];
-            } else {
-                // helm upgrade .. -f <values-yaml> --set <a> --set-file <args> --atomic
-                helm_upgrade_extra_args =
-                    vec_to_strings!["-f", upgrade_values_file.path().to_string_lossy()];
-            }
-            helm_upgrade_extra_args.extend(vec_to_strings![
-                "--set",
+            let chart_upgrade = HelmChartUpgrade::new(
+                chart_dir,
+                upgrade_values_file,
+                self.helm_reset_then_reuse_values,
                 helm_args_set,
-                "--set-file",
                 helm_args_set_file,
-                "--atomic"
-            ]);
+            );
 
             Ok(Box::new(CoreHelmUpgrader {
-                chart_dir,
                 release_name,
                 client,
-                helm_upgrade_extra_args,
                 source_version,
                 target_version,
-                upgrade_values_file,
+                chart_upgrade,
             }))
         } else {
             // Case: Helm chart release is not a known helm chart installation.
@@ -293,17 +314,176 @@ impl HelmUpgraderBuilder {
     }
 }
 
-/// This is a HelmUpgrader for the core helm chart. Unlike the UmbrellaHelmUpgrader,
-/// this actually can set up a helm upgrade.
-pub(crate) struct CoreHelmUpgrader {
+/// This returns the Umbrella chart directory to upgrade an Umbrella chart release to, or None if
+/// the release's Core chart dependency is already at the target version. This fails if the
+/// release isn't upgraded and the Umbrella chart directory is absent, or if the Umbrella chart
+/// doesn't depend on the target version of the Core chart.
+fn umbrella_chart_dir_to_upgrade_to(
+    source_version: &Version,
+    target_version: &Version,
+    umbrella_chart_dir: Option<PathBuf>,
+) -> Result<Option<PathBuf>> {
+    // Check if already upgraded.
+    if target_version.eq(source_version) {
+        return Ok(None);
+    }
+
+    // Fail if the Umbrella chart isn't already upgraded, and it cannot be upgraded
+    // because the Umbrella chart directory is absent.
+    let umbrella_chart_dir = umbrella_chart_dir.ok_or(
+        UmbrellaChartNotUpgraded {
+            target_version: target_version.to_string(),
+        }
+        .build(),
+    )?;
+
+    // The Umbrella chart should depend on the Core chart which is available locally, as
+    // the Core chart's values are used as the defaults for its dependency.
+    let dependency_version =
+        core_version_from_umbrella_chart_yaml_file(umbrella_chart_dir.join("Chart.yaml"))?;
+    ensure!(
+        dependency_version.eq(target_version),
+        UmbrellaChartCoreDependencyMismatch {
+            dependency_version: dependency_version.to_string(),
+            core_chart_version: target_version.to_string(),
+        }
+    );
+
+    Ok(Some(umbrella_chart_dir))
+}
+
+/// This validates the upgrade path from the source_version to the target_version of the Core
+/// helm chart.
+fn validate_upgrade_path(source_version: &Version, target_version: &Version) -> Result<()> {
+    // Rollbacks not supported.
+    ensure!(
+        target_version.ge(source_version),
+        RollbackForbidden {
+            source_version: source_version.to_string(),
+            target_version: target_version.to_string()
+        }
+    );
+
+    // Check if upgrade path is disallowed, or needs an intermediate upgrade, via config file.
+    validate_core_chart_upgrade_path(source_version, target_version)
+}
+
+/// This is a helm upgrade of a release to a helm chart in a local directory. It is used by both
+/// the CoreHelmUpgrader and the UmbrellaHelmUpgrader.
+struct HelmChartUpgrade {
     chart_dir: PathBuf,
+    helm_upgrade_extra_args: Vec<String>,
+    upgrade_values_file: TempFile,
+}
+
+impl HelmChartUpgrade {
+    /// This sets up the helm upgrade options for the chart in chart_dir.
+    fn new(
+        chart_dir: PathBuf,
+        upgrade_values_file: TempFile,
+        helm_reset_then_reuse_values: bool,
+        helm_args_set: String,
+        helm_args_set_file: String,
+    ) -> Self {
+        let mut helm_upgrade_extra_args: Vec<String>;
+        if helm_reset_then_reuse_values {
+            // helm upgrade .. --reset-then-reuse-values --set <a> --set-file <args> --atomic
+            helm_upgrade_extra_args = vec_to_strings!["--reset-then-reuse-values"];
+        } else {
+            // helm upgrade .. -f <values-yaml> --set <a> --set-file <args> --atomic
+            helm_upgrade_extra_args =
+                vec_to_strings!["-f", upgrade_values_file.path().to_string_lossy()];
+        }
+        helm_upgrade_extra_args.extend(vec_to_strings![
+            "--set",
+            helm_args_set,
+            "--set-file",
+            helm_args_set_file,
+            "--atomic"
+        ]);
+
+        Self {
+            chart_dir,
+            helm_upgrade_extra_args,
+            upgrade_values_file,
+        }
+    }
+
+    /// This runs the 'helm upgrade --dry-run' command.
+    async fn dry_run(&self, client: &HelmReleaseClient, release_name: &str) -> Result<()> {
+        let mut dry_run_extra_args = self.helm_upgrade_extra_args.clone();
+        dry_run_extra_args.push("--dry-run".to_string());
+        info!("Running helm upgrade dry-run...");
+        client
+            .upgrade(
+                release_name,
+                self.chart_dir.as_path(),
+                Some(dry_run_extra_args),
+            )
+            .await?;
+        info!("Helm upgrade dry-run succeeded!");
+
+        Ok(())
+    }
+
+    /// This runs the 'helm upgrade --dry-run' command, and compares the release revision which it
+    /// renders with the deployed revision of the release.
+    async fn diff(&self, client: &HelmReleaseClient, release_name: &str) -> Result<ReleaseDiff> {
+        let mut dry_run_extra_args = self.helm_upgrade_extra_args.clone();
+        dry_run_extra_args.extend(vec_to_strings!["--dry-run", "-o", "json"]);
+        info!("Running helm upgrade dry-run to compare release revisions...");
+        let output = client
+            .upgrade(
+                release_name,
+                self.chart_dir.as_path(),
+                Some(dry_run_extra_args),
+            )
+            .await?;
+        let target_release: HelmChartRelease =
+            serde_json::from_slice(output.as_slice()).context(DeserializeHelmUpgradeDryRun)?;
+        let source_release = client.release_info(release_name).await?;
+
+        ReleaseDiff::new(&source_release, &target_release)
+    }
+
+    /// This runs the 'helm upgrade' command, and returns the upgraded release.
+    async fn run(self, client: &HelmReleaseClient, release_name: &str) -> Result<HelmChartRelease> {
+        // Pinning the helm values file handle to this function so that it is not
+        // dropped. This file handle needs to exist in memory for
+        // the helm upgrade's "-f <values_file>" argument to work.
+        // This handle is dropped when this function returns, after helm upgrade.
+        let _values_file = self.upgrade_values_file;
+
+        info!("Starting helm upgrade...");
+        client
+            .upgrade(
+                release_name,
+                self.chart_dir.as_path(),
+                Some(self.helm_upgrade_extra_args),
+            )
+            .await?;
+        let final_release = client.release_info(release_name).await?;
+        info!(
+            revision = final_release.revision(),
+            "Helm upgrade successful!"
+        );
+
+        Ok(final_release)
+    }
+
+    /// This is the directory of the helm chart which the release is upgraded to.
+    fn chart_dir(&self) -> &Path {
+        self.chart_dir.as_path()
+    }
+}
+
+/// This is a HelmUpgrader for the core helm chart.
+pub(crate) struct CoreHelmUpgrader {
     release_name: String,
     client: HelmReleaseClient,
-    helm_upgrade_extra_args: Vec<String>,
     source_version: Version,
     target_version: Version,
-    #[allow(dead_code)]
-    upgrade_values_file: TempFile,
+    chart_upgrade: HelmChartUpgrade,
 }
 
 #[async_trait]
@@ -313,39 +493,18 @@ impl HelmUpgrader for CoreHelmUpgrader {
     /// when awaited on.
     async fn dry_run(self: Box<Self>) -> Result<HelmUpgradeRunner> {
         // Running 'helm upgrade --dry-run'.
-        let mut dry_run_extra_args = self.helm_upgrade_extra_args.clone();
-        dry_run_extra_args.push("--dry-run".to_string());
-        info!("Running helm upgrade dry-run...");
-        self.client
-            .upgrade(
-                self.release_name.as_str(),
-                self.chart_dir.as_path(),
-                Some(dry_run_extra_args),
-            )
+        self.chart_upgrade
+            .dry_run(&self.client, self.release_name.as_str())
             .await?;
-        info!("Helm upgrade dry-run succeeded!");
 
         // Returning HelmUpgradeRunner.
         Ok(Box::pin(async move {
-            // Pinning the helm values file handle to this closure so that it is not
-            // dropped. This file handle needs to exist in memory for
-            // the helm upgrade's "-f <values_file>" argument to work.
-            // This handle is dropped when this closure returns, after helm upgrade.
-            let _values_file = self.upgrade_values_file;
-
-            info!("Starting helm upgrade...");
-            self.client
-                .upgrade(
-                    self.release_name.as_str(),
-                    self.chart_dir,
-                    Some(self.helm_upgrade_extra_args),
-                )
+            let final_release = self
+                .chart_upgrade
+                .run(&self.client, self.release_name.as_str())
                 .await?;
-            info!("Helm upgrade successful!");
 
-            let final_values_buf = self
-                .client
-                .get_values_as_yaml::<&str, String>(self.release_name.as_str(), None)?;
+            let final_values_buf = final_release.values_as_yaml()?;
             let final_values: Box<dyn HelmValuesCollection> =
                 Box::new(CoreValues::try_from(final_values_buf.as_slice())?);
 
@@ -353,6 +512,12 @@ impl HelmUpgrader for CoreHelmUpgrader {
         }))
     }
 
+    async fn diff(&self) -> Result<ReleaseDiff> {
+        self.chart_upgrade
+            .diff(&self.client, self.release_name.as_str())
+            .await
+    }
+
     fn source_version(&self) -> Version {
         self.source_version.clone()
     }
@@ -362,28 +527,50 @@ impl HelmUpgrader for CoreHelmUpgrader {
     }
 }
 
-/// This is a HelmUpgrader for the Umbrella chart. This gathers information, and doesn't
+/// This is a HelmUpgrader for the Umbrella chart. If the Umbrella chart release already has the
+/// target version of the Core chart as a dependency, this only gathers information, and doesn't
 /// set up a helm upgrade or a dry-run in any way.
 pub(crate) struct UmbrellaHelmUpgrader {
     release_name: String,
     client: HelmReleaseClient,
     source_version: Version,
     target_version: Version,
+    chart_upgrade: Option<HelmChartUpgrade>,
 }
 
 #[async_trait]
 impl HelmUpgrader for UmbrellaHelmUpgrader {
     async fn dry_run(self: Box<Self>) -> Result<HelmUpgradeRunner> {
-        Ok(Box::pin(async move {
+        // Running 'helm upgrade --dry-run', if the Umbrella chart is to be upgraded.
+        if let Some(chart_upgrade) = self.chart_upgrade.as_ref() {
             info!(
-                "Verified that '{UMBRELLA_CHART_NAME}' helm chart release '{}' has dependency '{CORE_CHART_NAME}' of version '{}'",
-                self.release_name.as_str(),
-                self.target_version.to_string()
+                chart_dir=%chart_upgrade.chart_dir().display(),
+                "Upgrading '{UMBRELLA_CHART_NAME}' helm chart release '{}'",
+                self.release_name.as_str()
             );
+            chart_upgrade
+                .dry_run(&self.client, self.release_name.as_str())
+                .await?;
+        }
 
-            let final_values_buf = self
-                .client
-                .get_values_as_yaml::<&str, String>(self.release_name.as_str(), None)?;
+        Ok(Box::pin(async move {
+            let final_values_buf = match self.chart_upgrade {
+                Some(chart_upgrade) => chart_upgrade
+                    .run(&self.client, self.release_name.as_str())
+                    .await?
+                    .values_as_yaml()?,
+                None => {
+                    info!(
+                        "Verified that '{UMBRELLA_CHART_NAME}' helm chart release '{}' has dependency '{CORE_CHART_NAME}' of version '{}'",
+                        self.release_name.as_str(),
+                        self.target_version.to_string()
+                    );
+
+                    self.client
+                        .get_values_as_yaml(self.release_name.as_str())
+                        .await?
+                }
+            };
             let final_values: Box<dyn HelmValuesCollection> =
                 Box::new(UmbrellaValues::try_from(final_values_buf.as_slice())?);
 
@@ -391,6 +578,18 @@ impl HelmUpgrader for UmbrellaHelmUpgrader {
         }))
     }
 
+    async fn diff(&self) -> Result<ReleaseDiff> {
+        // The release is not changed, if the Umbrella chart is not to be upgraded.
+        match self.chart_upgrade.as_ref() {
+            Some(chart_upgrade) => {
+                chart_upgrade
+                    .diff(&self.client, self.release_name.as_str())
+                    .await
+            }
+            None => Ok(ReleaseDiff::default()),
+        }
+    }
+
     fn source_version(&self) -> Version {
         self.source_version.clone()
     }
@@ -399,3 +598,97 @@ impl HelmUpgrader for UmbrellaHelmUpgrader {
         self.target_version.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::umbrella_chart_dir_to_upgrade_to;
+    use crate::{
+        common::{
+            constants::{CORE_CHART_NAME, UMBRELLA_CHART_NAME},
+            error::Error,
+        },
+        helm::storage::HelmChartRelease,
+        upgrade::path::core_version_from_umbrella_release,
+    };
+    use semver::Version;
+    use std::{fs, path::Path};
+    use tempfile::TempDir;
+
+    /// This is a release of the Umbrella chart, with the Core chart version 2.6.1 as a dependency.
+    fn umbrella_release() -> HelmChartRelease {
+        serde_json::from_value(serde_json::json!({
+            "name": "openebs",
+            "version": 1,
+            "info": { "status": "deployed" },
+            "chart": {
+                "metadata": {
+                    "name": UMBRELLA_CHART_NAME,
+                    "version": "4.0.1",
+                    "dependencies": [
+                        { "name": "lvm-localpv", "version": "1.5.1" },
+                        { "name": CORE_CHART_NAME, "version": "2.6.1" }
+                    ]
+                }
+            }
+        }))
+        .unwrap()
+    }
+
+    /// Writes the Chart.yaml file of an Umbrella chart which depends on the Core chart version.
+    fn umbrella_chart_dir(dir: &Path, core_version: &str) {
+        fs::write(
+            dir.join("Chart.yaml"),
+            format!(
+                "apiVersion: v2\nname: {UMBRELLA_CHART_NAME}\nversion: 4.1.0\ndependencies:\n  - name: {CORE_CHART_NAME}\n    version: {core_version}\n"
+            ),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn umbrella_release_upgrade() {
+        let source_version = core_version_from_umbrella_release(umbrella_release()).unwrap();
+        assert_eq!(source_version, Version::new(2, 6, 1));
+        let target_version = Version::new(2, 7, 0);
+
+        // The Umbrella chart cannot be upgraded without its directory.
+        assert!(matches!(
+            umbrella_chart_dir_to_upgrade_to(&source_version, &target_version, None),
+            Err(Error::UmbrellaChartNotUpgraded { .. })
+        ));
+
+        // The Umbrella chart directory must depend on the target Core chart version.
+        let dir = TempDir::new().unwrap();
+        umbrella_chart_dir(dir.path(), "2.6.1");
+        assert!(matches!(
+            umbrella_chart_dir_to_upgrade_to(
+                &source_version,
+                &target_version,
+                Some(dir.path().to_path_buf())
+            ),
+            Err(Error::UmbrellaChartCoreDependencyMismatch { .. })
+        ));
+
+        umbrella_chart_dir(dir.path(), "2.7.0");
+        assert_eq!(
+            umbrella_chart_dir_to_upgrade_to(
+                &source_version,
+                &target_version,
+                Some(dir.path().to_path_buf())
+            )
+            .unwrap(),
+            Some(dir.path().to_path_buf())
+        );
+    }
+
+    #[test]
+    fn umbrella_release_already_upgraded() {
+        let source_version = core_version_from_umbrella_release(umbrella_release()).unwrap();
+
+        // An upgraded release needs no Umbrella chart directory.
+        assert_eq!(
+            umbrella_chart_dir_to_upgrade_to(&source_version, &source_version, None).unwrap(),
+            None
+        );
+    }
+}
@@ -1,20 +1,13 @@
 use crate::{
     common::{
-        constants::{
-            KUBE_API_PAGE_SIZE, TWO_DOT_FIVE, TWO_DOT_FOUR, TWO_DOT_ONE, TWO_DOT_O_RC_ONE,
-            TWO_DOT_SIX, TWO_DOT_THREE,
-        },
-        error::{
-            DeserializePromtailExtraConfig, ListCrds, Result, SemverParse,
-            SerializePromtailConfigClientToJson, SerializePromtailExtraConfigToJson,
-            SerializePromtailInitContainerToJson,
-        },
+        constants::{CORE_CHART_NAME, KUBE_API_PAGE_SIZE},
+        error::{ListCrds, Result},
         file::write_to_tempfile,
         kube::client as KubeClient,
     },
     helm::{
-        chart::{CoreValues, PromtailConfigClient},
-        yaml::yq::{YamlKey, YqV4},
+        migrations::ValuesMigrations,
+        yaml::editor::{YamlDocument, YamlKey},
     },
 };
 use kube::{api::ListParams, ResourceExt};
@@ -24,33 +17,24 @@ use std::{collections::HashMap, path::Path};
 use tempfile::NamedTempFile as TempFile;
 
 /// This compiles all of the helm values options to be passed during the helm chart upgrade.
-/// The helm-chart to helm-chart upgrade has two sets of helm-values. They should both be
-/// deserialized prior to calling this.
+/// The helm-chart to helm-chart upgrade has two sets of helm-values.
 /// Parameters:
 ///     source_version: &Version --> The helm chart version of the source helm chart. Because the
-/// source chart is already installed, this value may be deserialized as a helm releaseElement
-/// (https://github.com/helm/helm/blob/v3.13.2/cmd/helm/list.go#L141) member, from a `helm list`
-/// output. The 'chart' may then be split into chart-name and chart-version.
+/// source chart is already installed, this value may be picked out from the chart metadata of
+/// the helm release's deployed revision.
 ///     target_version: &Version --> The helm chart version of the target helm chart. The target
 /// helm chart should be available in the local filesystem. The value may be picked out from the
 /// Chart.yaml file (version, not appVersion) in the helm chart directory.
-///     source_values: &CoreValues --> This is the deserialized struct generated from the helm
-/// values of the source chart. The values are sourced from `helm get values --all -o yaml`
-///     target_values: &CoreValues --> This is the deserialized struct generated from the
-/// locally available target helm chart's values.yaml file.
-///     source_values_buf: Vec<u8> --> This is the value that is read from the `helm get values
-/// --all -o yaml` output. This is required in a buffer so that it may be written to a file and
-/// used with the yq-go binary.
+///     source_values_buf: Vec<u8> --> This is the value that is read from the helm release, same
+/// as the `helm get values --all -o yaml` output.
 ///     target_values_filepath --> This is simply the path to the values.yaml file for the target
 /// helm chart, which is available locally.
-///     chart_dir --> This is the path to a directory that yq-go may use to write its output file
-/// into. The output file will be a merged values.yaml with special values set as per requirement
-/// (based on source_version and target_version).
+///     chart_dir --> This is the path to a directory that the output file is written into. The
+/// output file will be a merged values.yaml with special values set as per requirement (based on
+/// source_version and target_version).
 pub(crate) async fn generate_values_yaml_file<P, Q>(
     source_version: &Version,
     target_version: &Version,
-    source_values: &CoreValues,
-    target_values: &CoreValues,
     source_values_buf: Vec<u8>,
     target_values_filepath: P,
     chart_dir: Q,
@@ -59,437 +43,139 @@ where
     P: AsRef<Path>,
     Q: AsRef<Path>,
 {
-    // Write the source_values buffer fetched from the installed helm release to a temporary file.
-    let source_values_file: TempFile =
-        write_to_tempfile(Some(chart_dir.as_ref()), source_values_buf.as_slice())?;
+    let source_values = YamlDocument::try_from(source_values_buf.as_slice())?;
+    let target_values = YamlDocument::try_from(target_values_filepath.as_ref())?;
 
     // Resultant values yaml for helm upgrade command.
     // Merge the source values with the target values.
-    let yq = YqV4::new()?;
-    let upgrade_values_yaml =
-        yq.merge_files(source_values_file.path(), target_values_filepath.as_ref())?;
-    let upgrade_values_file: TempFile =
-        write_to_tempfile(Some(chart_dir.as_ref()), upgrade_values_yaml.as_slice())?;
-
-    // Not using semver::VersionReq because expressions like '>=2.1.0' don't include
-    // 2.3.0-rc.0. 2.3.0, 2.4.0, etc. are supported. So, not using VersionReq in the
-    // below comparisons because of this.
-
-    // Specific special-case values for version 2.0.x.
-    let two_dot_o_rc_zero = Version::parse(TWO_DOT_O_RC_ONE).context(SemverParse {
-        version_string: TWO_DOT_O_RC_ONE.to_string(),
-    })?;
-    let two_dot_one = Version::parse(TWO_DOT_ONE).context(SemverParse {
-        version_string: TWO_DOT_ONE.to_string(),
-    })?;
-    if source_version.ge(&two_dot_o_rc_zero) && source_version.lt(&two_dot_one) {
-        let log_level_to_replace = "info,io_engine=info";
-
-        if source_values.io_engine_log_level().eq(log_level_to_replace)
-            && target_values.io_engine_log_level().ne(log_level_to_replace)
-        {
-            yq.set_literal_value(
-                YamlKey::try_from(".io_engine.logLevel")?,
-                target_values.io_engine_log_level(),
-                upgrade_values_file.path(),
-            )?;
-        }
-    }
-
-    // Specific special-case values for to-version >=2.1.x.
-    if target_version.ge(&two_dot_one) {
-        // RepoTags fields will also be set to the values found in the target helm values file
-        // (low_priority file). This is so integration tests which use specific repo commits can
-        // upgrade to a custom helm chart.
-        yq.set_literal_value(
-            YamlKey::try_from(".image.repoTags.controlPlane")?,
-            target_values.control_plane_repotag(),
-            upgrade_values_file.path(),
-        )?;
-        yq.set_literal_value(
-            YamlKey::try_from(".image.repoTags.dataPlane")?,
-            target_values.data_plane_repotag(),
-            upgrade_values_file.path(),
-        )?;
-        yq.set_literal_value(
-            YamlKey::try_from(".image.repoTags.extensions")?,
-            target_values.extensions_repotag(),
-            upgrade_values_file.path(),
-        )?;
-    }
-
-    // Specific special-case values for version 2.3.x.
-    let two_dot_three = Version::parse(TWO_DOT_THREE).context(SemverParse {
-        version_string: TWO_DOT_THREE.to_string(),
-    })?;
-    let two_dot_four = Version::parse(TWO_DOT_FOUR).context(SemverParse {
-        version_string: TWO_DOT_FOUR.to_string(),
-    })?;
-    if source_version.ge(&two_dot_three)
-        && source_version.lt(&two_dot_four)
-        && source_values
-            .eventing_enabled()
-            .ne(&target_values.eventing_enabled())
-    {
-        yq.set_literal_value(
-            YamlKey::try_from(".eventing.enabled")?,
-            target_values.eventing_enabled(),
-            upgrade_values_file.path(),
-        )?;
-    }
-
-    // Special-case values for 2.5.x.
-    let two_dot_five = Version::parse(TWO_DOT_FIVE).context(SemverParse {
-        version_string: TWO_DOT_FIVE.to_string(),
-    })?;
-    if source_version.ge(&two_dot_o_rc_zero) && source_version.lt(&two_dot_five) {
-        // promtail
-        let scrape_configs_to_replace = r"- job_name: {{ .Release.Name }}-pods-name
-  pipeline_stages:
-    - docker: {}
-  kubernetes_sd_configs:
-  - role: pod
-  relabel_configs:
-  - source_labels:
-    - __meta_kubernetes_pod_node_name
-    target_label: hostname
-    action: replace
-  - action: labelmap
-    regex: __meta_kubernetes_pod_label_(.+)
-  - action: keep
-    source_labels:
-    - __meta_kubernetes_pod_label_openebs_io_logging
-    regex: true
-    target_label: {{ .Release.Name }}_component
-  - action: replace
-    replacement: $1
-    separator: /
-    source_labels:
-    - __meta_kubernetes_namespace
-    target_label: job
-  - action: replace
-    source_labels:
-    - __meta_kubernetes_pod_name
-    target_label: pod
-  - action: replace
-    source_labels:
-    - __meta_kubernetes_pod_container_name
-    target_label: container
-  - replacement: /var/log/pods/*$1/*.log
-    separator: /
-    source_labels:
-    - __meta_kubernetes_pod_uid
-    - __meta_kubernetes_pod_container_name
-    target_label: __path__
-";
-        if source_values
-            .loki_stack_promtail_scrape_configs()
-            .eq(scrape_configs_to_replace)
-            && target_values
-                .loki_stack_promtail_scrape_configs()
-                .ne(scrape_configs_to_replace)
-        {
-            yq.set_literal_value(
-                YamlKey::try_from(".loki-stack.promtail.config.snippets.scrapeConfigs")?,
-                target_values.loki_stack_promtail_scrape_configs(),
-                upgrade_values_file.path(),
-            )?;
-        }
-
-        // io_timeout
-        let io_timeout_to_replace = "30";
-        if source_values
-            .csi_node_nvme_io_timeout()
-            .eq(io_timeout_to_replace)
-            && target_values
-                .csi_node_nvme_io_timeout()
-                .ne(io_timeout_to_replace)
-        {
-            yq.set_literal_value(
-                YamlKey::try_from(".csi.node.nvme.io_timeout")?,
-                target_values.csi_node_nvme_io_timeout(),
-                upgrade_values_file.path(),
-            )?;
-        }
-    }
-
-    // Special-case values for 2.6.x.
-    let two_dot_six = Version::parse(TWO_DOT_SIX).context(SemverParse {
-        version_string: TWO_DOT_SIX.to_string(),
-    })?;
-    if source_version.ge(&two_dot_o_rc_zero) && source_version.lt(&two_dot_six) {
-        // Update localpv-provisioner helm chart.
-        // This change is meant for versions from 2.0.0 to 2.4.0. However, this code wasn't checked
-        // into 2.5.0, and likely users of upgrade-job 2.5.0 are using the localpv image tag
-        // from 2.4.0 (i.e. 3.4.0) with the 3.5.0 localpv helm chart. So these options should
-        // also be set for source version 2.5.0.
-        let localpv_version_to_replace = "3.4.0";
-        if source_values
-            .localpv_release_version()
-            .eq(localpv_version_to_replace)
-            && target_values
-                .localpv_release_version()
-                .ne(localpv_version_to_replace)
-        {
-            yq.set_literal_value(
-                YamlKey::try_from(".localpv-provisioner.release.version")?,
-                target_values.localpv_release_version(),
-                upgrade_values_file.path(),
-            )?;
-            yq.set_literal_value(
-                YamlKey::try_from(".localpv-provisioner.localpv.image.tag")?,
-                target_values.localpv_provisioner_image_tag(),
-                upgrade_values_file.path(),
-            )?;
-            yq.set_literal_value(
-                YamlKey::try_from(".localpv-provisioner.helperPod.image.tag")?,
-                target_values.localpv_helper_image_tag(),
-                upgrade_values_file.path(),
-            )?;
-        }
+    let mut upgrade_values = YamlDocument::merge(source_values.clone(), target_values.clone());
+
+    migrate_values(
+        source_version,
+        target_version,
+        &source_values,
+        &target_values,
+        &mut upgrade_values,
+    )?;
 
-        // Switch out image tag for the latest one.
-        yq.set_literal_value(
-            YamlKey::try_from(".loki-stack.loki.image.tag")?,
-            target_values.loki_stack_loki_image_tag(),
-            upgrade_values_file.path(),
-        )?;
-        yq.set_literal_value(
-            YamlKey::try_from(".loki-stack.filebeat.imageTag")?,
-            target_values.filebeat_image_tag(),
-            upgrade_values_file.path(),
-        )?;
-        yq.set_literal_value(
-            YamlKey::try_from(".loki-stack.logstash.imageTag")?,
-            target_values.logstash_image_tag(),
-            upgrade_values_file.path(),
-        )?;
-        yq.set_literal_value(
-            YamlKey::try_from(".loki-stack.grafana.downloadDashboardsImage.tag")?,
-            target_values.grafana_download_dashboards_image_tag(),
-            upgrade_values_file.path(),
-        )?;
-        yq.set_literal_value(
-            YamlKey::try_from(".loki-stack.grafana.image.tag")?,
-            target_values.grafana_image_tag(),
-            upgrade_values_file.path(),
-        )?;
-        yq.set_literal_value(
-            YamlKey::try_from(".loki-stack.grafana.sidecar.image.tag")?,
-            target_values.grafana_sidecar_image_tag(),
-            upgrade_values_file.path(),
-        )?;
-        yq.set_literal_value(
-            YamlKey::try_from(".loki-stack.prometheus.alertmanager.image.tag")?,
-            target_values.prometheus_alertmanager_image_tag(),
-            upgrade_values_file.path(),
-        )?;
-        yq.set_literal_value(
-            YamlKey::try_from(".loki-stack.prometheus.nodeExporter.image.tag")?,
-            target_values.prometheus_node_exporter_image_tag(),
-            upgrade_values_file.path(),
-        )?;
-        yq.set_literal_value(
-            YamlKey::try_from(".loki-stack.prometheus.pushgateway.image.tag")?,
-            target_values.prometheus_pushgateway_image_tag(),
-            upgrade_values_file.path(),
-        )?;
-        yq.set_literal_value(
-            YamlKey::try_from(".loki-stack.prometheus.server.image.tag")?,
-            target_values.prometheus_server_image_tag(),
-            upgrade_values_file.path(),
-        )?;
-
-        // Delete deprecated objects.
-        yq.delete_object(
-            YamlKey::try_from(".loki-stack.loki.config.ingester.lifecycler.ring.kvstore")?,
-            upgrade_values_file.path(),
-        )?;
-        yq.delete_object(
-            YamlKey::try_from(".loki-stack.promtail.config.snippets.extraClientConfigs")?,
-            upgrade_values_file.path(),
-        )?;
-        yq.delete_object(
-            YamlKey::try_from(".loki-stack.promtail.initContainer")?,
-            upgrade_values_file.path(),
-        )?;
-
-        loki_address_to_clients(source_values, upgrade_values_file.path(), &yq)?;
-
-        yq.set_literal_value(
-            YamlKey::try_from(".loki-stack.promtail.config.file")?,
-            target_values.loki_stack_promtail_config_file(),
-            upgrade_values_file.path(),
-        )?;
-        for container in target_values.promtail_init_container() {
-            let init_container = serde_json::to_string(&container).context(
-                SerializePromtailInitContainerToJson {
-                    object: container.clone(),
-                },
-            )?;
-            yq.append_to_object(
-                YamlKey::try_from(".loki-stack.promtail.initContainer")?,
-                init_container,
-                upgrade_values_file.path(),
-            )?;
-        }
-        yq.set_literal_value(
-            YamlKey::try_from(".loki-stack.promtail.readinessProbe.httpGet.path")?,
-            target_values.promtail_readiness_probe_http_get_path(),
-            upgrade_values_file.path(),
-        )?;
-
-        // This helm value key was changed:
-        // Ref: https://github.com/openebs/mayastor-extensions/pull/419
-        yq.set_literal_value(
-            YamlKey::try_from(".base.logging.silenceLevel")?,
-            source_values.deprecated_log_silence_level(),
-            upgrade_values_file.path(),
-        )?;
-        yq.delete_object(
-            YamlKey::try_from(".base.logSilenceLevel")?,
-            upgrade_values_file.path(),
-        )?;
-
-        // This is a fix for a typo in the .csi.node.pluginMounthPath key.
-        // It was fixed, and the key now is called .csi.node.pluginMountPath.
-        yq.set_literal_value(
-            YamlKey::try_from(".csi.node.pluginMountPath")?,
-            source_values.deprecated_node_csi_mount_path(),
-            upgrade_values_file.path(),
-        )?;
-        yq.delete_object(
-            YamlKey::try_from(".csi.node.pluginMounthPath")?,
-            upgrade_values_file.path(),
-        )?;
-
-        // This sets the image tag for the jaeger-operator. This is required for the
-        // jaeger-operator dependency update from 2.50.0 to 2.50.1.
-        yq.set_literal_value(
-            YamlKey::try_from(".jaeger-operator.image.tag")?,
-            source_values.jaeger_operator_image_tag(),
-            upgrade_values_file.path(),
-        )?;
-    }
+    // Disable CRD installation in case they already exist using helm values.
+    safe_crd_install(&mut upgrade_values, "").await?;
 
-    // Default options.
-    // Image tag is set because the high_priority file is the user's source options file.
-    // The target's image tag needs to be set for PRODUCT upgrade.
-    yq.set_literal_value(
-        YamlKey::try_from(".image.tag")?,
-        target_values.image_tag(),
-        upgrade_values_file.path(),
-    )?;
+    write_to_tempfile(
+        Some(chart_dir.as_ref()),
+        upgrade_values.to_vec()?.as_slice(),
+    )
+}
 
-    // The CSI sidecar images need to always be the versions set on the chart by default.
-    yq.set_literal_value(
-        YamlKey::try_from(".csi.image.provisionerTag")?,
-        target_values.csi_provisioner_image_tag(),
-        upgrade_values_file.path(),
-    )?;
-    yq.set_literal_value(
-        YamlKey::try_from(".csi.image.attacherTag")?,
-        target_values.csi_attacher_image_tag(),
-        upgrade_values_file.path(),
-    )?;
-    yq.set_literal_value(
-        YamlKey::try_from(".csi.image.snapshotterTag")?,
-        target_values.csi_snapshotter_image_tag(),
-        upgrade_values_file.path(),
-    )?;
-    yq.set_literal_value(
-        YamlKey::try_from(".csi.image.snapshotControllerTag")?,
-        target_values.csi_snapshot_controller_image_tag(),
-        upgrade_values_file.path(),
-    )?;
-    yq.set_literal_value(
-        YamlKey::try_from(".csi.image.registrarTag")?,
-        target_values.csi_node_driver_registrar_image_tag(),
-        upgrade_values_file.path(),
-    )?;
-    yq.set_literal_value(
-        YamlKey::try_from(".csi.image.resizerTag")?,
-        target_values.csi_resizer_image_tag(),
-        upgrade_values_file.path(),
+/// This compiles all of the helm values options to be passed during the Umbrella helm chart
+/// upgrade. The Core helm chart is a dependency of the Umbrella helm chart, and its values are
+/// nested under the '.<core-chart-name>' key of the Umbrella helm chart's values. The nested
+/// Core helm chart values go through the same migrations as that of a Core helm chart release.
+/// Parameters:
+///     source_version: &Version --> The version of the Core helm chart dependency of the source
+/// Umbrella helm chart.
+///     target_version: &Version --> The version of the Core helm chart dependency of the target
+/// Umbrella helm chart.
+///     source_values_buf: Vec<u8> --> This is the value that is read from the Umbrella helm
+/// chart release, same as the `helm get values --all -o yaml` output.
+///     target_values_filepath --> This is the path to the values.yaml file for the target
+/// Umbrella helm chart, which is available locally.
+///     core_target_values_filepath --> This is the path to the values.yaml file for the target
+/// Core helm chart, which is available locally. These are the defaults for the Core helm chart
+/// dependency, which may be overridden in the target Umbrella helm chart's values.yaml.
+///     chart_dir --> This is the path to a directory that the output file is written into.
+pub(crate) async fn generate_umbrella_values_yaml_file<P, Q, R>(
+    source_version: &Version,
+    target_version: &Version,
+    source_values_buf: Vec<u8>,
+    target_values_filepath: P,
+    core_target_values_filepath: Q,
+    chart_dir: R,
+) -> Result<TempFile>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+    R: AsRef<Path>,
+{
+    let core_key = umbrella_core_values_key()?;
+
+    let mut upgrade_values = umbrella_upgrade_values(
+        source_version,
+        target_version,
+        YamlDocument::try_from(source_values_buf.as_slice())?,
+        YamlDocument::try_from(target_values_filepath.as_ref())?,
+        YamlDocument::try_from(core_target_values_filepath.as_ref())?,
     )?;
 
     // Disable CRD installation in case they already exist using helm values.
-    safe_crd_install(upgrade_values_file.path(), &yq).await?;
-
-    // helm upgrade .. --set image.tag=<version> --set image.repoTags.controlPlane= --set
-    // image.repoTags.dataPlane= --set image.repoTags.extensions=
+    safe_crd_install(&mut upgrade_values, core_key.as_str()).await?;
 
-    Ok(upgrade_values_file)
+    write_to_tempfile(
+        Some(chart_dir.as_ref()),
+        upgrade_values.to_vec()?.as_slice(),
+    )
 }
 
-/// Converts config.lokiAddress and config.snippets.extraClientConfigs from the promtail helm chart
-/// v3.11.0 to config.clients[] which is compatible with promtail helm chart v6.13.1.
-fn loki_address_to_clients(
-    source_values: &CoreValues,
-    upgrade_values_filepath: &Path,
-    yq: &YqV4,
-) -> Result<()> {
-    let promtail_config_clients_yaml_key =
-        YamlKey::try_from(".loki-stack.promtail.config.clients")?;
-    // Delete existing array, if any. The merge_files() should have added it with the default value
-    // set.
-    yq.delete_object(
-        promtail_config_clients_yaml_key.clone(),
-        upgrade_values_filepath,
-    )?;
-    let loki_address = source_values.loki_stack_promtail_loki_address();
-    let promtail_config_client = PromtailConfigClient::with_url(loki_address);
-    // Serializing to JSON, because the yq command requires the input in JSON.
-    let promtail_config_client = serde_json::to_string(&promtail_config_client).context(
-        SerializePromtailConfigClientToJson {
-            object: promtail_config_client,
-        },
-    )?;
-    yq.append_to_array(
-        promtail_config_clients_yaml_key,
-        promtail_config_client,
-        upgrade_values_filepath,
+/// This merges the Umbrella helm chart's source and target values, and replaces the Core helm
+/// chart's values within them with the migrated Core helm chart values.
+fn umbrella_upgrade_values(
+    source_version: &Version,
+    target_version: &Version,
+    source_values: YamlDocument,
+    target_values: YamlDocument,
+    core_target_defaults: YamlDocument,
+) -> Result<YamlDocument> {
+    let core_key = umbrella_core_values_key()?;
+
+    // The Core helm chart values of the source and the target.
+    let core_source_values = source_values.sub_document(&core_key);
+    let core_target_values =
+        YamlDocument::merge(target_values.sub_document(&core_key), core_target_defaults);
+    let mut core_upgrade_values =
+        YamlDocument::merge(core_source_values.clone(), core_target_values.clone());
+
+    migrate_values(
+        source_version,
+        target_version,
+        &core_source_values,
+        &core_target_values,
+        &mut core_upgrade_values,
     )?;
-    // Merge the extraClientConfigs from the promtail v3.11.0 chart to the v6.13.1 chart's
-    // config.clients block. Ref: https://github.com/grafana/helm-charts/issues/1214
-    // Ref: https://github.com/grafana/helm-charts/pull/1425
-    if !source_values.promtail_extra_client_configs().is_empty() {
-        // Converting the YAML to a JSON because the yq command requires the object input as a JSON.
-        let promtail_extra_client_config: serde_json::Value = serde_yaml::from_str(
-            source_values.promtail_extra_client_configs(),
-        )
-        .context(DeserializePromtailExtraConfig {
-            config: source_values.promtail_extra_client_configs().to_string(),
-        })?;
-        let promtail_extra_client_config = serde_json::to_string(&promtail_extra_client_config)
-            .context(SerializePromtailExtraConfigToJson {
-                config: promtail_extra_client_config,
-            })?;
-
-        yq.append_to_object(
-            YamlKey::try_from(".loki-stack.promtail.config.clients[0]")?,
-            promtail_extra_client_config,
-            upgrade_values_filepath,
-        )?;
-    }
 
-    // Cleanup config.snippets.extraClientConfig from the promtail chart.
-    yq.delete_object(
-        YamlKey::try_from(".loki-stack.promtail.config.snippets.extraClientConfigs")?,
-        upgrade_values_filepath,
-    )?;
+    let mut upgrade_values = YamlDocument::merge(source_values, target_values);
+    upgrade_values.set_sub_document(core_key, core_upgrade_values)?;
 
-    // Cleanup config.lokiAddress from the promtail chart.
-    yq.delete_object(
-        YamlKey::try_from(".loki-stack.promtail.config.lokiAddress")?,
-        upgrade_values_filepath,
-    )?;
+    Ok(upgrade_values)
+}
 
-    Ok(())
+/// This is the key of the Core helm chart's values within the Umbrella helm chart's values.
+fn umbrella_core_values_key() -> Result<YamlKey> {
+    YamlKey::try_from(format!(".\"{CORE_CHART_NAME}\""))
+}
+
+/// This sets the special-case values for the version-specific migrations, and the default
+/// options, on the merged upgrade values. The migrations are read from the values_migrations.yaml
+/// file which is embedded into this binary.
+fn migrate_values(
+    source_version: &Version,
+    target_version: &Version,
+    source_values: &YamlDocument,
+    target_values: &YamlDocument,
+    upgrade_values: &mut YamlDocument,
+) -> Result<()> {
+    ValuesMigrations::embedded()?.apply(
+        source_version,
+        target_version,
+        source_values,
+        target_values,
+        upgrade_values,
+    )
 }
 
 /// Use pre-defined helm chart templating to disable CRD installation if they already exist.
-async fn safe_crd_install(upgrade_values_filepath: &Path, yq: &YqV4) -> Result<()> {
+/// The key_prefix is the key of the Core helm chart's values within the upgrade values, it is
+/// empty for the Core helm chart itself.
+async fn safe_crd_install(upgrade_values: &mut YamlDocument, key_prefix: &str) -> Result<()> {
     let mut crd_set_to_helm_toggle: HashMap<Vec<&str>, YamlKey> = HashMap::new();
     // These 3 CRDs usually exist together.
     crd_set_to_helm_toggle.insert(
@@ -498,11 +184,11 @@ async fn safe_crd_install(upgrade_values_filepath: &Path, yq: &YqV4) -> Result<(
             "volumesnapshotcontents.snapshot.storage.k8s.io",
             "volumesnapshots.snapshot.storage.k8s.io",
         ],
-        YamlKey::try_from(".crds.csi.volumeSnapshots.enabled")?,
+        YamlKey::try_from(format!("{key_prefix}.crds.csi.volumeSnapshots.enabled"))?,
     );
     crd_set_to_helm_toggle.insert(
         vec!["jaegers.jaegertracing.io"],
-        YamlKey::try_from(".crds.jaeger.enabled")?,
+        YamlKey::try_from(format!("{key_prefix}.crds.jaeger.enabled"))?,
     );
 
     let crds_api = KubeClient::crds_api().await?;
@@ -535,9 +221,98 @@ async fn safe_crd_install(upgrade_values_filepath: &Path, yq: &YqV4) -> Result<(
             .iter()
             .any(|name| crd_set.contains(&name.as_str()))
         {
-            yq.set_literal_value(helm_toggle, false, upgrade_values_filepath)?
+            upgrade_values.set_literal_value(helm_toggle, false)?
         }
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::helm::{
+        values::{migrate_values, umbrella_upgrade_values},
+        yaml::editor::YamlDocument,
+    };
+    use semver::Version;
+    use std::{fs, path::PathBuf};
+
+    /// This is the helm chart version of the target_values.yaml test data.
+    const TARGET_VERSION: Version = Version::new(2, 7, 0);
+
+    /// Merges the source values with the target values, runs the values migrations and compares
+    /// the result with the golden file 'upgrade_values_<source_version>.yaml'. The golden file is
+    /// (re)generated instead if the UPDATE_GOLDEN environment variable is set.
+    fn assert_migration(source_version: &str) {
+        let testdata = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("src/bin/upgrade-job/helm/testdata/values");
+        let source_values_buf =
+            fs::read(testdata.join(format!("source_values_{source_version}.yaml"))).unwrap();
+        let target_values_path = testdata.join("target_values.yaml");
+        let golden_path = testdata.join(format!("upgrade_values_{source_version}.yaml"));
+
+        let source_values = YamlDocument::try_from(source_values_buf.as_slice()).unwrap();
+        let target_values = YamlDocument::try_from(target_values_path.as_path()).unwrap();
+        let mut upgrade_values = YamlDocument::merge(source_values.clone(), target_values.clone());
+
+        migrate_values(
+            &Version::parse(source_version).unwrap(),
+            &TARGET_VERSION,
+            &source_values,
+            &target_values,
+            &mut upgrade_values,
+        )
+        .unwrap();
+        let upgrade_values = String::from_utf8(upgrade_values.to_vec().unwrap()).unwrap();
+
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            fs::write(golden_path, upgrade_values).unwrap();
+            return;
+        }
+        assert_eq!(upgrade_values, fs::read_to_string(golden_path).unwrap());
+    }
+
+    #[test]
+    fn migrate_values_from_2_0() {
+        assert_migration("2.0.1");
+    }
+
+    #[test]
+    fn migrate_values_from_2_3() {
+        assert_migration("2.3.0");
+    }
+
+    #[test]
+    fn migrate_values_from_2_5() {
+        assert_migration("2.5.0");
+    }
+
+    #[test]
+    fn migrate_values_from_2_6() {
+        assert_migration("2.6.0");
+    }
+
+    #[test]
+    fn umbrella_values_nest_migrated_core_values() {
+        let document = |yaml: &str| YamlDocument::try_from(yaml.as_bytes()).unwrap();
+        let source_values = document(
+            "mayastor:\n  io_engine:\n    logLevel: info,io_engine=info\n  image:\n    tag: v2.0.1\nlvm:\n  enabled: true\n",
+        );
+        let target_values = document("mayastor:\n  csi:\n    node:\n      kubeletDir: /var/lib/kubelet\nlvm:\n  enabled: false\n  lvmNode: {}\n");
+        let core_target_defaults = document("io_engine:\n  logLevel: info\nimage:\n  tag: v2.7.0\ncsi:\n  node:\n    kubeletDir: /kubelet\n");
+
+        let upgrade_values = umbrella_upgrade_values(
+            &Version::new(2, 0, 1),
+            &TARGET_VERSION,
+            source_values,
+            target_values,
+            core_target_defaults,
+        )
+        .unwrap();
+
+        assert_eq!(
+            upgrade_values,
+            document("mayastor:\n  io_engine:\n    logLevel: info\n  image:\n    tag: v2.7.0\n  csi:\n    node:\n      kubeletDir: /var/lib/kubelet\nlvm:\n  enabled: true\n  lvmNode: {}\n")
+        );
+    }
+}
@@ -1,2 +1,4 @@
-/// This contains apis to use the yq binary.
-pub(crate) mod yq;
+/// This contains the comments of yaml documents, which the yaml document editor retains.
+pub(crate) mod comments;
+/// This contains an in-process editor for yaml documents.
+pub(crate) mod editor;
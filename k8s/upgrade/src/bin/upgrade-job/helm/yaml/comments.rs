@@ -0,0 +1,389 @@
+use crate::helm::yaml::editor::YamlKeySegment;
+use std::collections::HashMap;
+
+/// This is the path of an object key or an array member, from the root of a yaml document.
+type NodePath = Vec<YamlKeySegment>;
+
+/// The comments of a single object key or array member.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct NodeComments {
+    /// The comment lines above the node.
+    above: Vec<String>,
+    /// The comment at the end of the node's line.
+    inline: Option<String>,
+}
+
+/// These are the comments of a yaml document. Each comment belongs to the object key or the array
+/// member on the line below it, or on the same line, and it is kept against the path of that
+/// node. The comments are written back above and beside the same nodes when the document is
+/// serialized, so they survive edits to the values of the nodes. The comments of the nodes which
+/// have been removed are dropped with them.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Comments {
+    nodes: HashMap<NodePath, NodeComments>,
+    /// The comment lines after the last node of the document.
+    footer: Vec<String>,
+}
+
+impl Comments {
+    /// Collects the comments of a yaml document.
+    pub(crate) fn parse(text: &str) -> Self {
+        let mut comments = Self::default();
+        let mut pending = Vec::new();
+
+        for line in scan(text) {
+            match line {
+                Line::Comment(comment) => pending.push(comment.to_string()),
+                Line::Nodes { paths, inline } => {
+                    if let Some(path) = paths.first() {
+                        comments
+                            .nodes
+                            .entry(path.clone())
+                            .or_default()
+                            .above
+                            .append(&mut pending);
+                    }
+                    if let (Some(path), Some(inline)) = (paths.last(), inline) {
+                        comments.nodes.entry(path.clone()).or_default().inline =
+                            Some(inline.to_string());
+                    }
+                }
+                Line::Other => {}
+            }
+        }
+        comments.footer = pending;
+        comments
+    }
+
+    /// Adds the comments of the nodes which have no comments of their own.
+    pub(crate) fn merge(&mut self, other: Self) {
+        for (path, node_comments) in other.nodes {
+            self.nodes.entry(path).or_insert(node_comments);
+        }
+        if self.footer.is_empty() {
+            self.footer = other.footer;
+        }
+    }
+
+    /// Returns the comments of the nodes under the path, with paths relative to it.
+    pub(crate) fn sub_comments(&self, prefix: &[YamlKeySegment]) -> Self {
+        let nodes = self
+            .nodes
+            .iter()
+            .filter(|(path, _)| path.len() > prefix.len() && path.starts_with(prefix))
+            .map(|(path, node_comments)| (path[prefix.len() ..].to_vec(), node_comments.clone()))
+            .collect();
+        Self {
+            nodes,
+            footer: Vec::new(),
+        }
+    }
+
+    /// Replaces the comments of the nodes under the path with the comments of a sub-document.
+    pub(crate) fn set_sub_comments(&mut self, prefix: &[YamlKeySegment], comments: Self) {
+        self.nodes
+            .retain(|path, _| !(path.len() > prefix.len() && path.starts_with(prefix)));
+        for (path, node_comments) in comments.nodes {
+            let mut full_path = prefix.to_vec();
+            full_path.extend(path);
+            self.nodes.insert(full_path, node_comments);
+        }
+    }
+
+    /// Drops the comments of a node which has been removed, and of the nodes under it. The
+    /// comments of the array members after a removed array member move up along with them.
+    pub(crate) fn remove(&mut self, removed: &[YamlKeySegment]) {
+        self.nodes.retain(|path, _| !path.starts_with(removed));
+
+        let Some((YamlKeySegment::Index(removed_index), parent)) = removed.split_last() else {
+            return;
+        };
+        self.nodes = std::mem::take(&mut self.nodes)
+            .into_iter()
+            .map(|(mut path, node_comments)| {
+                if path.len() > parent.len() && path.starts_with(parent) {
+                    if let YamlKeySegment::Index(index) = &mut path[parent.len()] {
+                        if *index > *removed_index {
+                            *index -= 1;
+                        }
+                    }
+                }
+                (path, node_comments)
+            })
+            .collect();
+    }
+
+    /// Writes the comments into a serialized yaml document, above and beside the nodes which
+    /// they belong to.
+    pub(crate) fn render(&self, yaml: &str) -> String {
+        let mut rendered = String::with_capacity(yaml.len());
+
+        for (text, line) in yaml.lines().zip(scan(yaml)) {
+            if let Line::Nodes { paths, .. } = &line {
+                let indent = " ".repeat(text.len() - text.trim_start_matches(' ').len());
+                for node_comments in paths.iter().filter_map(|path| self.nodes.get(path)) {
+                    for comment in &node_comments.above {
+                        rendered.push_str(format!("{indent}{comment}\n").as_str());
+                    }
+                }
+                rendered.push_str(text);
+                if let Some(inline) = paths
+                    .iter()
+                    .rev()
+                    .find_map(|path| self.nodes.get(path)?.inline.as_deref())
+                {
+                    rendered.push_str(format!(" {inline}").as_str());
+                }
+            } else {
+                rendered.push_str(text);
+            }
+            rendered.push('\n');
+        }
+
+        for comment in &self.footer {
+            rendered.push_str(format!("{comment}\n").as_str());
+        }
+        rendered
+    }
+}
+
+/// A line of a yaml document, as seen by the comment scanner.
+#[derive(Debug, PartialEq)]
+enum Line<'a> {
+    /// A line with only a comment on it.
+    Comment(&'a str),
+    /// A line which starts one or more nodes, i.e. an object key, an array member, or both, e.g.
+    /// '- name: x'. The paths are ordered from the outermost node.
+    Nodes {
+        paths: Vec<NodePath>,
+        inline: Option<&'a str>,
+    },
+    /// Every other line, e.g. a blank line, or a line of a multi-line value.
+    Other,
+}
+
+/// Works out the nodes which each line of a block style yaml document starts, from the
+/// indentation of the lines.
+fn scan(text: &str) -> Vec<Line<'_>> {
+    let mut lines = Vec::new();
+    // The nodes which enclose the current line, with the columns they start at.
+    let mut stack: Vec<(usize, YamlKeySegment)> = Vec::new();
+    // The column of the node whose block scalar value is being scanned, if any.
+    let mut block_scalar: Option<usize> = None;
+
+    for text in text.lines() {
+        let content = text.trim_start_matches(' ');
+        let indent = text.len() - content.len();
+
+        if let Some(owner) = block_scalar {
+            if content.is_empty() || indent > owner {
+                lines.push(Line::Other);
+                continue;
+            }
+            block_scalar = None;
+        }
+        if content.starts_with('#') {
+            lines.push(Line::Comment(content));
+            continue;
+        }
+
+        let mut paths = Vec::new();
+        let mut column = indent;
+        let mut owner = indent;
+        let mut rest = content;
+        loop {
+            if rest == "-" || rest.starts_with("- ") {
+                let mut index = 0;
+                while let Some((start, segment)) = stack.last() {
+                    let is_member = matches!(segment, YamlKeySegment::Index(_));
+                    if *start < column || (*start == column && !is_member) {
+                        break;
+                    }
+                    if let (true, YamlKeySegment::Index(previous)) = (*start == column, segment) {
+                        index = previous + 1;
+                    }
+                    stack.pop();
+                }
+                stack.push((column, YamlKeySegment::Index(index)));
+                paths.push(stack.iter().map(|(_, segment)| segment.clone()).collect());
+                owner = column;
+
+                let value = rest[1 ..].trim_start_matches(' ');
+                column += rest.len() - value.len();
+                rest = value;
+                continue;
+            }
+            if let Some((key, value)) = split_key(rest) {
+                while stack.last().is_some_and(|(start, _)| *start >= column) {
+                    stack.pop();
+                }
+                stack.push((column, YamlKeySegment::Field(key)));
+                paths.push(stack.iter().map(|(_, segment)| segment.clone()).collect());
+                owner = column;
+                rest = value;
+            }
+            break;
+        }
+
+        if paths.is_empty() {
+            lines.push(Line::Other);
+            continue;
+        }
+        let (value, inline) = split_inline_comment(rest);
+        if value.starts_with('|') || value.starts_with('>') {
+            block_scalar = Some(owner);
+        }
+        lines.push(Line::Nodes { paths, inline });
+    }
+    lines
+}
+
+/// Splits an object member into its key and the rest of the line, e.g. 'a: b' into 'a' and 'b'.
+/// Returns None if the line does not start with an object key.
+fn split_key(content: &str) -> Option<(String, &str)> {
+    let (key, after_key) = match content.chars().next()? {
+        quote @ ('"' | '\'') => {
+            let (key, length) = quoted(content, quote)?;
+            (key, &content[length ..])
+        }
+        '[' | '{' | '#' | '&' | '*' | '!' | '|' | '>' | '%' | '@' | '`' | '?' => return None,
+        _ => {
+            let colon = content.char_indices().find_map(|(index, c)| {
+                let after = &content[index + 1 ..];
+                (c == ':' && (after.is_empty() || after.starts_with(' '))).then_some(index)
+            })?;
+            let key = &content[.. colon];
+            if key.contains(" #") {
+                return None;
+            }
+            (key.trim_end().to_string(), &content[colon ..])
+        }
+    };
+
+    let value = after_key.strip_prefix(':')?;
+    if !(value.is_empty() || value.starts_with(' ')) {
+        return None;
+    }
+    Some((key, value.trim_start_matches(' ')))
+}
+
+/// Reads a quoted yaml scalar from the start of the input. Returns the unquoted scalar and the
+/// length of the quoted scalar.
+fn quoted(input: &str, quote: char) -> Option<(String, usize)> {
+    let mut scalar = String::new();
+    let mut chars = input.char_indices().skip(1).peekable();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '\'' if quote == '\'' => {
+                if chars.peek().map(|(_, next)| *next) == Some('\'') {
+                    chars.next();
+                    scalar.push('\'');
+                } else {
+                    return Some((scalar, index + 1));
+                }
+            }
+            '"' if quote == '"' => return Some((scalar, index + 1)),
+            '\\' if quote == '"' => {
+                let (_, escaped) = chars.next()?;
+                scalar.push(escaped);
+            }
+            c => scalar.push(c),
+        }
+    }
+    None
+}
+
+/// Splits the value of a node from the comment at the end of its line, if any.
+fn split_inline_comment(value: &str) -> (&str, Option<&str>) {
+    let start = match value.chars().next() {
+        Some(quote @ ('"' | '\'')) => quoted(value, quote)
+            .map(|(_, length)| length)
+            .unwrap_or(value.len()),
+        _ => 0,
+    };
+    let comment = value[start ..].char_indices().find_map(|(index, c)| {
+        let position = start + index;
+        (c == '#' && (position == 0 || value[.. position].ends_with(' '))).then_some(position)
+    });
+    match comment {
+        Some(position) => (value[.. position].trim_end(), Some(&value[position ..])),
+        None => (value, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{scan, Comments, Line, YamlKeySegment};
+
+    fn path(segments: &[&str]) -> Vec<YamlKeySegment> {
+        segments
+            .iter()
+            .map(|segment| match segment.parse() {
+                Ok(index) => YamlKeySegment::Index(index),
+                Err(_) => YamlKeySegment::Field(segment.to_string()),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn scan_node_paths() {
+        let lines = scan(
+            "# header\na:\n  b: c # inline\n  'd.e': |\n    # not a comment\n    f: g\n  list:\n  - x\n  - name: y\n    value: \"z # q\"\nh:\n- - i\n",
+        );
+        assert_eq!(
+            lines,
+            vec![
+                Line::Comment("# header"),
+                Line::Nodes {
+                    paths: vec![path(&["a"])],
+                    inline: None
+                },
+                Line::Nodes {
+                    paths: vec![path(&["a", "b"])],
+                    inline: Some("# inline")
+                },
+                Line::Nodes {
+                    paths: vec![path(&["a", "d.e"])],
+                    inline: None
+                },
+                Line::Other,
+                Line::Other,
+                Line::Nodes {
+                    paths: vec![path(&["a", "list"])],
+                    inline: None
+                },
+                Line::Nodes {
+                    paths: vec![path(&["a", "list", "0"])],
+                    inline: None
+                },
+                Line::Nodes {
+                    paths: vec![path(&["a", "list", "1"]), path(&["a", "list", "1", "name"])],
+                    inline: None
+                },
+                Line::Nodes {
+                    paths: vec![path(&["a", "list", "1", "value"])],
+                    inline: None
+                },
+                Line::Nodes {
+                    paths: vec![path(&["h"])],
+                    inline: None
+                },
+                Line::Nodes {
+                    paths: vec![path(&["h", "0"]), path(&["h", "0", "0"])],
+                    inline: None
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn remove_shifts_array_members() {
+        let mut comments =
+            Comments::parse("list:\n# first\n- a\n# second\n- b: c\n  # third\n  d: e\n");
+        comments.remove(&path(&["list", "0"]));
+        assert_eq!(
+            comments.render("list:\n- b: c\n  d: e\n"),
+            "list:\n# second\n- b: c\n  # third\n  d: e\n"
+        );
+    }
+}
@@ -0,0 +1,604 @@
+use crate::{
+    common::error::{
+        NotAValidYamlKeyForStringValue, ReadingFile, RegexCompile, Result, SerializeYamlValue,
+        U8VectorToString, YamlAppendToNonArray, YamlAppendToNonObject, YamlArrayIndexOutOfBounds,
+        YamlParseFromFile, YamlParseFromSlice, YamlPathNotTraversable,
+    },
+    helm::yaml::comments::Comments,
+};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_yaml::{Mapping, Value};
+use snafu::{ensure, ResultExt};
+use std::{fmt::Display, fs, ops::Deref, path::Path, str};
+
+/// This is a single step in the path of a YamlKey.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub(crate) enum YamlKeySegment {
+    /// This is the key of a yaml object member, e.g. 'b' in '.a.b'.
+    Field(String),
+    /// This is the index of a yaml array member, e.g. '0' in '.a[0]'.
+    Index(usize),
+}
+
+/// This is a container for the String of an input yaml key.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(try_from = "String")]
+pub(crate) struct YamlKey {
+    key: String,
+    segments: Vec<YamlKeySegment>,
+}
+
+impl TryFrom<&str> for YamlKey {
+    type Error = crate::common::error::Error;
+
+    /// This generates a YamlKey after vetting it. A yaml dot notation
+    /// pattern is considered a valid input. Array members may be picked out using their index
+    /// within square brackets, e.g. ".a.b[0].c". Object keys which contain a '.' or a '[' may be
+    /// wrapped in double quotes, e.g. '.a."b.c"'.
+    fn try_from(value: &str) -> Result<Self> {
+        let value_as_string = value.to_string();
+        // A string where '.' followed by any character, any number of times,
+        // again the set may be repeated any number of times. E.g: ".a.x.p.j".
+        let yaml_key_regex = r"^(\..+)+$";
+        ensure!(
+            Regex::new(yaml_key_regex)
+                .context(RegexCompile {
+                    expression: yaml_key_regex.to_string(),
+                })?
+                .is_match(value),
+            NotAValidYamlKeyForStringValue {
+                key: value_as_string
+            }
+        );
+
+        let segments = parse_key_segments(value).ok_or(
+            NotAValidYamlKeyForStringValue {
+                key: value_as_string.clone(),
+            }
+            .build(),
+        )?;
+
+        Ok(YamlKey {
+            key: value_as_string,
+            segments,
+        })
+    }
+}
+
+impl TryFrom<String> for YamlKey {
+    type Error = crate::common::error::Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        YamlKey::try_from(value.as_str())
+    }
+}
+
+impl Deref for YamlKey {
+    type Target = String;
+
+    /// This Deref implementation lets the inner String stand in for the YamlKey.
+    fn deref(&self) -> &Self::Target {
+        &self.key
+    }
+}
+
+/// Splits a yaml dot notation key into object keys and array indices. Returns None if the key
+/// is malformed, e.g. if it has an empty object key or an unterminated quote or bracket.
+fn parse_key_segments(key: &str) -> Option<Vec<YamlKeySegment>> {
+    let mut segments = Vec::new();
+    let mut chars = key.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '.' => {
+                let mut field = String::new();
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    loop {
+                        match chars.next()? {
+                            '"' => break,
+                            c => field.push(c),
+                        }
+                    }
+                } else {
+                    while let Some(&c) = chars.peek() {
+                        if c == '.' || c == '[' {
+                            break;
+                        }
+                        field.push(c);
+                        chars.next();
+                    }
+                }
+                if field.is_empty() {
+                    return None;
+                }
+                segments.push(YamlKeySegment::Field(field));
+            }
+            '[' => {
+                let mut index = String::new();
+                loop {
+                    match chars.next()? {
+                        ']' => break,
+                        c => index.push(c),
+                    }
+                }
+                segments.push(YamlKeySegment::Index(index.parse().ok()?));
+            }
+            _ => return None,
+        }
+    }
+
+    Some(segments)
+}
+
+/// This is an in-process editor for yaml documents. Object key order is preserved across edits.
+/// Comments are retained against the object keys and the array members which they are written
+/// above or beside. The comments of the keys which are removed are dropped along with them.
+#[derive(Clone, Debug)]
+pub(crate) struct YamlDocument {
+    root: Value,
+    comments: Comments,
+}
+
+impl PartialEq for YamlDocument {
+    /// Yaml documents are equal if their values are equal, i.e. comments are not compared.
+    fn eq(&self, other: &Self) -> bool {
+        self.root == other.root
+    }
+}
+
+impl TryFrom<&[u8]> for YamlDocument {
+    type Error = crate::common::error::Error;
+
+    fn try_from(buf: &[u8]) -> Result<Self> {
+        let text = str::from_utf8(buf).context(U8VectorToString)?;
+        let root = serde_yaml::from_str(text).context(YamlParseFromSlice {
+            input_yaml: text.to_string(),
+        })?;
+        Ok(Self {
+            root,
+            comments: Comments::parse(text),
+        })
+    }
+}
+
+impl TryFrom<&Path> for YamlDocument {
+    type Error = crate::common::error::Error;
+
+    fn try_from(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path).context(ReadingFile {
+            filepath: path.to_path_buf(),
+        })?;
+        let root = serde_yaml::from_str(text.as_str()).context(YamlParseFromFile {
+            filepath: path.to_path_buf(),
+        })?;
+        Ok(Self {
+            root,
+            comments: Comments::parse(text.as_str()),
+        })
+    }
+}
+
+impl YamlDocument {
+    // TODO:
+    // 1. Arrays are treated like unique values on their own, and high_priority is preferred over
+    //    low_priority. Arrays are not merged, if the object in the array member is identical to an
+    //    existing member in the other file, we cannot decide on the key-value-pairs to compare to
+    //    check for identical array objects.
+    // 2. If the default value in the upgrade target has changed, and the user has also changed the
+    //    default value (of the upgrade source), which one should be preferred?
+    /// Deep-merge two yaml documents. The high_priority document's values are preferred over
+    /// those of the low_priority document. In case there are values absent in the high_priority
+    /// document which exist in the low_priority document, the values of the low_priority
+    /// document are taken. The keys of the low_priority document retain their order, and keys
+    /// which only exist in the high_priority document are added after them.
+    /// E.g:
+    ///       high_priority file:
+    ///       ===================
+    ///       foo:
+    ///         bar: "foobar"
+    ///         baz:
+    ///           - "alpha"
+    ///           - "beta"
+    ///
+    ///       low_priority file:
+    ///       ==================
+    ///       foo:
+    ///         bar: "foobaz"
+    ///         baz:
+    ///           - "gamma"
+    ///           - "delta"
+    ///       friend: "ferris"
+    ///
+    ///       result:
+    ///       =======
+    ///       foo:
+    ///         bar: "foobar"
+    ///         baz:
+    ///           - "alpha"
+    ///           - "beta"
+    ///       friend: "ferris"
+    ///
+    /// Special case: When the default value has changed, and the user has not customised that
+    /// option, special upgrade values yaml updates have to be added to single out specific cases
+    /// and migrate the older default to the newer one. E.g.: the .io_engine.logLevel is set to
+    /// 'info' deliberately if the upgrade source file is seen to contain the value
+    /// 'info,io_engine=info' and the target yaml is seen to not contain it.
+    ///
+    /// The comments of the low_priority document are preferred, as these are the comments of
+    /// the helm chart's values.yaml.
+    pub(crate) fn merge(high_priority: Self, low_priority: Self) -> Self {
+        let mut root = low_priority.root;
+        // An empty high_priority document has nothing to contribute.
+        if !high_priority.root.is_null() {
+            merge_values(&mut root, high_priority.root);
+        }
+        let mut comments = low_priority.comments;
+        comments.merge(high_priority.comments);
+        Self { root, comments }
+    }
+
+    /// Append objects to yaml arrays. The array is created if the key is absent or null.
+    pub(crate) fn append_to_array<V>(&mut self, key: YamlKey, value: V) -> Result<()>
+    where
+        V: Serialize,
+    {
+        let value = serde_yaml::to_value(value).context(SerializeYamlValue)?;
+        let target = self.get_or_insert_mut(&key)?;
+        match target {
+            Value::Null => *target = Value::Sequence(vec![value]),
+            Value::Sequence(sequence) => sequence.push(value),
+            _ => {
+                return YamlAppendToNonArray {
+                    key: key.to_string(),
+                }
+                .fail()
+            }
+        }
+        Ok(())
+    }
+
+    /// Append fields to yaml objects. The fields of the input object replace the existing fields
+    /// with the same keys. If the key is absent or null, it is set to the input object. If the key
+    /// is an array, the input object is added to the end of the array.
+    pub(crate) fn append_to_object<V>(&mut self, key: YamlKey, value: V) -> Result<()>
+    where
+        V: Serialize,
+    {
+        let value = serde_yaml::to_value(value).context(SerializeYamlValue)?;
+        let target = self.get_or_insert_mut(&key)?;
+        match (target, value) {
+            (target @ Value::Null, value) => *target = value,
+            (Value::Sequence(sequence), value) => sequence.push(value),
+            (Value::Mapping(mapping), Value::Mapping(fields)) => {
+                for (field, field_value) in fields {
+                    mapping.insert(field, field_value);
+                }
+            }
+            _ => {
+                return YamlAppendToNonObject {
+                    key: key.to_string(),
+                }
+                .fail()
+            }
+        }
+        Ok(())
+    }
+
+    /// Delete objects from a yaml document. Deleting an absent key is not an error.
+    pub(crate) fn delete_object(&mut self, key: YamlKey) -> Result<()> {
+        let Some((last, parents)) = key.segments.split_last() else {
+            return Ok(());
+        };
+
+        let mut current = &mut self.root;
+        for segment in parents {
+            current = match (current, segment) {
+                (Value::Mapping(mapping), YamlKeySegment::Field(field)) => {
+                    match mapping.get_mut(field.as_str()) {
+                        Some(value) => value,
+                        None => return Ok(()),
+                    }
+                }
+                (Value::Sequence(sequence), YamlKeySegment::Index(index)) => {
+                    match sequence.get_mut(*index) {
+                        Some(value) => value,
+                        None => return Ok(()),
+                    }
+                }
+                _ => return Ok(()),
+            };
+        }
+
+        match (current, last) {
+            (Value::Mapping(mapping), YamlKeySegment::Field(field)) => {
+                if mapping.shift_remove(field.as_str()).is_none() {
+                    return Ok(());
+                }
+            }
+            (Value::Sequence(sequence), YamlKeySegment::Index(index)) => {
+                if *index >= sequence.len() {
+                    return Ok(());
+                }
+                sequence.remove(*index);
+            }
+            _ => return Ok(()),
+        }
+        self.comments.remove(&key.segments);
+        Ok(())
+    }
+
+    /// Returns the value at the key, if it exists.
+    pub(crate) fn get(&self, key: &YamlKey) -> Option<&Value> {
+        key.segments
+            .iter()
+            .try_fold(&self.root, |current, segment| match (current, segment) {
+                (Value::Mapping(mapping), YamlKeySegment::Field(field)) => {
+                    mapping.get(field.as_str())
+                }
+                (Value::Sequence(sequence), YamlKeySegment::Index(index)) => sequence.get(*index),
+                _ => None,
+            })
+    }
+
+    /// Returns the value at the key as a yaml document of its own. The document is empty if the
+    /// key is absent.
+    pub(crate) fn sub_document(&self, key: &YamlKey) -> Self {
+        Self {
+            root: self.get(key).cloned().unwrap_or_default(),
+            comments: self.comments.sub_comments(&key.segments),
+        }
+    }
+
+    /// This sets the contents of a yaml document as the value at the key.
+    pub(crate) fn set_sub_document(&mut self, key: YamlKey, document: Self) -> Result<()> {
+        *self.get_or_insert_mut(&key)? = document.root;
+        self.comments
+            .set_sub_comments(&key.segments, document.comments);
+        Ok(())
+    }
+
+    /// This sets a yaml value in the document, as is.
+    pub(crate) fn set_value<V>(&mut self, key: YamlKey, value: V) -> Result<()>
+    where
+        V: Serialize,
+    {
+        *self.get_or_insert_mut(&key)? = serde_yaml::to_value(value).context(SerializeYamlValue)?;
+        Ok(())
+    }
+
+    /// This sets yaml values in the document. The 'true', 'false' and 'null' literals, and
+    /// integers are set as yaml booleans, null and numbers respectively. Everything else is set
+    /// as a yaml string.
+    pub(crate) fn set_literal_value<V>(&mut self, key: YamlKey, value: V) -> Result<()>
+    where
+        V: Display + Sized,
+    {
+        let value = match format!("{value}").as_str() {
+            "true" => Value::Bool(true),
+            "false" => Value::Bool(false),
+            "null" => Value::Null,
+            something_else => match something_else.parse::<i64>() {
+                Ok(integer) => Value::Number(integer.into()),
+                Err(_) => Value::String(something_else.to_string()),
+            },
+        };
+
+        *self.get_or_insert_mut(&key)? = value;
+        Ok(())
+    }
+
+    /// Serializes the yaml document, along with its comments.
+    pub(crate) fn to_vec(&self) -> Result<Vec<u8>> {
+        let yaml = serde_yaml::to_string(&self.root).context(SerializeYamlValue)?;
+        Ok(self.comments.render(yaml.as_str()).into_bytes())
+    }
+
+    /// Returns a mutable reference to the value at the key. Absent object keys, and null
+    /// values along the path, are filled in with empty objects or arrays. An array index which is
+    /// equal to the length of the array adds a member to the end of the array. The value at the
+    /// key is null if it did not exist.
+    fn get_or_insert_mut(&mut self, key: &YamlKey) -> Result<&mut Value> {
+        let mut current = &mut self.root;
+        for segment in &key.segments {
+            if current.is_null() {
+                *current = match segment {
+                    YamlKeySegment::Field(_) => Value::Mapping(Mapping::new()),
+                    YamlKeySegment::Index(_) => Value::Sequence(Vec::new()),
+                };
+            }
+
+            current = match (current, segment) {
+                (Value::Mapping(mapping), YamlKeySegment::Field(field)) => mapping
+                    .entry(Value::String(field.clone()))
+                    .or_insert(Value::Null),
+                (Value::Sequence(sequence), YamlKeySegment::Index(index)) => {
+                    let length = sequence.len();
+                    if *index == length {
+                        sequence.push(Value::Null);
+                    }
+                    sequence.get_mut(*index).ok_or(
+                        YamlArrayIndexOutOfBounds {
+                            key: key.to_string(),
+                            index: *index,
+                            length,
+                        }
+                        .build(),
+                    )?
+                }
+                _ => {
+                    return YamlPathNotTraversable {
+                        key: key.to_string(),
+                    }
+                    .fail()
+                }
+            };
+        }
+        Ok(current)
+    }
+}
+
+/// Merges the 'high_priority' value into the 'low_priority' value, in place. Objects are merged
+/// recursively. Every other kind of value from 'high_priority' replaces the one in
+/// 'low_priority'.
+fn merge_values(low_priority: &mut Value, high_priority: Value) {
+    match (low_priority, high_priority) {
+        (Value::Mapping(low), Value::Mapping(high)) => {
+            for (key, high_value) in high {
+                match low.get_mut(&key) {
+                    Some(low_value) => merge_values(low_value, high_value),
+                    None => {
+                        low.insert(key, high_value);
+                    }
+                }
+            }
+        }
+        (low, high) => *low = high,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{YamlDocument, YamlKey, YamlKeySegment};
+
+    fn document(yaml: &str) -> YamlDocument {
+        YamlDocument::try_from(yaml.as_bytes()).unwrap()
+    }
+
+    fn key(key: &str) -> YamlKey {
+        YamlKey::try_from(key).unwrap()
+    }
+
+    #[test]
+    fn yaml_key_parsing() {
+        assert_eq!(
+            key(".loki-stack.promtail.config.clients[0]").segments,
+            vec![
+                YamlKeySegment::Field("loki-stack".to_string()),
+                YamlKeySegment::Field("promtail".to_string()),
+                YamlKeySegment::Field("config".to_string()),
+                YamlKeySegment::Field("clients".to_string()),
+                YamlKeySegment::Index(0),
+            ]
+        );
+        assert_eq!(
+            key(r#".a."b.c"[1][2]"#).segments,
+            vec![
+                YamlKeySegment::Field("a".to_string()),
+                YamlKeySegment::Field("b.c".to_string()),
+                YamlKeySegment::Index(1),
+                YamlKeySegment::Index(2),
+            ]
+        );
+        assert!(YamlKey::try_from("a.b").is_err());
+        assert!(YamlKey::try_from(".a..b").is_err());
+        assert!(YamlKey::try_from(".a[x]").is_err());
+        assert!(YamlKey::try_from(".a[0").is_err());
+        assert!(YamlKey::try_from(r#".a."b"#).is_err());
+    }
+
+    #[test]
+    fn merge_prefers_high_priority() {
+        let high = document("foo:\n  bar: foobar\n  baz:\n  - alpha\n  - beta\n");
+        let low = document(
+            "foo:\n  bar: foobaz\n  baz:\n  - gamma\n  - delta\n  qux: 1\nfriend: ferris\n",
+        );
+        assert_eq!(
+            YamlDocument::merge(high, low),
+            document(
+                "foo:\n  bar: foobar\n  baz:\n  - alpha\n  - beta\n  qux: 1\nfriend: ferris\n"
+            )
+        );
+    }
+
+    #[test]
+    fn set_literal_value() {
+        let mut doc = document("a:\n  b: c\n");
+        doc.set_literal_value(key(".a.b"), "info").unwrap();
+        doc.set_literal_value(key(".a.d.e"), 30).unwrap();
+        doc.set_literal_value(key(".f"), false).unwrap();
+        doc.set_literal_value(key(".g"), "null").unwrap();
+        doc.set_literal_value(key(".h"), "a: b").unwrap();
+        assert_eq!(
+            doc,
+            document("a:\n  b: info\n  d:\n    e: 30\nf: false\ng: null\nh: 'a: b'\n")
+        );
+        assert!(doc.set_literal_value(key(".a.b.c"), "x").is_err());
+        assert!(doc.set_literal_value(key(".a[0]"), "x").is_err());
+    }
+
+    #[test]
+    fn get_and_set_value() {
+        let mut doc = document("a:\n  b:\n  - c: d\n");
+        assert_eq!(doc.get(&key(".a.b[0].c")), Some(&"d".into()));
+        assert_eq!(doc.get(&key(".a.b[1]")), None);
+        assert_eq!(doc.get(&key(".a.b.c")), None);
+
+        doc.set_value(key(".a.b[1].e"), "30").unwrap();
+        doc.set_value(key(".f[0]"), document("g: h").root).unwrap();
+        assert_eq!(
+            doc,
+            document("a:\n  b:\n  - c: d\n  - e: '30'\nf:\n- g: h\n")
+        );
+        assert!(doc.set_value(key(".a.b[3]"), "x").is_err());
+    }
+
+    #[test]
+    fn sub_documents() {
+        let mut doc = document("a:\n  b: c\nd: e\n");
+        assert_eq!(doc.sub_document(&key(".a")), document("b: c\n"));
+        assert_eq!(doc.sub_document(&key(".f")), document(""));
+
+        doc.set_sub_document(key(".a"), document("g: h\n")).unwrap();
+        doc.set_sub_document(key(".f.i"), document("j: k\n"))
+            .unwrap();
+        assert_eq!(doc, document("a:\n  g: h\nd: e\nf:\n  i:\n    j: k\n"));
+    }
+
+    #[test]
+    fn delete_object() {
+        let mut doc = document("a:\n  b: c\n  d:\n  - e\n  - f\n");
+        doc.delete_object(key(".a.b")).unwrap();
+        doc.delete_object(key(".a.d[0]")).unwrap();
+        doc.delete_object(key(".x.y")).unwrap();
+        doc.delete_object(key(".a.d[5]")).unwrap();
+        assert_eq!(doc, document("a:\n  d:\n  - f\n"));
+    }
+
+    #[test]
+    fn append_to_array_and_object() {
+        let mut doc = document("a:\n  list:\n  - x: 1\n");
+        doc.append_to_array(key(".a.list"), document("x: 2").root)
+            .unwrap();
+        doc.append_to_array(key(".a.new"), "y").unwrap();
+        doc.append_to_object(key(".a.list[0]"), document("z: 3").root)
+            .unwrap();
+        assert_eq!(
+            doc,
+            document("a:\n  list:\n  - x: 1\n    z: 3\n  - x: 2\n  new:\n  - y\n")
+        );
+        assert!(doc.append_to_array(key(".a.list[0]"), "y").is_err());
+    }
+
+    #[test]
+    fn comments_are_retained() {
+        let target = document(
+            "# Default values.\nio_engine:\n  # The log level.\n  logLevel: info # or debug\n  # Scratch space.\n  scratch: 1\ncsi:\n  nvme:\n    io_timeout: \"30\"\n# End of values.\n",
+        );
+        let source =
+            document("io_engine:\n  logLevel: debug\ncsi:\n  nvme:\n    io_timeout: 110\n");
+
+        let mut doc = YamlDocument::merge(source, target);
+        doc.set_literal_value(key(".csi.nvme.io_timeout"), 110)
+            .unwrap();
+        doc.delete_object(key(".io_engine.scratch")).unwrap();
+        let core = doc.sub_document(&key(".io_engine"));
+        doc.set_sub_document(key(".mayastor"), core).unwrap();
+
+        assert_eq!(
+            String::from_utf8(doc.to_vec().unwrap()).unwrap(),
+            "# Default values.\nio_engine:\n  # The log level.\n  logLevel: debug # or debug\ncsi:\n  nvme:\n    io_timeout: 110\nmayastor:\n  # The log level.\n  logLevel: debug # or debug\n# End of values.\n"
+        );
+    }
+}
@@ -58,8 +58,13 @@ pub(crate) async fn parse_cli_args() -> Result<CliArgs> {
     validate_rest_endpoint(opts.rest_endpoint()).await?;
 
     validate_helmv3_in_path()?;
-    validate_helm_release(opts.release_name(), opts.namespace())?;
-    validate_helm_chart_dir(opts.core_chart_dir())?;
+    validate_helm_release(
+        opts.release_name(),
+        opts.namespace(),
+        opts.helm_storage_driver(),
+    )
+    .await?;
+    validate_helm_chart_dir(opts.core_chart_dir(), opts.umbrella_chart_dir())?;
 
     info!("Validated all inputs");
 
@@ -1,6 +1,9 @@
 use crate::common::constants::product_train;
+use ::upgrade::schedule::MaintenanceWindow;
 use clap::Parser;
-use std::path::PathBuf;
+use k8s_openapi::chrono::{DateTime, Utc};
+use semver::Version;
+use std::{path::PathBuf, time::Duration};
 use utils::{package_description, tracing_telemetry::FmtStyle, version_info_str};
 
 /// Validate input whose validation depends on other inputs.
@@ -27,14 +30,106 @@ pub(crate) struct CliArgs {
     #[arg(long, env = "CORE_CHART_DIR", value_name = "DIR_PATH")]
     core_chart_dir: PathBuf,
 
+    /// This is the Helm chart directory filepath for the umbrella Helm chart variant. Releases of
+    /// the umbrella Helm chart are upgraded to this chart. The --helm-args-set and
+    /// --helm-args-set-file options are applied to the umbrella Helm chart for these releases.
+    #[arg(long, env = "UMBRELLA_CHART_DIR", value_name = "DIR_PATH")]
+    umbrella_chart_dir: Option<PathBuf>,
+
+    /// This is the version of the core Helm chart to upgrade to. The chart is pulled from the
+    /// --chart-repo Helm chart repository, instead of using the chart at --core-chart-dir.
+    #[arg(long, requires = "chart_repo")]
+    to_version: Option<Version>,
+
+    /// This is the Helm chart repository to pull the core Helm chart from. This may be the URL of
+    /// an HTTP(S) Helm chart repository index, or that of an OCI registry
+    /// (oci://<registry>/<path>). Mirrors of these may be used for air-gapped installations.
+    #[arg(long, requires = "to_version")]
+    chart_repo: Option<String>,
+
+    /// This is the expected sha256 digest of the pulled Helm chart tarball, i.e. sha256:<hex>.
+    #[arg(long, requires = "to_version")]
+    chart_digest: Option<String>,
+
+    /// This is a directory with the Helm chart repository's credentials and verification material,
+    /// usually a Secret volume. The optional files 'username', 'password', 'ca.crt' and
+    /// 'keyring.gpg' are used. If the keyring is present, the chart's provenance file is verified.
+    #[arg(long, env = "CHART_REPO_CREDENTIALS_DIR", value_name = "DIR_PATH")]
+    chart_repo_credentials_dir: Option<PathBuf>,
+
+    /// This is the version of the umbrella Helm chart to upgrade umbrella Helm chart releases to.
+    /// The chart is pulled from the --chart-repo Helm chart repository, instead of using the chart
+    /// at --umbrella-chart-dir. It must depend on the --to-version core Helm chart.
+    #[arg(long, requires = "to_version", conflicts_with = "umbrella_chart_dir")]
+    umbrella_chart_version: Option<Version>,
+
+    /// This is the expected sha256 digest of the pulled umbrella Helm chart tarball, i.e.
+    /// sha256:<hex>.
+    #[arg(long, requires = "umbrella_chart_version")]
+    umbrella_chart_digest: Option<String>,
+
+    /// Allow pulled Helm charts which can be verified neither with --chart-digest (or
+    /// --umbrella-chart-digest) nor with a keyring in --chart-repo-credentials-dir. These are
+    /// refused otherwise.
+    #[arg(long, default_value_t = false, requires = "to_version")]
+    allow_unverified_chart: bool,
+
+    /// Use plain HTTP to pull the Helm chart from an OCI registry, e.g. a local registry.
+    #[arg(long, default_value_t = false)]
+    chart_repo_plain_http: bool,
+
+    /// This is a directory with the webhook configuration for upgrade notifications, usually a
+    /// Secret volume. The file 'url' is required, the optional files 'hmac-key' and 'format'
+    /// ('generic', 'slack' or 'teams') are used if present. Each upgrade event is sent to the
+    /// webhook.
+    #[arg(long, env = "NOTIFICATION_CONFIG_DIR", value_name = "DIR_PATH")]
+    notification_config_dir: Option<PathBuf>,
+
     /// If not set, this skips the Kubernetes Pod restarts for the io-engine DaemonSet.
     #[arg(long, default_value_t = false)]
     skip_data_plane_restart: bool,
 
+    /// If set then this skips the pre-upgrade backup of the helm release and the control-plane
+    /// state.
+    #[arg(long, default_value_t = false)]
+    skip_backup: bool,
+
     /// If set then this skips the upgrade path validation.
     #[arg(long, default_value_t = false, hide = true)]
     skip_upgrade_path_validation: bool,
 
+    /// If set then this only computes the upgrade plan, i.e. the changes to the helm values and
+    /// to the Kubernetes objects, and the io-engine Pod restarts, and saves it to a ConfigMap.
+    /// The upgrade is not carried out.
+    #[arg(long, default_value_t = false)]
+    plan: bool,
+
+    /// This is the time to start the upgrade at, in RFC 3339 format. The upgrade waits until then
+    /// before upgrading the control-plane.
+    #[arg(long)]
+    start_at: Option<DateTime<Utc>>,
+
+    /// This is the daily maintenance window, in UTC, e.g. 22:00-04:00. The upgrade waits for the
+    /// window to open before upgrading the control-plane and before restarting each io-engine Pod.
+    #[arg(long, value_name = "HH:MM-HH:MM")]
+    window: Option<MaintenanceWindow>,
+
+    /// If set then the storage nodes are drained with awareness of the application Pods which use
+    /// the volumes targeted on them. The drain waits for the PodDisruptionBudgets of these Pods
+    /// to allow a disruption.
+    #[arg(long, default_value_t = false)]
+    workload_aware_drain: bool,
+
+    /// If set then the data-plane upgrade waits for the application Pods of the drained storage
+    /// node to be Ready, before moving on to the next node.
+    #[arg(long, default_value_t = false, requires = "workload_aware_drain")]
+    wait_for_workload_ready: bool,
+
+    /// This is the time to wait for the PodDisruptionBudgets of the application Pods to allow a
+    /// disruption, and for the application Pods to be Ready, before failing the upgrade.
+    #[arg(long, default_value = "30m")]
+    workload_drain_timeout: humantime::Duration,
+
     /// The name of the Kubernetes Job Pod. The Job object will be used to post upgrade event.
     #[arg(env = "POD_NAME")]
     pod_name: String,
@@ -61,7 +156,8 @@ pub(crate) struct CliArgs {
     #[arg(env = "HELM_DRIVER", default_value = "")]
     helm_storage_driver: String,
 
-    /// Use helm's --reset-then-reuse-values option instead of using yq to derive the helm values.
+    /// Use helm's --reset-then-reuse-values option instead of merging the source and target helm
+    /// values to derive the helm values.
     #[arg(long, default_value_t = false)]
     helm_reset_then_reuse_values: bool,
 }
@@ -97,17 +193,102 @@ impl CliArgs {
         self.core_chart_dir.clone()
     }
 
+    /// This returns the Helm chart directory filepath for the umbrella Helm chart, if it is set.
+    pub(crate) fn umbrella_chart_dir(&self) -> Option<PathBuf> {
+        self.umbrella_chart_dir.clone()
+    }
+
+    /// This returns the version of the core Helm chart to pull and upgrade to, if it is set.
+    pub(crate) fn to_version(&self) -> Option<Version> {
+        self.to_version.clone()
+    }
+
+    /// This returns the Helm chart repository to pull the core Helm chart from.
+    pub(crate) fn chart_repo(&self) -> Option<String> {
+        self.chart_repo.clone()
+    }
+
+    /// This returns the expected digest of the pulled Helm chart tarball.
+    pub(crate) fn chart_digest(&self) -> Option<String> {
+        self.chart_digest.clone()
+    }
+
+    /// This returns the version of the umbrella Helm chart to pull and upgrade to, if it is set.
+    pub(crate) fn umbrella_chart_version(&self) -> Option<Version> {
+        self.umbrella_chart_version.clone()
+    }
+
+    /// This returns the expected digest of the pulled umbrella Helm chart tarball.
+    pub(crate) fn umbrella_chart_digest(&self) -> Option<String> {
+        self.umbrella_chart_digest.clone()
+    }
+
+    /// This returns the directory with the Helm chart repository's credentials.
+    pub(crate) fn chart_repo_credentials_dir(&self) -> Option<PathBuf> {
+        self.chart_repo_credentials_dir.clone()
+    }
+
+    /// This returns the directory with the webhook configuration for upgrade notifications.
+    pub(crate) fn notification_config_dir(&self) -> Option<PathBuf> {
+        self.notification_config_dir.clone()
+    }
+
+    /// This is a predicate to decide if pulled Helm charts which cannot be verified are allowed.
+    pub(crate) fn allow_unverified_chart(&self) -> bool {
+        self.allow_unverified_chart
+    }
+
+    /// This is a predicate to decide if plain HTTP is used with OCI registries.
+    pub(crate) fn chart_repo_plain_http(&self) -> bool {
+        self.chart_repo_plain_http
+    }
+
     /// This is a predicate to decide if <release-name>-io-engine Kubernetes DaemonSet Pods should
     /// be restarted as a part of the data-plane upgrade.
     pub(crate) fn skip_data_plane_restart(&self) -> bool {
         self.skip_data_plane_restart
     }
 
+    /// This is a predicate to decide if the pre-upgrade backup is skipped.
+    pub(crate) fn skip_backup(&self) -> bool {
+        self.skip_backup
+    }
+
     /// This decides to skip upgrade path validation or not.
     pub(crate) fn skip_upgrade_path_validation(&self) -> bool {
         self.skip_upgrade_path_validation
     }
 
+    /// This is a predicate to decide if only the upgrade plan should be computed.
+    pub(crate) fn plan(&self) -> bool {
+        self.plan
+    }
+
+    /// This returns the time to start the upgrade at, if any.
+    pub(crate) fn start_at(&self) -> Option<DateTime<Utc>> {
+        self.start_at
+    }
+
+    /// This returns the daily maintenance window, if any.
+    pub(crate) fn window(&self) -> Option<MaintenanceWindow> {
+        self.window.clone()
+    }
+
+    /// This is a predicate to decide if storage nodes are drained with workload awareness.
+    pub(crate) fn workload_aware_drain(&self) -> bool {
+        self.workload_aware_drain
+    }
+
+    /// This is a predicate to decide if the application Pods are waited on to be Ready.
+    pub(crate) fn wait_for_workload_ready(&self) -> bool {
+        self.wait_for_workload_ready
+    }
+
+    /// This returns the time to wait for the application Pods during the workload-aware drain.
+    pub(crate) fn workload_drain_timeout(&self) -> Duration {
+        self.workload_drain_timeout.into()
+    }
+
     /// This returns the name of the Kubernetes Pod where this binary will be running.
     pub(crate) fn pod_name(&self) -> String {
         self.pod_name.clone()
@@ -1,16 +1,15 @@
 use crate::{
     common::{
-        constants::CORE_CHART_NAME,
+        constants::{CORE_CHART_NAME, UMBRELLA_CHART_NAME},
         error::{
-            FindingHelmChart, GetNamespace, HelmCommand, HelmListCommand, HelmRelease, HelmVersion,
-            HelmVersionCommand, ListStorageNodes, NotADirectory, NotAFile, ReadingFile,
-            RegexCompile, Result, U8VectorToString, ValidateDirPath, ValidateFilePath,
-            YamlParseFromFile,
+            FindingHelmChart, GetNamespace, HelmCommand, HelmVersion, HelmVersionCommand,
+            ListStorageNodes, NotADirectory, NotAFile, ReadingFile, RegexCompile, Result,
+            U8VectorToString, ValidateDirPath, ValidateFilePath, YamlParseFromFile,
         },
         kube::client as KubeClient,
         rest_client::RestClientSet,
     },
-    helm::chart::Chart,
+    helm::{chart::Chart, client::HelmReleaseClient},
     vec_to_strings,
 };
 use regex::bytes::Regex;
@@ -20,44 +19,23 @@ use tracing::debug;
 
 /// Validate that the helm release specified in the CLI options exists in the namespace,
 /// which is also specified in the CLI options.
-pub(crate) fn validate_helm_release(name: String, namespace: String) -> Result<()> {
-    let command: &str = "helm";
-    let args: Vec<String> =
-        vec_to_strings!["list", "-n", namespace.as_str(), "--deployed", "--short"];
-
-    debug!(%command, ?args, "Helm list command");
+pub(crate) async fn validate_helm_release(
+    name: String,
+    namespace: String,
+    storage_driver: String,
+) -> Result<()> {
+    let client = HelmReleaseClient::builder()
+        .with_namespace(namespace)
+        .with_storage_driver(storage_driver)
+        .build()?;
 
-    // Execute `helm list` to get a list of helm chart releases in the namespace.
-    let output = Command::new(command)
-        .args(args.clone())
-        .output()
-        .context(HelmCommand {
-            command: command.to_string(),
-            args: args.clone(),
-        })?;
-
-    let stdout_str = str::from_utf8(output.stdout.as_slice()).context(U8VectorToString)?;
-    debug!(stdout=%stdout_str, "Helm list command standard output");
-    ensure!(
-        output.status.success(),
-        HelmListCommand {
-            command: command.to_string(),
-            args,
-            std_err: str::from_utf8(output.stderr.as_slice())
-                .context(U8VectorToString)?
-                .to_string()
-        }
+    // Validate that a deployed revision of the release exists in the helm storage driver.
+    let release = client.release_info(name.as_str()).await?;
+    debug!(
+        revision = release.revision(),
+        "Found deployed helm release {name}"
     );
 
-    // Validate that the release-name list has the name which is specified in the CLI options.
-    let regex = format!(r"(\n)?{name}(\n)?");
-    if !Regex::new(regex.as_str())
-        .context(RegexCompile { expression: regex })?
-        .is_match(output.stdout.as_slice())
-    {
-        return HelmRelease { name, namespace }.fail();
-    }
-
     Ok(())
 }
 
@@ -107,52 +85,67 @@ pub(crate) fn validate_helmv3_in_path() -> Result<()> {
     Ok(())
 }
 
-/// Validate the input helm chart directory path.
-pub(crate) fn validate_helm_chart_dir(core_dir: PathBuf) -> Result<()> {
-    validate_core_helm_chart_variant_in_dir(core_dir)
+/// Validate the input helm chart directory paths.
+pub(crate) fn validate_helm_chart_dir(
+    core_dir: PathBuf,
+    umbrella_dir: Option<PathBuf>,
+) -> Result<()> {
+    validate_core_helm_chart_variant_in_dir(core_dir)?;
+    if let Some(umbrella_dir) = umbrella_dir {
+        validate_umbrella_helm_chart_variant_in_dir(umbrella_dir)?;
+    }
+    Ok(())
 }
 
-/// Validate the input helm chart directory path:
+/// Validate the input umbrella helm chart directory path:
 /// - validate if the path exists.
-/// - validate if the expected directory structure is present.
-/// - validate if the expected helm chart files are present.
-/// - validate if the chart name if the chart name in the Chart.yaml file is correct.
-fn validate_core_helm_chart_variant_in_dir(dir_path: PathBuf) -> Result<()> {
-    let path_exists_and_is_dir = |path: PathBuf| -> Result<bool> {
-        fs::metadata(path.as_path())
-            .map(|m| m.is_dir())
-            .context(ValidateDirPath { path })
-    };
-
-    let path_exists_and_is_file = |path: PathBuf| -> Result<bool> {
-        fs::metadata(path.as_path())
-            .map(|m| m.is_file())
-            .context(ValidateFilePath { path })
-    };
-
+/// - validate if the expected helm chart files and directories are present.
+/// - validate if the chart name in the Chart.yaml file is correct.
+fn validate_umbrella_helm_chart_variant_in_dir(dir_path: PathBuf) -> Result<()> {
     ensure!(
         path_exists_and_is_dir(dir_path.clone())?,
         NotADirectory { path: dir_path }
     );
 
-    // Validate Chart.yaml file.
-    let mut chart_yaml_path = dir_path.clone();
-    chart_yaml_path.push("Chart.yaml");
+    let chart_yaml = chart_yaml_in_dir(dir_path.clone())?;
     ensure!(
-        path_exists_and_is_file(chart_yaml_path.clone())?,
+        chart_yaml.name().eq(UMBRELLA_CHART_NAME),
+        FindingHelmChart { path: dir_path }
+    );
+
+    // Validate charts directory, it should exist if `helm dependency update` has been executed.
+    let charts_dir_path = dir_path.join("charts");
+    ensure!(
+        path_exists_and_is_dir(charts_dir_path.clone())?,
+        NotADirectory {
+            path: charts_dir_path
+        }
+    );
+
+    // Validate values.yaml file.
+    let values_yaml_path = dir_path.join("values.yaml");
+    ensure!(
+        path_exists_and_is_file(values_yaml_path.clone())?,
         NotAFile {
-            path: chart_yaml_path.clone()
+            path: values_yaml_path
         }
     );
 
-    let chart_yaml_file = fs::read(chart_yaml_path.as_path()).context(ReadingFile {
-        filepath: chart_yaml_path.clone(),
-    })?;
-    let chart_yaml: Chart =
-        serde_yaml::from_slice(chart_yaml_file.as_slice()).context(YamlParseFromFile {
-            filepath: chart_yaml_path.clone(),
-        })?;
+    Ok(())
+}
+
+/// Validate the input helm chart directory path:
+/// - validate if the path exists.
+/// - validate if the expected directory structure is present.
+/// - validate if the expected helm chart files are present.
+/// - validate if the chart name if the chart name in the Chart.yaml file is correct.
+fn validate_core_helm_chart_variant_in_dir(dir_path: PathBuf) -> Result<()> {
+    ensure!(
+        path_exists_and_is_dir(dir_path.clone())?,
+        NotADirectory { path: dir_path }
+    );
 
+    let chart_yaml = chart_yaml_in_dir(dir_path.clone())?;
     ensure!(
         chart_yaml.name().eq(CORE_CHART_NAME),
         FindingHelmChart { path: dir_path }
@@ -210,6 +203,38 @@ fn validate_core_helm_chart_variant_in_dir(dir_path: PathBuf) -> Result<()> {
     Ok(())
 }
 
+/// This checks if the path exists and is a directory.
+fn path_exists_and_is_dir(path: PathBuf) -> Result<bool> {
+    fs::metadata(path.as_path())
+        .map(|m| m.is_dir())
+        .context(ValidateDirPath { path })
+}
+
+/// This checks if the path exists and is a file.
+fn path_exists_and_is_file(path: PathBuf) -> Result<bool> {
+    fs::metadata(path.as_path())
+        .map(|m| m.is_file())
+        .context(ValidateFilePath { path })
+}
+
+/// This validates and deserializes the Chart.yaml file in a helm chart directory.
+fn chart_yaml_in_dir(dir_path: PathBuf) -> Result<Chart> {
+    let chart_yaml_path = dir_path.join("Chart.yaml");
+    ensure!(
+        path_exists_and_is_file(chart_yaml_path.clone())?,
+        NotAFile {
+            path: chart_yaml_path.clone()
+        }
+    );
+
+    let chart_yaml_file = fs::read(chart_yaml_path.as_path()).context(ReadingFile {
+        filepath: chart_yaml_path.clone(),
+    })?;
+    serde_yaml::from_slice(chart_yaml_file.as_slice()).context(YamlParseFromFile {
+        filepath: chart_yaml_path,
+    })
+}
+
 /// This checks for 2 things:
 /// - if the kubernetes API is reachable.
 /// - if the input namespace exists.
@@ -2,19 +2,37 @@ use crate::{
     common::{
         constants::{
             product_train, CORE_CHART_NAME, IO_ENGINE_LABEL, PARTIAL_REBUILD_DISABLE_EXTENTS,
+            UMBRELLA_CHART_NAME,
         },
         error::{PartialRebuildNotAllowed, Result},
         kube::client as KubeClient,
+        rest_client::RestClientSet,
     },
-    events::event_recorder::{EventAction, EventRecorder},
-    helm::upgrade::{HelmUpgradeRunner, HelmUpgraderBuilder},
-    opts::CliArgs,
+    events::{
+        event_recorder::{EventAction, EventRecorder},
+        notifier::notifiers,
+    },
+    helm::{
+        repository::{HelmChartPuller, PulledChart},
+        upgrade::{HelmUpgradeRunner, HelmUpgrader, HelmUpgraderBuilder},
+    },
+    opts::{validators::validate_helm_chart_dir, CliArgs},
 };
+use ::upgrade::schedule::MaintenanceWindow;
+use backup::backup_release;
 use constants::DS_CONTROLLER_REVISION_HASH_LABEL_KEY;
 use data_plane::upgrade_data_plane;
+use k8s_openapi::chrono::{DateTime, Utc};
+use plan::publish_upgrade_plan;
+use verify::{verify_upgrade, PreUpgradeSnapshot};
+use workload::WorkloadAwareDrain;
 
 use semver::Version;
-use tracing::error;
+use tokio::time::sleep;
+use tracing::{error, info, warn};
+
+/// Contains the pre-upgrade backup of the helm release and the control-plane state.
+pub(crate) mod backup;
 
 /// Contains the data-plane upgrade logic.
 pub(crate) mod data_plane;
@@ -25,11 +43,27 @@ pub(crate) mod utils;
 /// Tools to validate upgrade path.
 pub(crate) mod path;
 
+/// Contains the upgrade plan, which is computed instead of upgrading, for upgrade dry-runs.
+pub(crate) mod plan;
+
+/// Contains the post-upgrade verification of the upgraded components.
+pub(crate) mod verify;
+
+/// Contains the workload-aware drain of storage nodes.
+pub(crate) mod workload;
+
 /// This function starts and sees upgrade through to the end.
 pub(crate) async fn upgrade(opts: &CliArgs) -> Result<()> {
+    // Upgrade dry-runs only compute the upgrade plan. No upgrade events are published for these.
+    if opts.plan() {
+        return publish_upgrade_plan(opts).await;
+    }
+
     let mut event = EventRecorder::builder()
         .with_pod_name(opts.pod_name())
         .with_namespace(opts.namespace())
+        .with_release_name(opts.release_name())
+        .with_notifiers(notifiers(opts.notification_config_dir())?)
         .build()
         .await?;
 
@@ -42,20 +76,99 @@ pub(crate) async fn upgrade(opts: &CliArgs) -> Result<()> {
     result
 }
 
-/// This carries out the helm upgrade validation, actual helm upgrade, and the io-engine Pod
-/// restarts.
-async fn upgrade_product(opts: &CliArgs, event: &mut EventRecorder) -> Result<()> {
-    let helm_upgrader = HelmUpgraderBuilder::default()
+/// These are the helm charts pulled from the helm chart repository. The helm charts are removed
+/// when the PulledCharts goes out of scope.
+#[derive(Default)]
+pub(crate) struct PulledCharts {
+    core: Option<PulledChart>,
+    umbrella: Option<PulledChart>,
+}
+
+/// This pulls the target helm charts from the helm chart repository, if a target version is set.
+/// The umbrella helm chart is pulled as well, if its version is set.
+pub(crate) async fn pull_target_charts(opts: &CliArgs) -> Result<PulledCharts> {
+    let (Some(version), Some(repo)) = (opts.to_version(), opts.chart_repo()) else {
+        return Ok(PulledCharts::default());
+    };
+
+    let core = HelmChartPuller::builder()
+        .with_repo(repo.as_str())
+        .with_version(version)
+        .with_digest(opts.chart_digest())
+        .with_credentials_dir(opts.chart_repo_credentials_dir())
+        .with_plain_http(opts.chart_repo_plain_http())
+        .with_allow_unverified(opts.allow_unverified_chart())
+        .build()?
+        .pull()
+        .await?;
+
+    let umbrella = match opts.umbrella_chart_version() {
+        Some(umbrella_version) => Some(
+            HelmChartPuller::builder()
+                .with_chart_name(UMBRELLA_CHART_NAME)
+                .with_repo(repo.as_str())
+                .with_version(umbrella_version)
+                .with_digest(opts.umbrella_chart_digest())
+                .with_credentials_dir(opts.chart_repo_credentials_dir())
+                .with_plain_http(opts.chart_repo_plain_http())
+                .with_allow_unverified(opts.allow_unverified_chart())
+                .build()?
+                .pull()
+                .await?,
+        ),
+        None => None,
+    };
+
+    validate_helm_chart_dir(
+        core.chart_dir(),
+        umbrella
+            .as_ref()
+            .map(|chart| chart.chart_dir())
+            .or(opts.umbrella_chart_dir()),
+    )?;
+
+    Ok(PulledCharts {
+        core: Some(core),
+        umbrella,
+    })
+}
+
+/// This builds the HelmUpgrader for the release. The pulled helm charts are used instead of the
+/// ones in the helm chart directories, if they are set.
+pub(crate) async fn helm_upgrader(
+    opts: &CliArgs,
+    pulled_charts: &PulledCharts,
+) -> Result<Box<dyn HelmUpgrader>> {
+    let core_chart_dir = pulled_charts
+        .core
+        .as_ref()
+        .map_or(opts.core_chart_dir(), |chart| chart.chart_dir());
+    let umbrella_chart_dir = pulled_charts
+        .umbrella
+        .as_ref()
+        .map(|chart| chart.chart_dir())
+        .or(opts.umbrella_chart_dir());
+
+    HelmUpgraderBuilder::default()
         .with_namespace(opts.namespace())
         .with_release_name(opts.release_name())
-        .with_core_chart_dir(opts.core_chart_dir())
+        .with_core_chart_dir(core_chart_dir)
+        .with_umbrella_chart_dir(umbrella_chart_dir)
         .with_skip_upgrade_path_validation(opts.skip_upgrade_path_validation())
         .with_helm_args_set(opts.helm_args_set())
         .with_helm_args_set_file(opts.helm_args_set_file())
         .with_helm_storage_driver(opts.helm_storage_driver())
         .with_helm_reset_then_reuse_values(opts.helm_reset_then_reuse_values())
         .build()
-        .await?;
+        .await
+}
+
+/// This carries out the helm upgrade validation, actual helm upgrade, the io-engine Pod
+/// restarts, and the post-upgrade verification.
+async fn upgrade_product(opts: &CliArgs, event: &mut EventRecorder) -> Result<()> {
+    // The pulled helm charts, if any, are removed when this goes out of scope, after the upgrade.
+    let pulled_charts = pull_target_charts(opts).await?;
+    let helm_upgrader = helm_upgrader(opts, &pulled_charts).await?;
 
     // Update EventRecorder.
     let source_version = helm_upgrader.source_version();
@@ -76,6 +189,54 @@ async fn upgrade_product(opts: &CliArgs, event: &mut EventRecorder) -> Result<()
         Ok(run_helm_upgrade) => Ok(run_helm_upgrade),
     }?;
 
+    if let Some(start_at) = opts.start_at() {
+        wait_for_start_time(start_at, event).await?;
+    }
+    wait_for_window(opts.window().as_ref(), event, EventAction::UpgradingCP).await?;
+
+    if opts.skip_backup() {
+        info!("Skipping the pre-upgrade backup");
+    } else {
+        event
+            .publish_normal(
+                format!("Backing up {} release", product_train()),
+                EventAction::BackingUp,
+            )
+            .await?;
+        // The upgrade does not depend on the backup, so it goes on without one, e.g. if the
+        // backup is too large for a Secret or if etcd is not reachable.
+        match backup_release(opts, &source_version, &target_version).await {
+            Ok(name) => {
+                event
+                    .publish_normal(
+                        format!("Saved pre-upgrade backup to Secret {name}"),
+                        EventAction::BackedUp,
+                    )
+                    .await?
+            }
+            Err(error) => {
+                warn!(%error, "Failed to take the pre-upgrade backup");
+                event
+                    .publish_warning(
+                        format!("Upgrading without a pre-upgrade backup: {error}"),
+                        EventAction::BackingUp,
+                    )
+                    .await?
+            }
+        }
+    }
+
+    // The volumes' replicas are recorded so that the post-upgrade verification can find the
+    // volumes which lost replicas during the upgrade.
+    let rest_client = RestClientSet::new_with_url(opts.rest_endpoint())?;
+    let snapshot = match PreUpgradeSnapshot::new(&rest_client).await {
+        Ok(snapshot) => snapshot,
+        Err(error) => {
+            event.publish_unrecoverable(&error, false).await;
+            return Err(error);
+        }
+    };
+
     event
         .publish_normal(
             format!("Starting {} upgrade...", product_train()),
@@ -144,6 +305,10 @@ async fn upgrade_product(opts: &CliArgs, event: &mut EventRecorder) -> Result<()
             final_values.ha_is_enabled(),
             yet_to_upgrade_io_engine_label,
             yet_to_upgrade_io_engine_pods,
+            WorkloadAwareDrain::new(opts.workload_aware_drain(), opts.wait_for_workload_ready())
+                .with_timeout(opts.workload_drain_timeout()),
+            opts.window().as_ref(),
+            event,
         )
         .await
         {
@@ -159,6 +324,36 @@ async fn upgrade_product(opts: &CliArgs, event: &mut EventRecorder) -> Result<()
             .await?;
     }
 
+    event
+        .publish_normal(
+            format!("Verifying {} upgrade", product_train()),
+            EventAction::Verifying,
+        )
+        .await?;
+    let regressions = verify_upgrade(
+        opts.namespace().as_str(),
+        &rest_client,
+        &snapshot,
+        final_values.as_ref(),
+        !opts.skip_data_plane_restart(),
+    )
+    .await;
+    if !regressions.is_empty() {
+        event
+            .publish_with_warnings(
+                format!(
+                    "Upgraded {} with {} warning(s): {}",
+                    product_train(),
+                    regressions.len(),
+                    regressions.join("; ")
+                ),
+                EventAction::CompletedWithWarnings,
+                regressions,
+            )
+            .await?;
+        return Ok(());
+    }
+
     event
         .publish_normal(
             format!("Successfully upgraded {}", product_train()),
@@ -169,6 +364,70 @@ async fn upgrade_product(opts: &CliArgs, event: &mut EventRecorder) -> Result<()
     Ok(())
 }
 
+/// This waits until the upgrade's start time, publishing a 'Waiting for start time' event, if the
+/// start time is in the future.
+async fn wait_for_start_time(start_at: DateTime<Utc>, event: &EventRecorder) -> Result<()> {
+    let Ok(wait) = (start_at - Utc::now()).to_std() else {
+        return Ok(());
+    };
+
+    event
+        .publish_normal(
+            format!(
+                "Waiting to start {} upgrade at {}",
+                product_train(),
+                start_at.to_rfc3339()
+            ),
+            EventAction::WaitingForStartTime,
+        )
+        .await?;
+    info!(start_at = %start_at.to_rfc3339(), "Waiting for the upgrade start time");
+
+    sleep(wait).await;
+    Ok(())
+}
+
+/// This waits for the maintenance window to open, if it is closed. A 'Waiting for window' event is
+/// published while it waits, and an event with the resume action is published after. Returns true
+/// if it had to wait.
+pub(crate) async fn wait_for_window(
+    window: Option<&MaintenanceWindow>,
+    event: &EventRecorder,
+    resume_action: EventAction,
+) -> Result<bool> {
+    let Some(window) = window else {
+        return Ok(false);
+    };
+    let wait = window.until_open(Utc::now());
+    if wait.is_zero() {
+        return Ok(false);
+    }
+
+    event
+        .publish_normal(
+            format!(
+                "Waiting for maintenance window {window} to upgrade {}",
+                product_train()
+            ),
+            EventAction::WaitingForWindow,
+        )
+        .await?;
+    info!(%window, "Waiting for the maintenance window to open");
+
+    sleep(wait).await;
+
+    event
+        .publish_normal(
+            format!(
+                "Maintenance window {window} is open, resuming {} upgrade",
+                product_train()
+            ),
+            resume_action,
+        )
+        .await?;
+    Ok(true)
+}
+
 fn partial_rebuild_check(source_version: &Version, partial_rebuild_is_enabled: bool) -> Result<()> {
     let partial_rebuild_disable_required = source_version.ge(&PARTIAL_REBUILD_DISABLE_EXTENTS.0)
         && source_version.le(&PARTIAL_REBUILD_DISABLE_EXTENTS.1);
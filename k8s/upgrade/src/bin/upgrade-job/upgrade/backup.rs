@@ -0,0 +1,298 @@
+use crate::{
+    common::{
+        constants::{CORE_CHART_NAME, DEFAULT_ETCD_PORT, SECRET_DATA_SIZE_LIMIT},
+        error::{
+            CreateUpgradeBackup, EtcdConnect, EtcdDump, EtcdExternalUrlAbsent, GetPlatformInfo,
+            GetUpgradeBackup, GzipEncode, ReplaceUpgradeBackup, Result, SerializeUpgradeBackup,
+            UpgradeBackupTooLarge, YamlParseFromSlice,
+        },
+        kube::client as KubeClient,
+    },
+    helm::client::HelmReleaseClient,
+    opts::CliArgs,
+};
+use constants::{
+    upgrade_backup_label_key, upgrade_backup_secret_name, UPGRADE_BACKUP_ETCD_KEY,
+    UPGRADE_BACKUP_RELEASE_KEY, UPGRADE_BACKUP_VALUES_KEY,
+};
+use etcd_dump::PagedReader;
+use flate2::{write::GzEncoder, Compression};
+use k8s_openapi::{api::core::v1::Secret, chrono::Utc, ByteString};
+use kube::{
+    api::{Api, PostParams},
+    core::ObjectMeta,
+};
+use pstor::{etcd, API_VERSION};
+use semver::Version;
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use serde_yaml::Value as YamlValue;
+use snafu::ResultExt;
+use std::{collections::BTreeMap, io::Write};
+use tracing::info;
+
+/// This is the type of the pre-upgrade backup Secret.
+const BACKUP_SECRET_TYPE: &str = "Opaque";
+
+/// This is the metadata of the helm release which is backed up before upgrading.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BackupMetadata {
+    release_name: String,
+    namespace: String,
+    revision: u64,
+    chart: String,
+    source_version: String,
+    target_version: String,
+    created_at: String,
+    etcd_endpoint: String,
+    etcd_keys: usize,
+}
+
+/// This is a control-plane key in etcd, and its value.
+#[derive(Serialize)]
+struct EtcdEntry {
+    key: String,
+    value: JsonValue,
+}
+
+/// This takes a backup of the helm values and the release metadata of the source version, and a
+/// dump of the control-plane's keys in etcd. The backup is saved to a Secret which is named after
+/// the source version. A backup from an earlier attempt to upgrade from the same source version
+/// is replaced. Returns the name of the Secret.
+pub(crate) async fn backup_release(
+    opts: &CliArgs,
+    source_version: &Version,
+    target_version: &Version,
+) -> Result<String> {
+    let release_name = opts.release_name();
+    let namespace = opts.namespace();
+
+    let client = HelmReleaseClient::builder()
+        .with_namespace(namespace.as_str())
+        .with_storage_driver(opts.helm_storage_driver())
+        .build()?;
+    let release = client.release_info(release_name.as_str()).await?;
+    let values = release.values_as_yaml()?;
+
+    let values_yaml: YamlValue =
+        serde_yaml::from_slice(values.as_slice()).context(YamlParseFromSlice {
+            input_yaml: String::from_utf8_lossy(values.as_slice()).to_string(),
+        })?;
+    let endpoint = etcd_endpoint(&values_yaml, release_name.as_str(), namespace.as_str())?;
+    let etcd_entries = etcd_dump(endpoint.as_str(), namespace.as_str()).await?;
+
+    let metadata = BackupMetadata {
+        release_name: release_name.clone(),
+        namespace: namespace.clone(),
+        revision: release.revision(),
+        chart: release.chart()?,
+        source_version: source_version.to_string(),
+        target_version: target_version.to_string(),
+        created_at: Utc::now().to_rfc3339(),
+        etcd_endpoint: endpoint,
+        etcd_keys: etcd_entries.len(),
+    };
+
+    let etcd_dump = serde_json::to_vec(&etcd_entries).context(SerializeUpgradeBackup)?;
+    let data = BTreeMap::from([
+        (
+            UPGRADE_BACKUP_RELEASE_KEY.to_string(),
+            ByteString(serde_json::to_vec(&metadata).context(SerializeUpgradeBackup)?),
+        ),
+        (
+            UPGRADE_BACKUP_VALUES_KEY.to_string(),
+            ByteString(gzip(values.as_slice())?),
+        ),
+        (
+            UPGRADE_BACKUP_ETCD_KEY.to_string(),
+            ByteString(gzip(etcd_dump.as_slice())?),
+        ),
+    ]);
+
+    let name = upgrade_backup_secret_name(release_name.as_str(), &source_version.to_string());
+    let size: usize = data.values().map(|value| value.0.len()).sum();
+    if size > SECRET_DATA_SIZE_LIMIT {
+        return UpgradeBackupTooLarge {
+            name,
+            size,
+            limit: SECRET_DATA_SIZE_LIMIT,
+        }
+        .fail();
+    }
+
+    save_backup(
+        name.as_str(),
+        release_name.as_str(),
+        namespace.as_str(),
+        data,
+    )
+    .await?;
+    info!(
+        secret.name = %name,
+        secret.namespace = %namespace,
+        etcd.keys = metadata.etcd_keys,
+        "Saved the pre-upgrade backup"
+    );
+
+    Ok(name)
+}
+
+/// Returns the etcd endpoint of the control-plane from the helm values, same as the 'etcdUrl'
+/// helper of the helm chart. The values of the core chart are nested under its name when the
+/// release is that of the umbrella chart.
+fn etcd_endpoint(values: &YamlValue, release_name: &str, namespace: &str) -> Result<String> {
+    let etcd = values.get("etcd").or_else(|| {
+        values
+            .get(CORE_CHART_NAME)
+            .and_then(|core_values| core_values.get("etcd"))
+    });
+
+    let enabled = etcd
+        .and_then(|etcd| etcd.get("enabled"))
+        .and_then(YamlValue::as_bool)
+        .unwrap_or(true);
+    if !enabled {
+        return etcd
+            .and_then(|etcd| etcd.get("externalUrl"))
+            .and_then(YamlValue::as_str)
+            .filter(|url| !url.is_empty())
+            .map(ToString::to_string)
+            .ok_or(EtcdExternalUrlAbsent.build());
+    }
+
+    let port = etcd
+        .and_then(|etcd| etcd.get("service"))
+        .and_then(|service| service.get("port"))
+        .and_then(YamlValue::as_u64)
+        .unwrap_or(DEFAULT_ETCD_PORT);
+    Ok(format!("{release_name}-etcd.{namespace}:{port}"))
+}
+
+/// Reads all of the control-plane's keys from etcd, a page at a time.
+async fn etcd_dump(endpoint: &str, namespace: &str) -> Result<Vec<EtcdEntry>> {
+    let platform_info = platform::k8s::K8s::from_custom(KubeClient::client().await?, namespace)
+        .await
+        .map_err(|error| {
+            GetPlatformInfo {
+                reason: error.to_string(),
+            }
+            .build()
+        })?;
+    let key_prefix = pstor::build_key_prefix(&platform_info, API_VERSION);
+    let mut etcd = etcd::Etcd::new(endpoint).await.context(EtcdConnect {
+        endpoint: endpoint.to_string(),
+    })?;
+
+    let mut entries = Vec::new();
+    let mut reader = PagedReader::new(key_prefix.as_str());
+    while let Some(page) = reader.next_page(&mut etcd).await.context(EtcdDump {
+        prefix: key_prefix.clone(),
+    })? {
+        entries.extend(
+            page.into_iter()
+                .map(|(key, value)| EtcdEntry { key, value }),
+        );
+    }
+
+    Ok(entries)
+}
+
+/// Creates the backup Secret, or replaces it if it exists.
+async fn save_backup(
+    name: &str,
+    release_name: &str,
+    namespace: &str,
+    data: BTreeMap<String, ByteString>,
+) -> Result<()> {
+    let secrets_api: Api<Secret> = KubeClient::secrets_api(namespace).await?;
+    let labels = BTreeMap::from([(upgrade_backup_label_key(), release_name.to_string())]);
+
+    let maybe_secret = secrets_api.get_opt(name).await.context(GetUpgradeBackup {
+        name: name.to_string(),
+        namespace: namespace.to_string(),
+    })?;
+
+    match maybe_secret {
+        Some(mut secret) => {
+            secret
+                .metadata
+                .labels
+                .get_or_insert_with(Default::default)
+                .extend(labels);
+            secret.data = Some(data);
+
+            secrets_api
+                .replace(name, &PostParams::default(), &secret)
+                .await
+                .context(ReplaceUpgradeBackup {
+                    name: name.to_string(),
+                    namespace: namespace.to_string(),
+                })?;
+        }
+        None => {
+            let secret = Secret {
+                metadata: ObjectMeta {
+                    name: Some(name.to_string()),
+                    namespace: Some(namespace.to_string()),
+                    labels: Some(labels),
+                    ..Default::default()
+                },
+                type_: Some(BACKUP_SECRET_TYPE.to_string()),
+                data: Some(data),
+                ..Default::default()
+            };
+
+            secrets_api
+                .create(&PostParams::default(), &secret)
+                .await
+                .context(CreateUpgradeBackup {
+                    name: name.to_string(),
+                    namespace: namespace.to_string(),
+                })?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Gzip compresses the data.
+fn gzip(data: &[u8]) -> Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data).context(GzipEncode)?;
+    encoder.finish().context(GzipEncode)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::etcd_endpoint;
+    use serde_yaml::Value;
+
+    #[test]
+    fn etcd_endpoint_from_values() {
+        let values: Value = serde_yaml::from_str("etcd:\n  service:\n    port: 2380\n").unwrap();
+        assert_eq!(
+            etcd_endpoint(&values, "mayastor", "mayastor").unwrap(),
+            "mayastor-etcd.mayastor:2380"
+        );
+
+        // The core chart's values are nested under its name in the umbrella chart.
+        let values: Value = serde_yaml::from_str(
+            "mayastor:\n  etcd:\n    enabled: false\n    externalUrl: etcd.storage:2379\n",
+        )
+        .unwrap();
+        assert_eq!(
+            etcd_endpoint(&values, "openebs", "openebs").unwrap(),
+            "etcd.storage:2379"
+        );
+
+        let values: Value = serde_yaml::from_str("etcd:\n  enabled: false\n").unwrap();
+        assert!(etcd_endpoint(&values, "mayastor", "mayastor").is_err());
+
+        let values: Value = serde_yaml::from_str("{}").unwrap();
+        assert_eq!(
+            etcd_endpoint(&values, "mayastor", "mayastor").unwrap(),
+            "mayastor-etcd.mayastor:2379"
+        );
+    }
+}
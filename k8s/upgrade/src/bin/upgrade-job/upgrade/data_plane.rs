@@ -10,22 +10,33 @@ use crate::{
         kube::client as KubeClient,
         rest_client::RestClientSet,
     },
-    upgrade::utils::{
-        all_pods_are_ready, cordon_storage_node, list_all_volumes, rebuild_result,
-        uncordon_storage_node, RebuildResult,
+    events::event_recorder::{DataPlaneProgress, EventAction, EventRecorder},
+    upgrade::{
+        utils::{
+            all_pods_are_ready, cordon_storage_node, list_all_volumes, list_unhealthy_volumes,
+            rebuild_result, replica_rebuild_count, uncordon_storage_node, RebuildResult,
+        },
+        wait_for_window,
+        workload::WorkloadAwareDrain,
     },
 };
+use ::upgrade::schedule::MaintenanceWindow;
 use constants::DS_CONTROLLER_REVISION_HASH_LABEL_KEY;
-use k8s_openapi::api::core::v1::{Node, Pod};
+use k8s_openapi::{
+    api::core::v1::{Node, Pod},
+    chrono::Utc,
+};
 use kube::{api::DeleteParams, core::PartialObjectMeta, ResourceExt};
 use openapi::models::CordonDrainState;
 use snafu::ResultExt;
-use std::time::Duration;
+use std::{collections::HashSet, time::Duration};
 use tokio::time::sleep;
 use tracing::info;
 use utils::{csi_node_nvme_ana, API_REST_LABEL, ETCD_LABEL};
 
-/// Upgrade data plane by controlled restart of io-engine pods
+/// Upgrade data plane by controlled restart of io-engine pods. If there is a maintenance window,
+/// the upgrade waits for it to open before restarting each io-engine Pod.
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn upgrade_data_plane(
     namespace: String,
     rest_endpoint: String,
@@ -33,6 +44,9 @@ pub(crate) async fn upgrade_data_plane(
     ha_is_enabled: bool,
     yet_to_upgrade_io_engine_label: String,
     yet_to_upgrade_io_engine_pods: Vec<Pod>,
+    workload_drain: WorkloadAwareDrain,
+    window: Option<&MaintenanceWindow>,
+    event: &EventRecorder,
 ) -> Result<()> {
     // This makes data-plane upgrade idempotent.
     if yet_to_upgrade_io_engine_pods.is_empty() {
@@ -63,6 +77,9 @@ pub(crate) async fn upgrade_data_plane(
         uncordon_drained_storage_node(storage_node.id.as_str(), &rest_client).await?;
     }
 
+    // This is used to report the progress of the data-plane upgrade in the upgrade events.
+    let started_at = Utc::now().to_rfc3339();
+
     loop {
         let initial_io_engine_pod_list: Vec<Pod> = KubeClient::list_pods(
             namespace.clone(),
@@ -76,7 +93,23 @@ pub(crate) async fn upgrade_data_plane(
             break;
         }
 
+        // The nodes whose io-engine Pods are already upgraded, e.g. by a previous attempt of this
+        // Job, count towards the progress. New nodes which require upgrade may have been added
+        // since the last iteration.
+        let total_nodes =
+            KubeClient::list_pods(namespace.clone(), Some(IO_ENGINE_LABEL.to_string()), None)
+                .await?
+                .len()
+                .max(initial_io_engine_pod_list.len());
+        let mut completed_nodes = total_nodes - initial_io_engine_pod_list.len();
+
         for pod in initial_io_engine_pod_list.iter() {
+            // Park the upgrade while the maintenance window is closed. The io-engine Pods which
+            // are yet to be upgraded are listed again once it opens.
+            if wait_for_window(window, event, EventAction::UpgradingDP).await? {
+                break;
+            }
+
             // Validate the control plane pod is up and running before we start.
             verify_control_plane_is_running(namespace.clone()).await?;
 
@@ -107,16 +140,51 @@ pub(crate) async fn upgrade_data_plane(
                 node.name = %node_name,
                 "Starting upgrade for the data-plane pod"
             );
+            let progress = DataPlaneProgress::new(
+                node_name,
+                completed_nodes,
+                total_nodes,
+                started_at.as_str(),
+            );
 
             // Wait for any rebuild to complete
-            wait_for_rebuild(node_name, &rest_client).await?;
+            wait_for_rebuild(node_name, &rest_client, event, &progress).await?;
+
+            // The application Pods which use the volumes targeted on this node.
+            let workload_pods = if workload_drain.is_enabled() {
+                workload_drain
+                    .workload_pods(targeted_volumes(node_name, &rest_client).await?)
+                    .await?
+            } else {
+                Vec::new()
+            };
 
             if is_node_drainable(ha_is_enabled, node_name, &rest_client).await? {
+                workload_drain
+                    .wait_for_disruption_budgets(workload_pods.as_slice(), event)
+                    .await?;
+                event
+                    .publish_progress(
+                        format!("Draining node '{node_name}'"),
+                        EventAction::DrainingNode,
+                        progress.clone(),
+                    )
+                    .await;
                 // Issue node drain command if NVMe Ana is enabled.
                 drain_storage_node(node_name, &rest_client).await?;
             }
 
             // restart the data plane pod
+            event
+                .publish_progress(
+                    format!(
+                        "Restarting io-engine Pod '{}' on node '{node_name}'",
+                        pod.name_any()
+                    ),
+                    EventAction::RestartingIoEngine,
+                    progress,
+                )
+                .await;
             delete_data_plane_pod(node_name, pod, namespace.clone()).await?;
 
             // validate the new pod is up and running
@@ -129,6 +197,25 @@ pub(crate) async fn upgrade_data_plane(
 
             // Uncordon the drained node
             uncordon_drained_storage_node(node_name, &rest_client).await?;
+
+            // Wait for the application Pods to be Ready on the new path.
+            workload_drain
+                .wait_for_ready(workload_pods.as_slice(), event)
+                .await?;
+
+            completed_nodes += 1;
+            event
+                .publish_progress(
+                    format!("Upgraded node '{node_name}' ({completed_nodes}/{total_nodes})"),
+                    EventAction::UpgradedNode,
+                    DataPlaneProgress::new(
+                        node_name,
+                        completed_nodes,
+                        total_nodes,
+                        started_at.as_str(),
+                    ),
+                )
+                .await;
         }
 
         info!(
@@ -228,16 +315,38 @@ async fn verify_data_plane_pod_is_running(
     Ok(())
 }
 
-/// Wait for the rebuild to complete if any.
-async fn wait_for_rebuild(node_name: &str, rest_client: &RestClientSet) -> Result<()> {
+/// Wait for the rebuild to complete if any. An upgrade event is published whenever the number of
+/// volumes which are being rebuilt changes.
+async fn wait_for_rebuild(
+    node_name: &str,
+    rest_client: &RestClientSet,
+    event: &EventRecorder,
+    progress: &DataPlaneProgress,
+) -> Result<()> {
     // Wait for 60 seconds for any rebuilds to kick in.
     sleep(Duration::from_secs(60_u64)).await;
 
     let mut result = RebuildResult::default();
+    let mut last_rebuilding_volumes = 0;
     loop {
         let rebuild = rebuild_result(rest_client, &mut result.discarded_volumes, node_name).await?;
 
         if rebuild.rebuilding {
+            let rebuilding_volumes = rebuilding_volume_count(rest_client).await?;
+            if rebuilding_volumes != last_rebuilding_volumes {
+                event
+                    .publish_progress(
+                        format!(
+                            "Waiting for rebuilds of {rebuilding_volumes} volumes before \
+                            upgrading node '{node_name}'"
+                        ),
+                        EventAction::WaitingForRebuilds,
+                        progress.clone().with_rebuilding_volumes(rebuilding_volumes),
+                    )
+                    .await;
+                last_rebuilding_volumes = rebuilding_volumes;
+            }
+
             info!(node.name = %node_name, "Waiting for volume rebuilds to complete");
             sleep(Duration::from_secs(10_u64)).await;
         } else {
@@ -248,6 +357,15 @@ async fn wait_for_rebuild(node_name: &str, rest_client: &RestClientSet) -> Resul
     Ok(())
 }
 
+/// Returns the number of volumes with replicas which are being rebuilt.
+async fn rebuilding_volume_count(rest_client: &RestClientSet) -> Result<usize> {
+    let unhealthy_volumes = list_unhealthy_volumes(rest_client, &[]).await?;
+    Ok(unhealthy_volumes
+        .iter()
+        .filter(|volume| replica_rebuild_count(volume) > 0)
+        .count())
+}
+
 /// Issue the node drain command on the node.
 async fn drain_storage_node(node_id: &str, rest_client: &RestClientSet) -> Result<()> {
     let drain_label_for_upgrade: String = drain_for_upgrade();
@@ -385,6 +503,22 @@ async fn is_node_drainable(
     result
 }
 
+/// Returns the uuids of the volumes with a target on the node.
+async fn targeted_volumes(node_name: &str, rest_client: &RestClientSet) -> Result<HashSet<String>> {
+    let volumes = list_all_volumes(rest_client).await?;
+    Ok(volumes
+        .into_iter()
+        .filter(|volume| {
+            volume
+                .spec
+                .target
+                .as_ref()
+                .is_some_and(|target| target.node.eq(node_name))
+        })
+        .map(|volume| volume.spec.uuid.to_string())
+        .collect())
+}
+
 /// Returns true if any of the frontend nodes of the target at node_name have the label for
 /// ANA-incapability.
 async fn frontend_nodes_ana_check(
@@ -2,40 +2,57 @@ use crate::{
     common::{
         constants::CORE_CHART_NAME,
         error::{
-            HelmChartNameSplit, InvalidDependencyVersionInHelmReleaseData, ReadingFile, Result,
-            SemverParse, YamlParseBufferForUnsupportedVersion, YamlParseFromFile,
+            HelmChartNameSplit, IntermediateUpgradeRequired, InvalidDependencyVersionInChartYaml,
+            InvalidDependencyVersionInHelmReleaseData, InvalidUpgradePath, ReadingFile, Result,
+            SemverParse, UnsupportedUpgradePath, YamlParseBufferForUnsupportedVersion,
+            YamlParseFromFile,
         },
     },
-    helm::{chart::Chart, client::HelmReleaseClient},
+    helm::{chart::Chart, storage::HelmChartRelease},
 };
+use ::upgrade::path::{CompatibilityMatrix, DirectUpgrade};
 use semver::Version;
-use serde::Deserialize;
-use snafu::ResultExt;
+use snafu::{ensure, ResultExt};
 use std::{fs, path::PathBuf};
 
-/// Validates the upgrade path from 'from' Version to 'to' Version for the Core helm chart.
-pub(crate) fn is_valid_for_core_chart(from: &Version) -> Result<bool> {
-    let unsupported_version_buf =
+/// Validates the upgrade path from 'from' Version to 'to' Version for the Core helm chart, against
+/// the compatibility matrix. Upgrades which must go through an intermediate version fail, with
+/// the intermediate version and the reason.
+pub(crate) fn validate_core_chart_upgrade_path(from: &Version, to: &Version) -> Result<()> {
+    let compatibility_matrix_buf =
         &include_bytes!("../../../../../upgrade/config/unsupported_versions.yaml")[..];
-    let unsupported_versions = UnsupportedVersions::try_from(unsupported_version_buf)
+    let compatibility_matrix = CompatibilityMatrix::try_from(compatibility_matrix_buf)
         .context(YamlParseBufferForUnsupportedVersion)?;
-    Ok(!unsupported_versions.contains(from))
+    validate_upgrade_path(&compatibility_matrix, from, to)
 }
 
 /// Generate a semver::Version from the helm chart in local directory.
 pub(crate) fn version_from_chart_yaml_file(path: PathBuf) -> Result<Version> {
-    let values_yaml = fs::read(path.as_path()).context(ReadingFile {
+    let to_chart = chart_from_chart_yaml_file(path)?;
+    Ok(to_chart.version().clone())
+}
+
+/// Generate a semver::Version for the Core helm chart dependency, from the Chart.yaml file of the
+/// Umbrella helm chart in a local directory.
+pub(crate) fn core_version_from_umbrella_chart_yaml_file(path: PathBuf) -> Result<Version> {
+    let to_chart = chart_from_chart_yaml_file(path.clone())?;
+    to_chart
+        .dependency_version(CORE_CHART_NAME)
+        .and_then(|version| Version::parse(version).ok())
+        .ok_or(InvalidDependencyVersionInChartYaml { filepath: path }.build())
+}
+
+/// Deserialize a helm chart's Chart.yaml file.
+fn chart_from_chart_yaml_file(path: PathBuf) -> Result<Chart> {
+    let chart_yaml = fs::read(path.as_path()).context(ReadingFile {
         filepath: path.clone(),
     })?;
 
-    let to_chart: Chart = serde_yaml::from_slice(values_yaml.as_slice())
-        .context(YamlParseFromFile { filepath: path })?;
-
-    Ok(to_chart.version().clone())
+    serde_yaml::from_slice(chart_yaml.as_slice()).context(YamlParseFromFile { filepath: path })
 }
 
-/// Generate a semver::Version from the 'chart' member of the Helm chart's ReleaseElement.
-/// The output of `helm ls -n <namespace> -o yaml` is a list of ReleaseElements.
+/// Generate a semver::Version from the '<chart-name>-<chart-version>' chart name of a Helm
+/// release, e.g. the CHART column of `helm list`.
 pub(crate) fn version_from_core_chart_release(chart_name: &str) -> Result<Version> {
     let delimiter: char = '-';
     // e.g. <chart>-1.2.3-rc.5 -- here the 2nd chunk is the version
@@ -52,11 +69,10 @@ pub(crate) fn version_from_core_chart_release(chart_name: &str) -> Result<Versio
     })
 }
 
-pub(crate) async fn core_version_from_umbrella_release(
-    client: &HelmReleaseClient,
-    release_name: &str,
-) -> Result<Version> {
-    let deps = client.get_dependencies(release_name).await?;
+/// Generate a semver::Version from the Core helm chart dependency of an Umbrella helm chart
+/// release.
+pub(crate) fn core_version_from_umbrella_release(release: HelmChartRelease) -> Result<Version> {
+    let deps = release.dependencies()?;
     deps.into_iter()
         .find_map(|dep| {
             dep.name()
@@ -69,23 +85,85 @@ pub(crate) async fn core_version_from_umbrella_release(
         .ok_or(InvalidDependencyVersionInHelmReleaseData.build())
 }
 
-/// Struct to deserialize the unsupported version yaml.
-#[derive(Deserialize)]
-struct UnsupportedVersions {
-    unsupported_versions: Vec<Version>,
-}
+/// Validates the direct upgrade from 'from' to 'to' against the compatibility matrix.
+fn validate_upgrade_path(
+    compatibility_matrix: &CompatibilityMatrix,
+    from: &Version,
+    to: &Version,
+) -> Result<()> {
+    ensure!(
+        !compatibility_matrix.is_unsupported(from),
+        InvalidUpgradePath
+    );
 
-impl UnsupportedVersions {
-    fn contains(&self, v: &Version) -> bool {
-        self.unsupported_versions.contains(v)
+    match compatibility_matrix.direct_upgrade(from, to) {
+        DirectUpgrade::Supported => Ok(()),
+        DirectUpgrade::Via { version, reason } => IntermediateUpgradeRequired {
+            source_version: from.to_string(),
+            target_version: to.to_string(),
+            via: version.to_string(),
+            reason: reason.to_string(),
+        }
+        .fail(),
+        DirectUpgrade::Unsupported { reason } => UnsupportedUpgradePath {
+            source_version: from.to_string(),
+            target_version: to.to_string(),
+            reason: reason.to_string(),
+        }
+        .fail(),
     }
 }
 
-impl TryFrom<&[u8]> for UnsupportedVersions {
-    type Error = serde_yaml::Error;
+#[cfg(test)]
+mod tests {
+    use super::validate_upgrade_path;
+    use crate::common::error::Error;
+    use ::upgrade::path::CompatibilityMatrix;
+    use semver::Version;
+
+    fn version(version: &str) -> Version {
+        Version::parse(version).unwrap()
+    }
+
+    #[test]
+    fn compatibility_matrix_validation() {
+        let matrix = CompatibilityMatrix::try_from(
+            r#"
+unsupported_versions:
+  - 1.0.0
+compatibility:
+  - from: ">=2.0.0, <2.4.0"
+    to: ">=2.7.0"
+    via: 2.4.0
+    reason: "the etcd data layout changed in 2.4.0"
+  - from: "~2.5"
+    to: ">=2.8.0"
+    reason: "2.5 pools cannot be imported by 2.8"
+"#
+            .as_bytes(),
+        )
+        .unwrap();
+
+        assert!(validate_upgrade_path(&matrix, &version("2.2.0"), &version("2.4.0")).is_ok());
+        assert!(validate_upgrade_path(&matrix, &version("2.4.0"), &version("2.7.0")).is_ok());
+        assert!(validate_upgrade_path(&matrix, &version("2.6.0"), &version("2.8.0")).is_ok());
+        assert!(matches!(
+            validate_upgrade_path(&matrix, &version("2.2.0"), &version("2.7.0")),
+            Err(Error::IntermediateUpgradeRequired { via, .. }) if via == "2.4.0"
+        ));
+        assert!(matches!(
+            validate_upgrade_path(&matrix, &version("2.5.1"), &version("2.8.0")),
+            Err(Error::UnsupportedUpgradePath { .. })
+        ));
+        assert!(matches!(
+            validate_upgrade_path(&matrix, &version("1.0.0"), &version("2.0.0")),
+            Err(Error::InvalidUpgradePath)
+        ));
+    }
 
-    /// Returns an UnsupportedVersions object.
-    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
-        serde_yaml::from_reader(bytes)
+    #[test]
+    fn embedded_compatibility_matrix_is_valid() {
+        let buf = &include_bytes!("../../../../../upgrade/config/unsupported_versions.yaml")[..];
+        assert!(CompatibilityMatrix::try_from(buf).is_ok());
     }
 }
@@ -0,0 +1,324 @@
+use crate::{
+    common::{
+        constants::IO_ENGINE_LABEL,
+        error::{
+            CreateUpgradePlan, EmptyPodNodeName, EmptyPodSpec, GetUpgradePlan, ReplaceUpgradePlan,
+            Result, SerializeUpgradePlan,
+        },
+        kube::client as KubeClient,
+        rest_client::RestClientSet,
+    },
+    helm::diff::ReleaseDiff,
+    opts::CliArgs,
+    upgrade::{helm_upgrader, pull_target_charts, utils::list_all_volumes},
+};
+use constants::{upgrade_plan_configmap_name, UPGRADE_PLAN_DATA_KEY, UPGRADE_PLAN_ERROR_KEY};
+use k8s_openapi::api::core::v1::{ConfigMap, Pod};
+use kube::{
+    api::{Api, PostParams},
+    core::ObjectMeta,
+    ResourceExt,
+};
+use openapi::models::Volume;
+use serde::Serialize;
+use snafu::ResultExt;
+use std::collections::BTreeMap;
+use tracing::info;
+
+/// This is the plan of an upgrade. It is computed without making any changes to the cluster.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct UpgradePlan {
+    source_version: String,
+    target_version: String,
+    release_diff: ReleaseDiff,
+    data_plane: DataPlanePlan,
+}
+
+/// These are the io-engine Pod restarts of the data-plane upgrade.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DataPlanePlan {
+    restart_skipped: bool,
+    nodes: Vec<NodeRestart>,
+    estimated_rebuilds: usize,
+}
+
+/// This is the restart of the io-engine Pod on a node, and the volumes which it affects.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct NodeRestart {
+    node: String,
+    pod: String,
+    volumes: Vec<String>,
+    estimated_rebuilds: usize,
+}
+
+/// This computes the upgrade plan and saves it to the upgrade plan ConfigMap. If the upgrade plan
+/// cannot be computed, the error is saved instead.
+pub(crate) async fn publish_upgrade_plan(opts: &CliArgs) -> Result<()> {
+    let store =
+        UpgradePlanStore::new(opts.release_name().as_str(), opts.namespace().as_str()).await?;
+
+    match upgrade_plan(opts).await {
+        Ok(plan) => {
+            let plan = serde_json::to_string(&plan).context(SerializeUpgradePlan)?;
+            store.save(UPGRADE_PLAN_DATA_KEY, plan).await?;
+            info!("Saved the upgrade plan");
+            Ok(())
+        }
+        Err(error) => {
+            store
+                .save(UPGRADE_PLAN_ERROR_KEY, error.to_string())
+                .await?;
+            Err(error)
+        }
+    }
+}
+
+/// This validates the upgrade and computes the upgrade plan.
+async fn upgrade_plan(opts: &CliArgs) -> Result<UpgradePlan> {
+    // The pulled helm charts, if any, are removed when this goes out of scope.
+    let pulled_charts = pull_target_charts(opts).await?;
+    let helm_upgrader = helm_upgrader(opts, &pulled_charts).await?;
+
+    let release_diff = helm_upgrader.diff().await?;
+    let data_plane = data_plane_plan(opts).await?;
+
+    Ok(UpgradePlan {
+        source_version: helm_upgrader.source_version().to_string(),
+        target_version: helm_upgrader.target_version().to_string(),
+        release_diff,
+        data_plane,
+    })
+}
+
+/// This lists the io-engine Pods in the order in which the data-plane upgrade restarts them, and
+/// the volumes which have a replica or a target on each of their nodes.
+async fn data_plane_plan(opts: &CliArgs) -> Result<DataPlanePlan> {
+    let namespace = opts.namespace();
+    let io_engine_pods: Vec<Pod> =
+        KubeClient::list_pods(namespace.clone(), Some(IO_ENGINE_LABEL.to_string()), None).await?;
+
+    let rest_client = RestClientSet::new_with_url(opts.rest_endpoint())?;
+    let volumes = list_all_volumes(&rest_client).await?;
+    let placements: Vec<VolumePlacement> = volumes.iter().map(VolumePlacement::from).collect();
+
+    let mut nodes = Vec::with_capacity(io_engine_pods.len());
+    for pod in io_engine_pods.iter() {
+        let node_name = pod
+            .spec
+            .as_ref()
+            .ok_or(
+                EmptyPodSpec {
+                    name: pod.name_any(),
+                    namespace: namespace.clone(),
+                }
+                .build(),
+            )?
+            .node_name
+            .as_ref()
+            .ok_or(
+                EmptyPodNodeName {
+                    name: pod.name_any(),
+                    namespace: namespace.clone(),
+                }
+                .build(),
+            )?;
+
+        nodes.push(node_restart(
+            node_name,
+            pod.name_any(),
+            placements.as_slice(),
+        ));
+    }
+
+    Ok(DataPlanePlan {
+        restart_skipped: opts.skip_data_plane_restart(),
+        estimated_rebuilds: nodes.iter().map(|node| node.estimated_rebuilds).sum(),
+        nodes,
+    })
+}
+
+/// These are the nodes of a volume's target and replicas.
+struct VolumePlacement<'a> {
+    uuid: String,
+    target_node: Option<&'a str>,
+    replica_nodes: Vec<&'a str>,
+    num_replicas: u8,
+}
+
+impl<'a> From<&'a Volume> for VolumePlacement<'a> {
+    fn from(volume: &'a Volume) -> Self {
+        Self {
+            uuid: volume.spec.uuid.to_string(),
+            target_node: volume
+                .state
+                .target
+                .as_ref()
+                .map(|target| target.node.as_str()),
+            replica_nodes: volume
+                .state
+                .replica_topology
+                .values()
+                .filter_map(|topology| topology.node.as_deref())
+                .collect(),
+            num_replicas: volume.spec.num_replicas,
+        }
+    }
+}
+
+/// This collects the volumes with a replica or a target on the node. The replica on the node is
+/// expected to be rebuilt after the restart, if the volume is published and has other replicas.
+fn node_restart(node_name: &str, pod_name: String, volumes: &[VolumePlacement]) -> NodeRestart {
+    let mut affected_volumes = Vec::new();
+    let mut estimated_rebuilds = 0;
+
+    for volume in volumes {
+        let has_target = volume.target_node == Some(node_name);
+        let has_replica = volume.replica_nodes.contains(&node_name);
+        if !has_target && !has_replica {
+            continue;
+        }
+
+        affected_volumes.push(volume.uuid.clone());
+        if has_replica && volume.target_node.is_some() && volume.num_replicas > 1 {
+            estimated_rebuilds += 1;
+        }
+    }
+
+    NodeRestart {
+        node: node_name.to_string(),
+        pod: pod_name,
+        volumes: affected_volumes,
+        estimated_rebuilds,
+    }
+}
+
+/// This is used to save the upgrade plan ConfigMap of a helm release.
+struct UpgradePlanStore {
+    configmaps_api: Api<ConfigMap>,
+    name: String,
+    namespace: String,
+}
+
+impl UpgradePlanStore {
+    /// Creates an UpgradePlanStore for the helm release in the namespace.
+    async fn new(release_name: &str, namespace: &str) -> Result<Self> {
+        Ok(Self {
+            configmaps_api: KubeClient::configmaps_api(namespace).await?,
+            name: upgrade_plan_configmap_name(release_name),
+            namespace: namespace.to_string(),
+        })
+    }
+
+    /// Saves the value as the only data of the ConfigMap. The ConfigMap is created if it does not
+    /// exist.
+    async fn save(&self, key: &str, value: String) -> Result<()> {
+        let data = BTreeMap::from([(key.to_string(), value)]);
+
+        let maybe_cm = self
+            .configmaps_api
+            .get_opt(self.name.as_str())
+            .await
+            .context(GetUpgradePlan {
+                name: self.name.clone(),
+                namespace: self.namespace.clone(),
+            })?;
+
+        match maybe_cm {
+            Some(mut cm) => {
+                cm.data = Some(data);
+
+                self.configmaps_api
+                    .replace(self.name.as_str(), &PostParams::default(), &cm)
+                    .await
+                    .context(ReplaceUpgradePlan {
+                        name: self.name.clone(),
+                        namespace: self.namespace.clone(),
+                    })?;
+            }
+            None => {
+                let cm = ConfigMap {
+                    metadata: ObjectMeta {
+                        name: Some(self.name.clone()),
+                        namespace: Some(self.namespace.clone()),
+                        ..Default::default()
+                    },
+                    data: Some(data),
+                    ..Default::default()
+                };
+
+                self.configmaps_api
+                    .create(&PostParams::default(), &cm)
+                    .await
+                    .context(CreateUpgradePlan {
+                        name: self.name.clone(),
+                        namespace: self.namespace.clone(),
+                    })?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{node_restart, VolumePlacement};
+
+    fn placement<'a>(
+        uuid: &str,
+        target_node: Option<&'a str>,
+        replica_nodes: Vec<&'a str>,
+    ) -> VolumePlacement<'a> {
+        VolumePlacement {
+            uuid: uuid.to_string(),
+            target_node,
+            num_replicas: replica_nodes.len() as u8,
+            replica_nodes,
+        }
+    }
+
+    #[test]
+    fn node_restart_volumes_and_rebuilds() {
+        let volumes = vec![
+            // The target is on node-1, and the replicas are elsewhere.
+            placement("target-only", Some("node-1"), vec!["node-2", "node-3"]),
+            // A replica is on node-1, and the target is elsewhere.
+            placement("replica-only", Some("node-2"), vec!["node-1", "node-3"]),
+            // The only replica is on node-1. It has nothing to be rebuilt from.
+            placement("single-replica", Some("node-1"), vec!["node-1"]),
+            // A replica is on node-1, but the volume is not published.
+            placement("unpublished", None, vec!["node-1", "node-2"]),
+            // Nothing is on node-1.
+            placement("elsewhere", Some("node-2"), vec!["node-2", "node-3"]),
+        ];
+
+        let restart = node_restart("node-1", "io-engine-1".to_string(), volumes.as_slice());
+        assert_eq!(restart.node, "node-1");
+        assert_eq!(restart.pod, "io-engine-1");
+        assert_eq!(
+            restart.volumes,
+            vec![
+                "target-only",
+                "replica-only",
+                "single-replica",
+                "unpublished"
+            ]
+        );
+        // Only the replica of the published volume with other replicas is rebuilt.
+        assert_eq!(restart.estimated_rebuilds, 1);
+
+        let restart = node_restart("node-3", "io-engine-3".to_string(), volumes.as_slice());
+        assert_eq!(
+            restart.volumes,
+            vec!["target-only", "replica-only", "elsewhere"]
+        );
+        assert_eq!(restart.estimated_rebuilds, 3);
+
+        let restart = node_restart("node-4", "io-engine-4".to_string(), volumes.as_slice());
+        assert!(restart.volumes.is_empty());
+        assert_eq!(restart.estimated_rebuilds, 0);
+    }
+}
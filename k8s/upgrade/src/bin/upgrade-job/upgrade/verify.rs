@@ -0,0 +1,263 @@
+use crate::{
+    common::{
+        constants::{
+            product_train, AGENT_CORE_LABEL, CSI_NODE_LABEL, IO_ENGINE_LABEL,
+            POST_UPGRADE_VERIFICATION_ATTEMPTS, POST_UPGRADE_VERIFICATION_INTERVAL_SECS,
+        },
+        error::{ListStoragePools, Result},
+        kube::client as KubeClient,
+        rest_client::RestClientSet,
+    },
+    helm::chart::HelmValuesCollection,
+    upgrade::utils::{all_pods_are_ready, list_all_volumes},
+};
+use openapi::models::{PoolStatus, VolumeStatus};
+use snafu::ResultExt;
+use std::{collections::HashMap, time::Duration};
+use tracing::{info, warn};
+
+/// These are the Pod labels of the control-plane Pods, and the names of their containers which
+/// run the control-plane image tag.
+const CONTROL_PLANE_CONTAINERS: [(&str, &[&str]); 6] = [
+    (AGENT_CORE_LABEL, &["agent-core", "agent-ha-cluster"]),
+    ("app=agent-ha-node", &["agent-ha-node"]),
+    ("app=api-rest", &["api-rest"]),
+    ("app=csi-controller", &["csi-controller"]),
+    (CSI_NODE_LABEL, &["csi-node"]),
+    ("app=operator-diskpool", &["operator-diskpool"]),
+];
+
+/// This is the name of the io-engine container, which runs the data-plane image tag.
+const IO_ENGINE_CONTAINER: &str = "io-engine";
+
+/// This is the state of the volumes before upgrading. The volumes are compared against it after
+/// the upgrade, to find the ones which have lost replicas.
+pub(crate) struct PreUpgradeSnapshot {
+    volume_replicas: HashMap<String, usize>,
+}
+
+impl PreUpgradeSnapshot {
+    /// Records the number of replicas of each of the volumes.
+    pub(crate) async fn new(rest_client: &RestClientSet) -> Result<Self> {
+        let volume_replicas = list_all_volumes(rest_client)
+            .await?
+            .into_iter()
+            .map(|volume| {
+                (
+                    volume.spec.uuid.to_string(),
+                    volume.state.replica_topology.len(),
+                )
+            })
+            .collect();
+
+        Ok(Self { volume_replicas })
+    }
+}
+
+/// This checks that the upgraded components are healthy, after the upgrade. The checks are
+/// retried while the upgraded components settle. Returns the regressions which remain after the
+/// last attempt, if any. Failing to run a check is also reported as a regression, as the upgrade
+/// itself is complete by this point.
+pub(crate) async fn verify_upgrade(
+    namespace: &str,
+    rest_client: &RestClientSet,
+    snapshot: &PreUpgradeSnapshot,
+    values: &dyn HelmValuesCollection,
+    verify_data_plane_images: bool,
+) -> Vec<String> {
+    let mut regressions = Vec::new();
+    for attempt in 1 ..= POST_UPGRADE_VERIFICATION_ATTEMPTS {
+        regressions = find_regressions(
+            namespace,
+            rest_client,
+            snapshot,
+            values,
+            verify_data_plane_images,
+        )
+        .await;
+        if regressions.is_empty() {
+            info!("Post-upgrade verification of {} passed", product_train());
+            break;
+        }
+
+        warn!(
+            attempt,
+            ?regressions,
+            "Post-upgrade verification of {} found regressions",
+            product_train()
+        );
+        if attempt < POST_UPGRADE_VERIFICATION_ATTEMPTS {
+            tokio::time::sleep(Duration::from_secs(POST_UPGRADE_VERIFICATION_INTERVAL_SECS)).await;
+        }
+    }
+
+    regressions
+}
+
+/// Runs each of the post-upgrade checks once.
+async fn find_regressions(
+    namespace: &str,
+    rest_client: &RestClientSet,
+    snapshot: &PreUpgradeSnapshot,
+    values: &dyn HelmValuesCollection,
+    verify_data_plane_images: bool,
+) -> Vec<String> {
+    let mut regressions = Vec::new();
+    let mut check = |result: Result<Vec<String>>| match result {
+        Ok(found) => regressions.extend(found),
+        Err(error) => regressions.push(format!("Failed to verify the upgrade: {error}")),
+    };
+
+    for (label, containers) in CONTROL_PLANE_CONTAINERS {
+        check(
+            image_regressions(namespace, label, containers, values.control_plane_repotag()).await,
+        );
+    }
+    if verify_data_plane_images {
+        check(
+            image_regressions(
+                namespace,
+                IO_ENGINE_LABEL,
+                &[IO_ENGINE_CONTAINER],
+                values.data_plane_repotag(),
+            )
+            .await,
+        );
+    }
+    check(volume_regressions(rest_client, snapshot).await);
+    check(pool_regressions(rest_client).await);
+    check(csi_node_regressions(namespace).await);
+
+    regressions
+}
+
+/// Lists the containers of the Pods with the label, which do not run the expected image tag.
+async fn image_regressions(
+    namespace: &str,
+    label: &str,
+    containers: &[&str],
+    expected_tag: &str,
+) -> Result<Vec<String>> {
+    let pods = KubeClient::list_pods(namespace.to_string(), Some(label.to_string()), None).await?;
+
+    Ok(pods
+        .iter()
+        .flat_map(|pod| {
+            let pod_name = pod.metadata.name.clone().unwrap_or_default();
+            pod.spec
+                .iter()
+                .flat_map(|spec| spec.containers.iter())
+                .filter(|container| containers.contains(&container.name.as_str()))
+                .filter_map(move |container| {
+                    let image = container.image.as_deref().unwrap_or_default();
+                    (image_tag(image) != Some(expected_tag)).then(|| {
+                        format!(
+                            "Container '{}' of Pod '{pod_name}' runs image '{image}', expected tag '{expected_tag}'",
+                            container.name
+                        )
+                    })
+                })
+        })
+        .collect())
+}
+
+/// Lists the volumes which are not Online, or which have fewer replicas than they had before
+/// upgrading.
+async fn volume_regressions(
+    rest_client: &RestClientSet,
+    snapshot: &PreUpgradeSnapshot,
+) -> Result<Vec<String>> {
+    let mut regressions = Vec::new();
+    for volume in list_all_volumes(rest_client).await? {
+        let uuid = volume.spec.uuid.to_string();
+        if volume.state.status != VolumeStatus::Online {
+            regressions.push(format!(
+                "Volume '{uuid}' is {:?}, expected Online",
+                volume.state.status
+            ));
+        }
+
+        let replicas = volume.state.replica_topology.len();
+        if let Some(&replicas_before) = snapshot.volume_replicas.get(&uuid) {
+            if replicas < replicas_before {
+                regressions.push(format!(
+                    "Volume '{uuid}' has {replicas} replica(s), it had {replicas_before} before upgrading"
+                ));
+            }
+        }
+    }
+
+    Ok(regressions)
+}
+
+/// Lists the pools which are not Online.
+async fn pool_regressions(rest_client: &RestClientSet) -> Result<Vec<String>> {
+    let pools = rest_client
+        .pools_api()
+        .get_pools(None)
+        .await
+        .context(ListStoragePools)?
+        .into_body();
+
+    Ok(pools
+        .into_iter()
+        .filter_map(|pool| match pool.state {
+            Some(state) if state.status == PoolStatus::Online => None,
+            Some(state) => Some(format!(
+                "Pool '{}' on Node '{}' is {:?}, expected Online",
+                pool.id, state.node, state.status
+            )),
+            None => Some(format!("Pool '{}' has no state, expected Online", pool.id)),
+        })
+        .collect())
+}
+
+/// Lists the CSI node Pods which are not Ready.
+async fn csi_node_regressions(namespace: &str) -> Result<Vec<String>> {
+    let pods = KubeClient::list_pods(
+        namespace.to_string(),
+        Some(CSI_NODE_LABEL.to_string()),
+        None,
+    )
+    .await?;
+
+    Ok(pods
+        .into_iter()
+        .filter(|pod| !all_pods_are_ready(vec![pod.clone()]))
+        .map(|pod| {
+            format!(
+                "CSI node Pod '{}' is not Ready",
+                pod.metadata.name.unwrap_or_default()
+            )
+        })
+        .collect())
+}
+
+/// Returns the tag of a container image reference, if it has one. The digest, if any, is not a
+/// part of the tag.
+fn image_tag(image: &str) -> Option<&str> {
+    let image = image.split('@').next().unwrap_or(image);
+    let name_and_tag = image.rsplit('/').next().unwrap_or(image);
+    name_and_tag.split_once(':').map(|(_, tag)| tag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::image_tag;
+
+    #[test]
+    fn image_tag_from_reference() {
+        assert_eq!(
+            image_tag("docker.io/openebs/mayastor-agent-core:v2.7.0"),
+            Some("v2.7.0")
+        );
+        assert_eq!(
+            image_tag("registry.local:5000/openebs/mayastor-io-engine:v2.7.0@sha256:abcd"),
+            Some("v2.7.0")
+        );
+        assert_eq!(
+            image_tag("registry.local:5000/openebs/mayastor-csi-node"),
+            None
+        );
+    }
+}
@@ -0,0 +1,339 @@
+use crate::{
+    common::{
+        error::{DisruptionBudgetsTimeout, Result, WorkloadReadyTimeout},
+        kube::client as KubeClient,
+    },
+    events::event_recorder::{EventAction, EventRecorder},
+    upgrade::utils::all_pods_are_ready,
+};
+use k8s_openapi::{
+    api::core::v1::Pod,
+    apimachinery::pkg::apis::meta::v1::{LabelSelector, LabelSelectorRequirement},
+};
+use kube::ResourceExt;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    time::Duration,
+};
+use tokio::time::{sleep, Instant};
+use tracing::info;
+
+/// This is the workload-aware drain of storage nodes. When it is enabled, the application Pods
+/// which use the volumes targeted on a storage node are looked up before the node is drained. The
+/// drain waits for the PodDisruptionBudgets of these Pods to allow a disruption, and optionally,
+/// the upgrade waits for these Pods to be Ready again before moving on to the next node. Each of
+/// these waits gives up after the timeout.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct WorkloadAwareDrain {
+    enabled: bool,
+    wait_for_ready: bool,
+    timeout: Duration,
+}
+
+impl WorkloadAwareDrain {
+    /// Creates a WorkloadAwareDrain. Pods are waited on to be Ready only if it is enabled.
+    pub(crate) fn new(enabled: bool, wait_for_ready: bool) -> Self {
+        Self {
+            enabled,
+            wait_for_ready: enabled && wait_for_ready,
+            timeout: DEFAULT_WAIT_TIMEOUT,
+        }
+    }
+
+    /// This sets the time to wait for the PodDisruptionBudgets to allow a disruption, and for the
+    /// Pods to be Ready, before failing the upgrade.
+    #[must_use]
+    pub(crate) fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Returns true if the workload-aware drain is enabled.
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Returns the application Pods which use the volumes, i.e. the Pods which use the
+    /// PersistentVolumeClaims of the volumes' PersistentVolumes, on the nodes which the
+    /// volumes' VolumeAttachments attach them to. This is empty if the workload-aware drain is
+    /// disabled.
+    pub(crate) async fn workload_pods(&self, volume_uuids: HashSet<String>) -> Result<Vec<Pod>> {
+        if !self.enabled || volume_uuids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // PersistentVolume name -> (PersistentVolumeClaim namespace, PersistentVolumeClaim name).
+        let claims: BTreeMap<String, (String, String)> = KubeClient::list_persistent_volumes()
+            .await?
+            .into_iter()
+            .filter_map(|pv| {
+                let spec = pv.spec.as_ref()?;
+                let volume_handle = spec.csi.as_ref()?.volume_handle.as_str();
+                if !volume_uuids.contains(volume_handle) {
+                    return None;
+                }
+                let claim_ref = spec.claim_ref.as_ref()?;
+                Some((
+                    pv.name_any(),
+                    (claim_ref.namespace.clone()?, claim_ref.name.clone()?),
+                ))
+            })
+            .collect();
+
+        // (PersistentVolumeClaim namespace, attached node) -> PersistentVolumeClaim names.
+        let mut attached_claims: BTreeMap<(String, String), BTreeSet<String>> = BTreeMap::new();
+        for attachment in KubeClient::list_volume_attachments().await? {
+            let attached = attachment
+                .status
+                .as_ref()
+                .is_some_and(|status| status.attached);
+            let Some(pv_name) = attachment.spec.source.persistent_volume_name.as_ref() else {
+                continue;
+            };
+            let Some((namespace, claim)) = claims.get(pv_name) else {
+                continue;
+            };
+            if attached {
+                attached_claims
+                    .entry((namespace.clone(), attachment.spec.node_name.clone()))
+                    .or_default()
+                    .insert(claim.clone());
+            }
+        }
+
+        let mut pods = Vec::new();
+        for ((namespace, node_name), claim_names) in attached_claims {
+            let node_pods =
+                KubeClient::list_pods(namespace, None, Some(format!("spec.nodeName={node_name}")))
+                    .await?;
+            pods.extend(
+                node_pods
+                    .into_iter()
+                    .filter(|pod| pod_uses_claims(pod, &claim_names)),
+            );
+        }
+
+        Ok(pods)
+    }
+
+    /// Waits until all of the PodDisruptionBudgets which select the Pods allow a disruption. A
+    /// Warning event is published if they do not, before the timeout.
+    pub(crate) async fn wait_for_disruption_budgets(
+        &self,
+        pods: &[Pod],
+        event: &EventRecorder,
+    ) -> Result<()> {
+        if !self.enabled || pods.is_empty() {
+            return Ok(());
+        }
+
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            let blocking_pdbs = blocking_disruption_budgets(pods).await?;
+            if blocking_pdbs.is_empty() {
+                return Ok(());
+            }
+
+            if Instant::now() >= deadline {
+                let error = DisruptionBudgetsTimeout {
+                    pdbs: blocking_pdbs.join(", "),
+                    timeout: self.timeout,
+                }
+                .build();
+                event
+                    .publish_warning(error.to_string(), EventAction::DrainingNode)
+                    .await?;
+                return Err(error);
+            }
+
+            info!(
+                pdbs = blocking_pdbs.join(", "),
+                "Waiting for PodDisruptionBudgets of the application Pods to allow disruptions"
+            );
+            sleep(Duration::from_secs(10_u64)).await;
+        }
+    }
+
+    /// Waits until all of the Pods are Ready. Pods which have been removed are not waited on. A
+    /// Warning event is published if they are not Ready before the timeout.
+    pub(crate) async fn wait_for_ready(&self, pods: &[Pod], event: &EventRecorder) -> Result<()> {
+        if !self.wait_for_ready {
+            return Ok(());
+        }
+
+        let deadline = Instant::now() + self.timeout;
+        for pod in pods {
+            let namespace = pod.namespace().unwrap_or_default();
+            let name_field = format!("metadata.name={}", pod.name_any());
+            loop {
+                let current_pods =
+                    KubeClient::list_pods(namespace.clone(), None, Some(name_field.clone()))
+                        .await?;
+                if current_pods.is_empty() || all_pods_are_ready(current_pods) {
+                    break;
+                }
+
+                if Instant::now() >= deadline {
+                    let error = WorkloadReadyTimeout {
+                        name: pod.name_any(),
+                        namespace: namespace.clone(),
+                        timeout: self.timeout,
+                    }
+                    .build();
+                    event
+                        .publish_warning(error.to_string(), EventAction::UpgradingDP)
+                        .await?;
+                    return Err(error);
+                }
+
+                info!(
+                    pod.name = %pod.name_any(),
+                    pod.namespace = %namespace,
+                    "Waiting for application Pod to come to Ready state"
+                );
+                sleep(Duration::from_secs(5_u64)).await;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// This is the default time to wait for the application Pods, if no timeout is set.
+pub(crate) const DEFAULT_WAIT_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// Returns the PodDisruptionBudgets, as <namespace>/<name>, which select any of the Pods and allow
+/// no disruptions.
+async fn blocking_disruption_budgets(pods: &[Pod]) -> Result<Vec<String>> {
+    let namespaces: BTreeSet<String> = pods.iter().filter_map(|pod| pod.namespace()).collect();
+
+    let mut blocking_pdbs = Vec::new();
+    for namespace in namespaces {
+        for pdb in KubeClient::list_pod_disruption_budgets(namespace.clone()).await? {
+            let Some(selector) = pdb.spec.as_ref().and_then(|spec| spec.selector.as_ref()) else {
+                continue;
+            };
+            let selects_pods = pods.iter().any(|pod| {
+                pod.namespace().as_ref() == Some(&namespace)
+                    && label_selector_matches(selector, pod.labels())
+            });
+            let disruptions_allowed = pdb
+                .status
+                .as_ref()
+                .map_or(0, |status| status.disruptions_allowed);
+
+            if selects_pods && disruptions_allowed < 1 {
+                blocking_pdbs.push(format!("{namespace}/{}", pdb.name_any()));
+            }
+        }
+    }
+
+    Ok(blocking_pdbs)
+}
+
+/// Returns true if the Pod has a volume from any of the PersistentVolumeClaims.
+fn pod_uses_claims(pod: &Pod, claim_names: &BTreeSet<String>) -> bool {
+    pod.spec
+        .as_ref()
+        .and_then(|spec| spec.volumes.as_ref())
+        .is_some_and(|volumes| {
+            volumes.iter().any(|volume| {
+                volume
+                    .persistent_volume_claim
+                    .as_ref()
+                    .is_some_and(|claim| claim_names.contains(&claim.claim_name))
+            })
+        })
+}
+
+/// Returns true if the labels match the label selector. An empty label selector matches all
+/// labels.
+fn label_selector_matches(selector: &LabelSelector, labels: &BTreeMap<String, String>) -> bool {
+    let labels_match = selector
+        .match_labels
+        .iter()
+        .flatten()
+        .all(|(key, value)| labels.get(key) == Some(value));
+
+    let expressions_match = selector
+        .match_expressions
+        .iter()
+        .flatten()
+        .all(|expression| requirement_matches(expression, labels));
+
+    labels_match && expressions_match
+}
+
+/// Returns true if the labels meet the label selector requirement.
+fn requirement_matches(
+    requirement: &LabelSelectorRequirement,
+    labels: &BTreeMap<String, String>,
+) -> bool {
+    let value = labels.get(&requirement.key);
+    let in_values = |value: &String| {
+        requirement
+            .values
+            .as_ref()
+            .is_some_and(|values| values.contains(value))
+    };
+
+    match requirement.operator.as_str() {
+        "In" => value.is_some_and(in_values),
+        "NotIn" => !value.is_some_and(in_values),
+        "Exists" => value.is_some(),
+        "DoesNotExist" => value.is_none(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::label_selector_matches;
+    use k8s_openapi::apimachinery::pkg::apis::meta::v1::{LabelSelector, LabelSelectorRequirement};
+    use std::collections::BTreeMap;
+
+    fn requirement(key: &str, operator: &str, values: &[&str]) -> LabelSelectorRequirement {
+        LabelSelectorRequirement {
+            key: key.to_string(),
+            operator: operator.to_string(),
+            values: Some(values.iter().map(|value| value.to_string()).collect()),
+        }
+    }
+
+    #[test]
+    fn label_selector_matching() {
+        let labels = BTreeMap::from([
+            ("app".to_string(), "db".to_string()),
+            ("tier".to_string(), "backend".to_string()),
+        ]);
+
+        assert!(label_selector_matches(&LabelSelector::default(), &labels));
+
+        let selector = LabelSelector {
+            match_labels: Some(BTreeMap::from([("app".to_string(), "db".to_string())])),
+            match_expressions: Some(vec![
+                requirement("tier", "In", &["backend", "cache"]),
+                requirement("canary", "DoesNotExist", &[]),
+            ]),
+        };
+        assert!(label_selector_matches(&selector, &labels));
+
+        let selector = LabelSelector {
+            match_labels: Some(BTreeMap::from([("app".to_string(), "web".to_string())])),
+            match_expressions: None,
+        };
+        assert!(!label_selector_matches(&selector, &labels));
+
+        let selector = LabelSelector {
+            match_labels: None,
+            match_expressions: Some(vec![requirement("tier", "NotIn", &["backend"])]),
+        };
+        assert!(!label_selector_matches(&selector, &labels));
+
+        let selector = LabelSelector {
+            match_labels: None,
+            match_expressions: Some(vec![requirement("app", "Exists", &[])]),
+        };
+        assert!(label_selector_matches(&selector, &labels));
+    }
+}
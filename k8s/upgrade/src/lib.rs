@@ -3,6 +3,12 @@ pub mod plugin;
 /// Module for mayastor upgrade.
 pub use plugin::upgrade;
 
+/// Module for scheduled upgrades and maintenance windows.
+pub use plugin::schedule;
+
+/// Module for the upgrade paths of the compatibility matrix.
+pub use plugin::path;
+
 /// Validations before applying upgrade.
 pub use plugin::preflight_validations;
 
@@ -0,0 +1,227 @@
+use crate::plugin::{
+    error,
+    upgrade::{get_release_name, print_structured},
+    user_prompt::{UPGRADE_BACKUP_ETCD_DUMP, UPGRADE_BACKUP_VALUES},
+};
+use constants::{
+    upgrade_backup_label_key, upgrade_backup_secret_name, UPGRADE_BACKUP_ETCD_KEY,
+    UPGRADE_BACKUP_RELEASE_KEY, UPGRADE_BACKUP_VALUES_KEY,
+};
+use flate2::read::GzDecoder;
+use k8s_openapi::{api::core::v1::Secret, ByteString};
+use kube::{
+    api::{Api, ListParams},
+    Client, ResourceExt,
+};
+use rest_plugin::resources::utils::OutputFormat;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use snafu::ResultExt;
+use std::{collections::BTreeMap, io::Read};
+
+/// Inspect the backups which the upgrade-job takes before upgrading.
+#[derive(clap::Subcommand, Debug, Clone)]
+pub enum UpgradeBackupsCommand {
+    /// List the pre-upgrade backups of the helm release.
+    List,
+    /// Show the pre-upgrade backup taken before upgrading from a version.
+    Show(ShowUpgradeBackupArgs),
+}
+
+/// Arguments to be passed to show a pre-upgrade backup.
+#[derive(clap::Args, Debug, Clone)]
+pub struct ShowUpgradeBackupArgs {
+    /// The version which was upgraded from, i.e. the source version of the upgrade.
+    pub version: String,
+
+    /// Display the dump of the control-plane's keys in etcd as well.
+    #[clap(long, default_value_t = false)]
+    pub etcd: bool,
+}
+
+/// This struct is used to deserialize the release metadata of a pre-upgrade backup.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct UpgradeBackupMetadata {
+    release_name: String,
+    namespace: String,
+    revision: u64,
+    chart: String,
+    source_version: String,
+    target_version: String,
+    created_at: String,
+    etcd_endpoint: String,
+    etcd_keys: usize,
+}
+
+/// A pre-upgrade backup, as it is printed by the show command.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UpgradeBackup {
+    name: String,
+    release: UpgradeBackupMetadata,
+    values: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    etcd: Option<Value>,
+}
+
+impl UpgradeBackupsCommand {
+    /// Execute the backups command.
+    pub async fn execute(&self, namespace: &str, output: &OutputFormat) -> error::Result<()> {
+        let release_name = get_release_name(namespace).await?;
+        let client = Client::try_default()
+            .await
+            .context(error::K8sClientGeneration)?;
+        let secrets: Api<Secret> = Api::namespaced(client, namespace);
+
+        match self {
+            Self::List => list_backups(&secrets, release_name.as_str(), output).await,
+            Self::Show(args) => args.show(&secrets, release_name.as_str(), output).await,
+        }
+    }
+}
+
+impl ShowUpgradeBackupArgs {
+    /// Print the pre-upgrade backup of the version to console.
+    async fn show(
+        &self,
+        secrets: &Api<Secret>,
+        release_name: &str,
+        output: &OutputFormat,
+    ) -> error::Result<()> {
+        let name = upgrade_backup_secret_name(release_name, self.version.as_str());
+        let secret = secrets
+            .get_opt(name.as_str())
+            .await
+            .context(error::GetUpgradeBackup { name: name.clone() })?
+            .ok_or(error::UpgradeBackupNotPresent { name: name.clone() }.build())?;
+        let mut data = secret.data.unwrap_or_default();
+
+        let release = backup_metadata(name.as_str(), &data)?;
+        let values = String::from_utf8_lossy(
+            gunzip_data(name.as_str(), &mut data, UPGRADE_BACKUP_VALUES_KEY)?.as_slice(),
+        )
+        .to_string();
+        let etcd = match self.etcd {
+            true => Some(
+                serde_json::from_slice(
+                    gunzip_data(name.as_str(), &mut data, UPGRADE_BACKUP_ETCD_KEY)?.as_slice(),
+                )
+                .context(error::UpgradeBackupDeserialization { name: name.clone() })?,
+            ),
+            false => None,
+        };
+
+        let backup = UpgradeBackup {
+            name,
+            release,
+            values,
+            etcd,
+        };
+        match output {
+            OutputFormat::None => {
+                backup.release.print();
+                println!("{UPGRADE_BACKUP_VALUES}");
+                print!("{}", backup.values);
+                if let Some(etcd) = &backup.etcd {
+                    println!("{UPGRADE_BACKUP_ETCD_DUMP}");
+                    println!(
+                        "{}",
+                        serde_json::to_string_pretty(etcd).context(error::JsonSerialization)?
+                    );
+                }
+                Ok(())
+            }
+            _ => print_structured(&backup, output),
+        }
+    }
+}
+
+impl UpgradeBackupMetadata {
+    /// Print the release metadata of the backup to console.
+    fn print(&self) {
+        println!("Release: {}/{}", self.namespace, self.release_name);
+        println!("Revision: {}", self.revision);
+        println!("Chart: {}", self.chart);
+        println!("Upgrade From: {}", self.source_version);
+        println!("Upgrade To: {}", self.target_version);
+        println!("Created At: {}", self.created_at);
+        println!("Etcd Endpoint: {}", self.etcd_endpoint);
+        println!("Etcd Keys: {}", self.etcd_keys);
+    }
+}
+
+/// Print the pre-upgrade backups of the helm release to console, from the oldest to the latest.
+async fn list_backups(
+    secrets: &Api<Secret>,
+    release_name: &str,
+    output: &OutputFormat,
+) -> error::Result<()> {
+    let label = format!("{}={release_name}", upgrade_backup_label_key());
+    let mut backups = secrets
+        .list(&ListParams::default().labels(label.as_str()))
+        .await
+        .context(error::ListUpgradeBackups { label })?
+        .into_iter()
+        .map(|secret| {
+            let name = secret.name_any();
+            backup_metadata(name.as_str(), &secret.data.unwrap_or_default())
+        })
+        .collect::<error::Result<Vec<_>>>()?;
+    backups.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+
+    match output {
+        OutputFormat::None => {
+            println!(
+                "{:<14} {:<14} {:<32} {:<10} ETCD KEYS",
+                "FROM", "TO", "CREATED", "REVISION"
+            );
+            for backup in backups {
+                println!(
+                    "{:<14} {:<14} {:<32} {:<10} {}",
+                    backup.source_version,
+                    backup.target_version,
+                    backup.created_at,
+                    backup.revision,
+                    backup.etcd_keys
+                );
+            }
+            Ok(())
+        }
+        _ => print_structured(&backups, output),
+    }
+}
+
+/// Deserializes the release metadata from the data of a pre-upgrade backup Secret.
+fn backup_metadata(
+    name: &str,
+    data: &BTreeMap<String, ByteString>,
+) -> error::Result<UpgradeBackupMetadata> {
+    let metadata = data.get(UPGRADE_BACKUP_RELEASE_KEY).ok_or(
+        error::UpgradeBackupDataNotPresent {
+            name,
+            key: UPGRADE_BACKUP_RELEASE_KEY,
+        }
+        .build(),
+    )?;
+    serde_json::from_slice(metadata.0.as_slice())
+        .context(error::UpgradeBackupDeserialization { name })
+}
+
+/// Removes the gzip compressed value of the key from the data of a pre-upgrade backup Secret,
+/// and decompresses it.
+fn gunzip_data(
+    name: &str,
+    data: &mut BTreeMap<String, ByteString>,
+    key: &str,
+) -> error::Result<Vec<u8>> {
+    let compressed = data
+        .remove(key)
+        .ok_or(error::UpgradeBackupDataNotPresent { name, key }.build())?;
+
+    let mut decompressed = Vec::new();
+    GzDecoder::new(compressed.0.as_slice())
+        .read_to_end(&mut decompressed)
+        .context(error::UpgradeBackupDecompression { name, key })?;
+    Ok(decompressed)
+}
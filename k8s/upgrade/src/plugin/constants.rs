@@ -83,6 +83,18 @@ pub(crate) const UPGRADE_CONFIG_MAP: &str = "upgrade-config-map";
 /// ConfigMap mount path for upgrade.
 pub(crate) const UPGRADE_CONFIG_MAP_MOUNT_PATH: &str = "/upgrade-config-map";
 
+/// Volume name of the helm chart repository Secret for upgrade.
+pub(crate) const UPGRADE_CHART_REPO_SECRET: &str = "upgrade-chart-repo-secret";
+
+/// Helm chart repository Secret mount path for upgrade.
+pub(crate) const UPGRADE_CHART_REPO_SECRET_MOUNT_PATH: &str = "/upgrade-chart-repo-secret";
+
+/// Volume name of the notification Secret for upgrade.
+pub(crate) const UPGRADE_NOTIFICATION_SECRET: &str = "upgrade-notification-secret";
+
+/// Notification Secret mount path for upgrade.
+pub(crate) const UPGRADE_NOTIFICATION_SECRET_MOUNT_PATH: &str = "/upgrade-notification-secret";
+
 /// ServiceAccount name suffix for upgrade job.
 pub(crate) const UPGRADE_JOB_SERVICEACCOUNT_NAME_SUFFIX: &str = "upgrade-service-account";
 
@@ -121,5 +133,51 @@ pub(crate) const API_REST_POD_LABEL: &str = "app=api-rest";
 /// Upgrade to develop.
 pub(crate) const UPGRADE_TO_DEVELOP_BRANCH: &str = "develop";
 
+/// The action of the upgrade event of a successful upgrade.
+pub(crate) const UPGRADE_SUCCESSFUL_ACTION: &str = "Successful";
+
+/// The action of the upgrade event of an upgrade which completed, but whose post-upgrade
+/// verification found regressions.
+pub(crate) const UPGRADE_COMPLETED_WITH_WARNINGS_ACTION: &str = "Completed with warnings";
+
+/// The action of the upgrade event of a failed upgrade.
+pub(crate) const UPGRADE_FAILED_ACTION: &str = "Failed";
+
+/// The action of the upgrade event of an upgrade which failed validation.
+pub(crate) const UPGRADE_VALIDATION_FAILED_ACTION: &str = "Validation Failed";
+
+/// Number of seconds between polls of the upgrade events, while waiting for an upgrade to
+/// complete.
+pub(crate) const UPGRADE_COMPLETION_POLL_INTERVAL_SECS: u64 = 10;
+
+/// Number of times the upgrade events are polled while waiting for an upgrade to complete, i.e.
+/// 24 hours at the poll interval above.
+pub(crate) const UPGRADE_COMPLETION_MAX_POLL_ATTEMPTS: u32 = 8640;
+
+/// Number of seconds to wait for the application pods during the workload-aware drain of a
+/// storage node, before the upgrade fails.
+pub(crate) const WORKLOAD_DRAIN_TIMEOUT_SECS: u64 = 30 * 60;
+
 /// Number of retries for fetching the events.
 pub(crate) const MAX_RETRY_ATTEMPTS: u8 = 6;
+
+/// Number of times the upgrade plan ConfigMap is polled for the upgrade plan, during a dry-run.
+pub(crate) const UPGRADE_PLAN_MAX_POLL_ATTEMPTS: u8 = 120;
+
+/// Name suffix of the Pod which checks if the upgrade-job image can be pulled from the cluster.
+pub(crate) const REGISTRY_CHECK_POD_NAME_SUFFIX: &str = "registry-check";
+
+/// Number of times the image registry check Pod is polled for the image pull result.
+pub(crate) const REGISTRY_CHECK_MAX_POLL_ATTEMPTS: u8 = 12;
+
+/// Container waiting reasons which imply that the image could not be pulled.
+pub(crate) const IMAGE_PULL_FAILURE_REASONS: [&str; 3] =
+    ["ErrImagePull", "ImagePullBackOff", "InvalidImageName"];
+
+/// The minimum percentage of free capacity which a DiskPool should have before upgrade, to
+/// accommodate volume replica rebuilds.
+pub(crate) const POOL_CAPACITY_HEADROOM_PERCENT: u64 = 10;
+
+/// Helm release storage status values of releases with an operation in progress.
+pub(crate) const HELM_RELEASE_PENDING_STATUSES: [&str; 3] =
+    ["pending-install", "pending-upgrade", "pending-rollback"];
@@ -259,6 +259,192 @@ pub enum Error {
     /// Error for key not present in Map.
     #[snafu(display("Specified key not present."))]
     SpecifiedKeyNotPresent,
+
+    /// Error when a Get upgrade history config map fails.
+    #[snafu(display("Failed to get upgrade history Config Map {}: {}", name, source))]
+    GetUpgradeHistory { source: kube::Error, name: String },
+
+    /// Deserialization error for the upgrade history.
+    #[snafu(display(
+        "Error in deserializing upgrade history from Config Map {} Error {}",
+        name,
+        source
+    ))]
+    UpgradeHistoryDeserialization {
+        source: serde_json::Error,
+        name: String,
+    },
+
+    /// Error when serializing output to JSON.
+    #[snafu(display("Failed to serialize output to JSON: {}", source))]
+    JsonSerialization { source: serde_json::Error },
+
+    /// Error when serializing output to YAML.
+    #[snafu(display("Failed to serialize output to YAML: {}", source))]
+    YamlSerialization { source: serde_yaml::Error },
+
+    /// Error when one or more preflight checks fail.
+    #[snafu(display("{} preflight check(s) failed.", failed))]
+    PreflightChecksFailed { failed: usize },
+
+    /// Error for when listing storage pools fails.
+    #[snafu(display("Failed to list Pools: {}", source))]
+    ListStoragePools {
+        source: openapi::tower::client::Error<openapi::models::RestJsonError>,
+    },
+
+    /// Error for when listing Kubernetes Nodes fails.
+    #[snafu(display("Failed to list Kubernetes Nodes: {}", source))]
+    ListK8sNodes { source: kube::Error },
+
+    /// Error for when listing PodDisruptionBudgets fails.
+    #[snafu(display("Failed to list PodDisruptionBudgets: {}", source))]
+    ListPodDisruptionBudgets { source: kube::Error },
+
+    /// Error for when listing the helm release storage objects fails.
+    #[snafu(display(
+        "Failed to list helm storage objects with label {} in namespace {}: {}",
+        label,
+        namespace,
+        source
+    ))]
+    ListHelmReleaseStorage {
+        source: kube::Error,
+        label: String,
+        namespace: String,
+    },
+
+    /// Error when creating the image registry check Pod fails.
+    #[snafu(display("Failed to create Pod {}: {}", name, source))]
+    RegistryCheckPodCreate { source: kube::Error, name: String },
+
+    /// Error when fetching the image registry check Pod fails.
+    #[snafu(display("Failed to get Pod {}: {}", name, source))]
+    GetRegistryCheckPod { source: kube::Error, name: String },
+
+    /// Error when deleting the image registry check Pod fails.
+    #[snafu(display("Failed to delete Pod {}: {}", name, source))]
+    RegistryCheckPodDelete { source: kube::Error, name: String },
+
+    /// Error for when the upgrade job of an earlier upgrade is still present.
+    #[snafu(display(
+        "Upgrade Job: {} in namespace {} already exists. Please delete the upgrade resources using the 'delete upgrade' command.",
+        name,
+        namespace
+    ))]
+    UpgradeJobAlreadyPresent { name: String, namespace: String },
+
+    /// Error when a Get upgrade plan config map fails.
+    #[snafu(display("Failed to get upgrade plan Config Map {}: {}", name, source))]
+    GetUpgradePlan { source: kube::Error, name: String },
+
+    /// Error when deleting the upgrade plan config map fails.
+    #[snafu(display("Failed to delete upgrade plan Config Map {}: {}", name, source))]
+    UpgradePlanDelete { source: kube::Error, name: String },
+
+    /// Deserialization error for the upgrade plan.
+    #[snafu(display(
+        "Error in deserializing upgrade plan from Config Map {} Error {}",
+        name,
+        source
+    ))]
+    UpgradePlanDeserialization {
+        source: serde_json::Error,
+        name: String,
+    },
+
+    /// Error for when the upgrade job fails to compute the upgrade plan.
+    #[snafu(display("Failed to compute the upgrade plan: {}", message))]
+    UpgradePlanFailed { message: String },
+
+    /// Error for when the upgrade plan is not saved to the config map in time.
+    #[snafu(display("Timed out waiting for the upgrade plan in Config Map {}", name))]
+    UpgradePlanTimedOut { name: String },
+
+    /// Error when listing the upgrade backup secrets fails.
+    #[snafu(display(
+        "Failed to list upgrade backup Secrets with label {}: {}",
+        label,
+        source
+    ))]
+    ListUpgradeBackups { source: kube::Error, label: String },
+
+    /// Error when a Get upgrade backup secret fails.
+    #[snafu(display("Failed to get upgrade backup Secret {}: {}", name, source))]
+    GetUpgradeBackup { source: kube::Error, name: String },
+
+    /// Error for when the upgrade backup of a version is not present.
+    #[snafu(display("Upgrade backup Secret {} is not present", name))]
+    UpgradeBackupNotPresent { name: String },
+
+    /// Error for when a key is missing from the data of an upgrade backup secret.
+    #[snafu(display("Key {} is not present in upgrade backup Secret {}", key, name))]
+    UpgradeBackupDataNotPresent { name: String, key: String },
+
+    /// Deserialization error for the upgrade backup.
+    #[snafu(display(
+        "Error in deserializing upgrade backup from Secret {} Error {}",
+        name,
+        source
+    ))]
+    UpgradeBackupDeserialization {
+        source: serde_json::Error,
+        name: String,
+    },
+
+    /// Error for when the gzip compressed data of an upgrade backup cannot be decompressed.
+    #[snafu(display(
+        "Failed to decompress {} of upgrade backup Secret {}: {}",
+        key,
+        name,
+        source
+    ))]
+    UpgradeBackupDecompression {
+        source: std::io::Error,
+        name: String,
+        key: String,
+    },
+
+    /// Error for when the upgrade path is not supported, as per the compatibility matrix.
+    #[snafu(display(
+        "Upgrade from {} to {} is not supported: {}",
+        source_version,
+        target_version,
+        reason
+    ))]
+    UnsupportedUpgradePath {
+        source_version: String,
+        target_version: String,
+        reason: String,
+    },
+
+    /// Error for when the upgrade to an intermediate version of the upgrade path fails.
+    #[snafu(display("Upgrade to intermediate version {} failed: {}", version, message))]
+    IntermediateUpgradeFailed { version: String, message: String },
+
+    /// Error for when the upgrade to an intermediate version of the upgrade path does not complete
+    /// in time.
+    #[snafu(display(
+        "Upgrade to intermediate version {} did not complete after {} polls of the upgrade events",
+        version,
+        attempts
+    ))]
+    IntermediateUpgradeTimedOut { version: String, attempts: u32 },
+
+    /// Error for when the helm charts of the intermediate versions of the upgrade path cannot be
+    /// verified.
+    #[snafu(display(
+        "The helm charts of the intermediate versions {} cannot be verified without a digest: set '--chart-repo-secret' with a 'keyring.gpg' key, or '--allow-unverified-chart'",
+        versions
+    ))]
+    UnverifiableIntermediateCharts { versions: String },
+
+    /// Error for when a maintenance window is not of the form HH:MM-HH:MM.
+    #[snafu(display(
+        "Invalid maintenance window '{}', expected a UTC window of the form HH:MM-HH:MM",
+        window
+    ))]
+    InvalidMaintenanceWindow { window: String },
 }
 
 /// A wrapper type to remove repeated Result<T, Error> returns.
@@ -319,6 +505,35 @@ impl From<Error> for i32 {
             Error::UpgradeConfigMapDelete { .. } => 450,
             Error::InvalidSetFileArguments { .. } => 451,
             Error::SpecifiedKeyNotPresent { .. } => 452,
+            Error::GetUpgradeHistory { .. } => 453,
+            Error::UpgradeHistoryDeserialization { .. } => 454,
+            Error::JsonSerialization { .. } => 455,
+            Error::YamlSerialization { .. } => 456,
+            Error::PreflightChecksFailed { .. } => 457,
+            Error::ListStoragePools { .. } => 458,
+            Error::ListK8sNodes { .. } => 459,
+            Error::ListPodDisruptionBudgets { .. } => 460,
+            Error::ListHelmReleaseStorage { .. } => 461,
+            Error::RegistryCheckPodCreate { .. } => 462,
+            Error::GetRegistryCheckPod { .. } => 463,
+            Error::RegistryCheckPodDelete { .. } => 464,
+            Error::UpgradeJobAlreadyPresent { .. } => 465,
+            Error::GetUpgradePlan { .. } => 466,
+            Error::UpgradePlanDelete { .. } => 467,
+            Error::UpgradePlanDeserialization { .. } => 468,
+            Error::UpgradePlanFailed { .. } => 469,
+            Error::UpgradePlanTimedOut { .. } => 470,
+            Error::ListUpgradeBackups { .. } => 471,
+            Error::GetUpgradeBackup { .. } => 472,
+            Error::UpgradeBackupNotPresent { .. } => 473,
+            Error::UpgradeBackupDataNotPresent { .. } => 474,
+            Error::UpgradeBackupDeserialization { .. } => 475,
+            Error::UpgradeBackupDecompression { .. } => 476,
+            Error::UnsupportedUpgradePath { .. } => 477,
+            Error::IntermediateUpgradeFailed { .. } => 478,
+            Error::InvalidMaintenanceWindow { .. } => 479,
+            Error::UnverifiableIntermediateCharts { .. } => 480,
+            Error::IntermediateUpgradeTimedOut { .. } => 481,
         }
     }
 }
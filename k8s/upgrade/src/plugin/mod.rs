@@ -4,6 +4,21 @@ pub(crate) mod objects;
 /// Module for mayastor upgrade.
 pub mod upgrade;
 
+/// Module for the pre-upgrade backups of upgrades.
+pub mod backup;
+
+/// Module for the upgrade paths of the compatibility matrix.
+pub mod path;
+
+/// Module for the upgrade plan of upgrade dry-runs.
+pub(crate) mod plan;
+
+/// Module for the data-plane upgrade progress of upgrade status.
+pub(crate) mod progress;
+
+/// Module for scheduled upgrades and maintenance windows.
+pub mod schedule;
+
 /// Validations before applying upgrade.
 pub mod preflight_validations;
 
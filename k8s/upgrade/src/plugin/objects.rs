@@ -1,9 +1,11 @@
 use crate::{
     plugin::constants::{
-        upgrade_job_container_name, upgrade_name_concat, UPGRADE_BINARY_NAME, UPGRADE_CONFIG_MAP,
-        UPGRADE_CONFIG_MAP_MOUNT_PATH, UPGRADE_CONFIG_MAP_NAME_SUFFIX,
+        upgrade_job_container_name, upgrade_name_concat, REGISTRY_CHECK_POD_NAME_SUFFIX,
+        UPGRADE_BINARY_NAME, UPGRADE_CHART_REPO_SECRET, UPGRADE_CHART_REPO_SECRET_MOUNT_PATH,
+        UPGRADE_CONFIG_MAP, UPGRADE_CONFIG_MAP_MOUNT_PATH, UPGRADE_CONFIG_MAP_NAME_SUFFIX,
         UPGRADE_JOB_CLUSTERROLEBINDING_NAME_SUFFIX, UPGRADE_JOB_CLUSTERROLE_NAME_SUFFIX,
         UPGRADE_JOB_NAME_SUFFIX, UPGRADE_JOB_SERVICEACCOUNT_NAME_SUFFIX,
+        UPGRADE_NOTIFICATION_SECRET, UPGRADE_NOTIFICATION_SECRET_MOUNT_PATH,
     },
     upgrade::UpgradeArgs,
     upgrade_labels,
@@ -13,7 +15,8 @@ use k8s_openapi::api::{
     batch::v1::{Job, JobSpec},
     core::v1::{
         ConfigMap, ConfigMapVolumeSource, Container, EnvVar, EnvVarSource, ExecAction,
-        ObjectFieldSelector, PodSpec, PodTemplateSpec, Probe, ServiceAccount, Volume, VolumeMount,
+        ObjectFieldSelector, Pod, PodSpec, PodTemplateSpec, Probe, SecretVolumeSource,
+        ServiceAccount, Volume, VolumeMount,
     },
     rbac::v1::{ClusterRole, ClusterRoleBinding, PolicyRule, RoleRef, Subject},
 };
@@ -211,6 +214,12 @@ pub(crate) fn upgrade_job_cluster_role(
                 verbs: vec!["create", "list", "delete", "get", "patch"].into_vec(),
                 ..Default::default()
             },
+            PolicyRule {
+                api_groups: Some(vec!["storage.k8s.io"].into_vec()),
+                resources: Some(vec!["volumeattachments"].into_vec()),
+                verbs: vec!["get", "list"].into_vec(),
+                ..Default::default()
+            },
             PolicyRule {
                 api_groups: Some(vec!["scheduling.k8s.io"].into_vec()),
                 resources: Some(vec!["priorityclasses"].into_vec()),
@@ -298,12 +307,112 @@ pub(crate) fn upgrade_job(
     if args.skip_data_plane_restart {
         job_args.push("--skip-data-plane-restart".to_string());
     }
+    if args.skip_backup {
+        job_args.push("--skip-backup".to_string());
+    }
     if args.skip_upgrade_path_validation_for_unsupported_version {
         job_args.push("--skip-upgrade-path-validation".to_string());
     }
     if args.reset_then_reuse_values {
         job_args.push("--helm-reset-then-reuse-values".to_string());
     }
+    if let (Some(to_version), Some(chart_repo)) = (&args.to_version, &args.chart_repo) {
+        job_args.push(format!("--to-version={to_version}"));
+        job_args.push(format!("--chart-repo={chart_repo}"));
+    }
+    if let Some(chart_digest) = &args.chart_digest {
+        job_args.push(format!("--chart-digest={chart_digest}"));
+    }
+    if let Some(umbrella_chart_version) = &args.umbrella_chart_version {
+        job_args.push(format!("--umbrella-chart-version={umbrella_chart_version}"));
+    }
+    if let Some(umbrella_chart_digest) = &args.umbrella_chart_digest {
+        job_args.push(format!("--umbrella-chart-digest={umbrella_chart_digest}"));
+    }
+    if args.chart_repo_plain_http {
+        job_args.push("--chart-repo-plain-http".to_string());
+    }
+    if args.allow_unverified_chart {
+        job_args.push("--allow-unverified-chart".to_string());
+    }
+    if let Some(schedule) = &args.schedule {
+        job_args.push(format!("--start-at={}", schedule.start_at().to_rfc3339()));
+    }
+    if let Some(window) = &args.window {
+        job_args.push(format!("--window={window}"));
+    }
+    if args.workload_aware_drain {
+        job_args.push("--workload-aware-drain".to_string());
+        job_args.push(format!(
+            "--workload-drain-timeout={}",
+            args.workload_drain_timeout
+        ));
+    }
+    if args.wait_for_workload_ready {
+        job_args.push("--wait-for-workload-ready".to_string());
+    }
+    if args.dry_run {
+        job_args.push("--plan".to_string());
+    }
+
+    let mut volume_mounts = vec![VolumeMount {
+        read_only: Some(true),
+        mount_path: UPGRADE_CONFIG_MAP_MOUNT_PATH.to_string(),
+        name: UPGRADE_CONFIG_MAP.to_string(),
+        ..Default::default()
+    }];
+    let mut volumes = vec![Volume {
+        name: UPGRADE_CONFIG_MAP.to_string(),
+        config_map: Some(ConfigMapVolumeSource {
+            name: Some(upgrade_name_concat(
+                &release_name,
+                UPGRADE_CONFIG_MAP_NAME_SUFFIX,
+            )),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }];
+    // The helm chart repository's credentials are mounted from the Secret.
+    if let Some(secret_name) = &args.chart_repo_secret {
+        job_args.push(format!(
+            "--chart-repo-credentials-dir={UPGRADE_CHART_REPO_SECRET_MOUNT_PATH}"
+        ));
+        volume_mounts.push(VolumeMount {
+            read_only: Some(true),
+            mount_path: UPGRADE_CHART_REPO_SECRET_MOUNT_PATH.to_string(),
+            name: UPGRADE_CHART_REPO_SECRET.to_string(),
+            ..Default::default()
+        });
+        volumes.push(Volume {
+            name: UPGRADE_CHART_REPO_SECRET.to_string(),
+            secret: Some(SecretVolumeSource {
+                secret_name: Some(secret_name.clone()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+    }
+
+    // The webhook configuration for upgrade notifications is mounted from the Secret.
+    if let Some(secret_name) = &args.notification_secret {
+        job_args.push(format!(
+            "--notification-config-dir={UPGRADE_NOTIFICATION_SECRET_MOUNT_PATH}"
+        ));
+        volume_mounts.push(VolumeMount {
+            read_only: Some(true),
+            mount_path: UPGRADE_NOTIFICATION_SECRET_MOUNT_PATH.to_string(),
+            name: UPGRADE_NOTIFICATION_SECRET.to_string(),
+            ..Default::default()
+        });
+        volumes.push(Volume {
+            name: UPGRADE_NOTIFICATION_SECRET.to_string(),
+            secret: Some(SecretVolumeSource {
+                secret_name: Some(secret_name.clone()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+    }
 
     Job {
         metadata: ObjectMeta {
@@ -366,29 +475,14 @@ pub(crate) fn upgrade_job(
                             period_seconds: Some(60),
                             ..Default::default()
                         }),
-                        volume_mounts: Some(vec![VolumeMount {
-                            read_only: Some(true),
-                            mount_path: UPGRADE_CONFIG_MAP_MOUNT_PATH.to_string(),
-                            name: UPGRADE_CONFIG_MAP.to_string(),
-                            ..Default::default()
-                        }]),
+                        volume_mounts: Some(volume_mounts),
                         ..Default::default()
                     }],
                     service_account_name: Some(upgrade_name_concat(
                         &release_name,
                         UPGRADE_JOB_SERVICEACCOUNT_NAME_SUFFIX,
                     )),
-                    volumes: Some(vec![Volume {
-                        name: UPGRADE_CONFIG_MAP.to_string(),
-                        config_map: Some(ConfigMapVolumeSource {
-                            name: Some(upgrade_name_concat(
-                                &release_name,
-                                UPGRADE_CONFIG_MAP_NAME_SUFFIX,
-                            )),
-                            ..Default::default()
-                        }),
-                        ..Default::default()
-                    }]),
+                    volumes: Some(volumes),
                     ..Default::default()
                 }),
             },
@@ -397,3 +491,38 @@ pub(crate) fn upgrade_job(
         ..Default::default()
     }
 }
+
+/// Defines the Pod which is used to check if the upgrade-job image can be pulled from the
+/// cluster. The container only prints the upgrade-job's help text and exits.
+pub(crate) fn registry_check_pod(
+    namespace: &str,
+    upgrade_image: String,
+    release_name: &str,
+    image_pull_secrets: Option<Vec<k8s_openapi::api::core::v1::LocalObjectReference>>,
+    image_pull_policy: Option<String>,
+) -> Pod {
+    Pod {
+        metadata: ObjectMeta {
+            labels: Some(upgrade_labels!()),
+            name: Some(upgrade_name_concat(
+                release_name,
+                REGISTRY_CHECK_POD_NAME_SUFFIX,
+            )),
+            namespace: Some(namespace.to_string()),
+            ..Default::default()
+        },
+        spec: Some(PodSpec {
+            image_pull_secrets,
+            restart_policy: Some("Never".to_string()),
+            containers: vec![Container {
+                args: Some(vec!["--help".to_string()]),
+                image: Some(upgrade_image),
+                image_pull_policy,
+                name: REGISTRY_CHECK_POD_NAME_SUFFIX.to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
@@ -0,0 +1,278 @@
+use crate::plugin::error;
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use snafu::ResultExt;
+use std::fmt;
+
+/// Struct to deserialize the unsupported version yaml, i.e. the compatibility matrix of upgrade
+/// paths.
+#[derive(Deserialize)]
+pub struct CompatibilityMatrix {
+    #[serde(default)]
+    unsupported_versions: Vec<Version>,
+    #[serde(default)]
+    compatibility: Vec<CompatibilityRule>,
+}
+
+/// A constraint on the upgrade paths from the versions in the 'from' range, to the versions in the
+/// 'to' range, or to any version if it is absent. These upgrades must go through the 'via'
+/// version first. These upgrades are not supported if 'via' is absent.
+#[derive(Deserialize)]
+struct CompatibilityRule {
+    from: VersionReq,
+    #[serde(default)]
+    to: Option<VersionReq>,
+    #[serde(default)]
+    via: Option<Version>,
+    reason: String,
+}
+
+impl CompatibilityRule {
+    /// Returns true if the rule constrains the upgrade from 'from' to 'to'.
+    fn matches(&self, from: &Version, to: &Version) -> bool {
+        self.from.matches(from) && self.to.iter().all(|to_req| to_req.matches(to))
+    }
+}
+
+/// The constraint of the compatibility matrix on a direct upgrade, i.e. an upgrade which does not
+/// go through intermediate versions.
+#[derive(Debug, PartialEq)]
+pub enum DirectUpgrade<'a> {
+    /// The upgrade is not constrained.
+    Supported,
+    /// The upgrade must go through the intermediate version first.
+    Via {
+        version: &'a Version,
+        reason: &'a str,
+    },
+    /// The upgrade is not supported.
+    Unsupported { reason: &'a str },
+}
+
+/// A single upgrade of a multi-hop upgrade path. The reason is set for the upgrades to the
+/// intermediate versions.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub(crate) struct UpgradeHop {
+    from: Version,
+    to: Version,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+}
+
+impl UpgradeHop {
+    /// This is a getter for the version which this hop upgrades to.
+    pub(crate) fn to(&self) -> &Version {
+        &self.to
+    }
+}
+
+impl fmt::Display for UpgradeHop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} -> {}", self.from, self.to)?;
+        if let Some(reason) = &self.reason {
+            write!(f, " ({reason})")?;
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<&[u8]> for CompatibilityMatrix {
+    type Error = serde_yaml::Error;
+
+    /// Returns a CompatibilityMatrix object.
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        serde_yaml::from_reader(bytes)
+    }
+}
+
+impl CompatibilityMatrix {
+    /// Returns the compatibility matrix which is built into the plugin.
+    pub(crate) fn embedded() -> error::Result<Self> {
+        let unsupported_version_buf =
+            &std::include_bytes!("../../config/unsupported_versions.yaml")[..];
+        Self::try_from(unsupported_version_buf).context(error::YamlParseBufferForUnsupportedVersion)
+    }
+
+    /// Returns true if upgrades from the version are not supported.
+    pub fn is_unsupported(&self, version: &Version) -> bool {
+        self.unsupported_versions.contains(version)
+    }
+
+    /// Returns the list of versions which upgrades are not supported from.
+    pub(crate) fn unsupported_versions(&self) -> &[Version] {
+        self.unsupported_versions.as_slice()
+    }
+
+    /// Returns the constraint on the direct upgrade from the source version to the target version.
+    /// The first rule which matches the upgrade applies. The intermediate version of a rule must
+    /// lie in between the source and the target versions, else the upgrade is not supported.
+    pub fn direct_upgrade(&self, source: &Version, target: &Version) -> DirectUpgrade<'_> {
+        let Some(rule) = self
+            .compatibility
+            .iter()
+            .find(|rule| rule.matches(source, target))
+        else {
+            return DirectUpgrade::Supported;
+        };
+
+        match &rule.via {
+            Some(via) if via > source && via < target => DirectUpgrade::Via {
+                version: via,
+                reason: rule.reason.as_str(),
+            },
+            _ => DirectUpgrade::Unsupported {
+                reason: rule.reason.as_str(),
+            },
+        }
+    }
+
+    /// Returns the upgrades which take the source version to the target version. There are more
+    /// than one of these if the upgrade must go through intermediate versions.
+    pub(crate) fn upgrade_path(
+        &self,
+        source: &Version,
+        target: &Version,
+    ) -> error::Result<Vec<UpgradeHop>> {
+        if self.is_unsupported(source) {
+            return error::UnsupportedUpgradePath {
+                source_version: source.to_string(),
+                target_version: target.to_string(),
+                reason: format!("{source} is in the list of unsupported versions"),
+            }
+            .fail();
+        }
+
+        match self.direct_upgrade(source, target) {
+            DirectUpgrade::Supported => Ok(vec![UpgradeHop {
+                from: source.clone(),
+                to: target.clone(),
+                reason: None,
+            }]),
+            // The intermediate version lies in between, so that the path is finite.
+            DirectUpgrade::Via { version, reason } => {
+                let mut path = self.upgrade_path(source, version)?;
+                if let Some(hop) = path.last_mut() {
+                    hop.reason.get_or_insert_with(|| reason.to_string());
+                }
+                path.extend(self.upgrade_path(version, target)?);
+                Ok(path)
+            }
+            DirectUpgrade::Unsupported { reason } => error::UnsupportedUpgradePath {
+                source_version: source.to_string(),
+                target_version: target.to_string(),
+                reason: reason.to_string(),
+            }
+            .fail(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CompatibilityMatrix, DirectUpgrade};
+    use semver::Version;
+
+    fn version(version: &str) -> Version {
+        Version::parse(version).unwrap()
+    }
+
+    #[test]
+    fn multi_hop_upgrade_path() {
+        let matrix = CompatibilityMatrix::try_from(
+            r#"
+unsupported_versions:
+  - 1.0.0
+compatibility:
+  - from: ">=2.0.0, <2.4.0"
+    to: ">=2.5.0"
+    via: 2.4.0
+    reason: "the etcd data layout changed in 2.4.0"
+  - from: ">=2.4.0, <2.7.0"
+    to: ">=2.8.0"
+    via: 2.7.1
+    reason: "pools must be upgraded by 2.7.1"
+  - from: "~2.2"
+    to: ">=2.3.0, <2.4.0"
+    reason: "2.3 cannot import 2.2 pools"
+"#
+            .as_bytes(),
+        )
+        .unwrap();
+
+        let path = matrix
+            .upgrade_path(&version("2.1.0"), &version("2.9.0"))
+            .unwrap();
+        let hops: Vec<String> = path.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            hops,
+            vec![
+                "2.1.0 -> 2.4.0 (the etcd data layout changed in 2.4.0)",
+                "2.4.0 -> 2.7.1 (pools must be upgraded by 2.7.1)",
+                "2.7.1 -> 2.9.0",
+            ]
+        );
+
+        let path = matrix
+            .upgrade_path(&version("2.4.0"), &version("2.6.0"))
+            .unwrap();
+        assert_eq!(path.len(), 1);
+        assert_eq!(path[0].to(), &version("2.6.0"));
+
+        assert!(matrix
+            .upgrade_path(&version("2.2.0"), &version("2.3.0"))
+            .is_err());
+        assert!(matrix
+            .upgrade_path(&version("1.0.0"), &version("2.0.0"))
+            .is_err());
+    }
+
+    #[test]
+    fn direct_upgrade_constraints() {
+        let matrix = CompatibilityMatrix::try_from(
+            r#"
+compatibility:
+  - from: ">=2.0.0, <2.4.0"
+    to: ">=2.7.0"
+    via: 2.4.0
+    reason: "the etcd data layout changed in 2.4.0"
+  - from: "~2.5"
+    to: ">=2.8.0"
+    reason: "2.5 pools cannot be imported by 2.8"
+  - from: "~2.6"
+    via: 2.6.0
+    reason: "the intermediate version is not in between"
+"#
+            .as_bytes(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            matrix.direct_upgrade(&version("2.2.0"), &version("2.4.0")),
+            DirectUpgrade::Supported
+        );
+        assert_eq!(
+            matrix.direct_upgrade(&version("2.2.0"), &version("2.7.0")),
+            DirectUpgrade::Via {
+                version: &version("2.4.0"),
+                reason: "the etcd data layout changed in 2.4.0",
+            }
+        );
+        assert_eq!(
+            matrix.direct_upgrade(&version("2.5.1"), &version("2.8.0")),
+            DirectUpgrade::Unsupported {
+                reason: "2.5 pools cannot be imported by 2.8",
+            }
+        );
+        assert_eq!(
+            matrix.direct_upgrade(&version("2.6.1"), &version("2.9.0")),
+            DirectUpgrade::Unsupported {
+                reason: "the intermediate version is not in between",
+            }
+        );
+    }
+
+    #[test]
+    fn embedded_compatibility_matrix_is_valid() {
+        assert!(CompatibilityMatrix::embedded().is_ok());
+    }
+}
@@ -0,0 +1,322 @@
+use crate::plugin::{
+    constants::UPGRADE_PLAN_MAX_POLL_ATTEMPTS,
+    error,
+    user_prompt::{
+        DATA_PLANE_PODS_LIST, DATA_PLANE_PODS_LIST_SKIP_RESTART, UPGRADE_PLAN_ADDED_OBJECTS,
+        UPGRADE_PLAN_CRD_CHANGES, UPGRADE_PLAN_ESTIMATED_REBUILDS, UPGRADE_PLAN_IMAGE_CHANGES,
+        UPGRADE_PLAN_REMOVED_OBJECTS, UPGRADE_PLAN_VALUES_CHANGES,
+    },
+};
+use constants::{upgrade_plan_configmap_name, UPGRADE_PLAN_DATA_KEY, UPGRADE_PLAN_ERROR_KEY};
+use k8s_openapi::api::core::v1::ConfigMap;
+use kube::{
+    api::{Api, DeleteParams},
+    Client,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use snafu::ResultExt;
+use std::time::Duration;
+
+/// This struct is used to deserialize the upgrade plan computed by the upgrade-job, and to print
+/// it.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct UpgradePlan {
+    source_version: String,
+    target_version: String,
+    release_diff: ReleaseDiff,
+    data_plane: DataPlanePlan,
+}
+
+/// The changes to the helm release.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReleaseDiff {
+    values: Vec<ValueChange>,
+    manifest: ManifestDiff,
+}
+
+/// A change to a helm value.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ValueChange {
+    key: String,
+    from: Option<Value>,
+    to: Option<Value>,
+}
+
+/// The changes to the Kubernetes objects of the helm release.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ManifestDiff {
+    image_changes: Vec<ImageChange>,
+    added_objects: Vec<ManifestObject>,
+    removed_objects: Vec<ManifestObject>,
+    crd_changes: Vec<CrdChange>,
+}
+
+/// A Kubernetes object of the helm release.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct ManifestObject {
+    kind: String,
+    name: String,
+}
+
+/// A change to the image of a container of a workload.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ImageChange {
+    kind: String,
+    name: String,
+    container: String,
+    from: Option<String>,
+    to: Option<String>,
+}
+
+/// A change to a CustomResourceDefinition.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct CrdChange {
+    name: String,
+    change: String,
+}
+
+/// The io-engine Pod restarts of the data-plane upgrade.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DataPlanePlan {
+    restart_skipped: bool,
+    nodes: Vec<NodeRestart>,
+    estimated_rebuilds: usize,
+}
+
+/// The restart of the io-engine Pod on a node.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NodeRestart {
+    node: String,
+    pod: String,
+    volumes: Vec<String>,
+    estimated_rebuilds: usize,
+}
+
+impl UpgradePlan {
+    /// Print the upgrade plan to console.
+    pub(crate) fn print(&self) {
+        println!("Upgrade From: {}", self.source_version);
+        println!("Upgrade To: {}", self.target_version);
+
+        let values = self.release_diff.values.iter().map(|change| {
+            format!(
+                "{}: {} -> {}",
+                change.key,
+                value_or_absent(change.from.as_ref()),
+                value_or_absent(change.to.as_ref())
+            )
+        });
+        print_section(UPGRADE_PLAN_VALUES_CHANGES, values);
+
+        let manifest = &self.release_diff.manifest;
+        let images = manifest.image_changes.iter().map(|change| {
+            format!(
+                "{}/{} container {}: {} -> {}",
+                change.kind,
+                change.name,
+                change.container,
+                change.from.as_deref().unwrap_or("<absent>"),
+                change.to.as_deref().unwrap_or("<absent>")
+            )
+        });
+        print_section(UPGRADE_PLAN_IMAGE_CHANGES, images);
+
+        let added = manifest
+            .added_objects
+            .iter()
+            .map(|object| format!("{}/{}", object.kind, object.name));
+        print_section(UPGRADE_PLAN_ADDED_OBJECTS, added);
+
+        let removed = manifest
+            .removed_objects
+            .iter()
+            .map(|object| format!("{}/{}", object.kind, object.name));
+        print_section(UPGRADE_PLAN_REMOVED_OBJECTS, removed);
+
+        let crds = manifest
+            .crd_changes
+            .iter()
+            .map(|crd| format!("{} ({})", crd.name, crd.change));
+        print_section(UPGRADE_PLAN_CRD_CHANGES, crds);
+
+        let nodes = self.data_plane.nodes.iter().enumerate().map(|(i, node)| {
+            format!(
+                "{}. {} on node {}, volumes: [{}], estimated rebuilds: {}",
+                i + 1,
+                node.pod,
+                node.node,
+                node.volumes.join(", "),
+                node.estimated_rebuilds
+            )
+        });
+        if self.data_plane.restart_skipped {
+            print_section(DATA_PLANE_PODS_LIST_SKIP_RESTART, nodes);
+        } else {
+            print_section(DATA_PLANE_PODS_LIST, nodes);
+            console_logger::info(
+                UPGRADE_PLAN_ESTIMATED_REBUILDS,
+                self.data_plane.estimated_rebuilds.to_string().as_str(),
+            );
+        }
+    }
+}
+
+/// Prints a section of the upgrade plan, one line per item.
+fn print_section(heading: &str, lines: impl Iterator<Item = String>) {
+    let lines: Vec<String> = lines.collect();
+    if lines.is_empty() {
+        console_logger::info(heading, "None");
+    } else {
+        console_logger::info(heading, lines.join("\n ").as_str());
+    }
+}
+
+/// Formats a helm value for the console, or a placeholder if it is absent.
+fn value_or_absent(value: Option<&Value>) -> String {
+    value.map_or("<absent>".to_string(), Value::to_string)
+}
+
+/// This is used to read the upgrade plan ConfigMap, which the upgrade-job saves the upgrade plan
+/// to.
+pub(crate) struct UpgradePlanClient {
+    config_map: Api<ConfigMap>,
+    name: String,
+}
+
+impl UpgradePlanClient {
+    /// Returns an UpgradePlanClient for the helm release in the namespace.
+    pub(crate) async fn new(namespace: &str, release_name: &str) -> error::Result<Self> {
+        let client = Client::try_default().await.context(error::K8sClient)?;
+        Ok(Self {
+            config_map: Api::namespaced(client, namespace),
+            name: upgrade_plan_configmap_name(release_name),
+        })
+    }
+
+    /// Delete the upgrade plan of an earlier dry-run, if any.
+    pub(crate) async fn delete(&self) -> error::Result<()> {
+        let cm =
+            self.config_map
+                .get_opt(self.name.as_str())
+                .await
+                .context(error::GetUpgradePlan {
+                    name: self.name.clone(),
+                })?;
+        if cm.is_some() {
+            self.config_map
+                .delete(self.name.as_str(), &DeleteParams::default())
+                .await
+                .context(error::UpgradePlanDelete {
+                    name: self.name.clone(),
+                })?;
+        }
+        Ok(())
+    }
+
+    /// Polls the upgrade plan ConfigMap until the upgrade-job saves the upgrade plan, or the error
+    /// which failed it.
+    pub(crate) async fn wait_for_plan(&self) -> error::Result<UpgradePlan> {
+        for _ in 0 .. UPGRADE_PLAN_MAX_POLL_ATTEMPTS {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+
+            let Some(mut data) = self
+                .config_map
+                .get_opt(self.name.as_str())
+                .await
+                .context(error::GetUpgradePlan {
+                    name: self.name.clone(),
+                })?
+                .and_then(|cm| cm.data)
+            else {
+                continue;
+            };
+
+            if let Some(message) = data.remove(UPGRADE_PLAN_ERROR_KEY) {
+                return error::UpgradePlanFailed { message }.fail();
+            }
+            if let Some(plan) = data.remove(UPGRADE_PLAN_DATA_KEY) {
+                return serde_json::from_str(plan.as_str()).context(
+                    error::UpgradePlanDeserialization {
+                        name: self.name.clone(),
+                    },
+                );
+            }
+        }
+
+        error::UpgradePlanTimedOut {
+            name: self.name.clone(),
+        }
+        .fail()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{value_or_absent, UpgradePlan};
+    use serde_json::json;
+
+    #[test]
+    fn upgrade_plan_from_upgrade_job() {
+        // This is the upgrade plan as the upgrade-job serializes it.
+        let plan: UpgradePlan = serde_json::from_value(json!({
+            "sourceVersion": "2.6.1",
+            "targetVersion": "2.7.0",
+            "releaseDiff": {
+                "values": [
+                    { "key": "io_engine.logLevel", "from": "info", "to": "debug" },
+                    { "key": "base.metrics.cluster.enabled", "from": null, "to": false }
+                ],
+                "manifest": {
+                    "imageChanges": [{
+                        "kind": "DaemonSet",
+                        "name": "mayastor-io-engine",
+                        "container": "io-engine",
+                        "from": "io-engine:v2.6.1",
+                        "to": "io-engine:v2.7.0"
+                    }],
+                    "addedObjects": [{ "kind": "Service", "name": "mayastor-metrics" }],
+                    "removedObjects": [],
+                    "crdChanges": [{ "name": "diskpools.openebs.io", "change": "modified" }]
+                }
+            },
+            "dataPlane": {
+                "restartSkipped": false,
+                "nodes": [{
+                    "node": "node-1",
+                    "pod": "mayastor-io-engine-abcde",
+                    "volumes": ["ec4e66fd-3b33-4439-b504-d49aba53da26"],
+                    "estimatedRebuilds": 1
+                }],
+                "estimatedRebuilds": 1
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(plan.source_version, "2.6.1");
+        assert_eq!(plan.target_version, "2.7.0");
+        assert_eq!(plan.release_diff.values.len(), 2);
+        assert_eq!(
+            plan.release_diff.manifest.image_changes[0].to.as_deref(),
+            Some("io-engine:v2.7.0")
+        );
+        assert_eq!(plan.release_diff.manifest.added_objects[0].kind, "Service");
+        assert_eq!(plan.release_diff.manifest.crd_changes[0].change, "modified");
+        assert_eq!(plan.data_plane.nodes[0].estimated_rebuilds, 1);
+        assert_eq!(plan.data_plane.estimated_rebuilds, 1);
+    }
+
+    #[test]
+    fn absent_values() {
+        assert_eq!(value_or_absent(None), "<absent>");
+        assert_eq!(value_or_absent(Some(&json!("debug"))), "\"debug\"");
+        assert_eq!(value_or_absent(Some(&json!(false))), "false");
+    }
+}
@@ -1,31 +1,57 @@
 use crate::{
     plugin::{
-        constants::{get_image_version_tag, SINGLE_REPLICA_VOLUME, UPGRADE_TO_DEVELOP_BRANCH},
-        error,
-        upgrade::{get_pvc_from_uuid, get_source_version},
+        constants::{
+            get_image_version_tag, upgrade_name_concat, HELM_RELEASE_PENDING_STATUSES,
+            IMAGE_PULL_FAILURE_REASONS, IO_ENGINE_POD_LABEL, POOL_CAPACITY_HEADROOM_PERCENT,
+            REGISTRY_CHECK_MAX_POLL_ATTEMPTS, REGISTRY_CHECK_POD_NAME_SUFFIX,
+            SINGLE_REPLICA_VOLUME, UPGRADE_TO_DEVELOP_BRANCH,
+        },
+        error, objects,
+        path::CompatibilityMatrix,
+        upgrade::{
+            get_pvc_from_uuid, get_release_name, get_source_version, print_structured,
+            upgrade_job_image,
+        },
         user_prompt,
     },
     upgrade::UpgradeArgs,
 };
+use k8s_openapi::api::{
+    core::v1::{ConfigMap, Node, Pod, Secret},
+    policy::v1::PodDisruptionBudget,
+};
+use kube::{
+    api::{Api, DeleteParams, ListParams, PostParams},
+    Client, ResourceExt,
+};
 use openapi::{
     clients::tower::{self, Configuration},
     models::CordonDrainState,
 };
+use rest_plugin::resources::utils::OutputFormat;
 use semver::Version;
-use serde::Deserialize;
-use serde_yaml;
+use serde::Serialize;
 use snafu::ResultExt;
-use std::{collections::HashSet, ops::Deref, path::PathBuf};
+use std::{
+    collections::{BTreeMap, HashSet},
+    ops::Deref,
+    path::PathBuf,
+    time::Duration,
+};
 use utils::version_info;
 
-/// Validation to be done before applying upgrade.
+/// Validation to be done before applying upgrade. Every preflight check is run, and a report with
+/// the results is printed. This fails if any of the checks fail.
 pub async fn preflight_check(
     namespace: &str,
     kube_config_path: Option<PathBuf>,
     timeout: humantime::Duration,
     resources: &UpgradeArgs,
+    output: &OutputFormat,
 ) -> error::Result<()> {
-    console_logger::info(user_prompt::UPGRADE_WARNING, "");
+    if matches!(output, OutputFormat::None) {
+        console_logger::info(user_prompt::UPGRADE_WARNING, "");
+    }
     // Initialise the REST client.
     let config = kube_proxy::ConfigBuilder::default_api_rest()
         .with_kube_config(kube_config_path.clone())
@@ -36,26 +62,240 @@ pub async fn preflight_check(
         .context(error::OpenapiClientConfiguration)?;
     let rest_client = RestClient::new_with_config(config);
 
-    if !resources.skip_upgrade_path_validation_for_unsupported_version {
-        upgrade_path_validation(namespace, resources.allow_unstable).await?;
+    let context = PreflightContext {
+        namespace,
+        rest_client: &rest_client,
+        args: resources,
+    };
+
+    let mut report = PreflightReport::default();
+    for check in PreflightCheck::registry(resources) {
+        report.checks.push(check.run(&context).await);
+    }
+    report.print(output)?;
+
+    let failed = report.failed();
+    if failed > 0 {
+        if matches!(output, OutputFormat::None) {
+            console_logger::error(user_prompt::PREFLIGHT_CHECKS_FAILED, "");
+        }
+        return error::PreflightChecksFailed { failed }.fail();
+    }
+    Ok(())
+}
+
+/// The outcome of a preflight check.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    /// The check passed.
+    Pass,
+    /// The check found something which may affect the upgrade, but does not block it.
+    Warn,
+    /// The check found something which blocks the upgrade.
+    Fail,
+}
+
+impl std::fmt::Display for CheckStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Pass => write!(f, "PASS"),
+            Self::Warn => write!(f, "WARN"),
+            Self::Fail => write!(f, "FAIL"),
+        }
     }
+}
+
+/// The result of a single preflight check.
+#[derive(Clone, Debug, Serialize)]
+pub struct CheckResult {
+    name: &'static str,
+    status: CheckStatus,
+    message: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    details: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    remediation: Option<String>,
+}
 
-    if !resources.skip_replica_rebuild {
-        rebuild_in_progress_validation(&rest_client).await?;
+impl CheckResult {
+    /// Creates a passing result.
+    fn pass(name: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Pass,
+            message: message.into(),
+            details: Vec::new(),
+            remediation: None,
+        }
     }
 
-    if !resources.skip_cordoned_node_validation {
-        already_cordoned_nodes_validation(&rest_client).await?;
+    /// Creates a result which is not a pass, with the list of offending objects and the text
+    /// which describes how to resolve the issue.
+    fn not_pass(
+        name: &'static str,
+        status: CheckStatus,
+        message: impl Into<String>,
+        details: Vec<String>,
+        remediation: &str,
+    ) -> Self {
+        Self {
+            name,
+            status,
+            message: message.into(),
+            details,
+            remediation: Some(remediation.to_string()),
+        }
     }
 
-    if !resources.skip_single_replica_volume_validation {
-        single_volume_replica_validation(&rest_client).await?;
+    /// This is a getter for the check status.
+    pub fn status(&self) -> CheckStatus {
+        self.status
     }
-    Ok(())
 }
 
-/// Prompt to user and error out if some nodes are already in cordoned state.
-pub(crate) async fn already_cordoned_nodes_validation(client: &RestClient) -> error::Result<()> {
+/// The results of all of the preflight checks.
+#[derive(Debug, Default, Serialize)]
+pub struct PreflightReport {
+    checks: Vec<CheckResult>,
+}
+
+impl PreflightReport {
+    /// Returns the number of checks which failed.
+    pub fn failed(&self) -> usize {
+        self.checks
+            .iter()
+            .filter(|check| check.status() == CheckStatus::Fail)
+            .count()
+    }
+
+    /// Prints the report as a table, or as YAML or JSON.
+    pub fn print(&self, output: &OutputFormat) -> error::Result<()> {
+        if !matches!(output, OutputFormat::None) {
+            return print_structured(self, output);
+        }
+
+        println!("\n{:<32} {:<8} MESSAGE", "CHECK", "STATUS");
+        for check in &self.checks {
+            println!("{:<32} {:<8} {}", check.name, check.status, check.message);
+        }
+
+        for check in self
+            .checks
+            .iter()
+            .filter(|check| check.status != CheckStatus::Pass)
+        {
+            println!("\n[{}] {}", check.status, check.name);
+            for detail in &check.details {
+                println!("  {detail}");
+            }
+            if let Some(remediation) = &check.remediation {
+                println!("{remediation}");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The state which is shared by the preflight checks.
+struct PreflightContext<'a> {
+    namespace: &'a str,
+    rest_client: &'a RestClient,
+    args: &'a UpgradeArgs,
+}
+
+/// The registry of preflight checks.
+#[derive(Clone, Copy, Debug)]
+enum PreflightCheck {
+    UpgradePath,
+    RebuildInProgress,
+    CordonedNodes,
+    SingleReplicaVolumes,
+    PoolCapacityHeadroom,
+    NodesNotReady,
+    PodDisruptionBudgets,
+    HelmReleasePending,
+    ImageRegistryReachable,
+}
+
+impl PreflightCheck {
+    /// Returns the checks to run, in order, leaving out the ones which have been skipped.
+    fn registry(args: &UpgradeArgs) -> Vec<Self> {
+        let mut checks = Vec::new();
+        if !args.skip_upgrade_path_validation_for_unsupported_version {
+            checks.push(Self::UpgradePath);
+        }
+        if !args.skip_replica_rebuild {
+            checks.push(Self::RebuildInProgress);
+        }
+        if !args.skip_cordoned_node_validation {
+            checks.push(Self::CordonedNodes);
+        }
+        if !args.skip_single_replica_volume_validation {
+            checks.push(Self::SingleReplicaVolumes);
+        }
+        checks.extend([
+            Self::PoolCapacityHeadroom,
+            Self::NodesNotReady,
+            Self::PodDisruptionBudgets,
+            Self::HelmReleasePending,
+            Self::ImageRegistryReachable,
+        ]);
+        checks
+    }
+
+    /// The name of the check, as it is printed in the report.
+    fn name(&self) -> &'static str {
+        match self {
+            Self::UpgradePath => "upgrade-path",
+            Self::RebuildInProgress => "replica-rebuild-in-progress",
+            Self::CordonedNodes => "cordoned-nodes",
+            Self::SingleReplicaVolumes => "single-replica-volumes",
+            Self::PoolCapacityHeadroom => "pool-capacity-headroom",
+            Self::NodesNotReady => "nodes-not-ready",
+            Self::PodDisruptionBudgets => "pod-disruption-budgets",
+            Self::HelmReleasePending => "helm-release-pending",
+            Self::ImageRegistryReachable => "image-registry-reachable",
+        }
+    }
+
+    /// Runs the check. An error while running the check is reported as a failed check, so that
+    /// the remaining checks still run.
+    async fn run(&self, ctx: &PreflightContext<'_>) -> CheckResult {
+        let name = self.name();
+        let result = match self {
+            Self::UpgradePath => upgrade_path_validation(name, ctx.namespace, ctx.args).await,
+            Self::RebuildInProgress => rebuild_in_progress_validation(name, ctx.rest_client).await,
+            Self::CordonedNodes => already_cordoned_nodes_validation(name, ctx.rest_client).await,
+            Self::SingleReplicaVolumes => {
+                single_volume_replica_validation(name, ctx.rest_client).await
+            }
+            Self::PoolCapacityHeadroom => pool_capacity_validation(name, ctx.rest_client).await,
+            Self::NodesNotReady => nodes_not_ready_validation(name, ctx.namespace).await,
+            Self::PodDisruptionBudgets => pdb_conflict_validation(name).await,
+            Self::HelmReleasePending => helm_release_pending_validation(name, ctx.namespace).await,
+            Self::ImageRegistryReachable => {
+                image_registry_validation(name, ctx.namespace, ctx.args).await
+            }
+        };
+
+        result.unwrap_or_else(|error| {
+            CheckResult::not_pass(
+                name,
+                CheckStatus::Fail,
+                error.to_string(),
+                Vec::new(),
+                user_prompt::PREFLIGHT_CHECK_ERROR,
+            )
+        })
+    }
+}
+
+/// Fail if some nodes are already in cordoned state.
+pub(crate) async fn already_cordoned_nodes_validation(
+    name: &'static str,
+    client: &RestClient,
+) -> error::Result<CheckResult> {
     let mut cordoned_nodes_list = Vec::new();
     let nodes = client
         .nodes_api()
@@ -78,19 +318,28 @@ pub(crate) async fn already_cordoned_nodes_validation(client: &RestClient) -> er
             cordoned_nodes_list.push(node.id);
         }
     }
-    if !cordoned_nodes_list.is_empty() {
-        console_logger::error(
+    Ok(cordoned_nodes_result(name, cordoned_nodes_list))
+}
+
+/// Evaluates the cordoned nodes check from the list of the cordoned storage nodes.
+fn cordoned_nodes_result(name: &'static str, cordoned_nodes: Vec<String>) -> CheckResult {
+    if !cordoned_nodes.is_empty() {
+        return CheckResult::not_pass(
+            name,
+            CheckStatus::Fail,
+            "One or more nodes are in a Mayastor cordoned state",
+            cordoned_nodes,
             user_prompt::CORDONED_NODE_WARNING,
-            &cordoned_nodes_list.join("\n"),
         );
-        return error::NodesInCordonedState.fail();
     }
-    Ok(())
+    CheckResult::pass(name, "No cordoned nodes")
 }
 
-/// Prompt to user and error out if the cluster has single replica volume.
-pub(crate) async fn single_volume_replica_validation(client: &RestClient) -> error::Result<()> {
-    // let mut single_replica_volumes = Vec::new();
+/// Fail if the cluster has single replica volume.
+pub(crate) async fn single_volume_replica_validation(
+    name: &'static str,
+    client: &RestClient,
+) -> error::Result<CheckResult> {
     // The number of volumes to get per request.
     let max_entries = 200;
     let mut starting_token = Some(0_isize);
@@ -115,24 +364,48 @@ pub(crate) async fn single_volume_replica_validation(client: &RestClient) -> err
         starting_token = v.next_token;
     }
 
-    if !volumes.is_empty() {
-        let data = get_pvc_from_uuid(HashSet::from_iter(volumes))
-            .await?
-            .join("\n");
+    let pvcs = match volumes.is_empty() {
+        true => Vec::new(),
+        false => get_pvc_from_uuid(HashSet::from_iter(volumes)).await?,
+    };
+    Ok(single_replica_volumes_result(name, pvcs))
+}
 
-        console_logger::error(user_prompt::SINGLE_REPLICA_VOLUME_WARNING, &data);
-        return error::SingleReplicaVolumeErr.fail();
+/// Evaluates the single replica volumes check from the PVCs of the single replica volumes.
+fn single_replica_volumes_result(name: &'static str, pvcs: Vec<String>) -> CheckResult {
+    if !pvcs.is_empty() {
+        return CheckResult::not_pass(
+            name,
+            CheckStatus::Fail,
+            "Single replica volumes are present in the cluster",
+            pvcs,
+            user_prompt::SINGLE_REPLICA_VOLUME_WARNING,
+        );
     }
-    Ok(())
+    CheckResult::pass(name, "No single replica volumes")
+}
+
+/// Fail if any rebuild in progress.
+pub(crate) async fn rebuild_in_progress_validation(
+    name: &'static str,
+    client: &RestClient,
+) -> error::Result<CheckResult> {
+    let in_progress = is_rebuild_in_progress(client).await?;
+    Ok(rebuild_in_progress_result(name, in_progress))
 }
 
-/// Prompt to user and error out if any rebuild in progress.
-pub(crate) async fn rebuild_in_progress_validation(client: &RestClient) -> error::Result<()> {
-    if is_rebuild_in_progress(client).await? {
-        console_logger::error(user_prompt::REBUILD_WARNING, "");
-        return error::VolumeRebuildInProgress.fail();
+/// Evaluates the replica rebuild check.
+fn rebuild_in_progress_result(name: &'static str, in_progress: bool) -> CheckResult {
+    if in_progress {
+        return CheckResult::not_pass(
+            name,
+            CheckStatus::Fail,
+            "Volume replica rebuilds are in progress",
+            Vec::new(),
+            user_prompt::REBUILD_WARNING,
+        );
     }
-    Ok(())
+    CheckResult::pass(name, "No volume replica rebuilds in progress")
 }
 
 /// Check for rebuild in progress.
@@ -165,25 +438,370 @@ pub(crate) async fn is_rebuild_in_progress(client: &RestClient) -> error::Result
     Ok(false)
 }
 
-/// Struct to deserialize the unsupported version yaml.
-#[derive(Deserialize)]
-struct UnsupportedVersions {
-    unsupported_versions: Vec<Version>,
+/// Warn if any DiskPool has less free capacity than POOL_CAPACITY_HEADROOM_PERCENT.
+pub(crate) async fn pool_capacity_validation(
+    name: &'static str,
+    client: &RestClient,
+) -> error::Result<CheckResult> {
+    let pools = client
+        .pools_api()
+        .get_pools(None)
+        .await
+        .context(error::ListStoragePools)?
+        .into_body();
+
+    let low_capacity_pools: Vec<String> = pools
+        .into_iter()
+        .filter_map(|pool| pool.state)
+        .filter(|state| lacks_capacity_headroom(state.capacity, state.used))
+        .map(|state| {
+            format!(
+                "{} (node: {}, free: {} of {} bytes)",
+                state.id,
+                state.node,
+                state.capacity.saturating_sub(state.used),
+                state.capacity
+            )
+        })
+        .collect();
+
+    Ok(pool_capacity_result(name, low_capacity_pools))
+}
+
+/// Returns true if a DiskPool has less free capacity than POOL_CAPACITY_HEADROOM_PERCENT.
+fn lacks_capacity_headroom(capacity: u64, used: u64) -> bool {
+    capacity > 0 && capacity.saturating_sub(used) * 100 < capacity * POOL_CAPACITY_HEADROOM_PERCENT
+}
+
+/// Evaluates the pool capacity check from the list of the DiskPools which lack headroom.
+fn pool_capacity_result(name: &'static str, low_capacity_pools: Vec<String>) -> CheckResult {
+    if !low_capacity_pools.is_empty() {
+        return CheckResult::not_pass(
+            name,
+            CheckStatus::Warn,
+            format!("DiskPools with less than {POOL_CAPACITY_HEADROOM_PERCENT}% free capacity"),
+            low_capacity_pools,
+            user_prompt::POOL_CAPACITY_HEADROOM_WARNING,
+        );
+    }
+    CheckResult::pass(
+        name,
+        format!("All DiskPools have at least {POOL_CAPACITY_HEADROOM_PERCENT}% free capacity"),
+    )
+}
+
+/// Fail if any Kubernetes Node with an io-engine Pod is NotReady, and warn for the other
+/// NotReady Nodes.
+pub(crate) async fn nodes_not_ready_validation(
+    name: &'static str,
+    namespace: &str,
+) -> error::Result<CheckResult> {
+    let client = Client::try_default().await.context(error::K8sClient)?;
+    let nodes = Api::<Node>::all(client.clone())
+        .list(&ListParams::default())
+        .await
+        .context(error::ListK8sNodes)?;
+
+    let io_engine_nodes: HashSet<String> = Api::<Pod>::namespaced(client, namespace)
+        .list(&ListParams::default().labels(IO_ENGINE_POD_LABEL))
+        .await
+        .context(error::ListPodsWithLabel {
+            label: IO_ENGINE_POD_LABEL.to_string(),
+            namespace: namespace.to_string(),
+        })?
+        .into_iter()
+        .filter_map(|pod| pod.spec.and_then(|spec| spec.node_name))
+        .collect();
+
+    Ok(nodes_not_ready_result(name, nodes.items, &io_engine_nodes))
+}
+
+/// Evaluates the NotReady Nodes check from the Kubernetes Nodes, and the names of the Nodes with
+/// an io-engine Pod.
+fn nodes_not_ready_result(
+    name: &'static str,
+    nodes: Vec<Node>,
+    io_engine_nodes: &HashSet<String>,
+) -> CheckResult {
+    let not_ready_nodes: Vec<String> = nodes
+        .into_iter()
+        .filter(|node| {
+            !node
+                .status
+                .as_ref()
+                .and_then(|status| status.conditions.as_ref())
+                .is_some_and(|conditions| {
+                    conditions
+                        .iter()
+                        .any(|condition| condition.type_ == "Ready" && condition.status == "True")
+                })
+        })
+        .map(|node| node.name_any())
+        .collect();
+
+    if not_ready_nodes.is_empty() {
+        return CheckResult::pass(name, "All Kubernetes Nodes are Ready");
+    }
+
+    let status = match not_ready_nodes
+        .iter()
+        .any(|node| io_engine_nodes.contains(node))
+    {
+        true => CheckStatus::Fail,
+        false => CheckStatus::Warn,
+    };
+    CheckResult::not_pass(
+        name,
+        status,
+        "One or more Kubernetes Nodes are NotReady",
+        not_ready_nodes,
+        user_prompt::NODES_NOT_READY_WARNING,
+    )
+}
+
+/// Warn if any PodDisruptionBudget allows no disruptions.
+pub(crate) async fn pdb_conflict_validation(name: &'static str) -> error::Result<CheckResult> {
+    let client = Client::try_default().await.context(error::K8sClient)?;
+    let pdbs = Api::<PodDisruptionBudget>::all(client)
+        .list(&ListParams::default())
+        .await
+        .context(error::ListPodDisruptionBudgets)?;
+
+    Ok(pdb_conflict_result(name, pdbs.items))
+}
+
+/// Evaluates the PodDisruptionBudget check from the PodDisruptionBudgets of the cluster.
+fn pdb_conflict_result(name: &'static str, pdbs: Vec<PodDisruptionBudget>) -> CheckResult {
+    let blocking_pdbs: Vec<String> = pdbs
+        .into_iter()
+        .filter(|pdb| {
+            pdb.status
+                .as_ref()
+                .is_some_and(|status| status.expected_pods > 0 && status.disruptions_allowed == 0)
+        })
+        .map(|pdb| format!("{}/{}", pdb.namespace().unwrap_or_default(), pdb.name_any()))
+        .collect();
+
+    if !blocking_pdbs.is_empty() {
+        return CheckResult::not_pass(
+            name,
+            CheckStatus::Warn,
+            "PodDisruptionBudgets which allow no disruptions are present",
+            blocking_pdbs,
+            user_prompt::PDB_CONFLICT_WARNING,
+        );
+    }
+    CheckResult::pass(name, "No PodDisruptionBudgets block disruptions")
+}
+
+/// Fail if the latest revision of the helm release is in a pending state. Both the 'secret' and
+/// the 'configmap' helm storage drivers are checked.
+pub(crate) async fn helm_release_pending_validation(
+    name: &'static str,
+    namespace: &str,
+) -> error::Result<CheckResult> {
+    let release_name = get_release_name(namespace).await?;
+    let client = Client::try_default().await.context(error::K8sClient)?;
+    let label = format!("owner=helm,name={release_name}");
+    let lp = ListParams::default().labels(label.as_str());
+
+    let mut release_labels: Vec<BTreeMap<String, String>> =
+        Api::<Secret>::namespaced(client.clone(), namespace)
+            .list(&lp)
+            .await
+            .context(error::ListHelmReleaseStorage {
+                label: label.clone(),
+                namespace: namespace.to_string(),
+            })?
+            .into_iter()
+            .map(|secret| secret.labels().clone())
+            .collect();
+    release_labels.extend(
+        Api::<ConfigMap>::namespaced(client, namespace)
+            .list(&lp)
+            .await
+            .context(error::ListHelmReleaseStorage {
+                label,
+                namespace: namespace.to_string(),
+            })?
+            .into_iter()
+            .map(|cm| cm.labels().clone()),
+    );
+
+    Ok(helm_release_pending_result(
+        name,
+        release_name.as_str(),
+        &release_labels,
+    ))
+}
+
+/// Evaluates the pending helm release check from the labels of the helm release's storage
+/// objects.
+fn helm_release_pending_result(
+    name: &'static str,
+    release_name: &str,
+    release_labels: &[BTreeMap<String, String>],
+) -> CheckResult {
+    let latest_status = release_labels
+        .iter()
+        .max_by_key(|labels| {
+            labels
+                .get("version")
+                .and_then(|version| version.parse::<u64>().ok())
+                .unwrap_or_default()
+        })
+        .and_then(|labels| labels.get("status").cloned());
+
+    match latest_status {
+        Some(status) if HELM_RELEASE_PENDING_STATUSES.contains(&status.as_str()) => {
+            CheckResult::not_pass(
+                name,
+                CheckStatus::Fail,
+                format!("Helm release '{release_name}' is in the '{status}' state"),
+                Vec::new(),
+                user_prompt::HELM_RELEASE_PENDING_WARNING,
+            )
+        }
+        Some(status) => CheckResult::pass(
+            name,
+            format!("Helm release '{release_name}' is in the '{status}' state"),
+        ),
+        None => CheckResult::not_pass(
+            name,
+            CheckStatus::Warn,
+            format!("Could not find the storage objects of helm release '{release_name}'"),
+            Vec::new(),
+            user_prompt::HELM_RELEASE_PENDING_WARNING,
+        ),
+    }
+}
+
+/// Fail if the upgrade-job image cannot be pulled from the cluster. This is checked using a
+/// short-lived Pod which runs the upgrade-job image.
+pub(crate) async fn image_registry_validation(
+    name: &'static str,
+    namespace: &str,
+    args: &UpgradeArgs,
+) -> error::Result<CheckResult> {
+    let release_name = get_release_name(namespace).await?;
+    let (image, img) = upgrade_job_image(namespace, args).await?;
+    let client = Client::try_default().await.context(error::K8sClient)?;
+    let pods = Api::<Pod>::namespaced(client, namespace);
+    let pod_name = upgrade_name_concat(&release_name, REGISTRY_CHECK_POD_NAME_SUFFIX);
+
+    // Remove leftovers from an earlier check.
+    if pods
+        .get_opt(pod_name.as_str())
+        .await
+        .context(error::GetRegistryCheckPod {
+            name: pod_name.clone(),
+        })?
+        .is_some()
+    {
+        delete_registry_check_pod(&pods, pod_name.as_str()).await?;
+    }
+
+    let pod = objects::registry_check_pod(
+        namespace,
+        image.clone(),
+        &release_name,
+        img.pull_secrets(),
+        img.pull_policy(),
+    );
+    pods.create(&PostParams::default(), &pod)
+        .await
+        .context(error::RegistryCheckPodCreate {
+            name: pod_name.clone(),
+        })?;
+
+    let outcome = wait_for_image_pull(&pods, pod_name.as_str()).await;
+    delete_registry_check_pod(&pods, pod_name.as_str()).await?;
+
+    Ok(image_registry_result(name, image.as_str(), outcome?))
+}
+
+/// Evaluates the image registry check from the outcome of the image pull.
+fn image_registry_result(
+    name: &'static str,
+    image: &str,
+    outcome: ImagePullOutcome,
+) -> CheckResult {
+    match outcome {
+        ImagePullOutcome::Pulled => CheckResult::pass(
+            name,
+            format!("Image '{image}' can be pulled from the cluster"),
+        ),
+        ImagePullOutcome::Failed(reason) => CheckResult::not_pass(
+            name,
+            CheckStatus::Fail,
+            format!("Image '{image}' cannot be pulled from the cluster: {reason}"),
+            Vec::new(),
+            user_prompt::IMAGE_REGISTRY_UNREACHABLE_WARNING,
+        ),
+        ImagePullOutcome::Unknown => CheckResult::not_pass(
+            name,
+            CheckStatus::Warn,
+            format!("Timed out waiting for image '{image}' to be pulled from the cluster"),
+            Vec::new(),
+            user_prompt::IMAGE_REGISTRY_UNREACHABLE_WARNING,
+        ),
+    }
 }
 
-impl UnsupportedVersions {
-    fn contains(&self, v: &Version) -> bool {
-        self.unsupported_versions.contains(v)
+/// The outcome of the image pull of the image registry check Pod.
+#[derive(Debug, PartialEq)]
+enum ImagePullOutcome {
+    Pulled,
+    Failed(String),
+    Unknown,
+}
+
+/// Polls the image registry check Pod until its container has either started or failed to pull
+/// the image.
+async fn wait_for_image_pull(pods: &Api<Pod>, pod_name: &str) -> error::Result<ImagePullOutcome> {
+    for _ in 0 .. REGISTRY_CHECK_MAX_POLL_ATTEMPTS {
+        tokio::time::sleep(Duration::from_secs(5)).await;
+
+        let pod = pods
+            .get(pod_name)
+            .await
+            .context(error::GetRegistryCheckPod {
+                name: pod_name.to_string(),
+            })?;
+        if let Some(outcome) = image_pull_outcome(pod) {
+            return Ok(outcome);
+        }
     }
+    Ok(ImagePullOutcome::Unknown)
 }
 
-impl TryFrom<&[u8]> for UnsupportedVersions {
-    type Error = serde_yaml::Error;
+/// Returns the outcome of the image pull of the image registry check Pod, or None if the image
+/// is still being pulled.
+fn image_pull_outcome(pod: Pod) -> Option<ImagePullOutcome> {
+    let state = pod
+        .status
+        .and_then(|status| status.container_statuses)
+        .and_then(|statuses| statuses.into_iter().next())
+        .and_then(|status| status.state)?;
 
-    /// Returns an UnsupportedVersions object.
-    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
-        serde_yaml::from_reader(bytes)
+    if state.running.is_some() || state.terminated.is_some() {
+        return Some(ImagePullOutcome::Pulled);
     }
+    state
+        .waiting
+        .and_then(|waiting| waiting.reason)
+        .filter(|reason| IMAGE_PULL_FAILURE_REASONS.contains(&reason.as_str()))
+        .map(ImagePullOutcome::Failed)
+}
+
+/// Deletes the image registry check Pod.
+async fn delete_registry_check_pod(pods: &Api<Pod>, pod_name: &str) -> error::Result<()> {
+    pods.delete(pod_name, &DeleteParams::default())
+        .await
+        .context(error::RegistryCheckPodDelete {
+            name: pod_name.to_string(),
+        })?;
+    Ok(())
 }
 
 /// Strips the prefix 'v' from a semver-like literal, e.g.: v1.2.3 -> 1.2.3.
@@ -192,53 +810,79 @@ pub(crate) fn strip_v_prefix(version: &str) -> &str {
     version.strip_prefix('v').unwrap_or(version)
 }
 
+/// Returns the version to upgrade to. This is the '--to-version' helm chart version if it is set,
+/// or else this plugin's version.
+pub(crate) fn target_version(args: &UpgradeArgs) -> Option<Version> {
+    if args.to_version.is_some() {
+        return args.to_version.clone();
+    }
+
+    version_info!()
+        .version_tag
+        .filter(|tag| !tag.is_empty())
+        .and_then(|tag| Version::parse(strip_v_prefix(tag.as_str())).ok())
+}
+
+/// Fail if the upgrade from the installed version to this plugin's version is not supported.
 pub(crate) async fn upgrade_path_validation(
+    name: &'static str,
     namespace: &str,
-    allow_unstable: bool,
-) -> error::Result<()> {
-    let unsupported_version_buf =
-        &std::include_bytes!("../../config/unsupported_versions.yaml")[..];
-    let unsupported_versions = UnsupportedVersions::try_from(unsupported_version_buf)
-        .context(error::YamlParseBufferForUnsupportedVersion)?;
+    args: &UpgradeArgs,
+) -> error::Result<CheckResult> {
+    let compatibility_matrix = CompatibilityMatrix::embedded()?;
     let source_version = get_source_version(namespace).await?;
 
     let source = Version::parse(source_version.as_str()).context(error::SemverParse {
         version_string: source_version.clone(),
     })?;
+    // The version to upgrade to is this plugin's version, unless a helm chart version is set.
+    let to_develop_branch =
+        args.to_version.is_none() && get_image_version_tag().contains(UPGRADE_TO_DEVELOP_BRANCH);
 
-    if unsupported_versions.contains(&source) {
-        let mut invalid_source_list: String = Default::default();
-        for val in unsupported_versions.unsupported_versions.iter() {
-            invalid_source_list.push_str(val.to_string().as_str());
-            invalid_source_list.push('\n');
-        }
-        console_logger::error(
-            user_prompt::UPGRADE_PATH_NOT_VALID,
-            invalid_source_list.as_str(),
-        );
-        return error::NotAValidSourceForUpgrade.fail();
-    }
-    let destination_version = get_image_version_tag();
+    Ok(upgrade_path_result(
+        name,
+        &compatibility_matrix,
+        &source,
+        target_version(args),
+        to_develop_branch,
+        args,
+    ))
+}
 
-    if destination_version.contains(UPGRADE_TO_DEVELOP_BRANCH) {
-        console_logger::error("", user_prompt::UPGRADE_TO_UNSUPPORTED_VERSION);
-        return error::InvalidUpgradePath.fail();
-    }
+/// Evaluates the upgrade path check for the upgrade from the source version to the target
+/// version.
+fn upgrade_path_result(
+    name: &'static str,
+    compatibility_matrix: &CompatibilityMatrix,
+    source: &Version,
+    self_version: Option<Version>,
+    to_develop_branch: bool,
+    args: &UpgradeArgs,
+) -> CheckResult {
+    let invalid_path = |remediation: &str, details: Vec<String>| {
+        CheckResult::not_pass(
+            name,
+            CheckStatus::Fail,
+            format!("Cannot upgrade from version {source}"),
+            details,
+            remediation,
+        )
+    };
 
-    // Self version
-    let self_version_info = version_info!();
-    let mut self_version: Option<Version> = None;
-    if let Some(tag) = self_version_info.version_tag {
-        if !tag.is_empty() {
-            let tag = strip_v_prefix(tag.as_str());
-            if let Ok(sv) = Version::parse(tag) {
-                self_version = Some(sv);
-            }
-        }
+    if compatibility_matrix.is_unsupported(source) {
+        let invalid_source_list = compatibility_matrix
+            .unsupported_versions()
+            .iter()
+            .map(|val| val.to_string())
+            .collect();
+        return invalid_path(user_prompt::UPGRADE_PATH_NOT_VALID, invalid_source_list);
+    }
+    if to_develop_branch {
+        return invalid_path(user_prompt::UPGRADE_TO_UNSUPPORTED_VERSION, Vec::new());
     }
 
     // Stable to unstable check.
-    if !allow_unstable {
+    if !args.allow_unstable {
         let mut self_is_stable: bool = false;
         if let Some(ref version) = self_version {
             if version.pre.is_empty() {
@@ -246,20 +890,43 @@ pub(crate) async fn upgrade_path_validation(
             }
         }
         if source.pre.is_empty() && !self_is_stable {
-            console_logger::error("", user_prompt::STABLE_TO_UNSTABLE_UPGRADE);
-            return error::InvalidUpgradePath.fail();
+            return invalid_path(user_prompt::STABLE_TO_UNSTABLE_UPGRADE, Vec::new());
         }
     }
 
     // Upgrade not allowed to lower semver versions check.
     if let Some(ref version) = self_version {
-        if version.lt(&source) {
-            console_logger::error("", user_prompt::HIGHER_TO_LOWER_SEMVER_UPGRADE);
-            return error::InvalidUpgradePath.fail();
+        if version.lt(source) {
+            return invalid_path(user_prompt::HIGHER_TO_LOWER_SEMVER_UPGRADE, Vec::new());
+        }
+
+        // Upgrades which must go through intermediate versions, as per the compatibility matrix.
+        let path = match compatibility_matrix.upgrade_path(source, version) {
+            Ok(path) => path,
+            Err(error) => {
+                return invalid_path(
+                    user_prompt::UPGRADE_PATH_NOT_SUPPORTED,
+                    vec![error.to_string()],
+                )
+            }
+        };
+        if path.len() > 1 {
+            let hops = path.iter().map(ToString::to_string).collect();
+            let (status, remediation) = match args.auto_hop {
+                true => (CheckStatus::Warn, user_prompt::UPGRADE_PATH_AUTO_HOP),
+                false => (CheckStatus::Fail, user_prompt::UPGRADE_PATH_MULTI_HOP),
+            };
+            return CheckResult::not_pass(
+                name,
+                status,
+                format!("Upgrade from version {source} to {version} must go through intermediate versions"),
+                hops,
+                remediation,
+            );
         }
     }
 
-    Ok(())
+    CheckResult::pass(name, format!("Upgrade from version {source} is supported"))
 }
 
 /// New-Type for a RestClient over the tower openapi client.
@@ -284,3 +951,368 @@ impl RestClient {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k8s_openapi::{
+        api::{
+            core::v1::{
+                ContainerState, ContainerStateRunning, ContainerStateWaiting, ContainerStatus,
+                NodeCondition, NodeStatus, PodStatus,
+            },
+            policy::v1::PodDisruptionBudgetStatus,
+        },
+        apimachinery::pkg::apis::meta::v1::ObjectMeta,
+    };
+
+    const NAME: &str = "check";
+
+    fn version(version: &str) -> Version {
+        Version::parse(version).unwrap()
+    }
+
+    fn matrix() -> CompatibilityMatrix {
+        CompatibilityMatrix::try_from(
+            r#"
+unsupported_versions:
+  - 1.0.0
+compatibility:
+  - from: ">=2.0.0, <2.4.0"
+    to: ">=2.5.0"
+    via: 2.4.0
+    reason: "the etcd data layout changed in 2.4.0"
+  - from: "~2.2"
+    to: ">=2.3.0, <2.4.0"
+    reason: "2.3 cannot import 2.2 pools"
+"#
+            .as_bytes(),
+        )
+        .unwrap()
+    }
+
+    fn node(name: &str, ready: Option<&str>) -> Node {
+        Node {
+            metadata: ObjectMeta {
+                name: Some(name.to_string()),
+                ..Default::default()
+            },
+            status: ready.map(|ready| NodeStatus {
+                conditions: Some(vec![NodeCondition {
+                    type_: "Ready".to_string(),
+                    status: ready.to_string(),
+                    ..Default::default()
+                }]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn pdb(name: &str, expected_pods: i32, disruptions_allowed: i32) -> PodDisruptionBudget {
+        PodDisruptionBudget {
+            metadata: ObjectMeta {
+                name: Some(name.to_string()),
+                namespace: Some("apps".to_string()),
+                ..Default::default()
+            },
+            status: Some(PodDisruptionBudgetStatus {
+                expected_pods,
+                disruptions_allowed,
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn helm_labels(version: &str, status: &str) -> BTreeMap<String, String> {
+        BTreeMap::from([
+            ("version".to_string(), version.to_string()),
+            ("status".to_string(), status.to_string()),
+        ])
+    }
+
+    fn pod_with_state(state: ContainerState) -> Pod {
+        Pod {
+            status: Some(PodStatus {
+                container_statuses: Some(vec![ContainerStatus {
+                    state: Some(state),
+                    ..Default::default()
+                }]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn waiting_pod(reason: &str) -> Pod {
+        pod_with_state(ContainerState {
+            waiting: Some(ContainerStateWaiting {
+                reason: Some(reason.to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn check_registry_skips() {
+        assert_eq!(PreflightCheck::registry(&UpgradeArgs::new()).len(), 9);
+
+        let mut args = UpgradeArgs::new();
+        args.skip_upgrade_path_validation_for_unsupported_version = true;
+        args.skip_replica_rebuild = true;
+        args.skip_cordoned_node_validation = true;
+        args.skip_single_replica_volume_validation = true;
+        let names: Vec<&str> = PreflightCheck::registry(&args)
+            .iter()
+            .map(PreflightCheck::name)
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "pool-capacity-headroom",
+                "nodes-not-ready",
+                "pod-disruption-budgets",
+                "helm-release-pending",
+                "image-registry-reachable",
+            ]
+        );
+    }
+
+    #[test]
+    fn report_counts_failures() {
+        let report = PreflightReport {
+            checks: vec![
+                CheckResult::pass(NAME, "ok"),
+                rebuild_in_progress_result(NAME, true),
+                pool_capacity_result(NAME, vec!["pool-1".to_string()]),
+                cordoned_nodes_result(NAME, vec!["node-1".to_string()]),
+            ],
+        };
+        assert_eq!(report.failed(), 2);
+    }
+
+    #[test]
+    fn cordoned_nodes() {
+        assert_eq!(
+            cordoned_nodes_result(NAME, Vec::new()).status(),
+            CheckStatus::Pass
+        );
+        let result = cordoned_nodes_result(NAME, vec!["node-1".to_string()]);
+        assert_eq!(result.status(), CheckStatus::Fail);
+        assert_eq!(result.details, vec!["node-1"]);
+    }
+
+    #[test]
+    fn single_replica_volumes() {
+        assert_eq!(
+            single_replica_volumes_result(NAME, Vec::new()).status(),
+            CheckStatus::Pass
+        );
+        let result = single_replica_volumes_result(NAME, vec!["default/pvc-1".to_string()]);
+        assert_eq!(result.status(), CheckStatus::Fail);
+        assert_eq!(result.details, vec!["default/pvc-1"]);
+    }
+
+    #[test]
+    fn rebuild_in_progress() {
+        assert_eq!(
+            rebuild_in_progress_result(NAME, false).status(),
+            CheckStatus::Pass
+        );
+        assert_eq!(
+            rebuild_in_progress_result(NAME, true).status(),
+            CheckStatus::Fail
+        );
+    }
+
+    #[test]
+    fn pool_capacity_headroom() {
+        assert!(!lacks_capacity_headroom(0, 0));
+        assert!(!lacks_capacity_headroom(100, 0));
+        assert!(!lacks_capacity_headroom(100, 90));
+        assert!(lacks_capacity_headroom(100, 91));
+        assert!(lacks_capacity_headroom(100, 120));
+
+        assert_eq!(
+            pool_capacity_result(NAME, Vec::new()).status(),
+            CheckStatus::Pass
+        );
+        assert_eq!(
+            pool_capacity_result(NAME, vec!["pool-1".to_string()]).status(),
+            CheckStatus::Warn
+        );
+    }
+
+    #[test]
+    fn nodes_not_ready() {
+        let io_engine_nodes = HashSet::from(["node-1".to_string()]);
+
+        let result = nodes_not_ready_result(
+            NAME,
+            vec![node("node-1", Some("True")), node("node-2", Some("True"))],
+            &io_engine_nodes,
+        );
+        assert_eq!(result.status(), CheckStatus::Pass);
+
+        let result = nodes_not_ready_result(
+            NAME,
+            vec![node("node-1", Some("True")), node("node-2", Some("False"))],
+            &io_engine_nodes,
+        );
+        assert_eq!(result.status(), CheckStatus::Warn);
+        assert_eq!(result.details, vec!["node-2"]);
+
+        let result = nodes_not_ready_result(
+            NAME,
+            vec![node("node-1", Some("Unknown")), node("node-2", None)],
+            &io_engine_nodes,
+        );
+        assert_eq!(result.status(), CheckStatus::Fail);
+        assert_eq!(result.details, vec!["node-1", "node-2"]);
+    }
+
+    #[test]
+    fn pod_disruption_budgets() {
+        let result = pdb_conflict_result(NAME, vec![pdb("pdb-1", 2, 1), pdb("pdb-2", 0, 0)]);
+        assert_eq!(result.status(), CheckStatus::Pass);
+
+        let result = pdb_conflict_result(NAME, vec![pdb("pdb-1", 2, 1), pdb("pdb-2", 1, 0)]);
+        assert_eq!(result.status(), CheckStatus::Warn);
+        assert_eq!(result.details, vec!["apps/pdb-2"]);
+    }
+
+    #[test]
+    fn helm_release_pending() {
+        let result = helm_release_pending_result(
+            NAME,
+            "mayastor",
+            &[helm_labels("1", "superseded"), helm_labels("2", "deployed")],
+        );
+        assert_eq!(result.status(), CheckStatus::Pass);
+
+        let result = helm_release_pending_result(
+            NAME,
+            "mayastor",
+            &[
+                helm_labels("9", "deployed"),
+                helm_labels("10", "pending-upgrade"),
+            ],
+        );
+        assert_eq!(result.status(), CheckStatus::Fail);
+
+        let result = helm_release_pending_result(NAME, "mayastor", &[]);
+        assert_eq!(result.status(), CheckStatus::Warn);
+    }
+
+    #[test]
+    fn image_registry() {
+        assert_eq!(image_pull_outcome(Pod::default()), None);
+        assert_eq!(image_pull_outcome(waiting_pod("ContainerCreating")), None);
+        assert_eq!(
+            image_pull_outcome(waiting_pod("ImagePullBackOff")),
+            Some(ImagePullOutcome::Failed("ImagePullBackOff".to_string()))
+        );
+        assert_eq!(
+            image_pull_outcome(pod_with_state(ContainerState {
+                running: Some(ContainerStateRunning::default()),
+                ..Default::default()
+            })),
+            Some(ImagePullOutcome::Pulled)
+        );
+
+        let image = "docker.io/openebs/mayastor-upgrade-job:v2.6.0";
+        assert_eq!(
+            image_registry_result(NAME, image, ImagePullOutcome::Pulled).status(),
+            CheckStatus::Pass
+        );
+        assert_eq!(
+            image_registry_result(
+                NAME,
+                image,
+                ImagePullOutcome::Failed("ErrImagePull".to_string())
+            )
+            .status(),
+            CheckStatus::Fail
+        );
+        assert_eq!(
+            image_registry_result(NAME, image, ImagePullOutcome::Unknown).status(),
+            CheckStatus::Warn
+        );
+    }
+
+    #[test]
+    fn upgrade_path() {
+        let matrix = matrix();
+        let args = UpgradeArgs::new();
+        let result = |source: &str, target: Option<&str>, develop: bool, args: &UpgradeArgs| {
+            upgrade_path_result(
+                NAME,
+                &matrix,
+                &version(source),
+                target.map(version),
+                develop,
+                args,
+            )
+        };
+
+        let supported = result("2.4.0", Some("2.6.0"), false, &args);
+        assert_eq!(supported.status(), CheckStatus::Pass);
+
+        // Unsupported source version.
+        let unsupported = result("1.0.0", Some("2.6.0"), false, &args);
+        assert_eq!(unsupported.status(), CheckStatus::Fail);
+        assert_eq!(unsupported.details, vec!["1.0.0"]);
+
+        // Upgrade to the develop branch.
+        assert_eq!(
+            result("2.4.0", None, true, &args).status(),
+            CheckStatus::Fail
+        );
+
+        // Stable to unstable.
+        assert_eq!(
+            result("2.4.0", Some("2.6.0-rc.1"), false, &args).status(),
+            CheckStatus::Fail
+        );
+        assert_eq!(
+            result("2.4.0", None, false, &args).status(),
+            CheckStatus::Fail
+        );
+        let mut unstable_args = UpgradeArgs::new();
+        unstable_args.allow_unstable = true;
+        assert_eq!(
+            result("2.4.0", Some("2.6.0-rc.1"), false, &unstable_args).status(),
+            CheckStatus::Pass
+        );
+
+        // Downgrade.
+        assert_eq!(
+            result("2.6.0", Some("2.4.0"), false, &args).status(),
+            CheckStatus::Fail
+        );
+
+        // Unsupported upgrade path.
+        let not_supported = result("2.2.0", Some("2.3.0"), false, &args);
+        assert_eq!(not_supported.status(), CheckStatus::Fail);
+        assert_eq!(not_supported.details.len(), 1);
+
+        // Multi-hop upgrade path, which is carried out with '--auto-hop'.
+        let multi_hop = result("2.1.0", Some("2.6.0"), false, &args);
+        assert_eq!(multi_hop.status(), CheckStatus::Fail);
+        assert_eq!(
+            multi_hop.details,
+            vec![
+                "2.1.0 -> 2.4.0 (the etcd data layout changed in 2.4.0)",
+                "2.4.0 -> 2.6.0",
+            ]
+        );
+        let mut auto_hop_args = UpgradeArgs::new();
+        auto_hop_args.auto_hop = true;
+        assert_eq!(
+            result("2.1.0", Some("2.6.0"), false, &auto_hop_args).status(),
+            CheckStatus::Warn
+        );
+    }
+}
@@ -0,0 +1,95 @@
+use k8s_openapi::chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// This is the width of the data-plane upgrade progress bar, in characters.
+const PROGRESS_BAR_WIDTH: usize = 30;
+
+/// This is the progress of the data-plane upgrade, from the per-node upgrade events.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct UpgradeProgress {
+    node: String,
+    completed_nodes: usize,
+    total_nodes: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rebuilding_volumes: Option<usize>,
+    started_at: String,
+}
+
+impl UpgradeProgress {
+    /// Print the data-plane upgrade progress to console.
+    pub(crate) fn print(&self) {
+        println!(
+            "Data-plane Progress: {} {}/{} nodes",
+            progress_bar(self.completed_nodes, self.total_nodes, PROGRESS_BAR_WIDTH),
+            self.completed_nodes,
+            self.total_nodes
+        );
+        println!("Current Node: {}", self.node);
+        if let Some(rebuilding_volumes) = self.rebuilding_volumes {
+            println!("Rebuilding Volumes: {rebuilding_volumes}");
+        }
+        let eta = self.eta(Utc::now()).map_or("unknown".to_string(), |eta| {
+            humantime::format_duration(eta).to_string()
+        });
+        println!("ETA: {eta}");
+    }
+
+    /// Returns the estimated time until the data-plane upgrade completes, based on the average
+    /// time taken for each of the upgraded nodes. This is unknown until a node is upgraded.
+    pub(crate) fn eta(&self, now: DateTime<Utc>) -> Option<Duration> {
+        if self.completed_nodes == 0 {
+            return None;
+        }
+
+        let started_at = DateTime::parse_from_rfc3339(self.started_at.as_str()).ok()?;
+        let elapsed = (now - started_at.with_timezone(&Utc)).to_std().ok()?;
+        let remaining_nodes = self.total_nodes.saturating_sub(self.completed_nodes) as u32;
+
+        // Rounded down to the second, for display.
+        let eta = elapsed / self.completed_nodes as u32 * remaining_nodes;
+        Some(Duration::from_secs(eta.as_secs()))
+    }
+}
+
+/// Returns a progress bar, e.g. [#########-----].
+fn progress_bar(completed: usize, total: usize, width: usize) -> String {
+    let filled = match total {
+        0 => width,
+        _ => (completed.min(total) * width) / total,
+    };
+    format!("[{}{}]", "#".repeat(filled), "-".repeat(width - filled))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{progress_bar, UpgradeProgress};
+    use k8s_openapi::chrono::{DateTime, Utc};
+    use std::time::Duration;
+
+    #[test]
+    fn progress_bar_fill() {
+        assert_eq!(progress_bar(0, 4, 8), "[--------]");
+        assert_eq!(progress_bar(1, 4, 8), "[##------]");
+        assert_eq!(progress_bar(4, 4, 8), "[########]");
+        assert_eq!(progress_bar(0, 0, 4), "[####]");
+    }
+
+    #[test]
+    fn progress_eta() {
+        let progress: UpgradeProgress = serde_json::from_str(
+            r#"{"node":"node-3","completedNodes":2,"totalNodes":5,"startedAt":"2024-05-01T10:00:00+00:00"}"#,
+        )
+        .unwrap();
+        let now: DateTime<Utc> = "2024-05-01T10:40:00Z".parse().unwrap();
+        // 20 minutes for each node, for the 3 remaining nodes.
+        assert_eq!(progress.eta(now), Some(Duration::from_secs(60 * 60)));
+
+        let progress = UpgradeProgress {
+            completed_nodes: 0,
+            ..progress
+        };
+        assert_eq!(progress.eta(now), None);
+    }
+}
@@ -0,0 +1,210 @@
+use crate::plugin::error;
+use cron::{
+    error::{Error as CronError, ErrorKind as CronErrorKind},
+    Schedule,
+};
+use k8s_openapi::chrono::{DateTime, Days, NaiveTime, Utc};
+use std::{cmp::Ordering, fmt, str::FromStr, time::Duration};
+
+/// This is the format of the start and the end of a maintenance window, e.g. 22:30.
+const WINDOW_TIME_FORMAT: &str = "%H:%M";
+
+/// This is the start time of a scheduled upgrade. It is the next occurrence of the cron schedule,
+/// in UTC, when the upgrade command is run. The upgrade-job waits until then to start upgrading.
+#[derive(Clone, Debug)]
+pub struct UpgradeSchedule {
+    expression: String,
+    start_at: DateTime<Utc>,
+}
+
+impl FromStr for UpgradeSchedule {
+    type Err = CronError;
+
+    fn from_str(expression: &str) -> Result<Self, Self::Err> {
+        // The cron crate expects a seconds field, which standard 5-field cron schedules lack.
+        let schedule = match expression.split_whitespace().count() {
+            5 => Schedule::from_str(format!("0 {expression}").as_str()),
+            _ => Schedule::from_str(expression),
+        }?;
+
+        let start_at = schedule.upcoming(Utc).next().ok_or_else(|| {
+            CronError::from(CronErrorKind::Expression(format!(
+                "schedule '{expression}' has no upcoming occurrence"
+            )))
+        })?;
+
+        Ok(Self {
+            expression: expression.to_string(),
+            start_at,
+        })
+    }
+}
+
+impl fmt::Display for UpgradeSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.expression)
+    }
+}
+
+impl UpgradeSchedule {
+    /// Returns the time to start the upgrade at.
+    pub(crate) fn start_at(&self) -> DateTime<Utc> {
+        self.start_at
+    }
+}
+
+/// This is a daily maintenance window, in UTC, e.g. 22:00-04:00. A window which ends before it
+/// starts spans midnight. A window which ends when it starts is always open.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MaintenanceWindow {
+    start: NaiveTime,
+    end: NaiveTime,
+}
+
+impl FromStr for MaintenanceWindow {
+    type Err = error::Error;
+
+    fn from_str(window: &str) -> error::Result<Self> {
+        let invalid = || {
+            error::InvalidMaintenanceWindow {
+                window: window.to_string(),
+            }
+            .build()
+        };
+
+        let (start, end) = window.split_once('-').ok_or_else(invalid)?;
+        let start =
+            NaiveTime::parse_from_str(start.trim(), WINDOW_TIME_FORMAT).map_err(|_| invalid())?;
+        let end =
+            NaiveTime::parse_from_str(end.trim(), WINDOW_TIME_FORMAT).map_err(|_| invalid())?;
+
+        Ok(Self { start, end })
+    }
+}
+
+impl fmt::Display for MaintenanceWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}-{} UTC",
+            self.start.format(WINDOW_TIME_FORMAT),
+            self.end.format(WINDOW_TIME_FORMAT)
+        )
+    }
+}
+
+impl MaintenanceWindow {
+    /// Returns true if the window is open at the time of the day.
+    pub fn is_open_at(&self, time: NaiveTime) -> bool {
+        match self.start.cmp(&self.end) {
+            Ordering::Less => time >= self.start && time < self.end,
+            Ordering::Greater => time >= self.start || time < self.end,
+            Ordering::Equal => true,
+        }
+    }
+
+    /// Returns the time from 'now' until the window opens next. This is zero if the window is
+    /// open.
+    pub fn until_open(&self, now: DateTime<Utc>) -> Duration {
+        if self.is_open_at(now.time()) {
+            return Duration::ZERO;
+        }
+
+        let mut next_start = now.date_naive().and_time(self.start).and_utc();
+        if next_start <= now {
+            next_start = next_start + Days::new(1);
+        }
+
+        (next_start - now).to_std().unwrap_or_default()
+    }
+}
+
+/// Validates a daily maintenance window, in UTC, e.g. 22:00-04:00. The upgrade-job parses the
+/// window in the same way.
+pub(crate) fn validate_maintenance_window(window: &str) -> Result<String, String> {
+    window
+        .parse::<MaintenanceWindow>()
+        .map(|_| window.to_string())
+        .map_err(|error| error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{validate_maintenance_window, MaintenanceWindow, UpgradeSchedule};
+    use k8s_openapi::chrono::{DateTime, NaiveTime, Timelike, Utc};
+    use std::time::Duration;
+
+    fn time(hh_mm: &str) -> NaiveTime {
+        NaiveTime::parse_from_str(hh_mm, "%H:%M").unwrap()
+    }
+
+    fn at(rfc3339: &str) -> DateTime<Utc> {
+        rfc3339.parse().unwrap()
+    }
+
+    #[test]
+    fn upgrade_schedule_next_start() {
+        let schedule: UpgradeSchedule = "30 2 * * *".parse().unwrap();
+        let start_at = schedule.start_at();
+        assert!(start_at > Utc::now());
+        assert_eq!((start_at.hour(), start_at.minute()), (2, 30));
+        assert_eq!(schedule.to_string(), "30 2 * * *");
+
+        assert!("0 0 2 * * Sat".parse::<UpgradeSchedule>().is_ok());
+        assert!("every night".parse::<UpgradeSchedule>().is_err());
+    }
+
+    #[test]
+    fn maintenance_window_validation() {
+        assert!(validate_maintenance_window("22:00-04:00").is_ok());
+        assert!(validate_maintenance_window("24:00-04:00").is_err());
+        assert!(validate_maintenance_window("22:00").is_err());
+    }
+
+    #[test]
+    fn maintenance_window_parse() {
+        let window: MaintenanceWindow = "22:00-04:30".parse().unwrap();
+        assert_eq!(window.to_string(), "22:00-04:30 UTC");
+
+        assert!("22:00".parse::<MaintenanceWindow>().is_err());
+        assert!("25:00-04:00".parse::<MaintenanceWindow>().is_err());
+        assert!("22:00-4pm".parse::<MaintenanceWindow>().is_err());
+    }
+
+    #[test]
+    fn maintenance_window_is_open() {
+        let window: MaintenanceWindow = "01:00-05:00".parse().unwrap();
+        assert!(window.is_open_at(time("01:00")));
+        assert!(window.is_open_at(time("04:59")));
+        assert!(!window.is_open_at(time("05:00")));
+        assert!(!window.is_open_at(time("00:59")));
+
+        // This window spans midnight.
+        let window: MaintenanceWindow = "22:00-04:00".parse().unwrap();
+        assert!(window.is_open_at(time("23:30")));
+        assert!(window.is_open_at(time("03:00")));
+        assert!(!window.is_open_at(time("12:00")));
+
+        let window: MaintenanceWindow = "00:00-00:00".parse().unwrap();
+        assert!(window.is_open_at(time("12:00")));
+    }
+
+    #[test]
+    fn maintenance_window_until_open() {
+        let window: MaintenanceWindow = "22:00-04:00".parse().unwrap();
+        assert_eq!(
+            window.until_open(at("2024-05-01T23:00:00Z")),
+            Duration::ZERO
+        );
+        assert_eq!(
+            window.until_open(at("2024-05-01T21:30:00Z")),
+            Duration::from_secs(30 * 60)
+        );
+
+        let window: MaintenanceWindow = "01:00-05:00".parse().unwrap();
+        assert_eq!(
+            window.until_open(at("2024-05-01T06:00:00Z")),
+            Duration::from_secs(19 * 60 * 60)
+        );
+    }
+}
@@ -1,20 +1,32 @@
 use crate::plugin::{
+    backup::UpgradeBackupsCommand,
     constants::{
         get_image_version_tag, upgrade_event_selector, upgrade_image_concat, upgrade_job_img,
         upgrade_name_concat, AGENT_CORE_POD_LABEL, API_REST_LABEL_SELECTOR, API_REST_POD_LABEL,
-        DEFAULT_IMAGE_REGISTRY, DEFAULT_RELEASE_NAME, IO_ENGINE_POD_LABEL, MAX_RETRY_ATTEMPTS,
-        UPGRADE_CONFIG_MAP_MOUNT_PATH, UPGRADE_CONFIG_MAP_NAME_SUFFIX,
+        DEFAULT_IMAGE_REGISTRY, DEFAULT_RELEASE_NAME, MAX_RETRY_ATTEMPTS,
+        UPGRADE_COMPLETED_WITH_WARNINGS_ACTION, UPGRADE_COMPLETION_MAX_POLL_ATTEMPTS,
+        UPGRADE_COMPLETION_POLL_INTERVAL_SECS, UPGRADE_CONFIG_MAP_MOUNT_PATH,
+        UPGRADE_CONFIG_MAP_NAME_SUFFIX, UPGRADE_FAILED_ACTION,
         UPGRADE_JOB_CLUSTERROLEBINDING_NAME_SUFFIX, UPGRADE_JOB_CLUSTERROLE_NAME_SUFFIX,
         UPGRADE_JOB_IMAGE_REPO, UPGRADE_JOB_NAME_SUFFIX, UPGRADE_JOB_SERVICEACCOUNT_NAME_SUFFIX,
+        UPGRADE_SUCCESSFUL_ACTION, UPGRADE_VALIDATION_FAILED_ACTION, WORKLOAD_DRAIN_TIMEOUT_SECS,
     },
     error, objects,
+    path::CompatibilityMatrix,
+    plan::UpgradePlanClient,
+    preflight_validations::target_version,
+    progress::UpgradeProgress,
+    schedule::{validate_maintenance_window, UpgradeSchedule},
     user_prompt::{
-        upgrade_dry_run_summary, CONTROL_PLANE_PODS_LIST, DATA_PLANE_PODS_LIST,
-        DATA_PLANE_PODS_LIST_SKIP_RESTART, DELETE_INCOMPLETE_JOB, HELM_UPGRADE_VALIDATION_ERROR,
+        upgrade_dry_run_summary, upgrade_intermediate_version, upgrade_scheduled,
+        CONTROL_PLANE_PODS_LIST, DELETE_INCOMPLETE_JOB, HELM_UPGRADE_VALIDATION_ERROR,
         UPGRADE_DRY_RUN_SUMMARY, UPGRADE_JOB_STARTED,
     },
 };
-use constants::{helm_release_name_key, helm_release_version_key, upgrade_event_reason};
+use constants::{
+    helm_release_name_key, helm_release_version_key, upgrade_event_reason,
+    upgrade_history_configmap_name, UPGRADE_HISTORY_DATA_KEY,
+};
 use k8s_openapi::api::{
     apps::v1::Deployment,
     batch::v1::Job,
@@ -26,7 +38,9 @@ use kube::{
     core::ObjectList,
     Client,
 };
-use serde::Deserialize;
+use rest_plugin::resources::utils::OutputFormat;
+use semver::Version;
+use serde::{Deserialize, Serialize};
 use snafu::ResultExt;
 use std::{
     collections::{BTreeMap, HashMap, HashSet},
@@ -67,6 +81,15 @@ impl DeleteUpgradeArgs {
     }
 }
 
+/// Commands to inspect the upgrades of the helm release.
+#[derive(clap::Subcommand, Debug, Clone)]
+pub enum UpgradeCommand {
+    /// Inspect the backups of the helm values and the control-plane state, which are taken
+    /// before upgrading.
+    #[clap(subcommand)]
+    Backups(UpgradeBackupsCommand),
+}
+
 #[derive(clap::Subcommand, Debug)]
 /// Actions to be performed.
 pub enum Actions {
@@ -88,7 +111,9 @@ pub struct UpgradeArgs {
     #[clap(global = true, long, hide = true)]
     pub allow_unstable: bool,
 
-    /// Display all the validations output but will not execute upgrade.
+    /// Display all the validations output and the upgrade plan, i.e. the changes to the helm
+    /// values and the Kubernetes objects, and the io-engine pod restarts, but will not execute
+    /// upgrade.
     #[clap(global = true, long, short)]
     pub dry_run: bool,
 
@@ -96,6 +121,12 @@ pub struct UpgradeArgs {
     #[clap(global = true, long, default_value_t = false)]
     pub skip_data_plane_restart: bool,
 
+    /// If set then upgrade will skip the backup of the helm values and the control-plane state,
+    /// which is otherwise saved to a Secret before upgrading. The upgrade goes on without a backup
+    /// if it cannot be taken, e.g. if it is too large for a Secret.
+    #[clap(global = true, long, default_value_t = false)]
+    pub skip_backup: bool,
+
     /// If set then it will continue with upgrade without validating singla replica volume.
     #[clap(global = true, long)]
     pub skip_single_replica_volume_validation: bool,
@@ -125,6 +156,110 @@ pub struct UpgradeArgs {
     /// Use helm's --reset-then-reuse-values option.
     #[arg(long, default_value_t = false)]
     pub reset_then_reuse_values: bool,
+
+    /// The helm chart version to upgrade to. The upgrade-job pulls this helm chart from the
+    /// '--chart-repo' helm chart repository, instead of using the helm chart in its image.
+    #[clap(global = true, long, requires = "chart_repo")]
+    pub to_version: Option<Version>,
+
+    /// The helm chart repository to pull the '--to-version' helm chart from. This may be the URL
+    /// of an HTTP(S) helm chart repository, or that of an OCI registry, i.e.
+    /// oci://<registry>/<path>. Mirrors of these may be used for air-gapped clusters.
+    #[clap(global = true, long, requires = "to_version")]
+    pub chart_repo: Option<String>,
+
+    /// The expected sha256 digest of the '--to-version' helm chart tarball, i.e. sha256:<hex>.
+    #[clap(global = true, long, requires = "to_version")]
+    pub chart_digest: Option<String>,
+
+    /// The umbrella helm chart version to upgrade releases of the umbrella helm chart to. The
+    /// upgrade-job pulls this helm chart from the '--chart-repo' helm chart repository. It must
+    /// depend on the '--to-version' helm chart.
+    #[clap(global = true, long, requires = "to_version")]
+    pub umbrella_chart_version: Option<Version>,
+
+    /// The expected sha256 digest of the '--umbrella-chart-version' helm chart tarball, i.e.
+    /// sha256:<hex>.
+    #[clap(global = true, long, requires = "umbrella_chart_version")]
+    pub umbrella_chart_digest: Option<String>,
+
+    /// The name of a Secret in the installation namespace with the helm chart repository's
+    /// credentials and verification material. The optional keys 'username', 'password', 'ca.crt'
+    /// and 'keyring.gpg' are used. If the keyring is present, the helm chart's provenance file
+    /// is verified.
+    #[clap(global = true, long, requires = "to_version")]
+    pub chart_repo_secret: Option<String>,
+
+    /// Use plain HTTP to pull the '--to-version' helm chart from an OCI registry, e.g. a local
+    /// registry.
+    #[clap(global = true, long, requires = "to_version")]
+    pub chart_repo_plain_http: bool,
+
+    /// Allow the pulled helm charts to be installed if they can be verified neither with the
+    /// '--chart-digest' (or '--umbrella-chart-digest') digest, nor with the keyring in the
+    /// '--chart-repo-secret' Secret. These are refused otherwise.
+    #[clap(global = true, long, requires = "to_version")]
+    pub allow_unverified_chart: bool,
+
+    /// Schedule the upgrade to start at the next occurrence of this cron schedule, in UTC, e.g.
+    /// '0 2 * * Sat'. The upgrade-job is created right away, and it waits until then to upgrade.
+    #[clap(global = true, long, value_name = "CRON")]
+    pub schedule: Option<UpgradeSchedule>,
+
+    /// The daily maintenance window to upgrade in, in UTC, e.g. '22:00-04:00'. The upgrade waits
+    /// for the window to open before upgrading the control-plane, and before restarting each
+    /// io-engine pod. An upgrade which is parked when the window closes resumes in the next one.
+    #[clap(
+        global = true,
+        long,
+        value_name = "HH:MM-HH:MM",
+        value_parser = validate_maintenance_window
+    )]
+    pub window: Option<String>,
+
+    /// If set then the storage nodes are drained with awareness of the application pods which
+    /// use the volumes targeted on them. The drain of a node waits for the PodDisruptionBudgets of
+    /// these pods to allow a disruption.
+    #[clap(global = true, long, default_value_t = false)]
+    pub workload_aware_drain: bool,
+
+    /// If set then the upgrade waits for the application pods of a drained storage node to be
+    /// Ready again, before moving on to the next node.
+    #[clap(
+        global = true,
+        long,
+        default_value_t = false,
+        requires = "workload_aware_drain"
+    )]
+    pub wait_for_workload_ready: bool,
+
+    /// The time to wait for the PodDisruptionBudgets of the application pods to allow a
+    /// disruption, and for the application pods to be Ready, before the upgrade fails.
+    #[clap(
+        global = true,
+        long,
+        default_value_t = Duration::from_secs(WORKLOAD_DRAIN_TIMEOUT_SECS).into(),
+        requires = "workload_aware_drain"
+    )]
+    pub workload_drain_timeout: humantime::Duration,
+
+    /// The name of a Secret in the installation namespace with the webhook configuration for
+    /// upgrade notifications. The key 'url' is required, and the optional keys 'hmac-key' and
+    /// 'format' ('generic', 'slack' or 'teams') are used if present. Each upgrade event, including
+    /// the data-plane progress of each node and failures, is sent to the webhook.
+    #[clap(global = true, long)]
+    pub notification_secret: Option<String>,
+
+    /// If set then an upgrade which must go through intermediate versions, as per the
+    /// compatibility matrix, is carried out as an upgrade to each of these versions in order. Each
+    /// of these upgrades is waited on to complete. The helm charts of the intermediate versions
+    /// are pulled from the '--chart-repo' helm chart repository.
+    #[clap(global = true, long, default_value_t = false, requires = "to_version")]
+    pub auto_hop: bool,
+
+    /// Inspect the upgrades of the helm release, instead of upgrading.
+    #[clap(subcommand)]
+    pub command: Option<UpgradeCommand>,
 }
 
 impl Default for UpgradeArgs {
@@ -141,6 +276,7 @@ impl UpgradeArgs {
             allow_unstable: false,
             dry_run: false,
             skip_data_plane_restart: false,
+            skip_backup: false,
             skip_single_replica_volume_validation: false,
             skip_replica_rebuild: false,
             skip_cordoned_node_validation: false,
@@ -148,6 +284,22 @@ impl UpgradeArgs {
             set: Default::default(),
             set_file: Default::default(),
             reset_then_reuse_values: false,
+            to_version: None,
+            chart_repo: None,
+            chart_digest: None,
+            umbrella_chart_version: None,
+            umbrella_chart_digest: None,
+            chart_repo_secret: None,
+            chart_repo_plain_http: false,
+            allow_unverified_chart: false,
+            schedule: None,
+            window: None,
+            workload_aware_drain: false,
+            wait_for_workload_ready: false,
+            workload_drain_timeout: Duration::from_secs(WORKLOAD_DRAIN_TIMEOUT_SECS).into(),
+            notification_secret: None,
+            auto_hop: false,
+            command: None,
         }
     }
     ///  Upgrade the resources.
@@ -155,6 +307,11 @@ impl UpgradeArgs {
         let upgrade_event_client = UpgradeEventClient::new(namespace).await?;
         let release_name = get_release_name(namespace).await?;
 
+        if self.auto_hop {
+            self.apply_intermediate_upgrades(namespace, &upgrade_event_client, &release_name)
+                .await?;
+        }
+
         // Delete any previous upgrade events
         upgrade_event_client
             .delete_upgrade_events(release_name.clone())
@@ -178,6 +335,74 @@ impl UpgradeArgs {
         Ok(())
     }
 
+    /// Upgrades to each of the intermediate versions of the upgrade path, in order. Each of these
+    /// upgrades is waited on to complete, and its upgrade resources are removed after.
+    async fn apply_intermediate_upgrades(
+        &self,
+        namespace: &str,
+        upgrade_event_client: &UpgradeEventClient,
+        release_name: &str,
+    ) -> error::Result<()> {
+        let Some(target) = target_version(self) else {
+            return Ok(());
+        };
+        let source_version = get_source_version(namespace).await?;
+        let source = Version::parse(source_version.as_str()).context(error::SemverParse {
+            version_string: source_version.clone(),
+        })?;
+
+        let path = CompatibilityMatrix::embedded()?.upgrade_path(&source, &target)?;
+        // The last hop is the upgrade to the target version.
+        let intermediate_versions: Vec<&Version> = path
+            .iter()
+            .take(path.len().saturating_sub(1))
+            .map(|hop| hop.to())
+            .collect();
+        self.validate_intermediate_charts_verifiable(intermediate_versions.as_slice())?;
+
+        for version in intermediate_versions {
+            console_logger::info(upgrade_intermediate_version(version).as_str(), "");
+
+            // The chart digests and the umbrella chart version are those of the target version.
+            let hop_args = Self {
+                to_version: Some(version.clone()),
+                chart_digest: None,
+                umbrella_chart_version: None,
+                umbrella_chart_digest: None,
+                auto_hop: false,
+                ..self.clone()
+            };
+            upgrade_event_client
+                .delete_upgrade_events(release_name.to_string())
+                .await?;
+            UpgradeResources::create_upgrade_resources(namespace, &hop_args).await?;
+            upgrade_event_client
+                .wait_for_upgrade_completion(release_name.to_string(), version)
+                .await?;
+            UpgradeResources::delete_upgrade_resources(namespace).await?;
+        }
+
+        Ok(())
+    }
+
+    /// The helm charts of the intermediate versions are pulled without a digest, so these can only
+    /// be verified with the keyring in the helm chart repository Secret. This fails early, before
+    /// any upgrade-job is created, if there is no such Secret and unverified helm charts are not
+    /// allowed.
+    fn validate_intermediate_charts_verifiable(&self, versions: &[&Version]) -> error::Result<()> {
+        if versions.is_empty() || self.chart_repo_secret.is_some() || self.allow_unverified_chart {
+            return Ok(());
+        }
+        error::UnverifiableIntermediateCharts {
+            versions: versions
+                .iter()
+                .map(|version| version.to_string())
+                .collect::<Vec<String>>()
+                .join(", "),
+        }
+        .fail()
+    }
+
     /// Handle the event and errors out for invalid helm command.
     async fn handle_upgrade_event(
         &self,
@@ -185,7 +410,7 @@ impl UpgradeArgs {
         namespace: &str,
     ) -> error::Result<()> {
         if let Some(action) = latest_event.action {
-            if action == "Validation Failed" {
+            if action == UPGRADE_VALIDATION_FAILED_ACTION {
                 if let Some(data) = latest_event.message {
                     let ev: UpgradeEvent = serde_json::from_str(data.as_str())
                         .context(error::EventSerdeDeserialization { event: data })?;
@@ -195,6 +420,8 @@ impl UpgradeArgs {
                 } else {
                     return error::MessageInEventNotPresent.fail();
                 }
+            } else if let Some(schedule) = &self.schedule {
+                console_logger::info(upgrade_scheduled(schedule.start_at()).as_str(), "");
             } else {
                 console_logger::info(UPGRADE_JOB_STARTED, "");
             }
@@ -203,36 +430,65 @@ impl UpgradeArgs {
     }
 
     /// Execute the upgrade command.
-    pub async fn execute(&self, namespace: &str) -> error::Result<()> {
+    pub async fn execute(&self, namespace: &str, output: &OutputFormat) -> error::Result<()> {
+        if let Some(UpgradeCommand::Backups(command)) = &self.command {
+            return command.execute(namespace, output).await;
+        }
+
         if self.dry_run {
-            self.dummy_apply(namespace).await
+            self.dummy_apply(namespace, output).await
         } else {
             self.apply(namespace).await
         }
     }
 
-    /// Dummy upgrade the resources.
-    pub async fn dummy_apply(&self, namespace: &str) -> error::Result<()> {
-        let mut pods_names: Vec<String> = Vec::new();
-        list_pods(AGENT_CORE_POD_LABEL, namespace, &mut pods_names).await?;
-        list_pods(API_REST_POD_LABEL, namespace, &mut pods_names).await?;
-        console_logger::info(CONTROL_PLANE_PODS_LIST, &pods_names.join("\n"));
+    /// Dummy upgrade the resources. The upgrade-job computes the upgrade plan, without upgrading.
+    pub async fn dummy_apply(&self, namespace: &str, output: &OutputFormat) -> error::Result<()> {
+        let uo = UpgradeResources::new(namespace).await?;
+        let job_name = upgrade_name_concat(&uo.release_name, UPGRADE_JOB_NAME_SUFFIX);
+        // The upgrade-job of an earlier upgrade would be mistaken for the dry-run's upgrade-job.
+        if uo
+            .job
+            .get_opt(&job_name)
+            .await
+            .context(error::GetUpgradeJob {
+                name: job_name.clone(),
+            })?
+            .is_some()
+        {
+            return error::UpgradeJobAlreadyPresent {
+                name: job_name,
+                namespace,
+            }
+            .fail();
+        }
 
-        let mut io_engine_pods_names: Vec<String> = Vec::new();
-        list_pods(IO_ENGINE_POD_LABEL, namespace, &mut io_engine_pods_names).await?;
-        if self.skip_data_plane_restart {
-            console_logger::info(
-                DATA_PLANE_PODS_LIST_SKIP_RESTART,
-                &io_engine_pods_names.join("\n"),
-            );
-        } else {
-            console_logger::info(DATA_PLANE_PODS_LIST, &io_engine_pods_names.join("\n"));
+        let plan_client = UpgradePlanClient::new(namespace, uo.release_name.as_str()).await?;
+        plan_client.delete().await?;
+
+        UpgradeResources::create_upgrade_resources(namespace, self).await?;
+        let plan = plan_client.wait_for_plan().await;
+        // The upgrade resources are removed, so that they don't block the upgrade.
+        UpgradeResources::delete_upgrade_resources(namespace).await?;
+        let plan = plan?;
+
+        match output {
+            OutputFormat::None => {
+                let mut pods_names: Vec<String> = Vec::new();
+                list_pods(AGENT_CORE_POD_LABEL, namespace, &mut pods_names).await?;
+                list_pods(API_REST_POD_LABEL, namespace, &mut pods_names).await?;
+
+                plan.print();
+                console_logger::info(CONTROL_PLANE_PODS_LIST, &pods_names.join("\n"));
+                console_logger::info(
+                    upgrade_dry_run_summary(UPGRADE_DRY_RUN_SUMMARY, self.to_version.as_ref())
+                        .as_str(),
+                    "",
+                );
+                Ok(())
+            }
+            _ => print_structured(&plan, output),
         }
-        console_logger::info(
-            upgrade_dry_run_summary(UPGRADE_DRY_RUN_SUMMARY).as_str(),
-            "",
-        );
-        Ok(())
     }
 }
 
@@ -266,13 +522,29 @@ pub(crate) async fn list_pods(
 
 /// Arguments to be passed for upgrade.
 #[derive(Debug, Clone, clap::Args)]
-pub struct GetUpgradeArgs {}
+pub struct GetUpgradeArgs {
+    /// Display every recorded upgrade event for the release, instead of only the latest one.
+    #[clap(global = false, long, default_value_t = false)]
+    pub history: bool,
+}
 
 impl GetUpgradeArgs {
     ///  Upgrade the resources.
-    pub async fn get_upgrade(&self, namespace: &str) -> error::Result<()> {
-        // Create resources for getting upgrade status
-        UpgradeEventClient::create_get_upgrade_resource(namespace).await
+    pub async fn get_upgrade(&self, namespace: &str, output: &OutputFormat) -> error::Result<()> {
+        let release_name = get_release_name(namespace).await?;
+        let upgrade_event_client = UpgradeEventClient::new(namespace).await?;
+
+        if self.history {
+            let history = upgrade_event_client
+                .get_upgrade_history(release_name)
+                .await?;
+            return log_upgrade_history(&history, output);
+        }
+
+        let latest_event = upgrade_event_client
+            .get_latest_upgrade_event(release_name)
+            .await?;
+        log_upgrade_result(&latest_event, output).await
     }
 }
 
@@ -283,11 +555,69 @@ pub(crate) struct UpgradeEvent {
     from_version: String,
     to_version: String,
     message: String,
+    #[serde(default)]
+    progress: Option<UpgradeProgress>,
+    #[serde(default)]
+    warnings: Vec<String>,
+}
+
+/// This struct is used to deserialize the upgrade history record, and to print the upgrade status.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct UpgradeHistoryRecord {
+    #[serde(default)]
+    timestamp: String,
+    #[serde(default)]
+    event_type: String,
+    #[serde(default)]
+    action: String,
+    from_version: String,
+    to_version: String,
+    message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    progress: Option<UpgradeProgress>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<String>,
+}
+
+impl TryFrom<&Event> for UpgradeHistoryRecord {
+    type Error = error::Error;
+
+    fn try_from(event: &Event) -> error::Result<Self> {
+        let data = event
+            .message
+            .clone()
+            .ok_or(error::MessageInEventNotPresent.build())?;
+        let ev: UpgradeEvent = serde_json::from_str(data.as_str())
+            .context(error::EventSerdeDeserialization { event: data })?;
+
+        let timestamp = event
+            .event_time
+            .as_ref()
+            .map(|time| time.0)
+            .or(event.last_timestamp.as_ref().map(|time| time.0))
+            .map(|time| time.to_rfc3339())
+            .unwrap_or_default();
+
+        Ok(Self {
+            timestamp,
+            event_type: event.type_.clone().unwrap_or_default(),
+            action: event.action.clone().unwrap_or_default(),
+            from_version: ev.from_version,
+            to_version: ev.to_version,
+            message: ev.message,
+            progress: ev.progress,
+            warnings: ev.warnings,
+        })
+    }
 }
 
 /// Resource to be created to get upgrade status.
 struct UpgradeEventClient {
     upgrade_event: Api<Event>,
+    upgrade_history: Api<ConfigMap>,
+    upgrade_job: Api<Job>,
+    namespace: String,
 }
 
 /// Methods implemented by UpgradeEventClient.
@@ -297,7 +627,10 @@ impl UpgradeEventClient {
             .await
             .context(error::K8sClientGeneration)?;
         Ok(Self {
-            upgrade_event: Api::<Event>::namespaced(client, ns),
+            upgrade_event: Api::<Event>::namespaced(client.clone(), ns),
+            upgrade_history: Api::<ConfigMap>::namespaced(client.clone(), ns),
+            upgrade_job: Api::<Job>::namespaced(client, ns),
+            namespace: ns.to_string(),
         })
     }
 
@@ -320,8 +653,8 @@ impl UpgradeEventClient {
         Ok(())
     }
 
-    /// Fetch latest upgrade event.
-    pub async fn get_latest_upgrade_event(&self, release_name: String) -> error::Result<Event> {
+    /// Fetch upgrade events, sorted from the oldest to the latest.
+    pub async fn list_upgrade_events(&self, release_name: String) -> error::Result<Vec<Event>> {
         let selector = upgrade_event_selector(release_name.as_str(), UPGRADE_JOB_NAME_SUFFIX);
         let event_lp = ListParams {
             field_selector: Some(selector.clone()),
@@ -337,38 +670,197 @@ impl UpgradeEventClient {
             .filter(|e| e.reason == Some(upgrade_event_reason()))
             .collect::<Vec<_>>();
 
-        event_list.sort_by(|a, b| b.event_time.cmp(&a.event_time));
-        let latest_event = event_list
-            .first()
-            .ok_or(error::UpgradeEventNotPresent.build())?;
-        Ok(latest_event.to_owned())
+        event_list.sort_by(|a, b| a.event_time.cmp(&b.event_time));
+        Ok(event_list)
     }
 
-    /// Create resources for fetching upgrade events.
-    pub async fn create_get_upgrade_resource(ns: &str) -> error::Result<()> {
-        let release_name = get_release_name(ns).await?;
-        let upgrade_event_client = UpgradeEventClient::new(ns).await?;
-        let latest_event = upgrade_event_client
-            .get_latest_upgrade_event(release_name)
-            .await?;
-        log_upgrade_result(&latest_event).await?;
-        Ok(())
+    /// Fetch latest upgrade event.
+    pub async fn get_latest_upgrade_event(&self, release_name: String) -> error::Result<Event> {
+        self.list_upgrade_events(release_name)
+            .await?
+            .pop()
+            .ok_or(error::UpgradeEventNotPresent.build())
+    }
+
+    /// Waits for the upgrade to the version to complete, i.e. for an upgrade event with the action
+    /// of a successful or a failed upgrade. Fails if the upgrade fails, if the upgrade-job fails
+    /// without publishing such an event, e.g. if it cannot start, or if the upgrade does not
+    /// complete in time. An upgrade which completed with warnings is not a failure.
+    pub(crate) async fn wait_for_upgrade_completion(
+        &self,
+        release_name: String,
+        version: &Version,
+    ) -> error::Result<()> {
+        for _ in 0 .. UPGRADE_COMPLETION_MAX_POLL_ATTEMPTS {
+            tokio::time::sleep(Duration::from_secs(UPGRADE_COMPLETION_POLL_INTERVAL_SECS)).await;
+
+            // There are no upgrade events until the upgrade-job starts.
+            if let Some(latest_event) = self.list_upgrade_events(release_name.clone()).await?.pop()
+            {
+                match latest_event.action.as_deref() {
+                    Some(UPGRADE_SUCCESSFUL_ACTION | UPGRADE_COMPLETED_WITH_WARNINGS_ACTION) => {
+                        return Ok(())
+                    }
+                    Some(UPGRADE_FAILED_ACTION | UPGRADE_VALIDATION_FAILED_ACTION) => {
+                        let status = UpgradeHistoryRecord::try_from(&latest_event)?;
+                        return error::IntermediateUpgradeFailed {
+                            version: version.to_string(),
+                            message: status.message,
+                        }
+                        .fail();
+                    }
+                    _ => {}
+                }
+            }
+
+            let job_name = upgrade_name_concat(release_name.as_str(), UPGRADE_JOB_NAME_SUFFIX);
+            let job = self
+                .upgrade_job
+                .get_opt(job_name.as_str())
+                .await
+                .context(error::GetUpgradeJob {
+                    name: job_name.clone(),
+                })?
+                .ok_or(
+                    error::UpgradeJobNotPresent {
+                        name: job_name,
+                        namespace: self.namespace.clone(),
+                    }
+                    .build(),
+                )?;
+            if let Some(message) = upgrade_job_failure(&job) {
+                return error::IntermediateUpgradeFailed {
+                    version: version.to_string(),
+                    message,
+                }
+                .fail();
+            }
+        }
+
+        error::IntermediateUpgradeTimedOut {
+            version: version.to_string(),
+            attempts: UPGRADE_COMPLETION_MAX_POLL_ATTEMPTS,
+        }
+        .fail()
+    }
+
+    /// Fetch the upgrade history of the release. The durable history record kept by the upgrade
+    /// job is preferred, as upgrade events expire. The upgrade events are used when the record
+    /// is absent.
+    pub async fn get_upgrade_history(
+        &self,
+        release_name: String,
+    ) -> error::Result<Vec<UpgradeHistoryRecord>> {
+        let cm_name = upgrade_history_configmap_name(release_name.as_str());
+        let maybe_cm = self
+            .upgrade_history
+            .get_opt(cm_name.as_str())
+            .await
+            .context(error::GetUpgradeHistory {
+                name: cm_name.clone(),
+            })?;
+
+        if let Some(history) = history_from_configmap(maybe_cm, cm_name.as_str())? {
+            return Ok(history);
+        }
+
+        let history = self
+            .list_upgrade_events(release_name)
+            .await?
+            .iter()
+            .map(UpgradeHistoryRecord::try_from)
+            .collect::<error::Result<Vec<_>>>()?;
+        if history.is_empty() {
+            return error::UpgradeEventNotPresent.fail();
+        }
+        Ok(history)
     }
 }
 
+/// Reads the upgrade history records from the upgrade history ConfigMap, if it holds any.
+fn history_from_configmap(
+    maybe_cm: Option<ConfigMap>,
+    cm_name: &str,
+) -> error::Result<Option<Vec<UpgradeHistoryRecord>>> {
+    let Some(history) = maybe_cm
+        .and_then(|cm| cm.data)
+        .and_then(|mut data| data.remove(UPGRADE_HISTORY_DATA_KEY))
+    else {
+        return Ok(None);
+    };
+
+    serde_json::from_str(history.as_str())
+        .map(Some)
+        .context(error::UpgradeHistoryDeserialization { name: cm_name })
+}
+
 /// Print the upgrade iutput to console.
-pub async fn log_upgrade_result(event: &Event) -> error::Result<()> {
-    _ = match event.message.clone() {
-        Some(data) => {
-            let e: UpgradeEvent = serde_json::from_str(data.as_str())
-                .context(error::EventSerdeDeserialization { event: data })?;
-            println!("Upgrade From: {}", e.from_version);
-            println!("Upgrade To: {}", e.to_version);
-            println!("Upgrade Status: {}", e.message);
+pub async fn log_upgrade_result(event: &Event, output: &OutputFormat) -> error::Result<()> {
+    let status = UpgradeHistoryRecord::try_from(event)?;
+    match output {
+        OutputFormat::None => {
+            println!("Upgrade From: {}", status.from_version);
+            println!("Upgrade To: {}", status.to_version);
+            println!("Upgrade Status: {}", status.message);
+            if let Some(progress) = &status.progress {
+                progress.print();
+            }
+            if !status.warnings.is_empty() {
+                println!("Upgrade Warnings:");
+                for warning in status.warnings.iter() {
+                    println!("  - {warning}");
+                }
+            }
             Ok(())
         }
-        None => error::MessageInEventNotPresent.fail(),
-    };
+        _ => print_structured(&status, output),
+    }
+}
+
+/// Print the upgrade history to console.
+pub(crate) fn log_upgrade_history(
+    history: &[UpgradeHistoryRecord],
+    output: &OutputFormat,
+) -> error::Result<()> {
+    match output {
+        OutputFormat::None => {
+            println!(
+                "{:<32} {:<24} {:<14} {:<14} MESSAGE",
+                "TIMESTAMP", "ACTION", "FROM", "TO"
+            );
+            for record in history {
+                println!(
+                    "{:<32} {:<24} {:<14} {:<14} {}",
+                    record.timestamp,
+                    record.action,
+                    record.from_version,
+                    record.to_version,
+                    record.message
+                );
+            }
+            Ok(())
+        }
+        _ => print_structured(history, output),
+    }
+}
+
+/// Print a serializable value to console, as YAML or JSON.
+pub(crate) fn print_structured<T>(value: &T, output: &OutputFormat) -> error::Result<()>
+where
+    T: Serialize + ?Sized,
+{
+    match output {
+        OutputFormat::Yaml => {
+            print!(
+                "{}",
+                serde_yaml::to_string(value).context(error::YamlSerialization)?
+            )
+        }
+        OutputFormat::Json | OutputFormat::None => println!(
+            "{}",
+            serde_json::to_string_pretty(value).context(error::JsonSerialization)?
+        ),
+    }
     Ok(())
 }
 
@@ -705,25 +1197,12 @@ impl UpgradeResources {
         } else {
             match action {
                 Actions::Create => {
-                    let upgrade_job_image_tag = get_image_version_tag();
-                    let rest_deployment = get_deployment_for_rest(ns).await?;
-                    let img = ImageProperties::try_from(rest_deployment)?;
+                    let (upgrade_image, img) = upgrade_job_image(ns, args).await?;
                     let set_file = create_helm_set_file_args(args, set_file_map).await?;
 
-                    // Image registry override check.
-                    let registry: &str = match args.registry {
-                        Some(ref registry_override) => registry_override,
-                        None => img.registry(),
-                    };
-
                     let upgrade_deploy = objects::upgrade_job(
                         ns,
-                        upgrade_image_concat(
-                            registry,
-                            UPGRADE_JOB_IMAGE_REPO,
-                            &upgrade_job_img(),
-                            upgrade_job_image_tag.as_str(),
-                        ),
+                        upgrade_image,
                         self.release_name.clone(),
                         args,
                         set_file.unwrap_or_default(),
@@ -846,6 +1325,39 @@ pub(crate) async fn get_release_name(ns: &str) -> error::Result<String> {
     }
 }
 
+/// Returns the reason of the upgrade-job's failure, if it has failed, i.e. if it has the Failed
+/// condition, or if its Pods have failed more times than its backoff limit allows.
+fn upgrade_job_failure(job: &Job) -> Option<String> {
+    let status = job.status.as_ref()?;
+
+    let failed_condition = status
+        .conditions
+        .iter()
+        .flatten()
+        .find(|condition| condition.type_.eq("Failed") && condition.status.eq("True"));
+    if let Some(condition) = failed_condition {
+        return Some(format!(
+            "the upgrade-job failed: {}",
+            condition
+                .message
+                .as_deref()
+                .or(condition.reason.as_deref())
+                .unwrap_or("unknown reason")
+        ));
+    }
+
+    // The backoff limit defaults to 6 in Kubernetes.
+    let backoff_limit = job
+        .spec
+        .as_ref()
+        .and_then(|spec| spec.backoff_limit)
+        .unwrap_or(6);
+    status
+        .failed
+        .filter(|failed| *failed > backoff_limit)
+        .map(|failed| format!("the upgrade-job's Pods failed {failed} times"))
+}
+
 /// Return true if upgrade job is completed
 pub(crate) async fn is_upgrade_job_completed(ns: &str) -> error::Result<bool> {
     let uo = UpgradeResources::new(ns).await?;
@@ -881,7 +1393,32 @@ pub(crate) async fn is_upgrade_job_completed(ns: &str) -> error::Result<bool> {
     }
 }
 
-struct ImageProperties {
+/// Returns the upgrade-job image and the image properties of the REST API deployment, which the
+/// upgrade-job image pull is modelled after.
+pub(crate) async fn upgrade_job_image(
+    ns: &str,
+    args: &UpgradeArgs,
+) -> error::Result<(String, ImageProperties)> {
+    let upgrade_job_image_tag = get_image_version_tag();
+    let rest_deployment = get_deployment_for_rest(ns).await?;
+    let img = ImageProperties::try_from(rest_deployment)?;
+
+    // Image registry override check.
+    let registry: &str = match args.registry {
+        Some(ref registry_override) => registry_override,
+        None => img.registry(),
+    };
+
+    let image = upgrade_image_concat(
+        registry,
+        UPGRADE_JOB_IMAGE_REPO,
+        &upgrade_job_img(),
+        upgrade_job_image_tag.as_str(),
+    );
+    Ok((image, img))
+}
+
+pub(crate) struct ImageProperties {
     pull_secrets: Option<Vec<k8s_openapi::api::core::v1::LocalObjectReference>>,
     registry: String,
     pull_policy: Option<String>,
@@ -928,7 +1465,9 @@ impl TryFrom<Deployment> for ImageProperties {
 }
 
 impl ImageProperties {
-    fn pull_secrets(&self) -> Option<Vec<k8s_openapi::api::core::v1::LocalObjectReference>> {
+    pub(crate) fn pull_secrets(
+        &self,
+    ) -> Option<Vec<k8s_openapi::api::core::v1::LocalObjectReference>> {
         self.pull_secrets.clone()
     }
 
@@ -936,7 +1475,7 @@ impl ImageProperties {
         self.registry.as_str()
     }
 
-    fn pull_policy(&self) -> Option<String> {
+    pub(crate) fn pull_policy(&self) -> Option<String> {
         self.pull_policy.clone()
     }
 }
@@ -1013,3 +1552,184 @@ pub(crate) async fn create_helm_set_file_args(
     }
     Ok(None)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{history_from_configmap, upgrade_job_failure, UpgradeArgs, UpgradeHistoryRecord};
+    use crate::plugin::error::Error;
+    use constants::UPGRADE_HISTORY_DATA_KEY;
+    use k8s_openapi::{
+        api::{
+            batch::v1::{Job, JobCondition, JobSpec, JobStatus},
+            core::v1::{ConfigMap, Event},
+        },
+        apimachinery::pkg::apis::meta::v1::MicroTime,
+        chrono::{TimeZone, Utc},
+    };
+    use semver::Version;
+    use std::collections::BTreeMap;
+
+    const CM_NAME: &str = "mayastor-upgrade-history";
+
+    fn history_configmap(history: &str) -> ConfigMap {
+        ConfigMap {
+            data: Some(BTreeMap::from([(
+                UPGRADE_HISTORY_DATA_KEY.to_string(),
+                history.to_string(),
+            )])),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn history_is_read_from_configmap() {
+        let cm = history_configmap(
+            r#"[
+                {
+                    "timestamp": "2024-05-01T10:00:00+00:00",
+                    "eventType": "Normal",
+                    "action": "Upgrading",
+                    "fromVersion": "2.5.0",
+                    "toVersion": "2.6.0",
+                    "message": "Upgrading to 2.6.0"
+                },
+                {
+                    "timestamp": "2024-05-01T10:05:00+00:00",
+                    "eventType": "Normal",
+                    "action": "Successful",
+                    "fromVersion": "2.5.0",
+                    "toVersion": "2.6.0",
+                    "message": "Upgraded to 2.6.0"
+                }
+            ]"#,
+        );
+
+        let history = history_from_configmap(Some(cm), CM_NAME).unwrap().unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].action, "Upgrading");
+        assert_eq!(history[1].action, "Successful");
+        assert_eq!(history[1].from_version, "2.5.0");
+        assert_eq!(history[1].to_version, "2.6.0");
+        assert!(history[1].progress.is_none());
+    }
+
+    #[test]
+    fn missing_history_falls_back() {
+        assert!(history_from_configmap(None, CM_NAME).unwrap().is_none());
+        assert!(history_from_configmap(Some(ConfigMap::default()), CM_NAME)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn malformed_history_is_an_error() {
+        let cm = history_configmap("not json");
+        assert!(history_from_configmap(Some(cm), CM_NAME).is_err());
+    }
+
+    #[test]
+    fn history_record_from_event() {
+        let event = Event {
+            type_: Some("Warning".to_string()),
+            action: Some("Failed".to_string()),
+            event_time: Some(MicroTime(
+                Utc.with_ymd_and_hms(2024, 5, 1, 10, 0, 0).unwrap(),
+            )),
+            message: Some(
+                r#"{"fromVersion":"2.5.0","toVersion":"2.6.0","message":"Upgrade failed"}"#
+                    .to_string(),
+            ),
+            ..Default::default()
+        };
+
+        let record = UpgradeHistoryRecord::try_from(&event).unwrap();
+        assert_eq!(record.timestamp, "2024-05-01T10:00:00+00:00");
+        assert_eq!(record.event_type, "Warning");
+        assert_eq!(record.action, "Failed");
+        assert_eq!(record.message, "Upgrade failed");
+        assert!(record.warnings.is_empty());
+
+        let event = Event {
+            message: None,
+            ..event
+        };
+        assert!(UpgradeHistoryRecord::try_from(&event).is_err());
+    }
+
+    #[test]
+    fn intermediate_charts_verification() {
+        let version = Version::new(2, 4, 0);
+        let args = UpgradeArgs::new();
+
+        // An upgrade without intermediate versions pulls only the target version's helm chart.
+        assert!(args.validate_intermediate_charts_verifiable(&[]).is_ok());
+        assert!(matches!(
+            args.validate_intermediate_charts_verifiable(&[&version]),
+            Err(Error::UnverifiableIntermediateCharts { versions }) if versions == "2.4.0"
+        ));
+
+        let args = UpgradeArgs {
+            chart_repo_secret: Some("chart-repo".to_string()),
+            ..UpgradeArgs::new()
+        };
+        assert!(args
+            .validate_intermediate_charts_verifiable(&[&version])
+            .is_ok());
+
+        let args = UpgradeArgs {
+            allow_unverified_chart: true,
+            ..UpgradeArgs::new()
+        };
+        assert!(args
+            .validate_intermediate_charts_verifiable(&[&version])
+            .is_ok());
+    }
+
+    #[test]
+    fn upgrade_job_failure_from_status() {
+        let job = |spec: Option<JobSpec>, status: JobStatus| Job {
+            spec,
+            status: Some(status),
+            ..Default::default()
+        };
+
+        // A running upgrade-job, and one which has retried its Pod.
+        assert_eq!(upgrade_job_failure(&Job::default()), None);
+        let running = JobStatus {
+            active: Some(1),
+            failed: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(upgrade_job_failure(&job(None, running)), None);
+
+        // An upgrade-job with the Failed condition.
+        let failed = JobStatus {
+            conditions: Some(vec![JobCondition {
+                type_: "Failed".to_string(),
+                status: "True".to_string(),
+                reason: Some("BackoffLimitExceeded".to_string()),
+                message: Some("Job has reached the specified backoff limit".to_string()),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        };
+        assert_eq!(
+            upgrade_job_failure(&job(None, failed)).unwrap(),
+            "the upgrade-job failed: Job has reached the specified backoff limit"
+        );
+
+        // An upgrade-job whose Pods have failed more times than its backoff limit.
+        let spec = JobSpec {
+            backoff_limit: Some(1),
+            ..Default::default()
+        };
+        let exhausted = JobStatus {
+            failed: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(
+            upgrade_job_failure(&job(Some(spec), exhausted)).unwrap(),
+            "the upgrade-job's Pods failed 2 times"
+        );
+    }
+}
@@ -1,16 +1,41 @@
 use crate::plugin::constants::release_version;
+use k8s_openapi::chrono::{DateTime, Utc};
+use semver::Version;
 
 /// Warning to users before doing an upgrade.
 pub const UPGRADE_WARNING: &str =  "\nVolumes which make use of a single volume replica instance will be unavailable for some time during upgrade.\nIt is recommended that you do not create new volumes which make use of only one volume replica.";
 
 /// Warning to users before doing an upgrade.
-pub const REBUILD_WARNING: &str =  "\nThe cluster is rebuilding replica of some volumes.\nTo skip this validation please run after some time or re-run with '--skip-replica-rebuild` flag.";
+pub const REBUILD_WARNING: &str =  "The cluster is rebuilding replica of some volumes.\nTo skip this validation please run after some time or re-run with '--skip-replica-rebuild` flag.";
 
 /// Warning to users before doing an upgrade
-pub const SINGLE_REPLICA_VOLUME_WARNING: &str =  "\nThe list below shows the single replica volumes in cluster.\nThese single replica volumes may not be accessible during upgrade.\nTo skip this validation, please re-run with '--skip-single-replica-volume-validation` flag.";
+pub const SINGLE_REPLICA_VOLUME_WARNING: &str =  "The single replica volumes listed above may not be accessible during upgrade.\nTo skip this validation, please re-run with '--skip-single-replica-volume-validation` flag.";
 
 /// Warning to users before doing an upgrade.
-pub const CORDONED_NODE_WARNING: &str =  "\nOne or more nodes in this cluster are in a Mayastor cordoned state.\nThis implies that the storage space of DiskPools on these nodes cannot be utilized for volume replica rebuilds.\nPlease ensure remaining storage nodes have enough available DiskPool space to accommodate volume replica rebuilds,\nthat get triggered during the upgrade process.\nTo skip this validation, please re-run with '--skip-cordoned-node-validation` flag.\nBelow is a list of the Mayastor cordoned nodes:";
+pub const CORDONED_NODE_WARNING: &str =  "The storage space of DiskPools on the Mayastor cordoned nodes listed above cannot be utilized for volume replica rebuilds.\nPlease ensure remaining storage nodes have enough available DiskPool space to accommodate volume replica rebuilds,\nthat get triggered during the upgrade process.\nTo skip this validation, please re-run with '--skip-cordoned-node-validation` flag.";
+
+/// Remediation for DiskPools with low free capacity.
+pub const POOL_CAPACITY_HEADROOM_WARNING: &str = "Volume replica rebuilds triggered during the upgrade may fail on the DiskPools listed above.\nPlease free up space or add capacity to these DiskPools before upgrading.";
+
+/// Remediation for Kubernetes Nodes which are not Ready.
+pub const NODES_NOT_READY_WARNING: &str = "The io-engine pods on NotReady nodes cannot be restarted during upgrade.\nPlease bring the nodes listed above back to Ready, or remove them from the cluster before upgrading.";
+
+/// Remediation for PodDisruptionBudgets which allow no disruptions.
+pub const PDB_CONFLICT_WARNING: &str = "The PodDisruptionBudgets listed above allow no disruptions, and may block pod evictions while nodes are drained during upgrade.\nPlease scale up the affected workloads or relax the PodDisruptionBudgets before upgrading.";
+
+/// Remediation for a helm release in a pending state.
+pub const HELM_RELEASE_PENDING_WARNING: &str = "Another helm operation is in progress on the release, or an earlier one did not complete.\nPlease wait for it to complete, or rollback the release using 'helm rollback', before upgrading.";
+
+/// Remediation for an image registry which is not reachable from the cluster.
+pub const IMAGE_REGISTRY_UNREACHABLE_WARNING: &str = "The upgrade-job image could not be pulled from the cluster.\nPlease check the cluster's network access to the registry and the image pull secrets,\nor re-run with the '--registry' option to use a reachable registry mirror.";
+
+/// Remediation for a preflight check which could not be completed.
+pub const PREFLIGHT_CHECK_ERROR: &str =
+    "The check could not be completed. Please resolve the error and re-run upgrade.";
+
+/// Summary of preflight checks which failed.
+pub const PREFLIGHT_CHECKS_FAILED: &str =
+    "\nOne or more preflight checks failed. The upgrade will not proceed.";
 
 /// Info about the control plane pods.
 pub const CONTROL_PLANE_PODS_LIST: &str =
@@ -24,10 +49,36 @@ pub const DATA_PLANE_PODS_LIST: &str =
 pub const DATA_PLANE_PODS_LIST_SKIP_RESTART: &str =
     "\nList of data plane pods which need to be manually restarted to reflect upgrade as --skip-data-plane-restart flag is passed during upgrade.";
 
+/// Info about the helm values changes in the upgrade plan.
+pub const UPGRADE_PLAN_VALUES_CHANGES: &str =
+    "\nChanges to the helm values of the release, which will be applied during upgrade.";
+
+/// Info about the container image changes in the upgrade plan.
+pub const UPGRADE_PLAN_IMAGE_CHANGES: &str =
+    "\nChanges to the container images of the Deployments, DaemonSets and StatefulSets.";
+
+/// Info about the new Kubernetes objects in the upgrade plan.
+pub const UPGRADE_PLAN_ADDED_OBJECTS: &str =
+    "\nList of Kubernetes objects which will be created during upgrade.";
+
+/// Info about the removed Kubernetes objects in the upgrade plan.
+pub const UPGRADE_PLAN_REMOVED_OBJECTS: &str =
+    "\nList of Kubernetes objects which will be deleted during upgrade.";
+
+/// Info about the CustomResourceDefinition changes in the upgrade plan.
+pub const UPGRADE_PLAN_CRD_CHANGES: &str =
+    "\nList of CustomResourceDefinitions which will be changed during upgrade.";
+
+/// Info about the volume replica rebuilds in the upgrade plan.
+pub const UPGRADE_PLAN_ESTIMATED_REBUILDS: &str =
+    "\nEstimated number of volume replica rebuilds during the data plane upgrade.";
+
 /// Append the release name to k8s objects.
-pub(crate) fn upgrade_dry_run_summary(message: &str) -> String {
-    let tag = release_version();
-    let version = tag.unwrap_or("develop".to_string());
+pub(crate) fn upgrade_dry_run_summary(message: &str, to_version: Option<&Version>) -> String {
+    let version = match to_version {
+        Some(version) => version.to_string(),
+        None => release_version().unwrap_or("develop".to_string()),
+    };
     format!("{message} : {version}")
 }
 
@@ -39,13 +90,33 @@ pub const UPGRADE_DRY_RUN_SUMMARY: &str =
 pub const UPGRADE_JOB_STARTED: &str =
     "\nThe upgrade has started. You can see the recent upgrade status using 'get upgrade-status` command.";
 
+/// Information about the start time of a scheduled upgrade.
+pub(crate) fn upgrade_scheduled(start_at: DateTime<Utc>) -> String {
+    format!(
+        "\nThe upgrade is scheduled to start at {}. You can see the recent upgrade status using 'get upgrade-status` command.",
+        start_at.to_rfc3339()
+    )
+}
+
 /// Upgrade path not valid.
 pub const UPGRADE_PATH_NOT_VALID: &str =
-    "\nThe upgrade path is not valid. The source version is in the list of unsupported versions:";
+    "The upgrade path is not valid. The source version is in the list of unsupported versions";
+
+/// Upgrade path not supported as per the compatibility matrix.
+pub const UPGRADE_PATH_NOT_SUPPORTED: &str =
+    "The upgrade path is not supported. Please upgrade to a different version.";
+
+/// Upgrade path must go through intermediate versions.
+pub const UPGRADE_PATH_MULTI_HOP: &str =
+    "Upgrade to each of the intermediate versions in order, or try again with '--auto-hop' to upgrade through them one after the other.";
+
+/// Upgrade path goes through intermediate versions with '--auto-hop'.
+pub const UPGRADE_PATH_AUTO_HOP: &str =
+    "The upgrade goes through each of the intermediate versions in order, with an upgrade job for each one.";
 
 /// Upgrade to unsupported version not valid.
 pub const UPGRADE_TO_UNSUPPORTED_VERSION: &str =
-    "\nUpgrade failed as destination version is unsupported. Please try with `--skip-upgrade-path-validation-for-unsupported-version.`";
+    "Upgrade failed as destination version is unsupported. Please try with `--skip-upgrade-path-validation-for-unsupported-version.`";
 
 /// Delete an incomplete job.
 pub const DELETE_INCOMPLETE_JOB: &str =
@@ -63,3 +134,14 @@ pub const STABLE_TO_UNSTABLE_UPGRADE: &str =
 pub const HIGHER_TO_LOWER_SEMVER_UPGRADE: &str =
     "Cannot upgrade from a higher version to a lower version. \
 If this is intentional, try again with '--skip-upgrade-path-validation-for-unsupported-version'";
+
+/// Info about the helm values in the pre-upgrade backup.
+pub const UPGRADE_BACKUP_VALUES: &str = "\nHelm values of the release before upgrade.";
+
+/// Info about the etcd dump in the pre-upgrade backup.
+pub const UPGRADE_BACKUP_ETCD_DUMP: &str = "\nControl-plane keys in etcd before upgrade.";
+
+/// Info about the upgrade to an intermediate version with '--auto-hop'.
+pub fn upgrade_intermediate_version(version: &Version) -> String {
+    format!("\nUpgrading to intermediate version {version}...")
+}
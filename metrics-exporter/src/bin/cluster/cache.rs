@@ -0,0 +1,201 @@
+use crate::{
+    client::{
+        node::Nodes, pool::Pools, rest_client::RestClient, snapshot::Snapshots, volume::Volumes,
+    },
+    error::ExporterError,
+};
+use metrics_exporter::poll::{PollStatus, PollStatuses};
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use std::{sync::Mutex, time::Duration};
+use strum_macros::{AsRefStr, EnumIter};
+use tokio::time::MissedTickBehavior;
+use tracing::error;
+
+static CACHE: OnceCell<Mutex<Cache>> = OnceCell::new();
+
+/// Cache to store data that has to be exposed though metrics-exporter.
+pub(crate) struct Cache {
+    data: Data,
+}
+
+/// Wrapper over all the data that has to be stored in cache.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub(crate) struct Data {
+    /// Contains Volume status and replica topology data.
+    volumes: Volumes,
+    /// Contains Pool status data.
+    pools: Pools,
+    /// Contains Node status and cordon and drain state data.
+    nodes: Nodes,
+    /// Contains Volume snapshots data.
+    snapshots: Snapshots,
+    /// Contains the outcome of the polls of each of the sources.
+    poll_status: PollStatuses<PollSource>,
+}
+
+/// The sources of the data which is polled from the control plane.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, AsRefStr, EnumIter)]
+#[strum(serialize_all = "snake_case")]
+pub(crate) enum PollSource {
+    Volumes,
+    Pools,
+    Nodes,
+    Snapshots,
+}
+
+impl Cache {
+    /// Initialize the cache with default value.
+    pub(crate) fn initialize(data: Data) {
+        CACHE.get_or_init(|| Mutex::new(Self { data }));
+    }
+
+    /// Returns cache.
+    pub(crate) fn get_cache() -> &'static Mutex<Cache> {
+        CACHE.get().expect("Cache is not initialized")
+    }
+
+    /// Get a reference to Volumes.
+    pub(crate) fn volume(&self) -> &Volumes {
+        &self.data.volumes
+    }
+
+    /// Get a reference to Pools.
+    pub(crate) fn pool(&self) -> &Pools {
+        &self.data.pools
+    }
+
+    /// Get a reference to Nodes.
+    pub(crate) fn node(&self) -> &Nodes {
+        &self.data.nodes
+    }
+
+    /// Get a reference to Snapshots.
+    pub(crate) fn snapshot(&self) -> &Snapshots {
+        &self.data.snapshots
+    }
+
+    /// Get the outcome of the polls of the source.
+    pub(crate) fn poll_status(&self, source: PollSource) -> PollStatus {
+        self.data.poll_status.get(source)
+    }
+
+    /// Returns the data of a poll of the source, and records its outcome. The data is invalidated
+    /// in case of error.
+    fn store<T: Default>(&mut self, source: PollSource, polled: Result<T, ExporterError>) -> T {
+        self.data.poll_status.record(source, polled.is_ok());
+        match polled {
+            Ok(data) => data,
+            Err(error) => {
+                error!(
+                    ?error,
+                    source = source.as_ref(),
+                    "Error polling the control plane, invalidating cache"
+                );
+                T::default()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+impl Cache {
+    /// Creates a cache with the data of the polls, rather than the global cache, for the
+    /// collector tests.
+    pub(crate) fn polled(
+        volumes: Result<Volumes, ExporterError>,
+        pools: Result<Pools, ExporterError>,
+        nodes: Result<Nodes, ExporterError>,
+        snapshots: Result<Snapshots, ExporterError>,
+    ) -> Self {
+        let mut cache = Self {
+            data: Data::default(),
+        };
+        cache.data.volumes = cache.store(PollSource::Volumes, volumes);
+        cache.data.pools = cache.store(PollSource::Pools, pools);
+        cache.data.nodes = cache.store(PollSource::Nodes, nodes);
+        cache.data.snapshots = cache.store(PollSource::Snapshots, snapshots);
+        cache
+    }
+}
+
+/// Returns true if the last poll of any of the sources succeeded, i.e. if the data of the control
+/// plane is being served.
+pub(crate) fn is_ready() -> bool {
+    Cache::get_cache()
+        .lock()
+        .map(|cache| cache.data.poll_status.any_up())
+        .unwrap_or_default()
+}
+
+/// Populates Resource cache struct, and records the outcome of the poll of each of the sources.
+pub(crate) async fn store_resource_data(client: &RestClient) {
+    let volumes = client.list_volumes().await;
+    let pools = client.list_pools().await;
+    let nodes = client.list_nodes().await;
+    let snapshots = client.list_snapshots().await;
+
+    let mut cache = match Cache::get_cache().lock() {
+        Ok(cache) => cache,
+        Err(error) => {
+            error!(%error, "Error while getting cache resource");
+            return;
+        }
+    };
+    cache.data.volumes = cache.store(PollSource::Volumes, volumes);
+    cache.data.pools = cache.store(PollSource::Pools, pools);
+    cache.data.nodes = cache.store(PollSource::Nodes, nodes);
+    cache.data.snapshots = cache.store(PollSource::Snapshots, snapshots);
+}
+
+/// Populates the cache from the control plane at every interval, so that the scrapes are served
+/// from the cache without waiting on the control plane. A poll which takes longer than the
+/// interval delays the next one, rather than having polls pile up.
+pub(crate) async fn poll_resource_data(client: RestClient, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        ticker.tick().await;
+        store_resource_data(&client).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cache, Data, PollSource};
+    use crate::error::ExporterError;
+
+    #[test]
+    fn store_records_poll_outcome() {
+        let mut cache = Cache {
+            data: Data::default(),
+        };
+        assert!(!cache.data.poll_status.any_up());
+
+        let stored = cache.store(PollSource::Pools, Ok(vec![1, 2]));
+        let status = cache.poll_status(PollSource::Pools);
+        assert_eq!(stored, vec![1, 2]);
+        assert!(status.up());
+        assert!(status.last_success().is_some());
+        assert!(cache.data.poll_status.any_up());
+
+        // A failed poll invalidates the data, but keeps the time of the last successful poll.
+        let stored: Vec<i32> = cache.store(
+            PollSource::Pools,
+            Err(ExporterError::RestResponseError("unavailable".to_string())),
+        );
+        assert!(stored.is_empty());
+        assert!(!cache.poll_status(PollSource::Pools).up());
+        assert_eq!(
+            cache.poll_status(PollSource::Pools).last_success(),
+            status.last_success()
+        );
+        assert!(!cache.data.poll_status.any_up());
+
+        // The sources are recorded independently.
+        cache.store(PollSource::Volumes, Ok(vec![3]));
+        assert!(cache.poll_status(PollSource::Volumes).up());
+        assert!(!cache.poll_status(PollSource::Nodes).up());
+        assert!(cache.data.poll_status.any_up());
+    }
+}
@@ -0,0 +1,10 @@
+/// NodeInfo module.
+pub(crate) mod node;
+/// PoolInfo module.
+pub(crate) mod pool;
+/// REST client module.
+pub(crate) mod rest_client;
+/// SnapshotInfo module.
+pub(crate) mod snapshot;
+/// VolumeInfo module.
+pub(crate) mod volume;
@@ -0,0 +1,85 @@
+use openapi::models::{CordonDrainState, NodeStatus};
+use serde::{Deserialize, Serialize};
+
+/// This stores the status and the cordon and drain state of a node.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct NodeInfo {
+    name: String,
+    status: u32,
+    cordon_drain_state: u32,
+}
+
+impl NodeInfo {
+    /// Get name of the node.
+    pub(crate) fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// Get status of the node.
+    pub(crate) fn status(&self) -> u32 {
+        self.status
+    }
+
+    /// Get the cordon and drain state of the node.
+    pub(crate) fn cordon_drain_state(&self) -> u32 {
+        self.cordon_drain_state
+    }
+}
+
+/// Array of NodeInfo objects.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub(crate) struct Nodes {
+    pub(crate) nodes: Vec<NodeInfo>,
+}
+
+/// Maps the node status to (0, 1, 2) = {"Unknown", "Online", "Offline"}.
+fn node_status(status: NodeStatus) -> u32 {
+    match status {
+        NodeStatus::Online => 1,
+        NodeStatus::Offline => 2,
+        _ => 0,
+    }
+}
+
+/// Maps the cordon and drain state to (0, 1, 2, 3) = {"None", "Cordoned", "Draining", "Drained"}.
+fn cordon_drain_state(state: Option<&CordonDrainState>) -> u32 {
+    match state {
+        None => 0,
+        Some(CordonDrainState::cordonedstate(_)) => 1,
+        Some(CordonDrainState::drainingstate(_)) => 2,
+        Some(CordonDrainState::drainedstate(_)) => 3,
+    }
+}
+
+impl From<openapi::models::Node> for NodeInfo {
+    fn from(value: openapi::models::Node) -> Self {
+        Self {
+            name: value.id,
+            status: value
+                .state
+                .map(|state| node_status(state.status))
+                .unwrap_or_default(),
+            cordon_drain_state: cordon_drain_state(
+                value
+                    .spec
+                    .as_ref()
+                    .and_then(|spec| spec.cordondrainstate.as_ref()),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cordon_drain_state, node_status};
+    use openapi::models::NodeStatus;
+
+    #[test]
+    fn status_mappings() {
+        assert_eq!(node_status(NodeStatus::Unknown), 0);
+        assert_eq!(node_status(NodeStatus::Online), 1);
+        assert_eq!(node_status(NodeStatus::Offline), 2);
+        // A node which is neither cordoned nor drained.
+        assert_eq!(cordon_drain_state(None), 0);
+    }
+}
@@ -0,0 +1,73 @@
+use openapi::models::PoolStatus;
+use serde::{Deserialize, Serialize};
+
+/// This stores the status of a pool, as seen by the control plane.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct PoolInfo {
+    name: String,
+    node: String,
+    status: u32,
+}
+
+impl PoolInfo {
+    /// Get name of the pool.
+    pub(crate) fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// Get node of the pool.
+    pub(crate) fn node(&self) -> &String {
+        &self.node
+    }
+
+    /// Get status of the pool.
+    pub(crate) fn status(&self) -> u32 {
+        self.status
+    }
+}
+
+/// Array of PoolInfo objects.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub(crate) struct Pools {
+    pub(crate) pools: Vec<PoolInfo>,
+}
+
+/// Maps the pool status to (0, 1, 2, 3) = {"Unknown", "Online", "Degraded", "Faulted"}.
+fn pool_status(status: PoolStatus) -> u32 {
+    match status {
+        PoolStatus::Online => 1,
+        PoolStatus::Degraded => 2,
+        PoolStatus::Faulted => 3,
+        _ => 0,
+    }
+}
+
+impl From<openapi::models::Pool> for PoolInfo {
+    fn from(value: openapi::models::Pool) -> Self {
+        // A pool which has no state, e.g. because its node is down, is known by its spec only.
+        let (node, status) = match (value.state, value.spec) {
+            (Some(state), _) => (state.node, pool_status(state.status)),
+            (None, Some(spec)) => (spec.node, 0),
+            (None, None) => (String::new(), 0),
+        };
+        Self {
+            name: value.id,
+            node,
+            status,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::pool_status;
+    use openapi::models::PoolStatus;
+
+    #[test]
+    fn status_mapping() {
+        assert_eq!(pool_status(PoolStatus::Unknown), 0);
+        assert_eq!(pool_status(PoolStatus::Online), 1);
+        assert_eq!(pool_status(PoolStatus::Degraded), 2);
+        assert_eq!(pool_status(PoolStatus::Faulted), 3);
+    }
+}
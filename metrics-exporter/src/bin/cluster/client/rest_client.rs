@@ -0,0 +1,111 @@
+use crate::{
+    client::{
+        node::{NodeInfo, Nodes},
+        pool::{PoolInfo, Pools},
+        snapshot::{SnapshotInfo, Snapshots},
+        volume::{VolumeInfo, Volumes},
+    },
+    error::ExporterError,
+};
+use openapi::tower::client::{ApiClient, Configuration};
+use std::time::Duration;
+use url::Url;
+
+/// Maximum number of entries of the paginated requests.
+const PAGE_SIZE: isize = 500;
+
+/// Control plane REST client.
+#[derive(Clone)]
+pub(crate) struct RestClient {
+    client: ApiClient,
+}
+
+impl RestClient {
+    /// Initialize the REST client for the endpoint, with the timeout for the requests.
+    pub(crate) fn new(endpoint: Url, timeout: Duration) -> Result<Self, ExporterError> {
+        let config = Configuration::builder()
+            .with_timeout(timeout)
+            .with_tracing(true)
+            .build_url(endpoint)
+            .map_err(|error| ExporterError::InvalidRestConfig(format!("{error:?}")))?;
+        Ok(Self {
+            client: ApiClient::new(config),
+        })
+    }
+
+    /// Gets the status and the replica topology of all volume, page by page. Maps the response
+    /// to VolumeInfo struct.
+    pub(crate) async fn list_volumes(&self) -> Result<Volumes, ExporterError> {
+        let mut volumes = Vec::new();
+        let mut starting_token = Some(0);
+
+        // The last paginated request will set the `starting_token` to `None`.
+        while starting_token.is_some() {
+            let page = self
+                .client
+                .volumes_api()
+                .get_volumes(PAGE_SIZE, None, starting_token)
+                .await
+                .map_err(|error| ExporterError::RestResponseError(error.to_string()))?
+                .into_body();
+            volumes.extend(page.entries.into_iter().map(VolumeInfo::from));
+            starting_token = page.next_token;
+        }
+
+        Ok(Volumes { volumes })
+    }
+
+    /// Gets the status of all pool. Maps the response to PoolInfo struct.
+    pub(crate) async fn list_pools(&self) -> Result<Pools, ExporterError> {
+        let pools = self
+            .client
+            .pools_api()
+            .get_pools(None)
+            .await
+            .map_err(|error| ExporterError::RestResponseError(error.to_string()))?
+            .into_body()
+            .into_iter()
+            .map(PoolInfo::from)
+            .collect();
+
+        Ok(Pools { pools })
+    }
+
+    /// Gets the status and the cordon and drain state of all node. Maps the response to
+    /// NodeInfo struct.
+    pub(crate) async fn list_nodes(&self) -> Result<Nodes, ExporterError> {
+        let nodes = self
+            .client
+            .nodes_api()
+            .get_nodes(None)
+            .await
+            .map_err(|error| ExporterError::RestResponseError(error.to_string()))?
+            .into_body()
+            .into_iter()
+            .map(NodeInfo::from)
+            .collect();
+
+        Ok(Nodes { nodes })
+    }
+
+    /// Gets all volume snapshot, page by page. Maps the response to SnapshotInfo struct.
+    pub(crate) async fn list_snapshots(&self) -> Result<Snapshots, ExporterError> {
+        let mut snapshots = Vec::new();
+        let mut starting_token = Some(0);
+
+        // The last paginated request will set the `starting_token` to `None`.
+        while starting_token.is_some() {
+            let page = self
+                .client
+                .snapshots_api()
+                .get_volumes_snapshots(PAGE_SIZE, None, None, starting_token)
+                .await
+                .map_err(|error| ExporterError::RestResponseError(error.to_string()))?
+                .into_body();
+            snapshots.extend(page.entries.into_iter().map(SnapshotInfo::from));
+            starting_token = page.next_token;
+        }
+
+        Ok(Snapshots { snapshots })
+    }
+}
@@ -0,0 +1,28 @@
+use serde::{Deserialize, Serialize};
+
+/// This stores the information of a volume snapshot.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct SnapshotInfo {
+    source_volume: String,
+}
+
+impl SnapshotInfo {
+    /// Get the uuid of the volume which the snapshot was taken of.
+    pub(crate) fn source_volume(&self) -> &String {
+        &self.source_volume
+    }
+}
+
+/// Array of SnapshotInfo objects.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub(crate) struct Snapshots {
+    pub(crate) snapshots: Vec<SnapshotInfo>,
+}
+
+impl From<openapi::models::VolumeSnapshot> for SnapshotInfo {
+    fn from(value: openapi::models::VolumeSnapshot) -> Self {
+        Self {
+            source_volume: value.definition.spec.source_volume.to_string(),
+        }
+    }
+}
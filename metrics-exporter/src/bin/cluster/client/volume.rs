@@ -0,0 +1,145 @@
+use openapi::models::{ReplicaState, VolumeStatus};
+use serde::{Deserialize, Serialize};
+
+/// This stores the status and the replica topology of a volume.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct VolumeInfo {
+    uuid: String,
+    status: u32,
+    num_replicas: u32,
+    target_node: Option<String>,
+    replicas: Vec<ReplicaTopologyInfo>,
+}
+
+impl VolumeInfo {
+    /// Get uuid of the volume.
+    pub(crate) fn uuid(&self) -> &String {
+        &self.uuid
+    }
+
+    /// Get status of the volume.
+    pub(crate) fn status(&self) -> u32 {
+        self.status
+    }
+
+    /// Get the number of replicas which the volume is meant to have.
+    pub(crate) fn num_replicas(&self) -> u32 {
+        self.num_replicas
+    }
+
+    /// Get the node of the target of the volume, if it is published.
+    pub(crate) fn target_node(&self) -> Option<&String> {
+        self.target_node.as_ref()
+    }
+
+    /// Get the replicas which the volume has.
+    pub(crate) fn replicas(&self) -> &Vec<ReplicaTopologyInfo> {
+        &self.replicas
+    }
+}
+
+/// This stores the placement and the state of a replica of a volume.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct ReplicaTopologyInfo {
+    uuid: String,
+    node: Option<String>,
+    pool: Option<String>,
+    state: u32,
+}
+
+impl ReplicaTopologyInfo {
+    /// Get uuid of the replica.
+    pub(crate) fn uuid(&self) -> &String {
+        &self.uuid
+    }
+
+    /// Get the node of the replica, if it is known.
+    pub(crate) fn node(&self) -> Option<&String> {
+        self.node.as_ref()
+    }
+
+    /// Get the pool of the replica, if it is known.
+    pub(crate) fn pool(&self) -> Option<&String> {
+        self.pool.as_ref()
+    }
+
+    /// Get state of the replica.
+    pub(crate) fn state(&self) -> u32 {
+        self.state
+    }
+
+    /// Returns true if the replica is online.
+    pub(crate) fn is_online(&self) -> bool {
+        self.state == 1
+    }
+}
+
+/// Array of VolumeInfo objects.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub(crate) struct Volumes {
+    pub(crate) volumes: Vec<VolumeInfo>,
+}
+
+/// Maps the volume status to (0, 1, 2, 3) = {"Unknown", "Online", "Degraded", "Faulted"}.
+fn volume_status(status: VolumeStatus) -> u32 {
+    match status {
+        VolumeStatus::Online => 1,
+        VolumeStatus::Degraded => 2,
+        VolumeStatus::Faulted => 3,
+        _ => 0,
+    }
+}
+
+/// Maps the replica state to (0, 1, 2, 3) = {"Unknown", "Online", "Degraded", "Faulted"}.
+fn replica_state(state: ReplicaState) -> u32 {
+    match state {
+        ReplicaState::Online => 1,
+        ReplicaState::Degraded => 2,
+        ReplicaState::Faulted => 3,
+        _ => 0,
+    }
+}
+
+impl From<openapi::models::Volume> for VolumeInfo {
+    fn from(value: openapi::models::Volume) -> Self {
+        let replicas = value
+            .state
+            .replica_topology
+            .into_iter()
+            .map(|(uuid, topology)| ReplicaTopologyInfo {
+                uuid,
+                node: topology.node,
+                pool: topology.pool,
+                state: replica_state(topology.state),
+            })
+            .collect();
+        Self {
+            uuid: value.spec.uuid.to_string(),
+            status: volume_status(value.state.status),
+            num_replicas: value.spec.num_replicas as u32,
+            target_node: value.state.target.map(|target| target.node),
+            replicas,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{replica_state, volume_status};
+    use openapi::models::{ReplicaState, VolumeStatus};
+
+    #[test]
+    fn status_mappings() {
+        assert_eq!(volume_status(VolumeStatus::Unknown), 0);
+        assert_eq!(volume_status(VolumeStatus::Online), 1);
+        assert_eq!(volume_status(VolumeStatus::Degraded), 2);
+        assert_eq!(volume_status(VolumeStatus::Faulted), 3);
+        // A volume which is shut down is not told apart from an unknown one.
+        assert_eq!(volume_status(VolumeStatus::Shutdown), 0);
+
+        assert_eq!(replica_state(ReplicaState::Unknown), 0);
+        assert_eq!(replica_state(ReplicaState::Online), 1);
+        assert_eq!(replica_state(ReplicaState::Degraded), 2);
+        assert_eq!(replica_state(ReplicaState::Faulted), 3);
+    }
+}
@@ -0,0 +1,49 @@
+use prometheus::{
+    core::{Collector, Desc},
+    GaugeVec, Opts,
+};
+
+pub(crate) mod node;
+pub(crate) mod poll_status;
+pub(crate) mod pool;
+pub(crate) mod volume;
+
+/// Initializes a GaugeVec metric with the provided subsystem, metric name, description, labels
+/// and descriptors. The metrics are prefixed with cluster_, so that these are told apart from
+/// the metrics which the io-engine exporter exposes per node.
+fn init_gauge_vec(
+    subsystem: &str,
+    metric_name: &str,
+    metric_desc: &str,
+    labels: &[&str],
+    descs: &mut Vec<Desc>,
+) -> GaugeVec {
+    let opts = Opts::new(metric_name, metric_desc)
+        .namespace("cluster")
+        .subsystem(subsystem);
+    let gauge_vec = GaugeVec::new(opts, labels)
+        .unwrap_or_else(|_| panic!("Unable to create gauge metric type for {}", metric_name));
+    descs.extend(gauge_vec.desc().into_iter().cloned());
+    gauge_vec
+}
+
+/// Returns the value of the gauge of the metric family with the label values, by label name.
+#[cfg(test)]
+fn gauge_value(
+    metrics: &[prometheus::proto::MetricFamily],
+    name: &str,
+    labels: &[(&str, &str)],
+) -> Option<f64> {
+    metrics
+        .iter()
+        .filter(|family| family.get_name() == name)
+        .flat_map(|family| family.get_metric())
+        .find(|metric| {
+            labels.iter().all(|(label_name, label_value)| {
+                metric.get_label().iter().any(|label| {
+                    label.get_name() == *label_name && label.get_value() == *label_value
+                })
+            })
+        })
+        .map(|metric| metric.get_gauge().get_value())
+}
@@ -0,0 +1,140 @@
+use super::init_gauge_vec;
+use crate::cache::Cache;
+use prometheus::{
+    core::{Collector, Desc},
+    proto::MetricFamily,
+    GaugeVec,
+};
+use std::{fmt::Debug, ops::Deref};
+use tracing::error;
+
+/// Collects the status and the cordon and drain state of the nodes from cache.
+#[derive(Clone, Debug)]
+pub(crate) struct NodeCollector {
+    status: GaugeVec,
+    cordon_drain_state: GaugeVec,
+    descs: Vec<Desc>,
+}
+
+impl Default for NodeCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NodeCollector {
+    /// Initialize all the metrics to be defined for the node collector.
+    pub fn new() -> Self {
+        let mut descs = Vec::new();
+        let status = init_gauge_vec(
+            "node",
+            "status",
+            "Status of the node",
+            &["node"],
+            &mut descs,
+        );
+        let cordon_drain_state = init_gauge_vec(
+            "node",
+            "cordon_drain_state",
+            "Cordon and drain state of the node",
+            &["node"],
+            &mut descs,
+        );
+        Self {
+            status,
+            cordon_drain_state,
+            descs,
+        }
+    }
+
+    /// Sets the metrics of the nodes in the cache, and returns these.
+    fn node_metrics(&self, cache: &Cache) -> Vec<MetricFamily> {
+        // Forget the nodes which no longer exist, as the metrics outlive the scrapes.
+        self.status.reset();
+        self.cordon_drain_state.reset();
+        let mut metric_family = Vec::with_capacity(2 * cache.node().nodes.capacity());
+
+        let mut set_gauge = |gauge_vec: &GaugeVec, label_values: &[&str], value: f64| {
+            let gauge = gauge_vec.get_metric_with_label_values(label_values)?;
+            gauge.set(value);
+            metric_family.extend(gauge.collect().pop());
+            Ok::<(), prometheus::Error>(())
+        };
+        let result = cache.node().nodes.iter().try_for_each(|node| {
+            let node_labels = [node.name().as_str()];
+            set_gauge(&self.status, &node_labels, node.status() as f64)?;
+            set_gauge(
+                &self.cordon_drain_state,
+                &node_labels,
+                node.cordon_drain_state() as f64,
+            )
+        });
+        if let Err(error) = result {
+            error!(%error, "Error while creating node metrics with label values");
+        }
+        metric_family
+    }
+}
+
+impl Collector for NodeCollector {
+    fn desc(&self) -> Vec<&Desc> {
+        self.descs.iter().collect()
+    }
+
+    fn collect(&self) -> Vec<MetricFamily> {
+        let cache = match Cache::get_cache().lock() {
+            Ok(cache) => cache,
+            Err(error) => {
+                error!(%error,"Error while getting cache resource");
+                return Vec::new();
+            }
+        };
+        self.node_metrics(cache.deref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NodeCollector;
+    use crate::{
+        cache::Cache,
+        client::{pool::Pools, snapshot::Snapshots, volume::Volumes},
+        collector::gauge_value as gauge,
+    };
+    use serde_json::json;
+
+    #[test]
+    fn node_metrics() {
+        let nodes = serde_json::from_value(json!({ "nodes": [
+            { "name": "node-1", "status": 1, "cordon_drain_state": 0 },
+            { "name": "node-2", "status": 1, "cordon_drain_state": 1 },
+            { "name": "node-3", "status": 2, "cordon_drain_state": 2 },
+            { "name": "node-4", "status": 0, "cordon_drain_state": 3 }
+        ]}))
+        .unwrap();
+        let cache = Cache::polled(
+            Ok(Volumes::default()),
+            Ok(Pools::default()),
+            Ok(nodes),
+            Ok(Snapshots::default()),
+        );
+
+        let metrics = NodeCollector::new().node_metrics(&cache);
+        for (node, status, cordon_drain_state) in [
+            ("node-1", 1.0, 0.0),
+            ("node-2", 1.0, 1.0),
+            ("node-3", 2.0, 2.0),
+            ("node-4", 0.0, 3.0),
+        ] {
+            let labels = [("node", node)];
+            assert_eq!(
+                gauge(&metrics, "cluster_node_status", &labels),
+                Some(status)
+            );
+            assert_eq!(
+                gauge(&metrics, "cluster_node_cordon_drain_state", &labels),
+                Some(cordon_drain_state)
+            );
+        }
+    }
+}
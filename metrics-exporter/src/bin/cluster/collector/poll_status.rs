@@ -0,0 +1,101 @@
+use crate::cache::{Cache, PollSource};
+use metrics_exporter::poll::PollStatusGauges;
+use prometheus::{
+    core::{Collector, Desc},
+    proto::MetricFamily,
+    Opts,
+};
+use std::{fmt::Debug, ops::Deref};
+use strum::IntoEnumIterator;
+use tracing::error;
+
+/// Collects the outcome of the polls of the control plane from cache, so that stale data can be
+/// told apart from fresh data.
+#[derive(Clone, Debug)]
+pub(crate) struct PollStatusCollector {
+    gauges: PollStatusGauges,
+}
+
+impl Default for PollStatusCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PollStatusCollector {
+    /// Initialize all the metrics to be defined for the poll status collector.
+    pub fn new() -> Self {
+        let gauges = PollStatusGauges::new(
+            |name, desc| {
+                Opts::new(name, desc)
+                    .namespace("cluster")
+                    .subsystem("rest_api")
+            },
+            &[],
+            "control plane",
+        );
+        Self { gauges }
+    }
+
+    /// Sets the metrics of the outcome of the polls in the cache, and returns these.
+    fn poll_status_metrics(&self, cache: &Cache) -> Vec<MetricFamily> {
+        self.gauges.collect(
+            &[],
+            PollSource::iter().map(|source| (source, cache.poll_status(source))),
+        )
+    }
+}
+
+impl Collector for PollStatusCollector {
+    fn desc(&self) -> Vec<&Desc> {
+        self.gauges.descs()
+    }
+
+    fn collect(&self) -> Vec<MetricFamily> {
+        let cache = match Cache::get_cache().lock() {
+            Ok(cache) => cache,
+            Err(error) => {
+                error!(%error,"Error while getting cache resource");
+                return Vec::new();
+            }
+        };
+        self.poll_status_metrics(cache.deref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PollStatusCollector;
+    use crate::{
+        cache::Cache,
+        client::{node::Nodes, pool::Pools, volume::Volumes},
+        collector::gauge_value as gauge,
+        error::ExporterError,
+    };
+
+    #[test]
+    fn poll_status_metrics() {
+        let cache = Cache::polled(
+            Ok(Volumes::default()),
+            Ok(Pools::default()),
+            Ok(Nodes::default()),
+            Err(ExporterError::RestResponseError("unavailable".to_string())),
+        );
+
+        let metrics = PollStatusCollector::new().poll_status_metrics(&cache);
+        for source in ["volumes", "pools", "nodes"] {
+            assert_eq!(
+                gauge(&metrics, "cluster_rest_api_up", &[("source", source)]),
+                Some(1.0)
+            );
+        }
+        assert_eq!(
+            gauge(&metrics, "cluster_rest_api_up", &[("source", "snapshots")]),
+            Some(0.0)
+        );
+        // The source has never been polled successfully.
+        let last_poll = "cluster_rest_api_last_successful_poll_timestamp_seconds";
+        assert!(gauge(&metrics, last_poll, &[("source", "volumes")]).is_some());
+        assert_eq!(gauge(&metrics, last_poll, &[("source", "snapshots")]), None);
+    }
+}
@@ -0,0 +1,122 @@
+use super::init_gauge_vec;
+use crate::cache::Cache;
+use prometheus::{
+    core::{Collector, Desc},
+    proto::MetricFamily,
+    GaugeVec,
+};
+use std::{fmt::Debug, ops::Deref};
+use tracing::error;
+
+/// Collects the status of the pools of every node, as seen by the control plane, from cache.
+#[derive(Clone, Debug)]
+pub(crate) struct PoolCollector {
+    status: GaugeVec,
+    descs: Vec<Desc>,
+}
+
+impl Default for PoolCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PoolCollector {
+    /// Initialize all the metrics to be defined for the pool collector.
+    pub fn new() -> Self {
+        let mut descs = Vec::new();
+        let status = init_gauge_vec(
+            "diskpool",
+            "status",
+            "Status of the pool",
+            &["node", "name"],
+            &mut descs,
+        );
+        Self { status, descs }
+    }
+
+    /// Sets the status metrics of the pools in the cache, and returns these.
+    fn pool_metrics(&self, cache: &Cache) -> Vec<MetricFamily> {
+        // Forget the pools which no longer exist, as the metrics outlive the scrapes.
+        self.status.reset();
+        let mut metric_family = Vec::with_capacity(cache.pool().pools.capacity());
+
+        for pool in &cache.pool().pools {
+            let status = match self
+                .status
+                .get_metric_with_label_values(&[pool.node().as_str(), pool.name().as_str()])
+            {
+                Ok(status) => status,
+                Err(error) => {
+                    error!(%error, "Error while creating pool status gauge with label values");
+                    return metric_family;
+                }
+            };
+            status.set(pool.status() as f64);
+            metric_family.extend(status.collect().pop());
+        }
+        metric_family
+    }
+}
+
+impl Collector for PoolCollector {
+    fn desc(&self) -> Vec<&Desc> {
+        self.descs.iter().collect()
+    }
+
+    fn collect(&self) -> Vec<MetricFamily> {
+        let cache = match Cache::get_cache().lock() {
+            Ok(cache) => cache,
+            Err(error) => {
+                error!(%error,"Error while getting cache resource");
+                return Vec::new();
+            }
+        };
+        self.pool_metrics(cache.deref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PoolCollector;
+    use crate::{
+        cache::Cache,
+        client::{node::Nodes, snapshot::Snapshots, volume::Volumes},
+        collector::gauge_value as gauge,
+    };
+    use serde_json::json;
+
+    #[test]
+    fn pool_metrics() {
+        let pools = serde_json::from_value(json!({ "pools": [
+            { "name": "pool-1", "node": "node-1", "status": 1 },
+            { "name": "pool-2", "node": "node-2", "status": 0 }
+        ]}))
+        .unwrap();
+        let cache = Cache::polled(
+            Ok(Volumes::default()),
+            Ok(pools),
+            Ok(Nodes::default()),
+            Ok(Snapshots::default()),
+        );
+
+        let metrics = PoolCollector::new().pool_metrics(&cache);
+        let pool = |node, name| [("node", node), ("name", name)];
+        assert_eq!(
+            gauge(
+                &metrics,
+                "cluster_diskpool_status",
+                &pool("node-1", "pool-1")
+            ),
+            Some(1.0)
+        );
+        assert_eq!(
+            gauge(
+                &metrics,
+                "cluster_diskpool_status",
+                &pool("node-2", "pool-2")
+            ),
+            Some(0.0)
+        );
+    }
+}
@@ -0,0 +1,349 @@
+use super::init_gauge_vec;
+use crate::cache::{Cache, PollSource};
+use prometheus::{
+    core::{Collector, Desc},
+    proto::MetricFamily,
+    GaugeVec,
+};
+use std::{collections::HashMap, fmt::Debug, ops::Deref};
+use tracing::error;
+
+/// Collects the volume health, replica topology and snapshot metrics from cache.
+#[derive(Clone, Debug)]
+pub(crate) struct VolumeCollector {
+    status: GaugeVec,
+    replicas: GaugeVec,
+    desired_replicas: GaugeVec,
+    online_replicas: GaugeVec,
+    target_node: GaugeVec,
+    replica_status: GaugeVec,
+    snapshots: GaugeVec,
+    descs: Vec<Desc>,
+}
+
+impl Default for VolumeCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VolumeCollector {
+    /// Initialize all the metrics to be defined for the volume collector.
+    pub fn new() -> Self {
+        let mut descs = Vec::new();
+        let mut gauge = |name: &str, desc: &str, labels: &[&str]| {
+            init_gauge_vec("volume", name, desc, labels, &mut descs)
+        };
+        let volume_labels = ["pv_name"];
+
+        Self {
+            status: gauge("status", "Status of the volume", &volume_labels),
+            replicas: gauge(
+                "replicas",
+                "Number of replicas of the volume",
+                &volume_labels,
+            ),
+            desired_replicas: gauge(
+                "desired_replicas",
+                "Number of replicas which the volume is meant to have",
+                &volume_labels,
+            ),
+            online_replicas: gauge(
+                "online_replicas",
+                "Number of online replicas of the volume",
+                &volume_labels,
+            ),
+            target_node: gauge(
+                "target_node",
+                "Node of the target of the volume, which is absent if the volume is unpublished",
+                &["pv_name", "node"],
+            ),
+            replica_status: gauge(
+                "replica_status",
+                "Status of the replica of the volume",
+                &["pv_name", "replica", "node", "pool"],
+            ),
+            snapshots: gauge(
+                "snapshots",
+                "Number of snapshots of the volume",
+                &volume_labels,
+            ),
+            descs,
+        }
+    }
+
+    /// Removes the metrics of all label values, so that the volumes and replicas which no longer
+    /// exist are forgotten, as the metrics outlive the scrapes.
+    fn reset(&self) {
+        [
+            &self.status,
+            &self.replicas,
+            &self.desired_replicas,
+            &self.online_replicas,
+            &self.target_node,
+            &self.replica_status,
+            &self.snapshots,
+        ]
+        .iter()
+        .for_each(|gauge_vec| gauge_vec.reset());
+    }
+
+    /// Sets the metrics of the volumes, their replicas and their snapshots in the cache, and
+    /// returns these.
+    fn volume_metrics(&self, cache: &Cache) -> Vec<MetricFamily> {
+        self.reset();
+        let mut metric_family = Vec::with_capacity(5 * cache.volume().volumes.capacity());
+
+        let mut set_gauge = |gauge_vec: &GaugeVec, label_values: &[&str], value: f64| {
+            let gauge = gauge_vec.get_metric_with_label_values(label_values)?;
+            gauge.set(value);
+            metric_family.extend(gauge.collect().pop());
+            Ok::<(), prometheus::Error>(())
+        };
+        let result = cache.volume().volumes.iter().try_for_each(|volume| {
+            let pv_name = "pvc-".to_string() + volume.uuid();
+            let volume_labels = [pv_name.as_str()];
+            let online_replicas = volume
+                .replicas()
+                .iter()
+                .filter(|replica| replica.is_online())
+                .count();
+            set_gauge(&self.status, &volume_labels, volume.status() as f64)?;
+            set_gauge(
+                &self.replicas,
+                &volume_labels,
+                volume.replicas().len() as f64,
+            )?;
+            set_gauge(
+                &self.desired_replicas,
+                &volume_labels,
+                volume.num_replicas() as f64,
+            )?;
+            set_gauge(
+                &self.online_replicas,
+                &volume_labels,
+                online_replicas as f64,
+            )?;
+            if let Some(node) = volume.target_node() {
+                set_gauge(&self.target_node, &[pv_name.as_str(), node.as_str()], 1.0)?;
+            }
+
+            for replica in volume.replicas() {
+                let replica_labels = [
+                    pv_name.as_str(),
+                    replica.uuid().as_str(),
+                    replica.node().map(String::as_str).unwrap_or_default(),
+                    replica.pool().map(String::as_str).unwrap_or_default(),
+                ];
+                set_gauge(
+                    &self.replica_status,
+                    &replica_labels,
+                    replica.state() as f64,
+                )?;
+            }
+            Ok::<(), prometheus::Error>(())
+        });
+        if let Err(error) = result {
+            error!(%error, "Error while creating volume metrics with label values");
+            return metric_family;
+        }
+
+        // The snapshot counts would all be 0 if the snapshots could not be listed.
+        if !cache.poll_status(PollSource::Snapshots).up() {
+            return metric_family;
+        }
+        // The snapshots of a volume outlive the volume, so these are counted for the volumes
+        // which no longer exist as well.
+        let mut snapshots = cache
+            .volume()
+            .volumes
+            .iter()
+            .map(|volume| (volume.uuid().as_str(), 0))
+            .collect::<HashMap<_, _>>();
+        for snapshot in &cache.snapshot().snapshots {
+            *snapshots
+                .entry(snapshot.source_volume().as_str())
+                .or_default() += 1;
+        }
+        let result = snapshots.into_iter().try_for_each(|(uuid, count)| {
+            let pv_name = "pvc-".to_string() + uuid;
+            set_gauge(&self.snapshots, &[pv_name.as_str()], count as f64)
+        });
+        if let Err(error) = result {
+            error!(%error, "Error while creating volume snapshot metrics with label values");
+        }
+        metric_family
+    }
+}
+
+impl Collector for VolumeCollector {
+    fn desc(&self) -> Vec<&Desc> {
+        self.descs.iter().collect()
+    }
+
+    fn collect(&self) -> Vec<MetricFamily> {
+        let cache = match Cache::get_cache().lock() {
+            Ok(cache) => cache,
+            Err(error) => {
+                error!(%error,"Error while getting cache resource");
+                return Vec::new();
+            }
+        };
+        self.volume_metrics(cache.deref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VolumeCollector;
+    use crate::{
+        cache::Cache,
+        client::{node::Nodes, pool::Pools, volume::Volumes},
+        collector::gauge_value as gauge,
+        error::ExporterError,
+    };
+    use serde_json::json;
+
+    /// A degraded volume which is published on node-1, and an unpublished volume.
+    fn volumes() -> Volumes {
+        serde_json::from_value(json!({ "volumes": [
+            {
+                "uuid": "volume-1",
+                "status": 2,
+                "num_replicas": 3,
+                "target_node": "node-1",
+                "replicas": [
+                    { "uuid": "replica-1", "node": "node-1", "pool": "pool-1", "state": 1 },
+                    { "uuid": "replica-2", "node": "node-2", "pool": "pool-2", "state": 3 }
+                ]
+            },
+            {
+                "uuid": "volume-2",
+                "status": 1,
+                "num_replicas": 1,
+                "target_node": null,
+                "replicas": [
+                    { "uuid": "replica-3", "node": null, "pool": null, "state": 1 }
+                ]
+            }
+        ]}))
+        .unwrap()
+    }
+
+    #[test]
+    fn volume_metrics() {
+        let snapshots = serde_json::from_value(json!({ "snapshots": [
+            { "source_volume": "volume-1" },
+            { "source_volume": "volume-1" },
+            { "source_volume": "volume-3" }
+        ]}))
+        .unwrap();
+        let cache = Cache::polled(
+            Ok(volumes()),
+            Ok(Pools::default()),
+            Ok(Nodes::default()),
+            Ok(snapshots),
+        );
+
+        let metrics = VolumeCollector::new().volume_metrics(&cache);
+        let volume = |uuid| [("pv_name", uuid)];
+        let degraded = volume("pvc-volume-1");
+        assert_eq!(
+            gauge(&metrics, "cluster_volume_status", &degraded),
+            Some(2.0)
+        );
+        assert_eq!(
+            gauge(&metrics, "cluster_volume_replicas", &degraded),
+            Some(2.0)
+        );
+        assert_eq!(
+            gauge(&metrics, "cluster_volume_desired_replicas", &degraded),
+            Some(3.0)
+        );
+        assert_eq!(
+            gauge(&metrics, "cluster_volume_online_replicas", &degraded),
+            Some(1.0)
+        );
+        assert_eq!(
+            gauge(
+                &metrics,
+                "cluster_volume_target_node",
+                &[("pv_name", "pvc-volume-1"), ("node", "node-1")]
+            ),
+            Some(1.0)
+        );
+        assert_eq!(
+            gauge(
+                &metrics,
+                "cluster_volume_replica_status",
+                &[
+                    ("pv_name", "pvc-volume-1"),
+                    ("replica", "replica-2"),
+                    ("node", "node-2"),
+                    ("pool", "pool-2")
+                ]
+            ),
+            Some(3.0)
+        );
+
+        let online = volume("pvc-volume-2");
+        assert_eq!(gauge(&metrics, "cluster_volume_status", &online), Some(1.0));
+        assert_eq!(
+            gauge(&metrics, "cluster_volume_online_replicas", &online),
+            Some(1.0)
+        );
+        // The volume is unpublished.
+        assert_eq!(gauge(&metrics, "cluster_volume_target_node", &online), None);
+        // The placement of the replica is not known.
+        assert_eq!(
+            gauge(
+                &metrics,
+                "cluster_volume_replica_status",
+                &[("replica", "replica-3"), ("node", ""), ("pool", "")]
+            ),
+            Some(1.0)
+        );
+
+        // The snapshots are counted for the volumes which no longer exist as well.
+        assert_eq!(
+            gauge(&metrics, "cluster_volume_snapshots", &degraded),
+            Some(2.0)
+        );
+        assert_eq!(
+            gauge(&metrics, "cluster_volume_snapshots", &online),
+            Some(0.0)
+        );
+        assert_eq!(
+            gauge(
+                &metrics,
+                "cluster_volume_snapshots",
+                &volume("pvc-volume-3")
+            ),
+            Some(1.0)
+        );
+    }
+
+    #[test]
+    fn snapshots_not_listed() {
+        let cache = Cache::polled(
+            Ok(volumes()),
+            Ok(Pools::default()),
+            Ok(Nodes::default()),
+            Err(ExporterError::RestResponseError("unavailable".to_string())),
+        );
+
+        // The snapshot counts are not reported, rather than being 0.
+        let metrics = VolumeCollector::new().volume_metrics(&cache);
+        assert_eq!(
+            gauge(
+                &metrics,
+                "cluster_volume_status",
+                &[("pv_name", "pvc-volume-1")]
+            ),
+            Some(2.0)
+        );
+        assert!(metrics
+            .iter()
+            .all(|family| family.get_name() != "cluster_volume_snapshots"));
+    }
+}
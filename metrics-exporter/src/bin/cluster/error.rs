@@ -0,0 +1,10 @@
+#[derive(Debug)]
+#[allow(unused)]
+/// Error used in exporters
+pub enum ExporterError {
+    InvalidRestConfig(String),
+    RestResponseError(String),
+    HttpServerError(String),
+    HttpBindError(String),
+    HttpServerConfigError(String),
+}
@@ -0,0 +1,112 @@
+use crate::{
+    client::rest_client::RestClient,
+    error::ExporterError,
+    serve::{init_registry, metric_route},
+};
+use actix_web::{middleware, web, HttpServer};
+use clap::Parser;
+use http_server::{authenticate, health_routes, on_connect, ServerArgs};
+use std::net::SocketAddr;
+use url::Url;
+use utils::tracing_telemetry::{FmtLayer, FmtStyle};
+
+/// Cache module for exporter.
+pub(crate) mod cache;
+/// REST client module.
+pub(crate) mod client;
+/// Collector module.
+pub(crate) mod collector;
+/// Error module.
+pub(crate) mod error;
+/// Prometheus metrics handler module.
+pub(crate) mod serve;
+
+#[derive(Parser, Debug)]
+#[clap(name = utils::package_description!(), version = utils::version_info_str!())]
+pub(crate) struct Cli {
+    /// TCP address where prometheus endpoint will listen to
+    #[clap(long, short, default_value = "[::]:9503")]
+    metrics_endpoint: SocketAddr,
+
+    /// An URL endpoint to the control plane's rest endpoint.
+    #[clap(long, short, default_value = "http://mayastor-api-rest:8081")]
+    rest_endpoint: Url,
+
+    /// Timeout of the requests to the control plane's rest endpoint.
+    #[clap(long, default_value = "30s")]
+    request_timeout: humantime::Duration,
+
+    /// Formatting style to be used while logging.
+    #[clap(default_value = FmtStyle::Pretty.as_ref(), short, long)]
+    fmt_style: FmtStyle,
+
+    /// Use ANSI colors for the logs.
+    #[clap(long, default_value_t = true, action = clap::ArgAction::Set)]
+    ansi_colors: bool,
+
+    /// Interval at which the data is polled from the control plane. The scrapes are served from
+    /// the data of the last poll.
+    #[clap(long, default_value = "30s")]
+    polling_interval: humantime::Duration,
+
+    /// TLS and authentication of the prometheus endpoint.
+    #[clap(flatten)]
+    server: ServerArgs,
+}
+
+impl Cli {
+    fn args() -> Self {
+        Cli::parse()
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), ExporterError> {
+    let args = Cli::args();
+    utils::print_package_info!();
+
+    utils::tracing_telemetry::TracingTelemetry::builder()
+        .with_writer(FmtLayer::Stdout)
+        .with_style(args.fmt_style)
+        .with_colours(args.ansi_colors)
+        .init("metrics-exporter-cluster");
+
+    cache::Cache::initialize(cache::Data::default());
+    init_registry();
+    let client = RestClient::new(args.rest_endpoint.clone(), args.request_timeout.into())?;
+    // Populate the cache in the background, rather than on every scrape.
+    tokio::spawn(cache::poll_resource_data(
+        client,
+        args.polling_interval.into(),
+    ));
+    let tls_config = args
+        .server
+        .tls_config()
+        .map_err(|error| ExporterError::HttpServerConfigError(error.to_string()))?;
+    let authentication = args
+        .server
+        .authentication()
+        .map_err(|error| ExporterError::HttpServerConfigError(error.to_string()))?;
+    let app = move || {
+        actix_web::App::new()
+            .app_data(web::Data::new(authentication.clone()))
+            .wrap(middleware::from_fn(authenticate))
+            .wrap(middleware::Logger::default())
+            .configure(health_routes(cache::is_ready))
+            .configure(metric_route)
+    };
+    let server = HttpServer::new(app).on_connect(on_connect);
+    let server = match tls_config {
+        Some(tls_config) => server.bind_rustls_021(args.metrics_endpoint, tls_config),
+        None => server.bind(args.metrics_endpoint),
+    };
+    server
+        .map_err(|_| {
+            ExporterError::HttpBindError("Failed to bind endpoint to http server".to_string())
+        })?
+        .workers(1)
+        .run()
+        .await
+        .map_err(|_| ExporterError::HttpServerError("Failed to start http Service".to_string()))?;
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use crate::collector::{
+    node::NodeCollector, poll_status::PollStatusCollector, pool::PoolCollector,
+    volume::VolumeCollector,
+};
+use actix_web::{http::header, HttpResponse, Responder};
+use once_cell::sync::OnceCell;
+use prometheus::{Encoder, Registry};
+use tracing::{error, warn};
+
+static REGISTRY: OnceCell<Registry> = OnceCell::new();
+
+/// Creates the registry, and registers all the collectors to it. The registry is shared by all
+/// of the scrapes, which are served from the cache.
+pub(crate) fn init_registry() {
+    REGISTRY.get_or_init(|| {
+        // Create collectors for all resources.
+        let volume_collector = VolumeCollector::default();
+        let pool_collector = PoolCollector::default();
+        let node_collector = NodeCollector::default();
+        let poll_status_collector = PollStatusCollector::default();
+        // Create a new registry for prometheus.
+        let registry = Registry::default();
+        // Register all collectors to the registry.
+        if let Err(error) = Registry::register(&registry, Box::new(volume_collector)) {
+            warn!(%error, "Volume collector already registered");
+        }
+        if let Err(error) = Registry::register(&registry, Box::new(pool_collector)) {
+            warn!(%error, "Pool collector already registered");
+        }
+        if let Err(error) = Registry::register(&registry, Box::new(node_collector)) {
+            warn!(%error, "Node collector already registered");
+        }
+        if let Err(error) = Registry::register(&registry, Box::new(poll_status_collector)) {
+            warn!(%error, "Poll status collector already registered");
+        }
+        registry
+    });
+}
+
+/// Handler for metrics. Serves the data in the cache over Http, which is populated in the
+/// background.
+pub(crate) async fn metrics_handler() -> impl Responder {
+    let registry = REGISTRY
+        .get()
+        .expect("Prometheus registry should have been initialised");
+    let mut buffer = Vec::new();
+
+    let encoder = prometheus::TextEncoder::new();
+    // Internally calls collect() on all collectors and encodes the data into the buffer.
+    if let Err(error) = encoder.encode(&registry.gather(), &mut buffer) {
+        error!(%error, "Could not encode custom metrics");
+    };
+
+    let res_custom = match String::from_utf8(buffer) {
+        Ok(v) => v,
+        Err(error) => {
+            error!(%error, "Prometheus metrics could not be parsed from_utf8'd");
+            String::default()
+        }
+    };
+    HttpResponse::Ok()
+        .insert_header(header::ContentType(mime::TEXT_PLAIN))
+        .body(res_custom)
+}
@@ -1,15 +1,23 @@
+mod nexus;
 mod nexus_stat;
 mod pool;
 mod pool_stat;
+mod replica;
 mod replica_stat;
+mod snapshot;
 
 use crate::client::{
-    grpc_client::GrpcClient, nexus_stat::NexusIoStats, pool::Pools, pool_stat::PoolIoStats,
-    replica_stat::ReplicaIoStats,
+    grpc_client::GrpcClient, nexus::Nexuses, nexus_stat::NexusIoStats, pool::Pools,
+    pool_stat::PoolIoStats, replica::Replicas, replica_stat::ReplicaIoStats, snapshot::Snapshots,
+    IoCounters,
 };
+use metrics_exporter::poll::{unix_time_now, PollStatus, PollStatuses};
 use once_cell::sync::OnceCell;
 use serde::{Deserialize, Serialize};
-use std::sync::Mutex;
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+use strum_macros::{AsRefStr, EnumIter};
+use tokio::time::MissedTickBehavior;
+use tracing::error;
 
 static CACHE: OnceCell<Mutex<Cache>> = OnceCell::new();
 
@@ -32,10 +40,158 @@ pub(crate) struct Data {
     pools: Pools,
     /// Contains Pool IOStats data.
     pool_stats: PoolIoStats,
+    /// Contains Nexus state, children and rebuilds data.
+    nexuses: Nexuses,
+    /// Contains the previous samples of the Nexus rebuilds.
+    rebuild_history: nexus::RebuildHistory,
     /// Contains Nexus IOStats data.
     nexus_stats: NexusIoStats,
+    /// Contains Replica capacity and space usage data.
+    replicas: Replicas,
+    /// Contains Replica snapshots data.
+    snapshots: Snapshots,
     /// Contains Replica IOStats data.
     replica_stats: ReplicaIoStats,
+    /// Contains the previous samples of the Pool IOStats counters.
+    pool_stats_history: IoStatHistory,
+    /// Contains the previous samples of the Nexus IOStats counters.
+    nexus_stats_history: IoStatHistory,
+    /// Contains the previous samples of the Replica IOStats counters.
+    replica_stats_history: IoStatHistory,
+    /// Contains the outcome of the polls of each of the sources.
+    poll_status: PollStatuses<PollSource>,
+}
+
+/// The sources of the data which is polled from the io-engine.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, AsRefStr, EnumIter)]
+#[strum(serialize_all = "snake_case")]
+pub(crate) enum PollSource {
+    Pools,
+    PoolStats,
+    Nexuses,
+    NexusStats,
+    Replicas,
+    ReplicaStats,
+    Snapshots,
+}
+
+/// The latest samples of the IO counters of the pools, nexuses or replicas, by name. These are
+/// kept across polls to derive the rates over the interval between the last two samples, and the
+/// start time of the counters.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub(crate) struct IoStatHistory {
+    samples: HashMap<String, IoStatSample>,
+}
+
+/// A sample of the IO counters of a pool, nexus or replica.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub(crate) struct IoStatSample {
+    counters: IoCounters,
+    /// The unix time at which the sample was taken.
+    sampled_at: f64,
+    /// The unix time at which the counters were first seen, or were last seen to go down, e.g.
+    /// because the io-engine restarted. This makes the counter resets visible, even when the
+    /// counters grow past their previous values in between scrapes.
+    start_time: f64,
+    /// The rates over the interval since the previous sample, if there is one and the counters
+    /// were not reset in between.
+    rates: Option<IoRates>,
+}
+
+/// The IO rates of a pool, nexus or replica over the interval between two samples.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct IoRates {
+    pub(crate) read_iops: f64,
+    pub(crate) write_iops: f64,
+    pub(crate) read_bytes_per_sec: f64,
+    pub(crate) write_bytes_per_sec: f64,
+    /// The average latency of the reads completed in the interval, in seconds.
+    pub(crate) read_latency_avg_sec: f64,
+    /// The average latency of the writes completed in the interval, in seconds.
+    pub(crate) write_latency_avg_sec: f64,
+}
+
+impl IoRates {
+    /// Derives the rates from two samples of the counters, taken the elapsed seconds apart.
+    /// Returns None if the counters were reset in between, or if no time has elapsed.
+    fn derive(previous: &IoCounters, latest: &IoCounters, elapsed_sec: f64) -> Option<Self> {
+        if latest.is_reset_since(previous) || elapsed_sec <= 0.0 {
+            return None;
+        }
+
+        let average_latency = |latency_us: u64, ops: u64| match ops {
+            0 => 0.0,
+            ops => latency_us as f64 / ops as f64 / 1_000_000.0,
+        };
+        let read_ops = latest.num_read_ops - previous.num_read_ops;
+        let write_ops = latest.num_write_ops - previous.num_write_ops;
+        Some(Self {
+            read_iops: read_ops as f64 / elapsed_sec,
+            write_iops: write_ops as f64 / elapsed_sec,
+            read_bytes_per_sec: (latest.bytes_read - previous.bytes_read) as f64 / elapsed_sec,
+            write_bytes_per_sec: (latest.bytes_written - previous.bytes_written) as f64
+                / elapsed_sec,
+            read_latency_avg_sec: average_latency(
+                latest.read_latency_us - previous.read_latency_us,
+                read_ops,
+            ),
+            write_latency_avg_sec: average_latency(
+                latest.write_latency_us - previous.write_latency_us,
+                write_ops,
+            ),
+        })
+    }
+}
+
+impl IoStatSample {
+    /// Get the start time of the counters.
+    pub(crate) fn start_time(&self) -> f64 {
+        self.start_time
+    }
+
+    /// Get the rates over the interval since the previous sample, if any.
+    pub(crate) fn rates(&self) -> Option<&IoRates> {
+        self.rates.as_ref()
+    }
+}
+
+impl IoStatHistory {
+    /// Records the latest counters of the resources. The resources which are absent are
+    /// forgotten.
+    fn observe<'a>(&mut self, latest: impl Iterator<Item = (&'a str, IoCounters)>) {
+        let now = unix_time_now();
+
+        self.samples = latest
+            .map(|(name, counters)| {
+                let sample = match self.samples.get(name) {
+                    Some(previous) if !counters.is_reset_since(&previous.counters) => {
+                        IoStatSample {
+                            counters,
+                            sampled_at: now,
+                            start_time: previous.start_time,
+                            rates: IoRates::derive(
+                                &previous.counters,
+                                &counters,
+                                now - previous.sampled_at,
+                            ),
+                        }
+                    }
+                    _ => IoStatSample {
+                        counters,
+                        sampled_at: now,
+                        start_time: now,
+                        rates: None,
+                    },
+                };
+                (name.to_string(), sample)
+            })
+            .collect();
+    }
+
+    /// Get the latest sample of the counters of the resource.
+    pub(crate) fn sample(&self, name: &str) -> Option<&IoStatSample> {
+        self.samples.get(name)
+    }
 }
 
 impl Cache {
@@ -59,6 +215,16 @@ impl Cache {
         &mut self.data.pool_stats
     }
 
+    /// Get mutable reference to Nexuses.
+    pub(crate) fn nexus_mut(&mut self) -> &mut Nexuses {
+        &mut self.data.nexuses
+    }
+
+    /// Get a reference to Nexuses.
+    pub(crate) fn nexus(&self) -> &Nexuses {
+        &self.data.nexuses
+    }
+
     /// Get mutable reference to NexusIOStats.
     pub(crate) fn nexus_iostat_mut(&mut self) -> &mut NexusIoStats {
         &mut self.data.nexus_stats
@@ -84,10 +250,78 @@ impl Cache {
         &self.data.replica_stats
     }
 
+    /// Get mutable reference to Replicas.
+    pub(crate) fn replica_mut(&mut self) -> &mut Replicas {
+        &mut self.data.replicas
+    }
+
+    /// Get a reference to Replicas.
+    pub(crate) fn replica(&self) -> &Replicas {
+        &self.data.replicas
+    }
+
+    /// Get mutable reference to Snapshots.
+    pub(crate) fn snapshot_mut(&mut self) -> &mut Snapshots {
+        &mut self.data.snapshots
+    }
+
+    /// Get a reference to Snapshots.
+    pub(crate) fn snapshot(&self) -> &Snapshots {
+        &self.data.snapshots
+    }
+
     /// Get mutable reference to ReplicaIOStats.
     pub(crate) fn replica_iostat_mut(&mut self) -> &mut ReplicaIoStats {
         &mut self.data.replica_stats
     }
+
+    /// Get a reference to the previous samples of the Pool IOStats counters.
+    pub(crate) fn pool_iostat_history(&self) -> &IoStatHistory {
+        &self.data.pool_stats_history
+    }
+
+    /// Get a reference to the previous samples of the Nexus IOStats counters.
+    pub(crate) fn nexus_iostat_history(&self) -> &IoStatHistory {
+        &self.data.nexus_stats_history
+    }
+
+    /// Get a reference to the previous samples of the Replica IOStats counters.
+    pub(crate) fn replica_iostat_history(&self) -> &IoStatHistory {
+        &self.data.replica_stats_history
+    }
+
+    /// Get the outcome of the polls of the source.
+    pub(crate) fn poll_status(&self, source: PollSource) -> PollStatus {
+        self.data.poll_status.get(source)
+    }
+
+    /// Get mutable reference to the previous samples of the Nexus rebuilds.
+    fn rebuild_history_mut(&mut self) -> &mut nexus::RebuildHistory {
+        &mut self.data.rebuild_history
+    }
+
+    /// Get mutable reference to the previous samples of the Pool IOStats counters.
+    fn pool_iostat_history_mut(&mut self) -> &mut IoStatHistory {
+        &mut self.data.pool_stats_history
+    }
+
+    /// Get mutable reference to the previous samples of the Nexus IOStats counters.
+    fn nexus_iostat_history_mut(&mut self) -> &mut IoStatHistory {
+        &mut self.data.nexus_stats_history
+    }
+
+    /// Get mutable reference to the previous samples of the Replica IOStats counters.
+    fn replica_iostat_history_mut(&mut self) -> &mut IoStatHistory {
+        &mut self.data.replica_stats_history
+    }
+}
+
+#[cfg(test)]
+impl Cache {
+    /// Creates a cache with the data, rather than the global cache, for the collector tests.
+    pub(crate) fn new(data: Data) -> Self {
+        Self { data }
+    }
 }
 
 impl Default for Data {
@@ -102,20 +336,157 @@ impl Data {
         Self {
             pools: Pools { pools: vec![] },
             pool_stats: PoolIoStats { pool_stats: vec![] },
+            nexuses: Nexuses { nexuses: vec![] },
+            rebuild_history: nexus::RebuildHistory::default(),
             nexus_stats: NexusIoStats {
                 nexus_stats: vec![],
             },
+            replicas: Replicas { replicas: vec![] },
+            snapshots: Snapshots { snapshots: vec![] },
             replica_stats: ReplicaIoStats {
                 replica_stats: vec![],
             },
+            pool_stats_history: IoStatHistory::default(),
+            nexus_stats_history: IoStatHistory::default(),
+            replica_stats_history: IoStatHistory::default(),
+            poll_status: PollStatuses::default(),
         }
     }
 }
 
-/// Populates Resource cache struct.
+/// Returns true if the last poll of any of the sources succeeded, i.e. if the data of the
+/// io-engine is being served.
+pub(crate) fn is_ready() -> bool {
+    Cache::get_cache()
+        .lock()
+        .map(|cache| cache.data.poll_status.any_up())
+        .unwrap_or_default()
+}
+
+/// Populates Resource cache struct, and records the outcome of the poll of each of the sources.
 pub(crate) async fn store_resource_data(client: &GrpcClient) {
-    let _ = pool::store_pool_info_data(client).await;
-    let _ = pool_stat::store_pool_stats_data(client).await;
-    let _ = nexus_stat::store_nexus_stats_data(client).await;
-    let _ = replica_stat::store_replica_stats_data(client).await;
+    let outcomes = [
+        (PollSource::Pools, pool::store_pool_info_data(client).await),
+        (
+            PollSource::PoolStats,
+            pool_stat::store_pool_stats_data(client).await,
+        ),
+        (
+            PollSource::Nexuses,
+            nexus::store_nexus_info_data(client).await,
+        ),
+        (
+            PollSource::NexusStats,
+            nexus_stat::store_nexus_stats_data(client).await,
+        ),
+        (
+            PollSource::Replicas,
+            replica::store_replica_info_data(client).await,
+        ),
+        (
+            PollSource::ReplicaStats,
+            replica_stat::store_replica_stats_data(client).await,
+        ),
+        (
+            PollSource::Snapshots,
+            snapshot::store_snapshot_info_data(client).await,
+        ),
+    ];
+
+    let mut cache = match Cache::get_cache().lock() {
+        Ok(cache) => cache,
+        Err(error) => {
+            error!(%error, "Error while getting cache resource");
+            return;
+        }
+    };
+    for (source, outcome) in outcomes {
+        cache.data.poll_status.record(source, outcome.is_ok());
+    }
+}
+
+/// Populates the cache from the io-engine at every interval, so that the scrapes are served from
+/// the cache without waiting on the io-engine. A poll which takes longer than the interval delays
+/// the next one, rather than having polls pile up.
+pub(crate) async fn poll_resource_data(client: &GrpcClient, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        ticker.tick().await;
+        store_resource_data(client).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IoRates;
+    use crate::client::IoCounters;
+
+    fn counters(
+        num_read_ops: u64,
+        bytes_read: u64,
+        read_latency_us: u64,
+        num_write_ops: u64,
+        bytes_written: u64,
+        write_latency_us: u64,
+    ) -> IoCounters {
+        IoCounters {
+            bytes_read,
+            num_read_ops,
+            bytes_written,
+            num_write_ops,
+            read_latency_us,
+            write_latency_us,
+        }
+    }
+
+    #[test]
+    fn io_rates_over_interval() {
+        let previous = counters(100, 409_600, 50_000, 50, 204_800, 40_000);
+        let latest = counters(300, 1_228_800, 250_000, 150, 614_400, 240_000);
+
+        let rates = IoRates::derive(&previous, &latest, 10.0).unwrap();
+        assert_eq!(
+            rates,
+            IoRates {
+                read_iops: 20.0,
+                write_iops: 10.0,
+                read_bytes_per_sec: 81_920.0,
+                write_bytes_per_sec: 40_960.0,
+                // 200ms of latency over 200 reads, and 200ms of latency over 100 writes.
+                read_latency_avg_sec: 0.001,
+                write_latency_avg_sec: 0.002,
+            }
+        );
+    }
+
+    #[test]
+    fn io_rates_over_idle_interval() {
+        let previous = counters(100, 409_600, 50_000, 50, 204_800, 40_000);
+
+        // No IO completed in the interval, so there is no latency to average.
+        let rates = IoRates::derive(&previous, &previous, 10.0).unwrap();
+        assert_eq!(rates, IoRates::default());
+        assert!(rates.read_latency_avg_sec.is_finite());
+        assert!(rates.write_latency_avg_sec.is_finite());
+    }
+
+    #[test]
+    fn io_rates_after_counter_reset() {
+        let previous = counters(100, 409_600, 50_000, 50, 204_800, 40_000);
+        let latest = counters(300, 1_228_800, 250_000, 150, 614_400, 240_000);
+
+        // e.g. the io-engine restarted in between the samples.
+        let reset = counters(10, 40_960, 5_000, 5, 20_480, 4_000);
+        assert_eq!(IoRates::derive(&previous, &reset, 10.0), None);
+
+        // Only some of the counters went down.
+        let partial_reset = IoCounters {
+            write_latency_us: 0,
+            ..latest
+        };
+        assert_eq!(IoRates::derive(&previous, &partial_reset, 10.0), None);
+
+        assert_eq!(IoRates::derive(&previous, &latest, 0.0), None);
+    }
 }
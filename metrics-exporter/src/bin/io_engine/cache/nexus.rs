@@ -0,0 +1,173 @@
+use super::{Cache, ResourceOps};
+use crate::client::{
+    grpc_client::GrpcClient,
+    nexus::{NexusInfo, Nexuses},
+};
+use metrics_exporter::poll::unix_time_now;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, ops::DerefMut};
+use tracing::error;
+
+impl ResourceOps for Nexuses {
+    type ResourceVec = Vec<NexusInfo>;
+
+    fn set(&mut self, val: Self::ResourceVec) {
+        self.nexuses = val
+    }
+
+    fn invalidate(&mut self) {
+        self.nexuses = vec![]
+    }
+}
+
+/// The latest samples of the bytes rebuilt by the rebuilds of the children of the nexuses, by
+/// nexus uuid and child uri. These are kept across polls to derive the rate of the rebuilds.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub(crate) struct RebuildHistory {
+    samples: HashMap<String, (u64, f64)>,
+}
+
+impl RebuildHistory {
+    /// Sets the rate of the rebuilds of the nexuses from their previous samples, and records
+    /// their latest samples, taken at the unix time now. The rebuilds which are absent are
+    /// forgotten.
+    fn observe(&mut self, nexuses: &mut [NexusInfo], now: f64) {
+        let mut samples = HashMap::new();
+        for nexus in nexuses {
+            let nexus_uuid = nexus.uuid().clone();
+            for child in nexus.children_mut() {
+                let key = format!("{nexus_uuid}/{}", child.uri());
+                let Some(rebuild) = child.rebuild_mut() else {
+                    continue;
+                };
+                let bytes_rebuilt = rebuild.bytes_rebuilt();
+                let rate = match self.samples.get(&key) {
+                    // A rebuild which restarted has rebuilt fewer bytes than before.
+                    Some(&(previous, sampled_at))
+                        if bytes_rebuilt >= previous && now > sampled_at =>
+                    {
+                        Some((bytes_rebuilt - previous) as f64 / (now - sampled_at))
+                    }
+                    _ => None,
+                };
+                rebuild.set_rate(rate);
+                samples.insert(key, (bytes_rebuilt, now));
+            }
+        }
+        self.samples = samples;
+    }
+}
+
+/// To store the nexus state, children and rebuilds data in cache.
+pub(crate) async fn store_nexus_info_data(client: &GrpcClient) -> Result<(), ()> {
+    let nexuses = client.list_nexuses().await;
+    let mut cache = match Cache::get_cache().lock() {
+        Ok(cache) => cache,
+        Err(error) => {
+            error!(%error, "Error while getting cache resource");
+            return Err(());
+        }
+    };
+    let nexus_cache = cache.deref_mut();
+    match nexuses {
+        Ok(mut nexuses) => {
+            nexus_cache
+                .rebuild_history_mut()
+                .observe(&mut nexuses.nexuses, unix_time_now());
+            nexus_cache.nexus_mut().set(nexuses.nexuses);
+        }
+        // invalidate cache in case of error
+        Err(error) => {
+            error!(?error, "Error getting nexus data, invalidating nexus cache");
+            nexus_cache.nexus_mut().invalidate();
+            return Err(());
+        }
+    };
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RebuildHistory;
+    use crate::client::nexus::NexusInfo;
+    use serde_json::json;
+
+    /// A nexus with a healthy child, and a child which is being rebuilt if it has rebuilt bytes.
+    fn nexus(bytes_rebuilt: Option<u64>) -> NexusInfo {
+        serde_json::from_value(json!({
+            "name": "ec4e66fd-3b33-4439-b504-d49aba53da26",
+            "uuid": "2b8d7f1c-41c6-4c4e-8a0e-5b0b6b3a8d3e",
+            "state": 1,
+            "children": [
+                { "uri": "bdev:///healthy", "state": 1, "rebuild_progress": null, "rebuild": null },
+                {
+                    "uri": "nvmf://10.0.0.2:8420/rebuilding",
+                    "state": 2,
+                    "rebuild_progress": bytes_rebuilt.map(|_| 25),
+                    "rebuild": bytes_rebuilt.map(|bytes| json!({ "bytes_rebuilt": bytes, "rate": null }))
+                }
+            ],
+            "rebuilds": u64::from(bytes_rebuilt.is_some())
+        }))
+        .unwrap()
+    }
+
+    /// Returns the rate of the rebuild of the rebuilding child.
+    fn rate(nexus: &NexusInfo) -> Option<f64> {
+        nexus.children()[1]
+            .rebuild()
+            .and_then(|rebuild| rebuild.rate())
+    }
+
+    #[test]
+    fn rebuild_rate_over_interval() {
+        let mut history = RebuildHistory::default();
+
+        // The first sample has no rate.
+        let mut nexuses = vec![nexus(Some(1_048_576))];
+        history.observe(&mut nexuses, 100.0);
+        assert_eq!(rate(&nexuses[0]), None);
+        assert!(nexuses[0].children()[0].rebuild().is_none());
+
+        let mut nexuses = vec![nexus(Some(11_534_336))];
+        history.observe(&mut nexuses, 110.0);
+        assert_eq!(rate(&nexuses[0]), Some(1_048_576.0));
+
+        // No time has elapsed.
+        let mut nexuses = vec![nexus(Some(11_534_336))];
+        history.observe(&mut nexuses, 110.0);
+        assert_eq!(rate(&nexuses[0]), None);
+    }
+
+    #[test]
+    fn restarted_rebuild_has_no_rate() {
+        let mut history = RebuildHistory::default();
+        history.observe(&mut [nexus(Some(11_534_336))], 100.0);
+
+        // The rebuild restarted, and has rebuilt fewer bytes than before.
+        let mut nexuses = vec![nexus(Some(1_048_576))];
+        history.observe(&mut nexuses, 110.0);
+        assert_eq!(rate(&nexuses[0]), None);
+
+        // The rate is derived from the restarted rebuild's sample.
+        let mut nexuses = vec![nexus(Some(6_291_456))];
+        history.observe(&mut nexuses, 115.0);
+        assert_eq!(rate(&nexuses[0]), Some(1_048_576.0));
+    }
+
+    #[test]
+    fn finished_rebuilds_are_forgotten() {
+        let mut history = RebuildHistory::default();
+        history.observe(&mut [nexus(Some(1_048_576))], 100.0);
+        assert_eq!(history.samples.len(), 1);
+
+        // The child is no longer being rebuilt.
+        history.observe(&mut [nexus(None)], 110.0);
+        assert!(history.samples.is_empty());
+
+        // A new rebuild of the child starts over, without a rate.
+        let mut nexuses = vec![nexus(Some(11_534_336))];
+        history.observe(&mut nexuses, 120.0);
+        assert_eq!(rate(&nexuses[0]), None);
+    }
+}
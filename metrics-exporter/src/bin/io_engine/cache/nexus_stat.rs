@@ -30,6 +30,12 @@ pub(crate) async fn store_nexus_stats_data(client: &GrpcClient) -> Result<(), ()
     let nexus_cache = cache.deref_mut();
     match nexus_stats {
         Ok(nexus) => {
+            nexus_cache.nexus_iostat_history_mut().observe(
+                nexus
+                    .nexus_stats
+                    .iter()
+                    .map(|stat| (stat.name().as_str(), stat.io_counters())),
+            );
             nexus_cache.nexus_iostat_mut().set(nexus.nexus_stats);
         }
         // invalidate cache in case of error
@@ -31,6 +31,12 @@ pub(crate) async fn store_pool_stats_data(client: &GrpcClient) -> Result<(), ()>
     let pools_cache = cache.deref_mut();
     match pool_stats {
         Ok(pools) => {
+            pools_cache.pool_iostat_history_mut().observe(
+                pools
+                    .pool_stats
+                    .iter()
+                    .map(|stat| (stat.name().as_str(), stat.io_counters())),
+            );
             pools_cache.pool_iostat_mut().set(pools.pool_stats);
         }
         // invalidate cache in case of error
@@ -0,0 +1,47 @@
+use super::{Cache, ResourceOps};
+use crate::client::{
+    grpc_client::GrpcClient,
+    replica::{ReplicaInfo, Replicas},
+};
+use std::ops::DerefMut;
+use tracing::error;
+
+impl ResourceOps for Replicas {
+    type ResourceVec = Vec<ReplicaInfo>;
+
+    fn set(&mut self, val: Self::ResourceVec) {
+        self.replicas = val
+    }
+
+    fn invalidate(&mut self) {
+        self.replicas = vec![]
+    }
+}
+
+/// To store replicas capacity and space usage data in cache.
+pub(crate) async fn store_replica_info_data(client: &GrpcClient) -> Result<(), ()> {
+    let replicas = client.list_replicas().await;
+    let mut cache = match Cache::get_cache().lock() {
+        Ok(cache) => cache,
+        Err(error) => {
+            error!(%error, "Error while getting cache resource");
+            return Err(());
+        }
+    };
+    let replicas_cache = cache.deref_mut();
+    match replicas {
+        Ok(replicas) => {
+            replicas_cache.replica_mut().set(replicas.replicas);
+        }
+        // invalidate cache in case of error
+        Err(error) => {
+            error!(
+                ?error,
+                "Error getting replicas data, invalidating replicas cache"
+            );
+            replicas_cache.replica_mut().invalidate();
+            return Err(());
+        }
+    };
+    Ok(())
+}
@@ -31,6 +31,12 @@ pub(crate) async fn store_replica_stats_data(client: &GrpcClient) -> Result<(),
     let replica_cache = cache.deref_mut();
     match replica_stats {
         Ok(replicas) => {
+            replica_cache.replica_iostat_history_mut().observe(
+                replicas
+                    .replica_stats
+                    .iter()
+                    .map(|stat| (stat.name().as_str(), stat.io_counters())),
+            );
             replica_cache
                 .replica_iostat_mut()
                 .set(replicas.replica_stats);
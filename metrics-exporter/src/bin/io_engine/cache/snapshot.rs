@@ -0,0 +1,47 @@
+use super::{Cache, ResourceOps};
+use crate::client::{
+    grpc_client::GrpcClient,
+    snapshot::{SnapshotInfo, Snapshots},
+};
+use std::ops::DerefMut;
+use tracing::error;
+
+impl ResourceOps for Snapshots {
+    type ResourceVec = Vec<SnapshotInfo>;
+
+    fn set(&mut self, val: Self::ResourceVec) {
+        self.snapshots = val
+    }
+
+    fn invalidate(&mut self) {
+        self.snapshots = vec![]
+    }
+}
+
+/// To store replica snapshots data in cache.
+pub(crate) async fn store_snapshot_info_data(client: &GrpcClient) -> Result<(), ()> {
+    let snapshots = client.list_snapshots().await;
+    let mut cache = match Cache::get_cache().lock() {
+        Ok(cache) => cache,
+        Err(error) => {
+            error!(%error, "Error while getting cache resource");
+            return Err(());
+        }
+    };
+    let snapshots_cache = cache.deref_mut();
+    match snapshots {
+        Ok(snapshots) => {
+            snapshots_cache.snapshot_mut().set(snapshots.snapshots);
+        }
+        // invalidate cache in case of error
+        Err(error) => {
+            error!(
+                ?error,
+                "Error getting snapshots data, invalidating snapshots cache"
+            );
+            snapshots_cache.snapshot_mut().invalidate();
+            return Err(());
+        }
+    };
+    Ok(())
+}
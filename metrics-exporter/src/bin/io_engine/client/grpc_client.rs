@@ -1,15 +1,20 @@
 use crate::{error::ExporterError, get_node_name, get_pod_ip};
 
 use crate::client::{
+    nexus::{NexusInfo, Nexuses, RebuildInfo},
     nexus_stat::{NexusIoStat, NexusIoStats},
     pool::{PoolInfo, Pools},
     pool_stat::{PoolIoStat, PoolIoStats},
+    replica::{ReplicaInfo, Replicas},
     replica_stat::{ReplicaIoStat, ReplicaIoStats},
+    snapshot::{SnapshotInfo, Snapshots},
 };
-use std::{net::SocketAddr, time::Duration};
-use tokio::time::sleep;
-use tonic::transport::{Channel, Uri};
-use tracing::error;
+use hyper_util::rt::TokioIo;
+use std::{net::SocketAddr, path::PathBuf, str::FromStr, sync::RwLock, time::Duration};
+use tokio::{net::UnixStream, time::sleep};
+use tonic::transport::{Channel, Endpoint, Uri};
+use tower::service_fn;
+use tracing::{debug, error, info};
 
 /// Timeout for gRPC connection.
 #[derive(Debug, Clone)]
@@ -33,92 +38,198 @@ impl Timeouts {
     }
 }
 
+/// The address of the io-engine's gRPC server.
+#[derive(Debug, Clone)]
+pub(crate) enum IoEngineEndpoint {
+    /// A URI of the form http://host:port or https://host:port.
+    Tcp(Uri),
+    /// The path of a unix socket, from a URI of the form unix:///path/to/socket.
+    Unix(PathBuf),
+}
+
+impl FromStr for IoEngineEndpoint {
+    type Err = String;
+
+    fn from_str(endpoint: &str) -> Result<Self, Self::Err> {
+        match endpoint.strip_prefix("unix://") {
+            Some(path) if !path.is_empty() => Ok(Self::Unix(PathBuf::from(path))),
+            Some(_) => Err(format!("No unix socket path in '{endpoint}'")),
+            None => Uri::from_str(endpoint)
+                .map(Self::Tcp)
+                .map_err(|error| error.to_string()),
+        }
+    }
+}
+
+/// The exponential backoff in between the attempts to connect to the io-engine.
+#[derive(Debug, Clone)]
+pub(crate) struct Backoff {
+    initial: Duration,
+    max: Duration,
+}
+
+impl Backoff {
+    /// Return a new `Self` which starts at initial and doubles up to max.
+    pub(crate) fn new(initial: Duration, max: Duration) -> Self {
+        Self { initial, max }
+    }
+}
+
 /// Context for Grpc client.
 #[derive(Debug, Clone)]
 pub(crate) struct GrpcContext {
-    endpoint: tonic::transport::Endpoint,
+    endpoint: Endpoint,
+    socket: Option<PathBuf>,
+    backoff: Backoff,
 }
 
 impl GrpcContext {
-    pub fn new(endpoint: Uri, timeouts: Timeouts) -> Self {
-        let endpoint = tonic::transport::Endpoint::from(endpoint)
+    pub fn new(endpoint: IoEngineEndpoint, timeouts: Timeouts, backoff: Backoff) -> Self {
+        let (uri, socket) = match endpoint {
+            IoEngineEndpoint::Tcp(uri) => (uri, None),
+            // The URI is not used to connect over a unix socket, but the Endpoint needs one.
+            IoEngineEndpoint::Unix(path) => (Uri::from_static("http://io-engine"), Some(path)),
+        };
+        let endpoint = Endpoint::from(uri)
             .connect_timeout(timeouts.connect())
             .timeout(timeouts.request());
-        Self { endpoint }
+        Self {
+            endpoint,
+            socket,
+            backoff,
+        }
+    }
+
+    /// Connects to the io-engine, over the unix socket if there is one.
+    async fn connect(&self) -> Result<Channel, tonic::transport::Error> {
+        match &self.socket {
+            Some(path) => {
+                let path = path.clone();
+                self.endpoint
+                    .connect_with_connector(service_fn(move |_: Uri| {
+                        let path = path.clone();
+                        async move { UnixStream::connect(path).await.map(TokioIo::new) }
+                    }))
+                    .await
+            }
+            None => self.endpoint.connect().await,
+        }
     }
 }
 
 /// The V1 PoolClient.
 type PoolClient = rpc::v1::pool::pool_rpc_client::PoolRpcClient<Channel>;
 type StatsClient = rpc::v1::stats::StatsRpcClient<Channel>;
+/// The V1 NexusClient.
+type NexusClient = rpc::v1::nexus::nexus_rpc_client::NexusRpcClient<Channel>;
+/// The V1 ReplicaClient.
+type ReplicaClient = rpc::v1::replica::replica_rpc_client::ReplicaRpcClient<Channel>;
+/// The V1 SnapshotClient.
+type SnapshotClient = rpc::v1::snapshot::snapshot_rpc_client::SnapshotRpcClient<Channel>;
 
 /// A wrapper for client for the V1 dataplane interface.
 #[derive(Clone, Debug)]
 pub(crate) struct MayaClientV1 {
     pub(crate) pool: PoolClient,
     pub(crate) stats: StatsClient,
+    pub(crate) nexus: NexusClient,
+    pub(crate) replica: ReplicaClient,
+    pub(crate) snapshot: SnapshotClient,
 }
 
-/// Dataplane grpc client.
-#[derive(Debug, Clone)]
+/// Dataplane grpc client. The client is absent until the io-engine is connected to.
+#[derive(Debug)]
 pub(crate) struct GrpcClient {
-    client: Option<MayaClientV1>,
+    context: GrpcContext,
+    client: RwLock<Option<MayaClientV1>>,
 }
 
 /// Number of grpc connect retries without error logging.
 const SILENT_RETRIES: i32 = 3;
 
 impl GrpcClient {
-    /// Initialize v1 io engine gRPC client.
-    pub(crate) async fn new(context: GrpcContext) -> Result<Self, ExporterError> {
-        let sleep_duration_sec = 10;
+    /// Initialize v1 io engine gRPC client. This does not connect to the io engine.
+    pub(crate) fn new(context: GrpcContext) -> Self {
+        Self {
+            context,
+            client: RwLock::new(None),
+        }
+    }
+
+    /// Connects to the io engine, retrying with exponential backoff until it succeeds. The
+    /// channel reconnects by itself if the connection is lost afterwards.
+    pub(crate) async fn connect(&self) {
+        let mut backoff = self.context.backoff.initial;
         let mut num_retires = 0;
         loop {
-            if let Ok(channel) = context.endpoint.connect().await {
-                let pool = PoolClient::new(channel.clone());
-                let stats = StatsClient::new(channel.clone());
-                return Ok(Self {
-                    client: Some(MayaClientV1 { pool, stats }),
-                });
-            } else {
-                if num_retires > SILENT_RETRIES {
-                    error!(
-                        "Grpc connection timeout, retrying after {}s",
-                        sleep_duration_sec
-                    );
+            match self.context.connect().await {
+                Ok(channel) => {
+                    let pool = PoolClient::new(channel.clone());
+                    let stats = StatsClient::new(channel.clone());
+                    let nexus = NexusClient::new(channel.clone());
+                    let replica = ReplicaClient::new(channel.clone());
+                    let snapshot = SnapshotClient::new(channel.clone());
+                    if let Ok(mut client) = self.client.write() {
+                        *client = Some(MayaClientV1 {
+                            pool,
+                            stats,
+                            nexus,
+                            replica,
+                            snapshot,
+                        });
+                    }
+                    info!("Connected to the io engine");
+                    return;
+                }
+                Err(error) => {
+                    if num_retires > SILENT_RETRIES {
+                        error!(
+                            %error,
+                            "Grpc connection failed, retrying after {}",
+                            humantime::format_duration(backoff)
+                        );
+                    }
+                    num_retires += 1;
                 }
-                num_retires += 1;
             }
-            sleep(Duration::from_secs(sleep_duration_sec)).await;
+            sleep(backoff).await;
+            backoff = (backoff * 2).min(self.context.backoff.max);
         }
     }
 
     /// Get the v1 api client.
     pub(crate) fn client_v1(&self) -> Result<MayaClientV1, ExporterError> {
-        match self.client.clone() {
+        match self.client.read().ok().and_then(|client| client.clone()) {
             Some(client) => Ok(client),
             None => Err(ExporterError::GrpcClientError(
-                "Could not get v1 client".to_string(),
+                "Not connected to the io engine".to_string(),
             )),
         }
     }
 }
 
-/// Initialize mayastor grpc client.
-pub(crate) async fn init_client() -> Result<GrpcClient, ExporterError> {
-    let timeout = Timeouts::new(Duration::from_secs(1), Duration::from_secs(5));
-    let pod_ip = get_pod_ip()?;
+/// Initialize mayastor grpc client, for the endpoint, or for the pod ip's port 10124 if there is
+/// none. The client is not connected to the io engine yet.
+pub(crate) fn init_client(
+    endpoint: Option<IoEngineEndpoint>,
+    timeouts: Timeouts,
+    backoff: Backoff,
+) -> Result<GrpcClient, ExporterError> {
     let _ = get_node_name()?;
 
-    let endpoint = Uri::builder()
-        .scheme("https")
-        .authority(SocketAddr::new(pod_ip, 10124).to_string())
-        .path_and_query("")
-        .build()
-        .map_err(|error| ExporterError::InvalidURI(error.to_string()))?;
-    let ctx = GrpcContext::new(endpoint, timeout);
-    let client = GrpcClient::new(ctx).await?;
-    Ok(client)
+    let endpoint = match endpoint {
+        Some(endpoint) => endpoint,
+        None => IoEngineEndpoint::Tcp(
+            Uri::builder()
+                .scheme("https")
+                .authority(SocketAddr::new(get_pod_ip()?, 10124).to_string())
+                .path_and_query("")
+                .build()
+                .map_err(|error| ExporterError::InvalidURI(error.to_string()))?,
+        ),
+    };
+    let ctx = GrpcContext::new(endpoint, timeouts, backoff);
+    Ok(GrpcClient::new(ctx))
 }
 
 impl GrpcClient {
@@ -143,6 +254,90 @@ impl GrpcClient {
         Ok(Pools { pools })
     }
 
+    /// Gets the state and the children of all nexus on the io engine, along with the statistics
+    /// of the rebuilds of the children which are being rebuilt. Maps the response to NexusInfo
+    /// struct.
+    pub(crate) async fn list_nexuses(&self) -> Result<Nexuses, ExporterError> {
+        let mut client = self.client_v1()?;
+        let mut nexuses = match client
+            .nexus
+            .list_nexus(rpc::v1::nexus::ListNexusOptions::default())
+            .await
+        {
+            Ok(response) => response
+                .into_inner()
+                .nexus_list
+                .into_iter()
+                .map(NexusInfo::from)
+                .collect::<Vec<_>>(),
+            Err(error) => return Err(ExporterError::GrpcResponseError(error.to_string())),
+        };
+
+        for nexus in &mut nexuses {
+            let nexus_uuid = nexus.uuid().clone();
+            for child in nexus.children_mut() {
+                if child.rebuild_progress().is_none() {
+                    continue;
+                }
+                let request = rpc::v1::nexus::RebuildStatsRequest {
+                    nexus_uuid: nexus_uuid.clone(),
+                    uri: child.uri().clone(),
+                };
+                match client.nexus.get_rebuild_stats(request).await {
+                    Ok(response) => child.set_rebuild(RebuildInfo::from(response.into_inner())),
+                    // The rebuild may have completed since the nexus was listed.
+                    Err(error) => {
+                        debug!(%error, child.uri = %child.uri(), "Unable to get rebuild stats")
+                    }
+                }
+            }
+        }
+
+        Ok(Nexuses { nexuses })
+    }
+
+    /// Gets Capacity and space usage of all replica on the io engine. The snapshots, which are
+    /// listed as replicas as well, are left out. Maps the response to ReplicaInfo struct.
+    pub(crate) async fn list_replicas(&self) -> Result<Replicas, ExporterError> {
+        let replicas = match self
+            .client_v1()?
+            .replica
+            .list_replicas(rpc::v1::replica::ListReplicaOptions::default())
+            .await
+        {
+            Ok(response) => response
+                .into_inner()
+                .replicas
+                .into_iter()
+                .filter(|replica| !replica.is_snapshot)
+                .map(ReplicaInfo::from)
+                .collect::<Vec<_>>(),
+            Err(error) => return Err(ExporterError::GrpcResponseError(error.to_string())),
+        };
+
+        Ok(Replicas { replicas })
+    }
+
+    /// Gets all replica snapshot on the io engine. Maps the response to SnapshotInfo struct.
+    pub(crate) async fn list_snapshots(&self) -> Result<Snapshots, ExporterError> {
+        let snapshots = match self
+            .client_v1()?
+            .snapshot
+            .list_snapshot(rpc::v1::snapshot::ListSnapshotsRequest::default())
+            .await
+        {
+            Ok(response) => response
+                .into_inner()
+                .snapshots
+                .into_iter()
+                .map(SnapshotInfo::from)
+                .collect::<Vec<_>>(),
+            Err(error) => return Err(ExporterError::GrpcResponseError(error.to_string())),
+        };
+
+        Ok(Snapshots { snapshots })
+    }
+
     /// Gets Io Statistics of all pool on the io engine. Maps the response to PoolIoStat struct.
     pub(crate) async fn get_pool_iostat(&self) -> Result<PoolIoStats, ExporterError> {
         let pool_stats = match self
@@ -1,14 +1,47 @@
 /// Grpc client module.
 pub(crate) mod grpc_client;
+/// NexusInfo module.
+pub(crate) mod nexus;
 /// NexusIoStats module.
 pub(crate) mod nexus_stat;
 /// PoolInfo module.
 pub(crate) mod pool;
 /// PoolIoStats module
 pub(crate) mod pool_stat;
+/// ReplicaInfo module.
+pub(crate) mod replica;
 pub(crate) mod replica_stat;
+/// SnapshotInfo module.
+pub(crate) mod snapshot;
+
+use serde::{Deserialize, Serialize};
 
 /// Convert ticks to time in microseconds.
 fn ticks_to_time(tick: u64, tick_rate: u64) -> u64 {
     ((tick as u128 * 1000000) / tick_rate as u128) as u64
 }
+
+/// The cumulative IO statistics of a pool, nexus or replica. These only ever go up, unless the
+/// io-engine restarts.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct IoCounters {
+    pub(crate) bytes_read: u64,
+    pub(crate) num_read_ops: u64,
+    pub(crate) bytes_written: u64,
+    pub(crate) num_write_ops: u64,
+    pub(crate) read_latency_us: u64,
+    pub(crate) write_latency_us: u64,
+}
+
+impl IoCounters {
+    /// Returns true if any of the counters went down since the previous sample, i.e. if the
+    /// counters were reset in between.
+    pub(crate) fn is_reset_since(&self, previous: &IoCounters) -> bool {
+        self.bytes_read < previous.bytes_read
+            || self.num_read_ops < previous.num_read_ops
+            || self.bytes_written < previous.bytes_written
+            || self.num_write_ops < previous.num_write_ops
+            || self.read_latency_us < previous.read_latency_us
+            || self.write_latency_us < previous.write_latency_us
+    }
+}
@@ -0,0 +1,149 @@
+use serde::{Deserialize, Serialize};
+
+/// This stores the state, children and rebuilds of a nexus.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct NexusInfo {
+    name: String,
+    uuid: String,
+    state: u64,
+    children: Vec<ChildInfo>,
+    rebuilds: u64,
+}
+
+impl NexusInfo {
+    /// Get name of the nexus.
+    pub(crate) fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// Get uuid of the nexus.
+    pub(crate) fn uuid(&self) -> &String {
+        &self.uuid
+    }
+
+    /// Get state of the nexus.
+    pub(crate) fn state(&self) -> u64 {
+        self.state
+    }
+
+    /// Get the children of the nexus.
+    pub(crate) fn children(&self) -> &[ChildInfo] {
+        self.children.as_slice()
+    }
+
+    /// Get mutable reference to the children of the nexus.
+    pub(crate) fn children_mut(&mut self) -> &mut [ChildInfo] {
+        self.children.as_mut_slice()
+    }
+
+    /// Get the number of active rebuild jobs of the nexus.
+    pub(crate) fn rebuilds(&self) -> u64 {
+        self.rebuilds
+    }
+}
+
+/// This stores the state of a child of a nexus, and its rebuild if it is being rebuilt.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct ChildInfo {
+    uri: String,
+    state: u64,
+    rebuild_progress: Option<u64>,
+    rebuild: Option<RebuildInfo>,
+}
+
+impl ChildInfo {
+    /// Get uri of the child.
+    pub(crate) fn uri(&self) -> &String {
+        &self.uri
+    }
+
+    /// Get state of the child.
+    pub(crate) fn state(&self) -> u64 {
+        self.state
+    }
+
+    /// Get the rebuild progress of the child in percent, if it is being rebuilt.
+    pub(crate) fn rebuild_progress(&self) -> Option<u64> {
+        self.rebuild_progress
+    }
+
+    /// Get the rebuild of the child, if its statistics are known.
+    pub(crate) fn rebuild(&self) -> Option<&RebuildInfo> {
+        self.rebuild.as_ref()
+    }
+
+    /// Set the rebuild statistics of the child.
+    pub(crate) fn set_rebuild(&mut self, rebuild: RebuildInfo) {
+        self.rebuild = Some(rebuild);
+    }
+
+    /// Get mutable reference to the rebuild of the child, if its statistics are known.
+    pub(crate) fn rebuild_mut(&mut self) -> Option<&mut RebuildInfo> {
+        self.rebuild.as_mut()
+    }
+}
+
+/// This stores the statistics of the rebuild of a child.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct RebuildInfo {
+    bytes_rebuilt: u64,
+    /// The rate of the rebuild in bytes per second, since the previous poll. This is derived by
+    /// the cache, as the io-engine does not report it.
+    rate: Option<f64>,
+}
+
+impl RebuildInfo {
+    /// Get the number of bytes rebuilt so far.
+    pub(crate) fn bytes_rebuilt(&self) -> u64 {
+        self.bytes_rebuilt
+    }
+
+    /// Get the rate of the rebuild in bytes per second, if it is known.
+    pub(crate) fn rate(&self) -> Option<f64> {
+        self.rate
+    }
+
+    /// Set the rate of the rebuild in bytes per second.
+    pub(crate) fn set_rate(&mut self, rate: Option<f64>) {
+        self.rate = rate;
+    }
+}
+
+/// Array of NexusInfo objects.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct Nexuses {
+    pub(crate) nexuses: Vec<NexusInfo>,
+}
+
+impl From<rpc::v1::nexus::Nexus> for NexusInfo {
+    fn from(value: rpc::v1::nexus::Nexus) -> Self {
+        Self {
+            name: value.name,
+            uuid: value.uuid,
+            state: value.state as u64,
+            children: value.children.into_iter().map(ChildInfo::from).collect(),
+            rebuilds: value.rebuilds as u64,
+        }
+    }
+}
+
+impl From<rpc::v1::nexus::Child> for ChildInfo {
+    fn from(value: rpc::v1::nexus::Child) -> Self {
+        Self {
+            uri: value.uri,
+            state: value.state as u64,
+            // The io-engine reports a negative progress if the child is not being rebuilt.
+            rebuild_progress: u64::try_from(value.rebuild_progress).ok(),
+            rebuild: None,
+        }
+    }
+}
+
+impl From<rpc::v1::nexus::RebuildStatsResponse> for RebuildInfo {
+    fn from(value: rpc::v1::nexus::RebuildStatsResponse) -> Self {
+        Self {
+            bytes_rebuilt: value.blocks_recovered * value.block_size,
+            rate: None,
+        }
+    }
+}
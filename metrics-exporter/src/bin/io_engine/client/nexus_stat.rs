@@ -1,4 +1,4 @@
-use super::ticks_to_time;
+use super::{ticks_to_time, IoCounters};
 use serde::{Deserialize, Serialize};
 
 /// This stores IoStat information of a nexus.
@@ -19,34 +19,16 @@ impl NexusIoStat {
         &self.name
     }
 
-    /// Get used bytes read of the nexus.
-    pub(crate) fn bytes_read(&self) -> u64 {
-        self.bytes_read
-    }
-
-    /// Get total number of read ops performed by the nexus.
-    pub(crate) fn num_read_ops(&self) -> u64 {
-        self.num_read_ops
-    }
-
-    /// Get the total bytes written in bytes.
-    pub(crate) fn bytes_written(&self) -> u64 {
-        self.bytes_written
-    }
-
-    /// Get total number of write ops performed by the nexus.
-    pub(crate) fn num_write_ops(&self) -> u64 {
-        self.num_write_ops
-    }
-
-    /// Get total read latency in usec on the nexus.
-    pub(crate) fn read_latency_us(&self) -> u64 {
-        self.read_latency_us
-    }
-
-    /// Get total write latency in usec on the nexus.
-    pub(crate) fn write_latency_us(&self) -> u64 {
-        self.write_latency_us
+    /// Get all of the IO counters of the nexus.
+    pub(crate) fn io_counters(&self) -> IoCounters {
+        IoCounters {
+            bytes_read: self.bytes_read,
+            num_read_ops: self.num_read_ops,
+            bytes_written: self.bytes_written,
+            num_write_ops: self.num_write_ops,
+            read_latency_us: self.read_latency_us,
+            write_latency_us: self.write_latency_us,
+        }
     }
 }
 
@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+
+/// This stores the capacity and space usage information of a replica.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct ReplicaInfo {
+    name: String,
+    pool_name: String,
+    entity_id: Option<String>,
+    size: u64,
+    allocated_bytes: u64,
+    allocated_bytes_snapshots: u64,
+}
+
+impl ReplicaInfo {
+    /// Get name of the replica.
+    pub(crate) fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// Get name of the pool of the replica.
+    pub(crate) fn pool_name(&self) -> &String {
+        &self.pool_name
+    }
+
+    /// Get entity_id of the replica, i.e. the uuid of its volume, if it belongs to one.
+    pub(crate) fn entity_id(&self) -> Option<&String> {
+        self.entity_id.as_ref()
+    }
+
+    /// Get the provisioned size of the replica.
+    pub(crate) fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Get the bytes allocated to the replica on its pool.
+    pub(crate) fn allocated_bytes(&self) -> u64 {
+        self.allocated_bytes
+    }
+
+    /// Get the bytes allocated to the snapshots of the replica on its pool.
+    pub(crate) fn allocated_bytes_snapshots(&self) -> u64 {
+        self.allocated_bytes_snapshots
+    }
+}
+
+/// Array of ReplicaInfo objects.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct Replicas {
+    pub(crate) replicas: Vec<ReplicaInfo>,
+}
+
+impl From<rpc::v1::replica::Replica> for ReplicaInfo {
+    fn from(value: rpc::v1::replica::Replica) -> Self {
+        let usage = value.usage.unwrap_or_default();
+        Self {
+            name: value.name,
+            pool_name: value.poolname,
+            entity_id: value.entity_id.filter(|id| !id.is_empty()),
+            size: value.size,
+            allocated_bytes: usage.allocated_bytes,
+            allocated_bytes_snapshots: usage.allocated_bytes_snapshots,
+        }
+    }
+}
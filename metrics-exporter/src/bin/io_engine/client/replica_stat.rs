@@ -1,4 +1,4 @@
-use super::ticks_to_time;
+use super::{ticks_to_time, IoCounters};
 use crate::error::ExporterError;
 use serde::{Deserialize, Serialize};
 
@@ -21,40 +21,22 @@ impl ReplicaIoStat {
         &self.name
     }
 
-    /// Get used bytes read of the replica.
-    pub(crate) fn bytes_read(&self) -> u64 {
-        self.bytes_read
-    }
-
-    /// Get total number of read ops performed by the replica.
-    pub(crate) fn num_read_ops(&self) -> u64 {
-        self.num_read_ops
-    }
-
-    /// Get the total bytes written in bytes.
-    pub(crate) fn bytes_written(&self) -> u64 {
-        self.bytes_written
-    }
-
-    /// Get total number of write ops performed by the replica.
-    pub(crate) fn num_write_ops(&self) -> u64 {
-        self.num_write_ops
-    }
-
-    /// Get total read latency in usec on the replica.
-    pub(crate) fn read_latency_us(&self) -> u64 {
-        self.read_latency_us
-    }
-
-    /// Get total write latency in usec on the replica.
-    pub(crate) fn write_latency_us(&self) -> u64 {
-        self.write_latency_us
-    }
-
     /// Get entity_id of the replica.
     pub(crate) fn entity_id(&self) -> String {
         self.entity_id.clone()
     }
+
+    /// Get all of the IO counters of the replica.
+    pub(crate) fn io_counters(&self) -> IoCounters {
+        IoCounters {
+            bytes_read: self.bytes_read,
+            num_read_ops: self.num_read_ops,
+            bytes_written: self.bytes_written,
+            num_write_ops: self.num_write_ops,
+            read_latency_us: self.read_latency_us,
+            write_latency_us: self.write_latency_us,
+        }
+    }
 }
 
 /// Array of NexusIoStat objects.
@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+
+/// This stores the information of a replica snapshot.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct SnapshotInfo {
+    pool_name: String,
+    entity_id: String,
+}
+
+impl SnapshotInfo {
+    /// Get name of the pool of the snapshot.
+    pub(crate) fn pool_name(&self) -> &String {
+        &self.pool_name
+    }
+
+    /// Get entity_id of the snapshot, i.e. the uuid of the volume it was taken of.
+    pub(crate) fn entity_id(&self) -> &String {
+        &self.entity_id
+    }
+}
+
+/// Array of SnapshotInfo objects.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct Snapshots {
+    pub(crate) snapshots: Vec<SnapshotInfo>,
+}
+
+impl From<rpc::v1::snapshot::SnapshotInfo> for SnapshotInfo {
+    fn from(value: rpc::v1::snapshot::SnapshotInfo) -> Self {
+        Self {
+            pool_name: value.pool_name,
+            entity_id: value.entity_id,
+        }
+    }
+}
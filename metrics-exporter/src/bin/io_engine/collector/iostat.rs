@@ -0,0 +1,293 @@
+use crate::{
+    cache::{IoRates, IoStatSample},
+    client::IoCounters,
+};
+use prometheus::{
+    core::{Collector, Desc},
+    proto::MetricFamily,
+    CounterVec, GaugeVec, Opts,
+};
+
+/// Number of microseconds in a second.
+const MICROS_PER_SEC: f64 = 1_000_000.0;
+
+/// The IO statistics metrics of a pool, volume or replica. The cumulative IO statistics are
+/// exposed as counters, along with the time at which the counters were last reset, and the rates
+/// over the interval between the last two samples.
+#[derive(Clone, Debug)]
+pub(crate) struct IoStatMetrics {
+    read_bytes: CounterVec,
+    read_ops: CounterVec,
+    written_bytes: CounterVec,
+    write_ops: CounterVec,
+    read_time: CounterVec,
+    write_time: CounterVec,
+    start_time: GaugeVec,
+    rates: IoRateGauges,
+    legacy_gauges: Option<LegacyIoStatGauges>,
+}
+
+/// The IO rates over the interval between the last two samples of the IO statistics.
+#[derive(Clone, Debug)]
+struct IoRateGauges {
+    read_iops: GaugeVec,
+    write_iops: GaugeVec,
+    read_throughput: GaugeVec,
+    write_throughput: GaugeVec,
+    read_latency_avg: GaugeVec,
+    write_latency_avg: GaugeVec,
+}
+
+/// The IO statistics metrics, as they were exposed before they were counters. These are
+/// deprecated, and are only exposed if they are enabled.
+#[derive(Clone, Debug)]
+struct LegacyIoStatGauges {
+    bytes_read: GaugeVec,
+    num_read_ops: GaugeVec,
+    bytes_written: GaugeVec,
+    num_write_ops: GaugeVec,
+    read_latency_us: GaugeVec,
+    write_latency_us: GaugeVec,
+}
+
+impl IoStatMetrics {
+    /// Initialize the IO statistics metrics of a resource, with the provided subsystem and labels.
+    /// The resource is the name of the resource in the metric descriptions.
+    pub(crate) fn new(
+        subsystem: &str,
+        labels: &[&str],
+        resource: &str,
+        legacy_gauges: bool,
+        descs: &mut Vec<Desc>,
+    ) -> Self {
+        let counter = |name: &str, desc: String, descs: &mut Vec<Desc>| {
+            let opts = Opts::new(name, desc).subsystem(subsystem);
+            let counter_vec = CounterVec::new(opts, labels)
+                .unwrap_or_else(|_| panic!("Unable to create counter metric type for {}", name));
+            descs.extend(counter_vec.desc().into_iter().cloned());
+            counter_vec
+        };
+        let gauge = |name: &str, desc: String, descs: &mut Vec<Desc>| {
+            let opts = Opts::new(name, desc).subsystem(subsystem);
+            let gauge_vec = GaugeVec::new(opts, labels)
+                .unwrap_or_else(|_| panic!("Unable to create gauge metric type for {}", name));
+            descs.extend(gauge_vec.desc().into_iter().cloned());
+            gauge_vec
+        };
+
+        let legacy_gauges = legacy_gauges.then(|| LegacyIoStatGauges {
+            bytes_read: gauge(
+                "bytes_read",
+                format!("Total bytes read on the {resource} (deprecated)"),
+                descs,
+            ),
+            num_read_ops: gauge(
+                "num_read_ops",
+                format!("Number of read operations on the {resource} (deprecated)"),
+                descs,
+            ),
+            bytes_written: gauge(
+                "bytes_written",
+                format!("Total bytes written on the {resource} (deprecated)"),
+                descs,
+            ),
+            num_write_ops: gauge(
+                "num_write_ops",
+                format!("Number of write operations on the {resource} (deprecated)"),
+                descs,
+            ),
+            read_latency_us: gauge(
+                "read_latency_us",
+                format!("Total read latency on the {resource} in usec (deprecated)"),
+                descs,
+            ),
+            write_latency_us: gauge(
+                "write_latency_us",
+                format!("Total write latency on the {resource} in usec (deprecated)"),
+                descs,
+            ),
+        });
+
+        Self {
+            read_bytes: counter(
+                "read_bytes_total",
+                format!("Total bytes read on the {resource}"),
+                descs,
+            ),
+            read_ops: counter(
+                "read_ops_total",
+                format!("Total number of read operations on the {resource}"),
+                descs,
+            ),
+            written_bytes: counter(
+                "written_bytes_total",
+                format!("Total bytes written on the {resource}"),
+                descs,
+            ),
+            write_ops: counter(
+                "write_ops_total",
+                format!("Total number of write operations on the {resource}"),
+                descs,
+            ),
+            read_time: counter(
+                "read_time_seconds_total",
+                format!("Total time spent on read operations on the {resource} in seconds"),
+                descs,
+            ),
+            write_time: counter(
+                "write_time_seconds_total",
+                format!("Total time spent on write operations on the {resource} in seconds"),
+                descs,
+            ),
+            start_time: gauge(
+                "iostat_start_time_seconds",
+                format!(
+                    "Unix time at which the IO counters of the {resource} were last reset, e.g. by an io-engine restart"
+                ),
+                descs,
+            ),
+            rates: IoRateGauges {
+                read_iops: gauge(
+                    "read_iops",
+                    format!("Read operations per second on the {resource}, over the last interval"),
+                    descs,
+                ),
+                write_iops: gauge(
+                    "write_iops",
+                    format!(
+                        "Write operations per second on the {resource}, over the last interval"
+                    ),
+                    descs,
+                ),
+                read_throughput: gauge(
+                    "read_throughput_bytes_per_second",
+                    format!("Bytes read per second on the {resource}, over the last interval"),
+                    descs,
+                ),
+                write_throughput: gauge(
+                    "write_throughput_bytes_per_second",
+                    format!("Bytes written per second on the {resource}, over the last interval"),
+                    descs,
+                ),
+                read_latency_avg: gauge(
+                    "read_latency_average_seconds",
+                    format!(
+                        "Average latency of the reads on the {resource} in seconds, over the last interval"
+                    ),
+                    descs,
+                ),
+                write_latency_avg: gauge(
+                    "write_latency_average_seconds",
+                    format!(
+                        "Average latency of the writes on the {resource} in seconds, over the last interval"
+                    ),
+                    descs,
+                ),
+            },
+            legacy_gauges,
+        }
+    }
+
+    /// Removes the metrics of all label values, so that the resources which no longer exist are
+    /// forgotten, as the metrics outlive the scrapes.
+    pub(crate) fn reset(&self) {
+        let counters = [
+            &self.read_bytes,
+            &self.read_ops,
+            &self.written_bytes,
+            &self.write_ops,
+            &self.read_time,
+            &self.write_time,
+        ];
+        counters.iter().for_each(|counter_vec| counter_vec.reset());
+        let mut gauges = vec![
+            &self.start_time,
+            &self.rates.read_iops,
+            &self.rates.write_iops,
+            &self.rates.read_throughput,
+            &self.rates.write_throughput,
+            &self.rates.read_latency_avg,
+            &self.rates.write_latency_avg,
+        ];
+        if let Some(legacy) = &self.legacy_gauges {
+            gauges.extend([
+                &legacy.bytes_read,
+                &legacy.num_read_ops,
+                &legacy.bytes_written,
+                &legacy.num_write_ops,
+                &legacy.read_latency_us,
+                &legacy.write_latency_us,
+            ]);
+        }
+        gauges.iter().for_each(|gauge_vec| gauge_vec.reset());
+    }
+
+    /// Sets the metrics with the label values to the IO counters of the resource, and adds them
+    /// to the metric families. The start time and the rates are taken from the latest sample of
+    /// the counters, if any.
+    pub(crate) fn collect(
+        &self,
+        label_values: &[&str],
+        counters: &IoCounters,
+        sample: Option<&IoStatSample>,
+        metric_family: &mut Vec<MetricFamily>,
+    ) -> Result<(), prometheus::Error> {
+        let mut set_counter = |counter_vec: &CounterVec, value: f64| {
+            let counter = counter_vec.get_metric_with_label_values(label_values)?;
+            // The counter is set to the value of the io-engine's counter, rather than incremented.
+            counter.reset();
+            counter.inc_by(value);
+            metric_family.extend(counter.collect().pop());
+            Ok::<(), prometheus::Error>(())
+        };
+        set_counter(&self.read_bytes, counters.bytes_read as f64)?;
+        set_counter(&self.read_ops, counters.num_read_ops as f64)?;
+        set_counter(&self.written_bytes, counters.bytes_written as f64)?;
+        set_counter(&self.write_ops, counters.num_write_ops as f64)?;
+        set_counter(
+            &self.read_time,
+            counters.read_latency_us as f64 / MICROS_PER_SEC,
+        )?;
+        set_counter(
+            &self.write_time,
+            counters.write_latency_us as f64 / MICROS_PER_SEC,
+        )?;
+
+        let mut set_gauge = |gauge_vec: &GaugeVec, value: f64| {
+            let gauge = gauge_vec.get_metric_with_label_values(label_values)?;
+            gauge.set(value);
+            metric_family.extend(gauge.collect().pop());
+            Ok::<(), prometheus::Error>(())
+        };
+        if let Some(sample) = sample {
+            set_gauge(&self.start_time, sample.start_time())?;
+        }
+        // The rates are only known once there are two samples since the counters were reset.
+        if let Some(rates) = sample.and_then(IoStatSample::rates) {
+            let IoRates {
+                read_iops,
+                write_iops,
+                read_bytes_per_sec,
+                write_bytes_per_sec,
+                read_latency_avg_sec,
+                write_latency_avg_sec,
+            } = *rates;
+            set_gauge(&self.rates.read_iops, read_iops)?;
+            set_gauge(&self.rates.write_iops, write_iops)?;
+            set_gauge(&self.rates.read_throughput, read_bytes_per_sec)?;
+            set_gauge(&self.rates.write_throughput, write_bytes_per_sec)?;
+            set_gauge(&self.rates.read_latency_avg, read_latency_avg_sec)?;
+            set_gauge(&self.rates.write_latency_avg, write_latency_avg_sec)?;
+        }
+        if let Some(legacy) = &self.legacy_gauges {
+            set_gauge(&legacy.bytes_read, counters.bytes_read as f64)?;
+            set_gauge(&legacy.num_read_ops, counters.num_read_ops as f64)?;
+            set_gauge(&legacy.bytes_written, counters.bytes_written as f64)?;
+            set_gauge(&legacy.num_write_ops, counters.num_write_ops as f64)?;
+            set_gauge(&legacy.read_latency_us, counters.read_latency_us as f64)?;
+            set_gauge(&legacy.write_latency_us, counters.write_latency_us as f64)?;
+        }
+
+        Ok(())
+    }
+}
@@ -3,10 +3,16 @@ use prometheus::{
     GaugeVec, Opts,
 };
 
+/// IO statistics metrics, shared by the iostat collectors.
+mod iostat;
+pub(crate) mod nexus;
 pub(crate) mod nexus_stat;
+pub(crate) mod poll_status;
 pub(crate) mod pool;
 pub(crate) mod pool_stat;
+pub(crate) mod replica;
 pub(crate) mod replica_stat;
+pub(crate) mod snapshot;
 
 /// Initializes a GaugeVec metric for diskpool with the provided metric name, description and
 /// descriptors.
@@ -24,30 +30,23 @@ fn init_diskpool_gauge_vec(
     gauge_vec
 }
 
-/// Initializes a GaugeVec metric for volume with the provided metric name, description and
-/// descriptors.
-fn init_volume_gauge_vec(metric_name: &str, metric_desc: &str, descs: &mut Vec<Desc>) -> GaugeVec {
-    let opts = Opts::new(metric_name, metric_desc)
-        .subsystem("volume")
-        .variable_labels(vec!["node".to_string(), "pv_name".to_string()]);
-    let gauge_vec = GaugeVec::new(opts, &["node", "pv_name"])
-        .unwrap_or_else(|_| panic!("Unable to create gauge metric type for {}", metric_name));
-    descs.extend(gauge_vec.desc().into_iter().cloned());
-    gauge_vec
-}
-
-/// Initializes a GaugeVec metric for replica with the provided metric name, description and
-/// descriptors.
-fn init_replica_gauge_vec(metric_name: &str, metric_desc: &str, descs: &mut Vec<Desc>) -> GaugeVec {
-    let opts = Opts::new(metric_name, metric_desc)
-        .subsystem("replica")
-        .variable_labels(vec![
-            "node".to_string(),
-            "name".to_string(),
-            "pv_name".to_string(),
-        ]);
-    let gauge_vec = GaugeVec::new(opts, &["node", "name", "pv_name"])
-        .unwrap_or_else(|_| panic!("Unable to create gauge metric type for {}", metric_name));
-    descs.extend(gauge_vec.desc().into_iter().cloned());
-    gauge_vec
+/// Returns the value of the gauge of the metric family with the label values, by label name.
+#[cfg(test)]
+fn gauge_value(
+    metrics: &[prometheus::proto::MetricFamily],
+    name: &str,
+    labels: &[(&str, &str)],
+) -> Option<f64> {
+    metrics
+        .iter()
+        .filter(|family| family.get_name() == name)
+        .flat_map(|family| family.get_metric())
+        .find(|metric| {
+            labels.iter().all(|(label_name, label_value)| {
+                metric.get_label().iter().any(|label| {
+                    label.get_name() == *label_name && label.get_value() == *label_value
+                })
+            })
+        })
+        .map(|metric| metric.get_gauge().get_value())
 }
@@ -0,0 +1,258 @@
+use crate::{cache::Cache, get_node_name, pvc_labels};
+use prometheus::{
+    core::{Collector, Desc},
+    proto::MetricFamily,
+    GaugeVec, Opts,
+};
+use std::{fmt::Debug, ops::Deref};
+use tracing::error;
+
+/// Collects the nexus state, children and rebuild metrics from cache.
+#[derive(Clone, Debug)]
+pub(crate) struct NexusCollector {
+    nexus_status: GaugeVec,
+    nexus_children: GaugeVec,
+    nexus_rebuilds: GaugeVec,
+    child_status: GaugeVec,
+    child_rebuild_progress: GaugeVec,
+    child_rebuilt_bytes: GaugeVec,
+    child_rebuild_rate: GaugeVec,
+    descs: Vec<Desc>,
+}
+
+impl Default for NexusCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NexusCollector {
+    /// Initialize all the metrics to be defined for the nexus collector.
+    pub fn new() -> Self {
+        let mut descs = Vec::new();
+        let mut gauge = |name: &str, desc: &str, labels: &[&str]| {
+            let opts = Opts::new(name, desc).subsystem("volume");
+            let gauge_vec = GaugeVec::new(opts, labels)
+                .unwrap_or_else(|_| panic!("Unable to create gauge metric type for {}", name));
+            descs.extend(gauge_vec.desc().into_iter().cloned());
+            gauge_vec
+        };
+        let nexus_labels = pvc_labels::label_names(&["node", "pv_name"]);
+        let child_labels = pvc_labels::label_names(&["node", "pv_name", "child_uri"]);
+
+        Self {
+            nexus_status: gauge("nexus_status", "Status of the nexus", &nexus_labels),
+            nexus_children: gauge(
+                "nexus_children",
+                "Number of children of the nexus",
+                &nexus_labels,
+            ),
+            nexus_rebuilds: gauge(
+                "nexus_rebuilds",
+                "Number of active rebuild jobs of the nexus",
+                &nexus_labels,
+            ),
+            child_status: gauge(
+                "nexus_child_status",
+                "Status of the nexus child",
+                &child_labels,
+            ),
+            child_rebuild_progress: gauge(
+                "nexus_child_rebuild_progress_percent",
+                "Progress of the rebuild of the nexus child in percent",
+                &child_labels,
+            ),
+            child_rebuilt_bytes: gauge(
+                "nexus_child_rebuilt_bytes",
+                "Bytes rebuilt so far by the rebuild of the nexus child",
+                &child_labels,
+            ),
+            child_rebuild_rate: gauge(
+                "nexus_child_rebuild_rate_bytes_per_second",
+                "Bytes rebuilt per second by the rebuild of the nexus child, over the last interval",
+                &child_labels,
+            ),
+            descs,
+        }
+    }
+
+    /// Removes the metrics of all label values, so that the nexuses and children which no longer
+    /// exist are forgotten, as the metrics outlive the scrapes.
+    fn reset(&self) {
+        [
+            &self.nexus_status,
+            &self.nexus_children,
+            &self.nexus_rebuilds,
+            &self.child_status,
+            &self.child_rebuild_progress,
+            &self.child_rebuilt_bytes,
+            &self.child_rebuild_rate,
+        ]
+        .iter()
+        .for_each(|gauge_vec| gauge_vec.reset());
+    }
+
+    /// Sets the metrics of the nexuses in the cache, and returns these.
+    fn nexus_metrics(&self, cache: &Cache, node_name: &str) -> Vec<MetricFamily> {
+        self.reset();
+        let mut metric_family = Vec::with_capacity(3 * cache.nexus().nexuses.capacity());
+
+        let mut set_gauge = |gauge_vec: &GaugeVec, label_values: &[&str], value: f64| {
+            let gauge = gauge_vec.get_metric_with_label_values(label_values)?;
+            gauge.set(value);
+            metric_family.extend(gauge.collect().pop());
+            Ok::<(), prometheus::Error>(())
+        };
+        let result = cache.nexus().nexuses.iter().try_for_each(|nexus| {
+            let pv_name = "pvc-".to_string() + nexus.name();
+            let pvc_label_values = pvc_labels::label_values(&pv_name);
+            let nexus_labels = pvc_label_values.append(&[node_name, pv_name.as_str()]);
+            set_gauge(&self.nexus_status, &nexus_labels, nexus.state() as f64)?;
+            set_gauge(
+                &self.nexus_children,
+                &nexus_labels,
+                nexus.children().len() as f64,
+            )?;
+            set_gauge(&self.nexus_rebuilds, &nexus_labels, nexus.rebuilds() as f64)?;
+
+            for child in nexus.children() {
+                let child_labels =
+                    pvc_label_values.append(&[node_name, pv_name.as_str(), child.uri().as_str()]);
+                set_gauge(&self.child_status, &child_labels, child.state() as f64)?;
+                if let Some(progress) = child.rebuild_progress() {
+                    set_gauge(&self.child_rebuild_progress, &child_labels, progress as f64)?;
+                }
+                if let Some(rebuild) = child.rebuild() {
+                    set_gauge(
+                        &self.child_rebuilt_bytes,
+                        &child_labels,
+                        rebuild.bytes_rebuilt() as f64,
+                    )?;
+                    if let Some(rate) = rebuild.rate() {
+                        set_gauge(&self.child_rebuild_rate, &child_labels, rate)?;
+                    }
+                }
+            }
+            Ok::<(), prometheus::Error>(())
+        });
+        if let Err(error) = result {
+            error!(%error, "Error while creating nexus metrics with label values");
+        }
+        metric_family
+    }
+}
+
+impl Collector for NexusCollector {
+    fn desc(&self) -> Vec<&Desc> {
+        self.descs.iter().collect()
+    }
+
+    fn collect(&self) -> Vec<MetricFamily> {
+        let cache = match Cache::get_cache().lock() {
+            Ok(cache) => cache,
+            Err(error) => {
+                error!(%error,"Error while getting cache resource");
+                return Vec::new();
+            }
+        };
+        let node_name = match get_node_name() {
+            Ok(name) => name,
+            Err(error) => {
+                error!(?error, "Unable to get node name");
+                return Vec::new();
+            }
+        };
+        self.nexus_metrics(cache.deref(), node_name.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NexusCollector;
+    use crate::{
+        cache::{Cache, Data},
+        collector::gauge_value as gauge,
+    };
+    use serde_json::json;
+
+    #[test]
+    fn nexus_and_child_metrics() {
+        let mut cache = Cache::new(Data::default());
+        cache.nexus_mut().nexuses = vec![serde_json::from_value(json!({
+            "name": "ec4e66fd-3b33-4439-b504-d49aba53da26",
+            "uuid": "2b8d7f1c-41c6-4c4e-8a0e-5b0b6b3a8d3e",
+            "state": 2,
+            "children": [
+                { "uri": "bdev:///healthy", "state": 1, "rebuild_progress": null, "rebuild": null },
+                {
+                    "uri": "nvmf://10.0.0.2:8420/rebuilding",
+                    "state": 2,
+                    "rebuild_progress": 25,
+                    "rebuild": { "bytes_rebuilt": 11_534_336, "rate": 1_048_576.0 }
+                }
+            ],
+            "rebuilds": 1
+        }))
+        .unwrap()];
+
+        let metrics = NexusCollector::new().nexus_metrics(&cache, "node-1");
+        let pv_name = "pvc-ec4e66fd-3b33-4439-b504-d49aba53da26";
+        let nexus = [("node", "node-1"), ("pv_name", pv_name)];
+        assert_eq!(gauge(&metrics, "volume_nexus_status", &nexus), Some(2.0));
+        assert_eq!(gauge(&metrics, "volume_nexus_children", &nexus), Some(2.0));
+        assert_eq!(gauge(&metrics, "volume_nexus_rebuilds", &nexus), Some(1.0));
+
+        let healthy = [("pv_name", pv_name), ("child_uri", "bdev:///healthy")];
+        assert_eq!(
+            gauge(&metrics, "volume_nexus_child_status", &healthy),
+            Some(1.0)
+        );
+        assert_eq!(
+            gauge(
+                &metrics,
+                "volume_nexus_child_rebuild_progress_percent",
+                &healthy
+            ),
+            None
+        );
+        assert_eq!(
+            gauge(&metrics, "volume_nexus_child_rebuilt_bytes", &healthy),
+            None
+        );
+
+        let rebuilding = [
+            ("pv_name", pv_name),
+            ("child_uri", "nvmf://10.0.0.2:8420/rebuilding"),
+        ];
+        assert_eq!(
+            gauge(&metrics, "volume_nexus_child_status", &rebuilding),
+            Some(2.0)
+        );
+        assert_eq!(
+            gauge(
+                &metrics,
+                "volume_nexus_child_rebuild_progress_percent",
+                &rebuilding
+            ),
+            Some(25.0)
+        );
+        assert_eq!(
+            gauge(&metrics, "volume_nexus_child_rebuilt_bytes", &rebuilding),
+            Some(11_534_336.0)
+        );
+        assert_eq!(
+            gauge(
+                &metrics,
+                "volume_nexus_child_rebuild_rate_bytes_per_second",
+                &rebuilding
+            ),
+            Some(1_048_576.0)
+        );
+
+        // The nexuses which no longer exist are forgotten.
+        let collector = NexusCollector::new();
+        collector.nexus_metrics(&cache, "node-1");
+        cache.nexus_mut().nexuses.clear();
+        assert!(collector.nexus_metrics(&cache, "node-1").is_empty());
+    }
+}
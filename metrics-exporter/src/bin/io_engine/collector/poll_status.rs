@@ -0,0 +1,66 @@
+use crate::{
+    cache::{Cache, PollSource},
+    get_node_name,
+};
+use metrics_exporter::poll::PollStatusGauges;
+use prometheus::{
+    core::{Collector, Desc},
+    Opts,
+};
+use std::{fmt::Debug, ops::Deref};
+use strum::IntoEnumIterator;
+use tracing::error;
+
+/// Collects the outcome of the polls of the io-engine from cache, so that stale data can be told
+/// apart from fresh data.
+#[derive(Clone, Debug)]
+pub(crate) struct PollStatusCollector {
+    gauges: PollStatusGauges,
+}
+
+impl Default for PollStatusCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PollStatusCollector {
+    /// Initialize all the metrics to be defined for the poll status collector.
+    pub fn new() -> Self {
+        let gauges = PollStatusGauges::new(
+            |name, desc| Opts::new(name, desc).subsystem("io_engine"),
+            &["node"],
+            "io-engine",
+        );
+        Self { gauges }
+    }
+}
+
+impl Collector for PollStatusCollector {
+    fn desc(&self) -> Vec<&Desc> {
+        self.gauges.descs()
+    }
+
+    fn collect(&self) -> Vec<prometheus::proto::MetricFamily> {
+        let cache = match Cache::get_cache().lock() {
+            Ok(cache) => cache,
+            Err(error) => {
+                error!(%error,"Error while getting cache resource");
+                return Vec::new();
+            }
+        };
+        let cache_deref = cache.deref();
+        let node_name = match get_node_name() {
+            Ok(name) => name,
+            Err(error) => {
+                error!(?error, "Unable to get node name");
+                return Vec::new();
+            }
+        };
+
+        self.gauges.collect(
+            &[node_name.as_str()],
+            PollSource::iter().map(|source| (source, cache_deref.poll_status(source))),
+        )
+    }
+}
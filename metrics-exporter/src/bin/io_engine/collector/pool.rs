@@ -1,6 +1,7 @@
 use crate::{cache::Cache, collector::init_diskpool_gauge_vec, get_node_name};
 use prometheus::{
     core::{Collector, Desc},
+    proto::MetricFamily,
     GaugeVec,
 };
 use std::{
@@ -15,6 +16,8 @@ pub(crate) struct PoolCapacityCollector {
     pool_total_size: GaugeVec,
     pool_used_size: GaugeVec,
     pool_committed_size: GaugeVec,
+    pool_used_ratio: GaugeVec,
+    pool_committed_ratio: GaugeVec,
     descs: Vec<Desc>,
 }
 
@@ -43,43 +46,41 @@ impl PoolCapacityCollector {
             "Committed size of the pool in bytes",
             &mut descs,
         );
+        let pool_used_ratio = init_diskpool_gauge_vec(
+            "used_ratio",
+            "Ratio of the used size to the total size of the pool",
+            &mut descs,
+        );
+        let pool_committed_ratio = init_diskpool_gauge_vec(
+            "committed_ratio",
+            "Ratio of the committed size to the total size of the pool",
+            &mut descs,
+        );
 
         Self {
             pool_total_size,
             pool_used_size,
             pool_committed_size,
+            pool_used_ratio,
+            pool_committed_ratio,
             descs,
         }
     }
-}
 
-impl Collector for PoolCapacityCollector {
-    fn desc(&self) -> Vec<&prometheus::core::Desc> {
-        self.descs.iter().collect()
-    }
+    /// Sets the capacity metrics of the pools in the cache, and returns these.
+    fn capacity_metrics(&self, cache: &Cache, node_name: &str) -> Vec<MetricFamily> {
+        // Forget the pools which no longer exist, as the metrics outlive the scrapes.
+        self.pool_total_size.reset();
+        self.pool_used_size.reset();
+        self.pool_committed_size.reset();
+        self.pool_used_ratio.reset();
+        self.pool_committed_ratio.reset();
+        let mut metric_family = Vec::with_capacity(5 * cache.pool().pools.capacity());
 
-    fn collect(&self) -> Vec<prometheus::proto::MetricFamily> {
-        let cache = match Cache::get_cache().lock() {
-            Ok(cache) => cache,
-            Err(error) => {
-                error!(%error,"Error while getting cache resource");
-                return Vec::new();
-            }
-        };
-        let cache_deref = cache.deref();
-        let mut metric_family = Vec::with_capacity(3 * cache_deref.pool().pools.capacity());
-        let node_name = match get_node_name() {
-            Ok(name) => name,
-            Err(error) => {
-                error!(?error, "Unable to get node name");
-                return metric_family;
-            }
-        };
-
-        for pool in &cache_deref.pool().pools {
+        for pool in &cache.pool().pools {
             let pool_total_size = match self
                 .pool_total_size
-                .get_metric_with_label_values(&[node_name.clone().as_str(), pool.name().as_str()])
+                .get_metric_with_label_values(&[node_name, pool.name().as_str()])
             {
                 Ok(pool_total_size) => pool_total_size,
                 Err(error) => {
@@ -93,7 +94,7 @@ impl Collector for PoolCapacityCollector {
 
             let pool_used_size = match self
                 .pool_used_size
-                .get_metric_with_label_values(&[node_name.clone().as_str(), pool.name().as_str()])
+                .get_metric_with_label_values(&[node_name, pool.name().as_str()])
             {
                 Ok(pool_used_size) => pool_used_size,
                 Err(error) => {
@@ -107,7 +108,7 @@ impl Collector for PoolCapacityCollector {
 
             let pool_committed_size = match self
                 .pool_committed_size
-                .get_metric_with_label_values(&[node_name.clone().as_str(), pool.name().as_str()])
+                .get_metric_with_label_values(&[node_name, pool.name().as_str()])
             {
                 Ok(pool_committed_size) => pool_committed_size,
                 Err(error) => {
@@ -118,11 +119,67 @@ impl Collector for PoolCapacityCollector {
             pool_committed_size.set(pool.committed() as f64);
             let mut metric_vec = pool_committed_size.collect();
             metric_family.extend(metric_vec.pop());
+
+            // The ratios are undefined for a pool without capacity.
+            if pool.capacity() == 0 {
+                continue;
+            }
+            let pool_used_ratio = match self
+                .pool_used_ratio
+                .get_metric_with_label_values(&[node_name, pool.name().as_str()])
+            {
+                Ok(pool_used_ratio) => pool_used_ratio,
+                Err(error) => {
+                    error!(%error, "Error while creating pool_used_ratio gauge with label values");
+                    return metric_family;
+                }
+            };
+            pool_used_ratio.set(pool.used() as f64 / pool.capacity() as f64);
+            let mut metric_vec = pool_used_ratio.collect();
+            metric_family.extend(metric_vec.pop());
+
+            let pool_committed_ratio = match self
+                .pool_committed_ratio
+                .get_metric_with_label_values(&[node_name, pool.name().as_str()])
+            {
+                Ok(pool_committed_ratio) => pool_committed_ratio,
+                Err(error) => {
+                    error!(%error, "Error while creating pool_committed_ratio gauge with label values");
+                    return metric_family;
+                }
+            };
+            pool_committed_ratio.set(pool.committed() as f64 / pool.capacity() as f64);
+            let mut metric_vec = pool_committed_ratio.collect();
+            metric_family.extend(metric_vec.pop());
         }
         metric_family
     }
 }
 
+impl Collector for PoolCapacityCollector {
+    fn desc(&self) -> Vec<&prometheus::core::Desc> {
+        self.descs.iter().collect()
+    }
+
+    fn collect(&self) -> Vec<MetricFamily> {
+        let cache = match Cache::get_cache().lock() {
+            Ok(cache) => cache,
+            Err(error) => {
+                error!(%error,"Error while getting cache resource");
+                return Vec::new();
+            }
+        };
+        let node_name = match get_node_name() {
+            Ok(name) => name,
+            Err(error) => {
+                error!(?error, "Unable to get node name");
+                return Vec::new();
+            }
+        };
+        self.capacity_metrics(cache.deref(), node_name.as_str())
+    }
+}
+
 /// Collects pool status info from cache.
 #[derive(Clone, Debug)]
 pub(crate) struct PoolStatusCollector {
@@ -158,6 +215,8 @@ impl Collector for PoolStatusCollector {
             }
         };
         let cache_deref = cache.deref_mut();
+        // Forget the pools which no longer exist, as the metrics outlive the scrapes.
+        self.pool_status.reset();
         let mut metric_family = Vec::with_capacity(3 * cache_deref.pool_mut().pools.capacity());
         let node_name = match get_node_name() {
             Ok(name) => name,
@@ -184,3 +243,59 @@ impl Collector for PoolStatusCollector {
         metric_family
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::PoolCapacityCollector;
+    use crate::{
+        cache::{Cache, Data},
+        collector::gauge_value as gauge,
+    };
+    use serde_json::json;
+
+    #[test]
+    fn pool_capacity_metrics() {
+        let mut cache = Cache::new(Data::default());
+        cache.pool_mut().pools = serde_json::from_value(json!([
+            {
+                "name": "pool-1",
+                "used": 2_147_483_648_u64,
+                "capacity": 8_589_934_592_u64,
+                "state": 1,
+                "committed": 17_179_869_184_u64
+            },
+            { "name": "pool-2", "used": 0, "capacity": 0, "state": 3, "committed": 0 }
+        ]))
+        .unwrap();
+
+        let metrics = PoolCapacityCollector::new().capacity_metrics(&cache, "node-1");
+        let pool = [("node", "node-1"), ("name", "pool-1")];
+        assert_eq!(
+            gauge(&metrics, "diskpool_total_size_bytes", &pool),
+            Some(8_589_934_592.0)
+        );
+        assert_eq!(
+            gauge(&metrics, "diskpool_used_size_bytes", &pool),
+            Some(2_147_483_648.0)
+        );
+        assert_eq!(
+            gauge(&metrics, "diskpool_committed_size_bytes", &pool),
+            Some(17_179_869_184.0)
+        );
+        assert_eq!(gauge(&metrics, "diskpool_used_ratio", &pool), Some(0.25));
+        // The pool is overcommitted.
+        assert_eq!(
+            gauge(&metrics, "diskpool_committed_ratio", &pool),
+            Some(2.0)
+        );
+
+        // The ratios of a pool without capacity are not reported, rather than being NaN.
+        let empty = [("node", "node-1"), ("name", "pool-2")];
+        assert_eq!(
+            gauge(&metrics, "diskpool_total_size_bytes", &empty),
+            Some(0.0)
+        );
+        assert_eq!(gauge(&metrics, "diskpool_used_ratio", &empty), None);
+        assert_eq!(gauge(&metrics, "diskpool_committed_ratio", &empty), None);
+    }
+}
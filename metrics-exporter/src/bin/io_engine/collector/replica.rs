@@ -0,0 +1,180 @@
+use crate::{cache::Cache, get_node_name, pvc_labels};
+use prometheus::{
+    core::{Collector, Desc},
+    proto::MetricFamily,
+    GaugeVec, Opts,
+};
+use std::{fmt::Debug, ops::Deref};
+use tracing::error;
+
+/// Collects Replica capacity and space usage metrics from cache.
+#[derive(Clone, Debug)]
+pub(crate) struct ReplicaCapacityCollector {
+    replica_size: GaugeVec,
+    replica_allocated: GaugeVec,
+    replica_snapshots_allocated: GaugeVec,
+    descs: Vec<Desc>,
+}
+
+impl Default for ReplicaCapacityCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReplicaCapacityCollector {
+    /// Initialize all the metrics to be defined for replicas capacity collector.
+    pub fn new() -> Self {
+        let mut descs = Vec::new();
+        let labels = pvc_labels::label_names(&["node", "name", "pv_name"]);
+        let mut gauge = |name: &str, desc: &str| {
+            let opts = Opts::new(name, desc).subsystem("replica");
+            let gauge_vec = GaugeVec::new(opts, &labels)
+                .unwrap_or_else(|_| panic!("Unable to create gauge metric type for {}", name));
+            descs.extend(gauge_vec.desc().into_iter().cloned());
+            gauge_vec
+        };
+
+        Self {
+            replica_size: gauge("size_bytes", "Provisioned size of the replica in bytes"),
+            replica_allocated: gauge(
+                "allocated_bytes",
+                "Bytes allocated to the replica on its pool",
+            ),
+            replica_snapshots_allocated: gauge(
+                "snapshots_allocated_bytes",
+                "Bytes allocated to the snapshots of the replica on its pool",
+            ),
+            descs,
+        }
+    }
+
+    /// Sets the capacity metrics of the replicas in the cache, and returns these.
+    fn replica_metrics(&self, cache: &Cache, node_name: &str) -> Vec<MetricFamily> {
+        // Forget the replicas which no longer exist, as the metrics outlive the scrapes.
+        self.replica_size.reset();
+        self.replica_allocated.reset();
+        self.replica_snapshots_allocated.reset();
+        let mut metric_family = Vec::with_capacity(3 * cache.replica().replicas.capacity());
+
+        let mut set_gauge = |gauge_vec: &GaugeVec, label_values: &[&str], value: f64| {
+            let gauge = gauge_vec.get_metric_with_label_values(label_values)?;
+            gauge.set(value);
+            metric_family.extend(gauge.collect().pop());
+            Ok::<(), prometheus::Error>(())
+        };
+        let result = cache.replica().replicas.iter().try_for_each(|replica| {
+            // The replicas which do not belong to a volume have an empty pv_name.
+            let pv_name = replica
+                .entity_id()
+                .map(|entity_id| format!("pvc-{entity_id}"))
+                .unwrap_or_default();
+            let pvc_label_values = pvc_labels::label_values(&pv_name);
+            let label_values =
+                pvc_label_values.append(&[node_name, replica.name().as_str(), pv_name.as_str()]);
+            set_gauge(&self.replica_size, &label_values, replica.size() as f64)?;
+            set_gauge(
+                &self.replica_allocated,
+                &label_values,
+                replica.allocated_bytes() as f64,
+            )?;
+            set_gauge(
+                &self.replica_snapshots_allocated,
+                &label_values,
+                replica.allocated_bytes_snapshots() as f64,
+            )
+        });
+        if let Err(error) = result {
+            error!(%error, "Error while creating replica capacity metrics with label values");
+        }
+        metric_family
+    }
+}
+
+impl Collector for ReplicaCapacityCollector {
+    fn desc(&self) -> Vec<&Desc> {
+        self.descs.iter().collect()
+    }
+
+    fn collect(&self) -> Vec<MetricFamily> {
+        let cache = match Cache::get_cache().lock() {
+            Ok(cache) => cache,
+            Err(error) => {
+                error!(%error,"Error while getting cache resource");
+                return Vec::new();
+            }
+        };
+        let node_name = match get_node_name() {
+            Ok(name) => name,
+            Err(error) => {
+                error!(?error, "Unable to get node name");
+                return Vec::new();
+            }
+        };
+        self.replica_metrics(cache.deref(), node_name.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReplicaCapacityCollector;
+    use crate::{
+        cache::{Cache, Data},
+        collector::gauge_value as gauge,
+    };
+    use serde_json::json;
+
+    #[test]
+    fn replica_capacity_metrics() {
+        let mut cache = Cache::new(Data::default());
+        cache.replica_mut().replicas = serde_json::from_value(json!([
+            {
+                "name": "replica-1",
+                "pool_name": "pool-1",
+                "entity_id": "ec4e66fd-3b33-4439-b504-d49aba53da26",
+                "size": 10_737_418_240_u64,
+                "allocated_bytes": 1_073_741_824,
+                "allocated_bytes_snapshots": 536_870_912
+            },
+            {
+                "name": "replica-2",
+                "pool_name": "pool-1",
+                "entity_id": null,
+                "size": 1_073_741_824,
+                "allocated_bytes": 0,
+                "allocated_bytes_snapshots": 0
+            }
+        ]))
+        .unwrap();
+
+        let metrics = ReplicaCapacityCollector::new().replica_metrics(&cache, "node-1");
+        let volume = [
+            ("node", "node-1"),
+            ("name", "replica-1"),
+            ("pv_name", "pvc-ec4e66fd-3b33-4439-b504-d49aba53da26"),
+        ];
+        assert_eq!(
+            gauge(&metrics, "replica_size_bytes", &volume),
+            Some(10_737_418_240.0)
+        );
+        assert_eq!(
+            gauge(&metrics, "replica_allocated_bytes", &volume),
+            Some(1_073_741_824.0)
+        );
+        assert_eq!(
+            gauge(&metrics, "replica_snapshots_allocated_bytes", &volume),
+            Some(536_870_912.0)
+        );
+
+        // The replica which does not belong to a volume has an empty pv_name.
+        let no_volume = [("name", "replica-2"), ("pv_name", "")];
+        assert_eq!(
+            gauge(&metrics, "replica_size_bytes", &no_volume),
+            Some(1_073_741_824.0)
+        );
+        assert_eq!(
+            gauge(&metrics, "replica_allocated_bytes", &no_volume),
+            Some(0.0)
+        );
+    }
+}
@@ -0,0 +1,229 @@
+use crate::{cache::Cache, get_node_name, pvc_labels};
+use prometheus::{
+    core::{Collector, Desc},
+    proto::MetricFamily,
+    GaugeVec, Opts,
+};
+use std::{collections::BTreeMap, fmt::Debug, ops::Deref};
+use tracing::error;
+
+/// Collects the snapshot count and space usage metrics of the volumes and pools from cache.
+#[derive(Clone, Debug)]
+pub(crate) struct SnapshotCollector {
+    volume_snapshots: GaugeVec,
+    pool_snapshots: GaugeVec,
+    pool_snapshots_allocated: GaugeVec,
+    descs: Vec<Desc>,
+}
+
+impl Default for SnapshotCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SnapshotCollector {
+    /// Initialize all the metrics to be defined for the snapshot collector.
+    pub fn new() -> Self {
+        let mut descs = Vec::new();
+        let mut gauge = |subsystem: &str, name: &str, desc: &str, labels: &[&str]| {
+            let opts = Opts::new(name, desc).subsystem(subsystem);
+            let gauge_vec = GaugeVec::new(opts, labels)
+                .unwrap_or_else(|_| panic!("Unable to create gauge metric type for {}", name));
+            descs.extend(gauge_vec.desc().into_iter().cloned());
+            gauge_vec
+        };
+
+        Self {
+            volume_snapshots: gauge(
+                "volume",
+                "snapshots",
+                "Number of replica snapshots of the volume on the node",
+                &pvc_labels::label_names(&["node", "pv_name"]),
+            ),
+            pool_snapshots: gauge(
+                "diskpool",
+                "snapshots",
+                "Number of replica snapshots on the pool",
+                &["node", "name"],
+            ),
+            pool_snapshots_allocated: gauge(
+                "diskpool",
+                "snapshots_allocated_bytes",
+                "Bytes allocated to the replica snapshots on the pool",
+                &["node", "name"],
+            ),
+            descs,
+        }
+    }
+
+    /// Sets the snapshot metrics of the volumes and pools in the cache, and returns these.
+    fn snapshot_metrics(&self, cache: &Cache, node_name: &str) -> Vec<MetricFamily> {
+        // Forget the volumes and pools which no longer exist, as the metrics outlive the scrapes.
+        self.volume_snapshots.reset();
+        self.pool_snapshots.reset();
+        self.pool_snapshots_allocated.reset();
+        let mut metric_family = Vec::with_capacity(3 * cache.pool().pools.capacity());
+
+        let mut volume_snapshots = BTreeMap::<String, u64>::new();
+        let mut pool_snapshots = BTreeMap::<&str, u64>::new();
+        for snapshot in &cache.snapshot().snapshots {
+            *volume_snapshots
+                .entry(format!("pvc-{}", snapshot.entity_id()))
+                .or_default() += 1;
+            *pool_snapshots.entry(snapshot.pool_name()).or_default() += 1;
+        }
+        // The snapshots of a replica are accounted for by the replica, so every pool with replicas
+        // is reported.
+        let mut pool_snapshots_allocated = BTreeMap::<&str, u64>::new();
+        for replica in &cache.replica().replicas {
+            *pool_snapshots_allocated
+                .entry(replica.pool_name())
+                .or_default() += replica.allocated_bytes_snapshots();
+        }
+
+        let mut set_gauge = |gauge_vec: &GaugeVec, label_values: &[&str], value: f64| {
+            let gauge = gauge_vec.get_metric_with_label_values(label_values)?;
+            gauge.set(value);
+            metric_family.extend(gauge.collect().pop());
+            Ok::<(), prometheus::Error>(())
+        };
+        let result = volume_snapshots
+            .iter()
+            .try_for_each(|(pv_name, count)| {
+                let pvc_label_values = pvc_labels::label_values(pv_name);
+                set_gauge(
+                    &self.volume_snapshots,
+                    &pvc_label_values.append(&[node_name, pv_name.as_str()]),
+                    *count as f64,
+                )
+            })
+            .and_then(|_| {
+                pool_snapshots.iter().try_for_each(|(pool, count)| {
+                    set_gauge(&self.pool_snapshots, &[node_name, pool], *count as f64)
+                })
+            })
+            .and_then(|_| {
+                pool_snapshots_allocated
+                    .iter()
+                    .try_for_each(|(pool, bytes)| {
+                        set_gauge(
+                            &self.pool_snapshots_allocated,
+                            &[node_name, pool],
+                            *bytes as f64,
+                        )
+                    })
+            });
+        if let Err(error) = result {
+            error!(%error, "Error while creating snapshot metrics with label values");
+        }
+        metric_family
+    }
+}
+
+impl Collector for SnapshotCollector {
+    fn desc(&self) -> Vec<&Desc> {
+        self.descs.iter().collect()
+    }
+
+    fn collect(&self) -> Vec<MetricFamily> {
+        let cache = match Cache::get_cache().lock() {
+            Ok(cache) => cache,
+            Err(error) => {
+                error!(%error,"Error while getting cache resource");
+                return Vec::new();
+            }
+        };
+        let node_name = match get_node_name() {
+            Ok(name) => name,
+            Err(error) => {
+                error!(?error, "Unable to get node name");
+                return Vec::new();
+            }
+        };
+        self.snapshot_metrics(cache.deref(), node_name.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SnapshotCollector;
+    use crate::{
+        cache::{Cache, Data},
+        collector::gauge_value as gauge,
+    };
+    use serde_json::json;
+
+    #[test]
+    fn snapshot_metrics() {
+        let mut cache = Cache::new(Data::default());
+        cache.snapshot_mut().snapshots = serde_json::from_value(json!([
+            { "pool_name": "pool-1", "entity_id": "volume-1" },
+            { "pool_name": "pool-1", "entity_id": "volume-1" },
+            { "pool_name": "pool-2", "entity_id": "volume-1" },
+            { "pool_name": "pool-2", "entity_id": "volume-2" }
+        ]))
+        .unwrap();
+        cache.replica_mut().replicas = serde_json::from_value(json!([
+            {
+                "name": "replica-1",
+                "pool_name": "pool-1",
+                "entity_id": "volume-1",
+                "size": 1_073_741_824,
+                "allocated_bytes": 1_048_576,
+                "allocated_bytes_snapshots": 2_097_152
+            },
+            {
+                "name": "replica-2",
+                "pool_name": "pool-3",
+                "entity_id": "volume-3",
+                "size": 1_073_741_824,
+                "allocated_bytes": 1_048_576,
+                "allocated_bytes_snapshots": 0
+            }
+        ]))
+        .unwrap();
+
+        let metrics = SnapshotCollector::new().snapshot_metrics(&cache, "node-1");
+        let volume = |pv_name| [("node", "node-1"), ("pv_name", pv_name)];
+        assert_eq!(
+            gauge(&metrics, "volume_snapshots", &volume("pvc-volume-1")),
+            Some(3.0)
+        );
+        assert_eq!(
+            gauge(&metrics, "volume_snapshots", &volume("pvc-volume-2")),
+            Some(1.0)
+        );
+        assert_eq!(
+            gauge(&metrics, "volume_snapshots", &volume("pvc-volume-3")),
+            None
+        );
+
+        let pool = |name| [("node", "node-1"), ("name", name)];
+        assert_eq!(
+            gauge(&metrics, "diskpool_snapshots", &pool("pool-1")),
+            Some(2.0)
+        );
+        assert_eq!(
+            gauge(&metrics, "diskpool_snapshots", &pool("pool-2")),
+            Some(2.0)
+        );
+        assert_eq!(
+            gauge(
+                &metrics,
+                "diskpool_snapshots_allocated_bytes",
+                &pool("pool-1")
+            ),
+            Some(2_097_152.0)
+        );
+        // Every pool with replicas is reported, even without snapshots.
+        assert_eq!(
+            gauge(
+                &metrics,
+                "diskpool_snapshots_allocated_bytes",
+                &pool("pool-3")
+            ),
+            Some(0.0)
+        );
+    }
+}
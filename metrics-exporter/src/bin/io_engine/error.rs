@@ -4,9 +4,13 @@
 pub enum ExporterError {
     GrpcResponseError(String),
     GetNodeError(String),
+    GetProductVersionError(String),
     InvalidURI(String),
     PodIPError(String),
     GrpcClientError(String),
     HttpServerError(String),
     HttpBindError(String),
+    HttpServerConfigError(String),
+    KubeClientError(String),
+    OtlpExportError(String),
 }
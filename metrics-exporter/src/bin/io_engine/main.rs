@@ -1,10 +1,12 @@
 use crate::{
-    client::grpc_client::{init_client, GrpcClient},
+    client::grpc_client::{init_client, Backoff, GrpcClient, IoEngineEndpoint, Timeouts},
     error::ExporterError,
-    serve::metric_route,
+    otlp::OtlpArgs,
+    serve::{init_registry, metric_route, registry},
 };
-use actix_web::{middleware, HttpServer};
+use actix_web::{middleware, web, HttpServer};
 use clap::Parser;
+use http_server::{authenticate, health_routes, on_connect, ServerArgs};
 use once_cell::sync::OnceCell;
 use std::{
     env,
@@ -20,6 +22,10 @@ pub(crate) mod client;
 pub(crate) mod collector;
 /// Error module.
 pub(crate) mod error;
+/// OTLP push module.
+pub(crate) mod otlp;
+/// PVC labels module.
+pub(crate) mod pvc_labels;
 /// Prometheus metrics handler module.
 pub(crate) mod serve;
 
@@ -56,6 +62,58 @@ pub(crate) struct Cli {
     /// Use ANSI colors for the logs.
     #[clap(long, default_value_t = true, action = clap::ArgAction::Set)]
     ansi_colors: bool,
+
+    /// Also expose the IO statistics as gauges, with the names they had before they were exposed
+    /// as counters, e.g. diskpool_bytes_read. These are deprecated, and will be removed in the
+    /// next release.
+    #[clap(long, default_value_t = false)]
+    legacy_iostat_gauges: bool,
+
+    /// Interval at which the data is polled from the io-engine. The scrapes are served from the
+    /// data of the last poll.
+    #[clap(long, default_value = "10s")]
+    polling_interval: humantime::Duration,
+
+    /// Add the namespace and name of the PVC, and the StorageClass, of the volumes as the
+    /// pvc_namespace, pvc_name and storage_class labels of the volume and replica metrics. These
+    /// are resolved from the PVs, which are listed from the kube API.
+    #[clap(long, default_value_t = false)]
+    pvc_labels: bool,
+
+    /// Interval at which the PVs are listed from the kube API, if the PVC labels are enabled.
+    #[clap(long, default_value = "5m")]
+    pvc_labels_refresh_interval: humantime::Duration,
+
+    /// Endpoint of the io-engine's gRPC server, e.g. https://10.1.0.5:10124, or a unix socket,
+    /// e.g. unix:///var/tmp/io-engine.sock. Defaults to https://$MY_POD_IP:10124.
+    #[clap(long)]
+    io_engine_endpoint: Option<IoEngineEndpoint>,
+
+    /// Timeout to connect to the io-engine.
+    #[clap(long, default_value = "1s")]
+    io_engine_connect_timeout: humantime::Duration,
+
+    /// Timeout of the requests to the io-engine.
+    #[clap(long, default_value = "5s")]
+    io_engine_request_timeout: humantime::Duration,
+
+    /// Delay before the first retry to connect to the io-engine. The delay doubles with every
+    /// retry, up to the max backoff. The metrics are served in the meantime, with io_engine_up
+    /// reporting 0.
+    #[clap(long, default_value = "1s")]
+    io_engine_min_backoff: humantime::Duration,
+
+    /// Maximum delay in between the retries to connect to the io-engine.
+    #[clap(long, default_value = "10s")]
+    io_engine_max_backoff: humantime::Duration,
+
+    /// Push mode of the metrics to an OTLP endpoint.
+    #[clap(flatten)]
+    otlp: OtlpArgs,
+
+    /// TLS and authentication of the prometheus endpoint.
+    #[clap(flatten)]
+    server: ServerArgs,
 }
 
 impl Cli {
@@ -85,18 +143,70 @@ async fn main() -> Result<(), ExporterError> {
         .init("metrics-exporter-io_engine");
 
     initialize_cache().await;
-    let client = init_client().await?;
+    if args.pvc_labels {
+        let client = kube::Client::try_default()
+            .await
+            .map_err(|error| ExporterError::KubeClientError(error.to_string()))?;
+        // The PVC labels must be enabled before the collectors are registered.
+        pvc_labels::enable();
+        tokio::spawn(pvc_labels::refresh_pvc_labels(
+            client,
+            args.pvc_labels_refresh_interval.into(),
+        ));
+    }
+    init_registry(args.legacy_iostat_gauges);
+    if let Some(exporter) = args.otlp.exporter()? {
+        // Push the same metrics as the ones served on the prometheus endpoint.
+        tokio::spawn(otlp::push_metrics(
+            exporter,
+            registry(),
+            args.otlp.push_interval(),
+        ));
+    }
+    let client = init_client(
+        args.io_engine_endpoint.clone(),
+        Timeouts::new(
+            args.io_engine_connect_timeout.into(),
+            args.io_engine_request_timeout.into(),
+        ),
+        Backoff::new(
+            args.io_engine_min_backoff.into(),
+            args.io_engine_max_backoff.into(),
+        ),
+    )?;
     // Initialize io engine gRPC client.
     GRPC_CLIENT
         .set(client)
         .expect("Expect to be initialised only once");
+    // Connect in the background, so that the metrics are served while the io engine is down.
+    tokio::spawn(grpc_client().connect());
+    // Populate the cache in the background, rather than on every scrape.
+    tokio::spawn(cache::poll_resource_data(
+        grpc_client(),
+        args.polling_interval.into(),
+    ));
+    let tls_config = args
+        .server
+        .tls_config()
+        .map_err(|error| ExporterError::HttpServerConfigError(error.to_string()))?;
+    let authentication = args
+        .server
+        .authentication()
+        .map_err(|error| ExporterError::HttpServerConfigError(error.to_string()))?;
     let app = move || {
         actix_web::App::new()
+            .app_data(web::Data::new(authentication.clone()))
+            .wrap(middleware::from_fn(authenticate))
             .wrap(middleware::Logger::default())
+            .configure(health_routes(cache::is_ready))
             .configure(metric_route)
     };
-    HttpServer::new(app)
-        .bind(args.metrics_endpoint)
+    let server = HttpServer::new(app).on_connect(on_connect);
+    let server = match tls_config {
+        Some(tls_config) => server.bind_rustls_021(args.metrics_endpoint, tls_config),
+        None => server.bind(args.metrics_endpoint),
+    };
+    server
         .map_err(|_| {
             ExporterError::HttpBindError("Failed to bind endpoint to http server".to_string())
         })?
@@ -0,0 +1,564 @@
+use crate::{error::ExporterError, get_node_name};
+use opentelemetry_proto::tonic::{
+    collector::metrics::v1::{
+        metrics_service_client::MetricsServiceClient, ExportMetricsServiceRequest,
+    },
+    common::v1::{any_value, AnyValue, InstrumentationScope, KeyValue},
+    metrics::v1::{
+        metric::Data, number_data_point, AggregationTemporality, Gauge, Metric, NumberDataPoint,
+        ResourceMetrics, ScopeMetrics, Sum,
+    },
+    resource::v1::Resource,
+};
+use prometheus::{
+    proto::{MetricFamily, MetricType},
+    Registry,
+};
+use prost::Message;
+use std::{
+    collections::HashMap,
+    env,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::time::MissedTickBehavior;
+use tonic::transport::{Channel, Endpoint};
+use tracing::{debug, error};
+use url::Url;
+
+/// The name of the exporter, as the service of the resource and the scope of the metrics.
+const SERVICE_NAME: &str = "metrics-exporter-io-engine";
+
+/// The environment variable of the version of the product, as the service version of the resource.
+const PRODUCT_VERSION_ENV: &str = "PRODUCT_VERSION";
+
+/// The name suffix of the gauges of the start time of the IO counters of the resources.
+const IOSTAT_START_TIME_SUFFIX: &str = "iostat_start_time_seconds";
+
+/// Protocol used to push the metrics to the OTLP endpoint.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(crate) enum OtlpProtocol {
+    /// OTLP over gRPC.
+    #[default]
+    Grpc,
+    /// OTLP over HTTP, with protobuf encoded bodies.
+    Http,
+}
+
+/// Arguments of the OTLP push mode.
+#[derive(clap::Args, Debug)]
+pub(crate) struct OtlpArgs {
+    /// Also push the metrics to this OTLP endpoint, e.g. http://otel-collector:4317 over gRPC,
+    /// or http://otel-collector:4318/v1/metrics over HTTP. The metrics are pushed in addition to
+    /// being served on the prometheus endpoint.
+    #[clap(long)]
+    otlp_endpoint: Option<Url>,
+
+    /// Protocol used to push the metrics to the OTLP endpoint.
+    #[clap(long, value_enum, default_value_t = OtlpProtocol::Grpc)]
+    otlp_protocol: OtlpProtocol,
+
+    /// Interval at which the metrics are pushed to the OTLP endpoint.
+    #[clap(long, default_value = "30s")]
+    otlp_push_interval: humantime::Duration,
+
+    /// Timeout of the pushes to the OTLP endpoint.
+    #[clap(long, default_value = "10s")]
+    otlp_timeout: humantime::Duration,
+}
+
+impl OtlpArgs {
+    /// Get the OTLP exporter, if the OTLP endpoint is set.
+    pub(crate) fn exporter(&self) -> Result<Option<OtlpExporter>, ExporterError> {
+        let Some(endpoint) = &self.otlp_endpoint else {
+            return Ok(None);
+        };
+        let node_name = get_node_name()?;
+        let product_version = get_product_version()?;
+        let client = OtlpClient::new(endpoint, self.otlp_protocol, self.otlp_timeout.into())?;
+        Ok(Some(OtlpExporter::new(
+            client,
+            &node_name,
+            &product_version,
+        )))
+    }
+
+    /// Get the interval at which the metrics are pushed.
+    pub(crate) fn push_interval(&self) -> Duration {
+        self.otlp_push_interval.into()
+    }
+}
+
+/// Get the product version from env.
+fn get_product_version() -> Result<String, ExporterError> {
+    env::var(PRODUCT_VERSION_ENV).map_err(|_| {
+        ExporterError::GetProductVersionError("Unable to get product version".to_string())
+    })
+}
+
+/// Client of the OTLP endpoint.
+#[derive(Clone, Debug)]
+pub(crate) enum OtlpClient {
+    Grpc(MetricsServiceClient<Channel>),
+    Http { client: reqwest::Client, url: Url },
+}
+
+impl OtlpClient {
+    /// Create a new client of the OTLP endpoint. The gRPC channel is connected lazily, so that
+    /// the exporter starts while the endpoint is down.
+    pub(crate) fn new(
+        endpoint: &Url,
+        protocol: OtlpProtocol,
+        timeout: Duration,
+    ) -> Result<Self, ExporterError> {
+        match protocol {
+            OtlpProtocol::Grpc => {
+                let channel = Endpoint::from_shared(endpoint.to_string())
+                    .map_err(|error| ExporterError::InvalidURI(error.to_string()))?
+                    .timeout(timeout)
+                    .connect_lazy();
+                Ok(Self::Grpc(MetricsServiceClient::new(channel)))
+            }
+            OtlpProtocol::Http => {
+                let client = reqwest::Client::builder()
+                    .timeout(timeout)
+                    .build()
+                    .map_err(|error| ExporterError::OtlpExportError(error.to_string()))?;
+                Ok(Self::Http {
+                    client,
+                    url: endpoint.clone(),
+                })
+            }
+        }
+    }
+
+    /// Export the metrics to the OTLP endpoint.
+    async fn export(&self, request: ExportMetricsServiceRequest) -> Result<(), ExporterError> {
+        match self {
+            Self::Grpc(client) => {
+                client
+                    .clone()
+                    .export(request)
+                    .await
+                    .map_err(|error| ExporterError::OtlpExportError(error.to_string()))?;
+            }
+            Self::Http { client, url } => {
+                client
+                    .post(url.clone())
+                    .header(reqwest::header::CONTENT_TYPE, "application/x-protobuf")
+                    .body(request.encode_to_vec())
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status())
+                    .map_err(|error| ExporterError::OtlpExportError(error.to_string()))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Pushes the metrics of a registry to an OTLP endpoint, as the metrics of a resource which
+/// identifies the node and the product version.
+#[derive(Debug)]
+pub(crate) struct OtlpExporter {
+    client: OtlpClient,
+    resource: Resource,
+    start_time_unix_nano: u64,
+}
+
+impl OtlpExporter {
+    /// Create a new exporter of the metrics of the given node, of the given product version.
+    pub(crate) fn new(client: OtlpClient, node_name: &str, product_version: &str) -> Self {
+        Self {
+            client,
+            resource: Resource {
+                attributes: vec![
+                    string_attribute("service.name", SERVICE_NAME),
+                    string_attribute("service.version", product_version),
+                    string_attribute("k8s.node.name", node_name),
+                ],
+                ..Default::default()
+            },
+            start_time_unix_nano: unix_time_nanos_now(),
+        }
+    }
+
+    /// Gather the metrics of the registry, and push them to the OTLP endpoint.
+    pub(crate) async fn push(&self, registry: &Registry) -> Result<(), ExporterError> {
+        let request = export_request(
+            &registry.gather(),
+            &self.resource,
+            self.start_time_unix_nano,
+            unix_time_nanos_now(),
+        );
+        self.client.export(request).await
+    }
+}
+
+/// Pushes the metrics of the registry to the OTLP endpoint at every interval. The metrics are
+/// gathered from the cache, just like the scrapes of the prometheus endpoint.
+pub(crate) async fn push_metrics(
+    exporter: OtlpExporter,
+    registry: &'static Registry,
+    interval: Duration,
+) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        ticker.tick().await;
+        match exporter.push(registry).await {
+            Ok(()) => debug!("Pushed the metrics to the OTLP endpoint"),
+            Err(error) => error!(?error, "Failed to push the metrics to the OTLP endpoint"),
+        }
+    }
+}
+
+/// The start times of the IO counters of the resources in unix nanoseconds, by the name prefix of
+/// their metrics, i.e. their subsystem, and by their labels.
+type IoStatStartTimes<'a> = HashMap<&'a str, HashMap<Vec<(&'a str, &'a str)>, u64>>;
+
+/// Get the start times of the IO counters of the resources, from the gauges of their start time.
+fn iostat_start_times(families: &[MetricFamily]) -> IoStatStartTimes<'_> {
+    let mut start_times = IoStatStartTimes::new();
+    for family in families {
+        let Some(prefix) = family.get_name().strip_suffix(IOSTAT_START_TIME_SUFFIX) else {
+            continue;
+        };
+        let resources = start_times.entry(prefix).or_default();
+        for metric in family.get_metric() {
+            let start_time = Duration::try_from_secs_f64(metric.get_gauge().get_value())
+                .map(|since_epoch| since_epoch.as_nanos() as u64)
+                .unwrap_or_default();
+            resources.insert(label_pairs(metric), start_time);
+        }
+    }
+    start_times
+}
+
+/// Get the name and value of the labels of the metric.
+fn label_pairs(metric: &prometheus::proto::Metric) -> Vec<(&str, &str)> {
+    metric
+        .get_label()
+        .iter()
+        .map(|label| (label.get_name(), label.get_value()))
+        .collect()
+}
+
+/// Convert the prometheus metric families to an OTLP export request. The gauges are exported as
+/// gauges, and the counters as cumulative monotonic sums, with the labels as attributes. The start
+/// time of the IO counters of a resource is the one of its iostat_start_time_seconds gauge, so
+/// that the counter resets are visible. The given start time is used when it is not known.
+fn export_request(
+    families: &[MetricFamily],
+    resource: &Resource,
+    start_time_unix_nano: u64,
+    time_unix_nano: u64,
+) -> ExportMetricsServiceRequest {
+    let iostat_start_times = iostat_start_times(families);
+    let metrics = families
+        .iter()
+        .filter_map(|family| {
+            let name = family.get_name();
+            let start_times = iostat_start_times
+                .iter()
+                .filter(|_| family.get_field_type() == MetricType::COUNTER)
+                .find(|(prefix, _)| name.starts_with(*prefix))
+                .map(|(_, start_times)| start_times);
+            let data_points = |value: fn(&prometheus::proto::Metric) -> f64| {
+                family
+                    .get_metric()
+                    .iter()
+                    .map(|metric| NumberDataPoint {
+                        attributes: metric
+                            .get_label()
+                            .iter()
+                            .map(|label| string_attribute(label.get_name(), label.get_value()))
+                            .collect(),
+                        start_time_unix_nano: start_times
+                            .and_then(|start_times| start_times.get(&label_pairs(metric)))
+                            .copied()
+                            .unwrap_or(start_time_unix_nano),
+                        time_unix_nano,
+                        value: Some(number_data_point::Value::AsDouble(value(metric))),
+                        ..Default::default()
+                    })
+                    .collect()
+            };
+            let data = match family.get_field_type() {
+                MetricType::GAUGE => Data::Gauge(Gauge {
+                    data_points: data_points(|metric| metric.get_gauge().get_value()),
+                }),
+                MetricType::COUNTER => Data::Sum(Sum {
+                    data_points: data_points(|metric| metric.get_counter().get_value()),
+                    aggregation_temporality: AggregationTemporality::Cumulative as i32,
+                    is_monotonic: true,
+                }),
+                other => {
+                    debug!(
+                        name = family.get_name(),
+                        ?other,
+                        "Skipping unsupported metric type"
+                    );
+                    return None;
+                }
+            };
+            Some(Metric {
+                name: family.get_name().to_string(),
+                description: family.get_help().to_string(),
+                data: Some(data),
+                ..Default::default()
+            })
+        })
+        .collect();
+
+    ExportMetricsServiceRequest {
+        resource_metrics: vec![ResourceMetrics {
+            resource: Some(resource.clone()),
+            scope_metrics: vec![ScopeMetrics {
+                scope: Some(InstrumentationScope {
+                    name: SERVICE_NAME.to_string(),
+                    ..Default::default()
+                }),
+                metrics,
+                ..Default::default()
+            }],
+            ..Default::default()
+        }],
+    }
+}
+
+/// Create an attribute with a string value.
+fn string_attribute(key: &str, value: &str) -> KeyValue {
+    KeyValue {
+        key: key.to_string(),
+        value: Some(AnyValue {
+            value: Some(any_value::Value::StringValue(value.to_string())),
+        }),
+    }
+}
+
+/// Get the current unix time in nanoseconds.
+fn unix_time_nanos_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since_epoch| since_epoch.as_nanos() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{web, App, HttpResponse, HttpServer};
+    use opentelemetry_proto::tonic::collector::metrics::v1::{
+        metrics_service_server::{MetricsService, MetricsServiceServer},
+        ExportMetricsServiceResponse,
+    };
+    use prometheus::{Counter, Gauge as PromGauge, Opts};
+    use std::{
+        net::{SocketAddr, TcpListener},
+        sync::{Arc, Mutex},
+    };
+    use tonic::{Request, Response, Status};
+
+    type Received = Arc<Mutex<Vec<ExportMetricsServiceRequest>>>;
+
+    /// OTLP receiver stub, which records the export requests.
+    #[derive(Default)]
+    struct ReceiverStub {
+        received: Received,
+    }
+
+    #[tonic::async_trait]
+    impl MetricsService for ReceiverStub {
+        async fn export(
+            &self,
+            request: Request<ExportMetricsServiceRequest>,
+        ) -> Result<Response<ExportMetricsServiceResponse>, Status> {
+            self.received.lock().unwrap().push(request.into_inner());
+            Ok(Response::new(ExportMetricsServiceResponse::default()))
+        }
+    }
+
+    fn test_registry() -> Registry {
+        let registry = Registry::new();
+        let gauge = PromGauge::with_opts(
+            Opts::new("pool_total_size_bytes", "Total size of the pool").subsystem("diskpool"),
+        )
+        .unwrap();
+        gauge.set(1024.0);
+        let counter = Counter::with_opts(
+            Opts::new("bytes_read_total", "Bytes read from the pool").subsystem("diskpool"),
+        )
+        .unwrap();
+        counter.inc_by(512.0);
+        registry.register(Box::new(gauge)).unwrap();
+        registry.register(Box::new(counter)).unwrap();
+        registry
+    }
+
+    fn unused_addr() -> SocketAddr {
+        TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+    }
+
+    /// Checks that a request carries the resource attributes and the metrics of the test registry.
+    fn assert_request(request: &ExportMetricsServiceRequest) {
+        let resource_metrics = &request.resource_metrics[0];
+        let attributes = &resource_metrics.resource.as_ref().unwrap().attributes;
+        assert!(attributes.contains(&string_attribute("k8s.node.name", "node-1")));
+        assert!(attributes.contains(&string_attribute("service.name", SERVICE_NAME)));
+        assert!(attributes.contains(&string_attribute("service.version", "2.9.0")));
+
+        let metrics = &resource_metrics.scope_metrics[0].metrics;
+        assert_eq!(metrics.len(), 2);
+        let metric = |name: &str| metrics.iter().find(|m| m.name == name).unwrap();
+        let Some(Data::Sum(sum)) = &metric("diskpool_bytes_read_total").data else {
+            panic!("Counters should be exported as sums");
+        };
+        assert!(sum.is_monotonic);
+        assert_eq!(
+            sum.data_points[0].value,
+            Some(number_data_point::Value::AsDouble(512.0))
+        );
+        let Some(Data::Gauge(gauge)) = &metric("diskpool_pool_total_size_bytes").data else {
+            panic!("Gauges should be exported as gauges");
+        };
+        assert_eq!(
+            gauge.data_points[0].value,
+            Some(number_data_point::Value::AsDouble(1024.0))
+        );
+    }
+
+    #[test]
+    fn counters_start_at_resource_start_time() {
+        let registry = Registry::new();
+        let labels = ["node", "name"];
+        let read_bytes = prometheus::CounterVec::new(
+            Opts::new("read_bytes_total", "Bytes read from the pool").subsystem("diskpool"),
+            &labels,
+        )
+        .unwrap();
+        let start_time = prometheus::GaugeVec::new(
+            Opts::new(IOSTAT_START_TIME_SUFFIX, "Start time of the IO counters")
+                .subsystem("diskpool"),
+            &labels,
+        )
+        .unwrap();
+        let total_size = prometheus::GaugeVec::new(
+            Opts::new("total_size_bytes", "Total size of the pool").subsystem("diskpool"),
+            &labels,
+        )
+        .unwrap();
+        read_bytes.with_label_values(&["node-1", "pool-1"]).inc();
+        read_bytes.with_label_values(&["node-1", "pool-2"]).inc();
+        // Only the counters of pool-1 were sampled.
+        start_time
+            .with_label_values(&["node-1", "pool-1"])
+            .set(1000.5);
+        total_size
+            .with_label_values(&["node-1", "pool-1"])
+            .set(1024.0);
+        registry.register(Box::new(read_bytes)).unwrap();
+        registry.register(Box::new(start_time)).unwrap();
+        registry.register(Box::new(total_size)).unwrap();
+
+        let request = export_request(&registry.gather(), &Resource::default(), 7, 9);
+        let metrics = &request.resource_metrics[0].scope_metrics[0].metrics;
+        let data_points = |name: &str| match &metrics.iter().find(|m| m.name == name).unwrap().data
+        {
+            Some(Data::Sum(sum)) => sum.data_points.clone(),
+            Some(Data::Gauge(gauge)) => gauge.data_points.clone(),
+            _ => panic!("Unexpected metric data"),
+        };
+        let start_time = |data_points: &[NumberDataPoint], name: &str| {
+            let name = string_attribute("name", name);
+            data_points
+                .iter()
+                .find(|point| point.attributes.contains(&name))
+                .unwrap()
+                .start_time_unix_nano
+        };
+
+        let read_bytes = data_points("diskpool_read_bytes_total");
+        assert_eq!(start_time(&read_bytes, "pool-1"), 1_000_500_000_000);
+        // The start time of the exporter is used when the one of the counters is not known.
+        assert_eq!(start_time(&read_bytes, "pool-2"), 7);
+        // The start time of the counters does not apply to the gauges.
+        let total_size = data_points("diskpool_total_size_bytes");
+        assert_eq!(start_time(&total_size, "pool-1"), 7);
+        assert!(read_bytes.iter().all(|point| point.time_unix_nano == 9));
+    }
+
+    #[tokio::test]
+    async fn push_grpc() {
+        let addr = unused_addr();
+        let stub = ReceiverStub::default();
+        let received = stub.received.clone();
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(MetricsServiceServer::new(stub))
+                .serve(addr),
+        );
+
+        let endpoint = Url::parse(&format!("http://{addr}")).unwrap();
+        let client =
+            OtlpClient::new(&endpoint, OtlpProtocol::Grpc, Duration::from_secs(5)).unwrap();
+        let exporter = OtlpExporter::new(client, "node-1", "2.9.0");
+        let registry = test_registry();
+        let mut result = exporter.push(&registry).await;
+        // Wait for the stub to start serving.
+        for _ in 0 .. 50 {
+            if result.is_ok() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            result = exporter.push(&registry).await;
+        }
+        result.unwrap();
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        assert_request(&received[0]);
+    }
+
+    #[actix_web::test]
+    async fn push_http() {
+        let received = Received::default();
+        let app_received = received.clone();
+        let server = HttpServer::new(move || {
+            let received = app_received.clone();
+            App::new().route(
+                "/v1/metrics",
+                web::post().to(move |body: web::Bytes| {
+                    let received = received.clone();
+                    async move {
+                        let request = ExportMetricsServiceRequest::decode(body).unwrap();
+                        received.lock().unwrap().push(request);
+                        HttpResponse::Ok().finish()
+                    }
+                }),
+            )
+        })
+        .workers(1)
+        .bind("127.0.0.1:0")
+        .unwrap();
+        let addr = server.addrs()[0];
+        let server = server.run();
+        let handle = server.handle();
+        actix_web::rt::spawn(server);
+
+        let endpoint = Url::parse(&format!("http://{addr}/v1/metrics")).unwrap();
+        let client =
+            OtlpClient::new(&endpoint, OtlpProtocol::Http, Duration::from_secs(5)).unwrap();
+        OtlpExporter::new(client, "node-1", "2.9.0")
+            .push(&test_registry())
+            .await
+            .unwrap();
+        handle.stop(true).await;
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        assert_request(&received[0]);
+    }
+}
@@ -0,0 +1,207 @@
+use k8s_openapi::api::core::v1::PersistentVolume;
+use kube::{api::ListParams, Api, Client};
+use once_cell::sync::OnceCell;
+use std::{collections::HashMap, sync::RwLock, time::Duration};
+use tokio::time::MissedTickBehavior;
+use tracing::{debug, error};
+
+/// The names of the labels of the PVC of a volume, which are appended to the labels of the volume
+/// and replica metrics if these are enabled.
+const PVC_LABEL_NAMES: [&str; 3] = ["pvc_namespace", "pvc_name", "storage_class"];
+
+/// The PVC labels of the PVs, by PV name. This is only initialized if the PVC labels are enabled.
+static PVC_LABELS: OnceCell<RwLock<HashMap<String, PvcLabels>>> = OnceCell::new();
+
+/// The namespace and name of the PVC which is bound to a PV, and the StorageClass of the PV.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PvcLabels {
+    namespace: String,
+    name: String,
+    storage_class: String,
+}
+
+impl From<PersistentVolume> for PvcLabels {
+    fn from(value: PersistentVolume) -> Self {
+        let spec = value.spec.unwrap_or_default();
+        let claim_ref = spec.claim_ref.unwrap_or_default();
+        Self {
+            namespace: claim_ref.namespace.unwrap_or_default(),
+            name: claim_ref.name.unwrap_or_default(),
+            storage_class: spec.storage_class_name.unwrap_or_default(),
+        }
+    }
+}
+
+/// The values of the PVC labels of a volume, if the PVC labels are enabled.
+pub(crate) struct PvcLabelValues(Option<PvcLabels>);
+
+impl PvcLabelValues {
+    /// Returns the label values, with the values of the PVC labels appended if these are enabled.
+    pub(crate) fn append<'a>(&'a self, label_values: &[&'a str]) -> Vec<&'a str> {
+        let mut values = label_values.to_vec();
+        if let Some(pvc) = &self.0 {
+            values.extend([
+                pvc.namespace.as_str(),
+                pvc.name.as_str(),
+                pvc.storage_class.as_str(),
+            ]);
+        }
+        values
+    }
+}
+
+/// Enables the PVC labels. This must be called before the collectors are created, as these
+/// define their labels when they are created.
+pub(crate) fn enable() {
+    PVC_LABELS.get_or_init(Default::default);
+}
+
+/// Returns the label names, with the names of the PVC labels appended if these are enabled.
+pub(crate) fn label_names(labels: &[&'static str]) -> Vec<&'static str> {
+    append_label_names(labels, PVC_LABELS.get().is_some())
+}
+
+/// Returns the label names, with the names of the PVC labels appended if enabled.
+fn append_label_names(labels: &[&'static str], enabled: bool) -> Vec<&'static str> {
+    let mut names = labels.to_vec();
+    if enabled {
+        names.extend(PVC_LABEL_NAMES);
+    }
+    names
+}
+
+/// Get the values of the PVC labels of the PV. The values are empty if the PV is not known, e.g.
+/// because it was created after the last refresh, or if the replica does not belong to a volume.
+pub(crate) fn label_values(pv_name: &str) -> PvcLabelValues {
+    pvc_label_values(PVC_LABELS.get(), pv_name)
+}
+
+/// Get the values of the PVC labels of the PV from the cached PVC labels, which are only present
+/// if the PVC labels are enabled.
+fn pvc_label_values(
+    pvc_labels: Option<&RwLock<HashMap<String, PvcLabels>>>,
+    pv_name: &str,
+) -> PvcLabelValues {
+    PvcLabelValues(pvc_labels.map(|pvc_labels| {
+        pvc_labels
+            .read()
+            .ok()
+            .and_then(|pvc_labels| pvc_labels.get(pv_name).cloned())
+            .unwrap_or_default()
+    }))
+}
+
+/// Lists the PVs from the kube API at every interval, and caches their PVC labels. The previous
+/// labels are kept if the PVs cannot be listed, as the PVC of a PV does not change.
+pub(crate) async fn refresh_pvc_labels(client: Client, interval: Duration) {
+    let api = Api::<PersistentVolume>::all(client);
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        ticker.tick().await;
+        let pvs = match api.list(&ListParams::default()).await {
+            Ok(pvs) => pvs,
+            Err(error) => {
+                error!(%error, "Error listing the PVs, keeping the previous PVC labels");
+                continue;
+            }
+        };
+        let labels = pvs
+            .items
+            .into_iter()
+            .filter_map(|pv| Some((pv.metadata.name.clone()?, PvcLabels::from(pv))))
+            .collect::<HashMap<_, _>>();
+        debug!(pvs = labels.len(), "Refreshed the PVC labels");
+        if let Some(Ok(mut pvc_labels)) = PVC_LABELS.get().map(RwLock::write) {
+            *pvc_labels = labels;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{append_label_names, pvc_label_values, PvcLabels};
+    use k8s_openapi::api::core::v1::{ObjectReference, PersistentVolume, PersistentVolumeSpec};
+    use std::{collections::HashMap, sync::RwLock};
+
+    /// A PV which is bound to the PVC, and has the StorageClass.
+    fn bound_pv() -> PersistentVolume {
+        PersistentVolume {
+            spec: Some(PersistentVolumeSpec {
+                claim_ref: Some(ObjectReference {
+                    namespace: Some("default".to_string()),
+                    name: Some("data".to_string()),
+                    ..Default::default()
+                }),
+                storage_class_name: Some("mayastor-3".to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn label_order() {
+        let pvc_labels = RwLock::new(HashMap::from([(
+            "pvc-1".to_string(),
+            PvcLabels::from(bound_pv()),
+        )]));
+
+        let names = append_label_names(&["node", "pv_name"], true);
+        let values = pvc_label_values(Some(&pvc_labels), "pvc-1");
+        let values = values.append(&["node-1", "pvc-1"]);
+        assert_eq!(
+            names,
+            [
+                "node",
+                "pv_name",
+                "pvc_namespace",
+                "pvc_name",
+                "storage_class"
+            ]
+        );
+        assert_eq!(values, ["node-1", "pvc-1", "default", "data", "mayastor-3"]);
+
+        // The labels are unchanged if the PVC labels are not enabled.
+        let names = append_label_names(&["node", "pv_name"], false);
+        let values = pvc_label_values(None, "pvc-1");
+        assert_eq!(names, ["node", "pv_name"]);
+        assert_eq!(values.append(&["node-1", "pvc-1"]), ["node-1", "pvc-1"]);
+    }
+
+    #[test]
+    fn unknown_pv() {
+        let pvc_labels = RwLock::new(HashMap::from([(
+            "pvc-1".to_string(),
+            PvcLabels::from(bound_pv()),
+        )]));
+
+        // The PV was created after the last refresh, or the replica does not belong to a volume.
+        for pv_name in ["pvc-2", ""] {
+            let values = pvc_label_values(Some(&pvc_labels), pv_name);
+            assert_eq!(values.append(&["node-1"]), ["node-1", "", "", ""]);
+        }
+    }
+
+    #[test]
+    fn unbound_pv() {
+        let pvc_labels = RwLock::new(HashMap::from([
+            (
+                "pvc-1".to_string(),
+                PvcLabels::from(PersistentVolume::default()),
+            ),
+            (
+                "pvc-2".to_string(),
+                PvcLabels::from(PersistentVolume {
+                    spec: Some(PersistentVolumeSpec::default()),
+                    ..Default::default()
+                }),
+            ),
+        ]));
+
+        for pv_name in ["pvc-1", "pvc-2"] {
+            let values = pvc_label_values(Some(&pvc_labels), pv_name);
+            assert_eq!(values.append(&[pv_name]), [pv_name, "", "", ""]);
+        }
+    }
+}
@@ -1,46 +1,80 @@
-use crate::{
-    cache::store_resource_data,
-    collector::{
-        nexus_stat::NexusIoStatsCollector,
-        pool::{PoolCapacityCollector, PoolStatusCollector},
-        pool_stat::PoolIoStatsCollector,
-        replica_stat::ReplicaIoStatsCollector,
-    },
-    grpc_client,
+use crate::collector::{
+    nexus::NexusCollector,
+    nexus_stat::NexusIoStatsCollector,
+    poll_status::PollStatusCollector,
+    pool::{PoolCapacityCollector, PoolStatusCollector},
+    pool_stat::PoolIoStatsCollector,
+    replica::ReplicaCapacityCollector,
+    replica_stat::ReplicaIoStatsCollector,
+    snapshot::SnapshotCollector,
 };
 use actix_web::{http::header, HttpResponse, Responder};
+use once_cell::sync::OnceCell;
 use prometheus::{Encoder, Registry};
 use tracing::{error, warn};
 
-/// Handler for metrics. Initializes all collector and serves data over Http.
-pub(crate) async fn metrics_handler() -> impl Responder {
-    // Fetches stats for all resource from io engine, Populates the cache.
-    store_resource_data(grpc_client()).await;
-    // Create collectors for all resources.
-    let pools_collector = PoolCapacityCollector::default();
-    let pool_status_collector = PoolStatusCollector::default();
-    let pool_iostat_collector = PoolIoStatsCollector::default();
-    let nexus_iostat_collector = NexusIoStatsCollector::default();
-    let replica_iostat_collector = ReplicaIoStatsCollector::default();
-    // Create a new registry for prometheus.
-    let registry = Registry::default();
-    // Register all collectors to the registry.
-    if let Err(error) = Registry::register(&registry, Box::new(pools_collector)) {
-        warn!(%error, "Pool capacity collector already registered");
-    }
-    if let Err(error) = Registry::register(&registry, Box::new(pool_status_collector)) {
-        warn!(%error, "Pool status collector already registered");
-    }
-    if let Err(error) = Registry::register(&registry, Box::new(pool_iostat_collector)) {
-        warn!(%error, "Pool IoStat collector already registered");
-    }
-    if let Err(error) = Registry::register(&registry, Box::new(nexus_iostat_collector)) {
-        warn!(%error, "Nexus IoStat collector already registered");
-    }
-    if let Err(error) = Registry::register(&registry, Box::new(replica_iostat_collector)) {
-        warn!(%error, "Replica IoStat collector already registered");
-    }
+static REGISTRY: OnceCell<Registry> = OnceCell::new();
 
+/// Creates the registry, and registers all the collectors to it. The registry is shared by all
+/// of the scrapes, which are served from the cache. The deprecated iostat gauges are registered as
+/// well, if legacy_iostat_gauges is set.
+pub(crate) fn init_registry(legacy_iostat_gauges: bool) {
+    REGISTRY.get_or_init(|| {
+        // Create collectors for all resources.
+        let pools_collector = PoolCapacityCollector::default();
+        let pool_status_collector = PoolStatusCollector::default();
+        let pool_iostat_collector = PoolIoStatsCollector::new(legacy_iostat_gauges);
+        let nexus_collector = NexusCollector::default();
+        let nexus_iostat_collector = NexusIoStatsCollector::new(legacy_iostat_gauges);
+        let replica_iostat_collector = ReplicaIoStatsCollector::new(legacy_iostat_gauges);
+        let replica_capacity_collector = ReplicaCapacityCollector::default();
+        let snapshot_collector = SnapshotCollector::default();
+        let poll_status_collector = PollStatusCollector::default();
+        // Create a new registry for prometheus.
+        let registry = Registry::default();
+        // Register all collectors to the registry.
+        if let Err(error) = Registry::register(&registry, Box::new(pools_collector)) {
+            warn!(%error, "Pool capacity collector already registered");
+        }
+        if let Err(error) = Registry::register(&registry, Box::new(pool_status_collector)) {
+            warn!(%error, "Pool status collector already registered");
+        }
+        if let Err(error) = Registry::register(&registry, Box::new(pool_iostat_collector)) {
+            warn!(%error, "Pool IoStat collector already registered");
+        }
+        if let Err(error) = Registry::register(&registry, Box::new(nexus_collector)) {
+            warn!(%error, "Nexus collector already registered");
+        }
+        if let Err(error) = Registry::register(&registry, Box::new(nexus_iostat_collector)) {
+            warn!(%error, "Nexus IoStat collector already registered");
+        }
+        if let Err(error) = Registry::register(&registry, Box::new(replica_iostat_collector)) {
+            warn!(%error, "Replica IoStat collector already registered");
+        }
+        if let Err(error) = Registry::register(&registry, Box::new(replica_capacity_collector)) {
+            warn!(%error, "Replica capacity collector already registered");
+        }
+        if let Err(error) = Registry::register(&registry, Box::new(snapshot_collector)) {
+            warn!(%error, "Snapshot collector already registered");
+        }
+        if let Err(error) = Registry::register(&registry, Box::new(poll_status_collector)) {
+            warn!(%error, "Poll status collector already registered");
+        }
+        registry
+    });
+}
+
+/// Get the registry, which the scrapes and the OTLP pushes are served from.
+pub(crate) fn registry() -> &'static Registry {
+    REGISTRY
+        .get()
+        .expect("Prometheus registry should have been initialised")
+}
+
+/// Handler for metrics. Serves the data in the cache over Http, which is populated in the
+/// background.
+pub(crate) async fn metrics_handler() -> impl Responder {
+    let registry = registry();
     let mut buffer = Vec::new();
 
     let encoder = prometheus::TextEncoder::new();
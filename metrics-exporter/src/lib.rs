@@ -0,0 +1,2 @@
+/// Module for the outcome of the polls of the sources of the exporters.
+pub mod poll;
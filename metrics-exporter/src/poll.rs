@@ -0,0 +1,258 @@
+use prometheus::{
+    core::{Collector, Desc},
+    proto::MetricFamily,
+    GaugeVec, Opts,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tracing::error;
+
+/// The outcome of the polls of a source.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+pub struct PollStatus {
+    /// Whether the last poll succeeded.
+    up: bool,
+    /// The unix time of the last poll which succeeded, if any.
+    last_success: Option<f64>,
+}
+
+impl PollStatus {
+    /// Returns true if the last poll succeeded.
+    pub fn up(&self) -> bool {
+        self.up
+    }
+
+    /// Get the unix time of the last poll which succeeded, if any.
+    pub fn last_success(&self) -> Option<f64> {
+        self.last_success
+    }
+
+    /// Records the outcome of a poll.
+    fn record(&mut self, up: bool) {
+        self.up = up;
+        if up {
+            self.last_success = Some(unix_time_now());
+        }
+    }
+}
+
+/// The outcome of the polls of each of the sources of an exporter.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(transparent)]
+pub struct PollStatuses<S: Eq + Hash> {
+    statuses: HashMap<S, PollStatus>,
+}
+
+impl<S: Eq + Hash> Default for PollStatuses<S> {
+    fn default() -> Self {
+        Self {
+            statuses: HashMap::new(),
+        }
+    }
+}
+
+impl<S: Copy + Eq + Hash> PollStatuses<S> {
+    /// Get the outcome of the polls of the source.
+    pub fn get(&self, source: S) -> PollStatus {
+        self.statuses.get(&source).copied().unwrap_or_default()
+    }
+
+    /// Records the outcome of a poll of the source.
+    pub fn record(&mut self, source: S, up: bool) {
+        self.statuses.entry(source).or_default().record(up);
+    }
+
+    /// Returns true if the last poll of any of the sources succeeded, i.e. if the polled data is
+    /// being served.
+    pub fn any_up(&self) -> bool {
+        self.statuses.values().any(PollStatus::up)
+    }
+}
+
+/// Returns the current unix time in seconds.
+pub fn unix_time_now() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since_epoch| since_epoch.as_secs_f64())
+        .unwrap_or_default()
+}
+
+/// The gauges of the outcome of the polls of the sources of an exporter, so that stale data can be
+/// told apart from fresh data.
+#[derive(Clone, Debug)]
+pub struct PollStatusGauges {
+    up: GaugeVec,
+    last_successful_poll: GaugeVec,
+    descs: Vec<Desc>,
+}
+
+impl PollStatusGauges {
+    /// Initialize the gauges. The options of each gauge are made from its name and description,
+    /// e.g. to set the namespace and the subsystem. The 'source' label follows the labels. The
+    /// origin is what the sources are polled from, e.g. the io-engine.
+    pub fn new(opts: impl Fn(&str, &str) -> Opts, labels: &[&str], origin: &str) -> Self {
+        let mut descs = Vec::new();
+        let labels: Vec<&str> = labels.iter().copied().chain(["source"]).collect();
+        let mut gauge = |name: &str, desc: String| {
+            let gauge_vec = GaugeVec::new(opts(name, desc.as_str()), labels.as_slice())
+                .unwrap_or_else(|_| panic!("Unable to create gauge metric type for {}", name));
+            descs.extend(gauge_vec.desc().into_iter().cloned());
+            gauge_vec
+        };
+        let up = gauge(
+            "up",
+            format!("Whether the last poll of the source from the {origin} succeeded"),
+        );
+        let last_successful_poll = gauge(
+            "last_successful_poll_timestamp_seconds",
+            format!("Unix time of the last poll of the source from the {origin} which succeeded"),
+        );
+
+        Self {
+            up,
+            last_successful_poll,
+            descs,
+        }
+    }
+
+    /// Get the descriptions of the gauges.
+    pub fn descs(&self) -> Vec<&Desc> {
+        self.descs.iter().collect()
+    }
+
+    /// Returns the metrics of the outcome of the polls of the sources. The label values are those
+    /// of the labels which precede the 'source' label. The timestamp of a source is absent until
+    /// its first poll succeeds.
+    pub fn collect<S: AsRef<str>>(
+        &self,
+        label_values: &[&str],
+        statuses: impl IntoIterator<Item = (S, PollStatus)>,
+    ) -> Vec<MetricFamily> {
+        let statuses = statuses.into_iter();
+        let mut metric_family = Vec::with_capacity(2 * statuses.size_hint().0);
+
+        for (source, status) in statuses {
+            let label_values: Vec<&str> = label_values
+                .iter()
+                .copied()
+                .chain([source.as_ref()])
+                .collect();
+
+            let up = match self.up.get_metric_with_label_values(&label_values) {
+                Ok(up) => up,
+                Err(error) => {
+                    error!(%error, "Error while creating up gauge with label values");
+                    return metric_family;
+                }
+            };
+            up.set(if status.up() { 1.0 } else { 0.0 });
+            metric_family.extend(up.collect().pop());
+
+            let Some(last_success) = status.last_success() else {
+                continue;
+            };
+            let last_successful_poll = match self
+                .last_successful_poll
+                .get_metric_with_label_values(&label_values)
+            {
+                Ok(last_successful_poll) => last_successful_poll,
+                Err(error) => {
+                    error!(%error, "Error while creating last_successful_poll gauge with label values");
+                    return metric_family;
+                }
+            };
+            last_successful_poll.set(last_success);
+            metric_family.extend(last_successful_poll.collect().pop());
+        }
+        metric_family
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PollStatusGauges, PollStatuses};
+    use prometheus::Opts;
+
+    #[test]
+    fn poll_statuses_record_outcomes() {
+        let mut statuses = PollStatuses::default();
+        assert!(!statuses.get("pools").up());
+        assert_eq!(statuses.get("pools").last_success(), None);
+        assert!(!statuses.any_up());
+
+        statuses.record("pools", true);
+        let last_success = statuses.get("pools").last_success();
+        assert!(statuses.get("pools").up());
+        assert!(last_success.is_some());
+        assert!(statuses.any_up());
+
+        // A failed poll keeps the time of the last poll which succeeded.
+        statuses.record("pools", false);
+        assert!(!statuses.get("pools").up());
+        assert_eq!(statuses.get("pools").last_success(), last_success);
+        assert!(!statuses.any_up());
+
+        statuses.record("nexuses", false);
+        assert_eq!(statuses.get("nexuses").last_success(), None);
+    }
+
+    #[test]
+    fn poll_status_gauges() {
+        let gauges = PollStatusGauges::new(
+            |name, desc| Opts::new(name, desc).subsystem("io_engine"),
+            &["node"],
+            "io-engine",
+        );
+        assert_eq!(gauges.descs().len(), 2);
+
+        let mut statuses = PollStatuses::default();
+        statuses.record("pools", true);
+        statuses.record("nexuses", false);
+
+        let metric_family = gauges.collect(
+            &["node-1"],
+            ["pools", "nexuses"].map(|source| (source, statuses.get(source))),
+        );
+        // Each metric as its name, its label pairs and its value.
+        let metrics: Vec<(String, String, f64)> = metric_family
+            .iter()
+            .flat_map(|family| {
+                family.get_metric().iter().map(|metric| {
+                    let labels = metric
+                        .get_label()
+                        .iter()
+                        .map(|label| format!("{}={}", label.get_name(), label.get_value()))
+                        .collect::<Vec<_>>()
+                        .join(",");
+                    (
+                        family.get_name().to_string(),
+                        labels,
+                        metric.get_gauge().get_value(),
+                    )
+                })
+            })
+            .collect();
+
+        let labels = |source: &str| format!("node=node-1,source={source}");
+        // The timestamp of the nexuses is absent, as their polls never succeeded.
+        assert_eq!(metrics.len(), 3);
+        assert_eq!(
+            metrics[0],
+            ("io_engine_up".to_string(), labels("pools"), 1.0)
+        );
+        assert_eq!(
+            metrics[1].0.as_str(),
+            "io_engine_last_successful_poll_timestamp_seconds"
+        );
+        assert_eq!(metrics[1].1, labels("pools"));
+        assert_eq!(Some(metrics[1].2), statuses.get("pools").last_success());
+        assert_eq!(
+            metrics[2],
+            ("io_engine_up".to_string(), labels("nexuses"), 0.0)
+        );
+    }
+}
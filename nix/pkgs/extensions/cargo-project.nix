@@ -64,6 +64,8 @@ let
     "call-home"
     "upgrade"
     "constants"
+    "etcd-dump"
+    "http-server"
     "dependencies/control-plane/openapi/Cargo.toml"
     "dependencies/control-plane/openapi/build.rs"
     "dependencies/control-plane/openapi/src/lib.rs"
@@ -36,6 +36,14 @@ let
               metrics_builder { inherit buildType builder; cargoBuildFlags = [ "--bin metrics-exporter-io-engine" ]; };
           pname = "metrics-exporter-io-engine";
         };
+        cluster = metrics_installer {
+          src =
+            if allInOne then
+              metrics_builder { inherit buildType builder; }
+            else
+              metrics_builder { inherit buildType builder; cargoBuildFlags = [ "--bin metrics-exporter-cluster" ]; };
+          pname = "metrics-exporter-cluster";
+        };
       };
     };
     upgrade = rec {
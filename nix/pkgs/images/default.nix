@@ -36,6 +36,16 @@ let
         };
       };
     };
+    cluster = build-extensions-image rec{
+      inherit buildType;
+      package = extensions.${buildType}.metrics.exporter.cluster;
+      pname = package.pname;
+      config = {
+        ExposedPorts = {
+          "9503/tcp" = { };
+        };
+      };
+    };
   };
   tagged_helm_chart = runCommand "tagged_helm_chart"
     {
@@ -74,7 +84,7 @@ let
     build-extensions-image rec{
       inherit buildType;
       package = extensions.${buildType}.upgrade.${name};
-      copyToRoot = [ kubernetes-helm-wrapped busybox tagged_helm_chart yq-go ];
+      copyToRoot = [ kubernetes-helm-wrapped busybox tagged_helm_chart ];
       pname = package.pname;
       config = {
         Env = [ "CORE_CHART_DIR=/chart" ];
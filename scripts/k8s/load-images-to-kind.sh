@@ -58,7 +58,7 @@ fi
 IMAGE_TAG="v${TAG#v}"
 # This list is static and is bound to fall out of date.
 # TODO: generate the list of container images at run time from build assets.
-images=("upgrade-job" "obs-callhome" "obs-callhome-stats" "metrics-exporter-io-engine")
+images=("upgrade-job" "obs-callhome" "obs-callhome-stats" "metrics-exporter-io-engine" "metrics-exporter-cluster")
 load_cmd="kind load docker-image"
 for image in "${images[@]}"; do
   if [ "$TRIM_DEBUG_SUFFIX" = 1 ]; then
@@ -11,7 +11,7 @@ if [ ! -f "$SOURCE_REL" ] && [ -z "$CI" ]; then
   git submodule update --init --recursive
 fi
 
-IMAGES="metrics.exporter.io-engine obs.callhome stats.aggregator upgrade.job"
+IMAGES="metrics.exporter.io-engine metrics.exporter.cluster obs.callhome stats.aggregator upgrade.job"
 HELM_DEPS_IMAGES="upgrade.job"
 BUILD_BINARIES="kubectl-plugin"
 PROJECT="extensions"